@@ -0,0 +1,118 @@
+//! Decoder throughput benchmark.
+//!
+//! Run with `cargo bench --features baseline`. Decodes a corpus of valid
+//! instructions covering every major opcode, and a corpus of pseudo-random
+//! words which are mostly invalid, reporting the number of words decoded per
+//! second for each, with `decode_isa` and with the table decoder it
+//! replaced.
+
+extern crate falcon_riscv;
+
+use falcon_riscv::{baseline, decode_bytes, decode_isa, DecodeError, Instruction, Isa};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const VALID: &[u32] = &[
+    0x00c58533, // add a0, a1, a2
+    0x12358513, // addi a0, a1, 291
+    0x12345517, // auipc a0, 0x12345
+    0x00b50663, // beq a0, a1, 12
+    0x0080006f, // j 8
+    0x00008067, // ret
+    0x00813503, // ld a0, 8(sp)
+    0x00a13423, // sd a0, 8(sp)
+    0x0005e503, // lwu a0, 0(a1)
+    0xfff5051b, // addiw a0, a0, -1
+    0x42855513, // srai a0, a0, 40
+    0x40c5d53b, // sraw a0, a1, a2
+    0x02c58533, // mul a0, a1, a2
+    0x02c5f53b, // remuw a0, a1, a2
+    0x0ff0000f, // fence
+    0x00000073, // ecall
+    0x30002573, // csrr a0, mstatus
+    0x06c5a52f, // amoadd.w.aqrl a0, a2, (a1)
+    0x1405a52f, // lr.w.aq a0, (a1)
+    0x00412507, // flw fa0, 4(sp)
+    0x00813427, // fsd fs0, 8(sp)
+    0x00c59553, // fadd.s fa0, fa1, fa2, rtz
+    0x6ac5f543, // fmadd.d fa0, fa1, fa2, fa3
+    0xc0059553, // fcvt.w.s a0, fa1, rtz
+    0xe2058553, // fmv.x.d a0, fa1
+];
+
+const ITERATIONS: usize = 200_000;
+
+type DecodeWord = fn(u32, &Isa) -> Result<Instruction, DecodeError>;
+type DecodeBytes = fn(&[u8], &Isa) -> Result<(Instruction, usize), DecodeError>;
+
+fn report(name: &str, words: usize, elapsed: Duration) {
+    let per_second = words as f64 / elapsed.as_secs_f64();
+    println!(
+        "{:<16} {:>12} words in {:>8.3}s  {:>14.0} words/s",
+        name,
+        words,
+        elapsed.as_secs_f64(),
+        per_second
+    );
+}
+
+fn bench_valid(name: &str, decode: DecodeWord, isa: &Isa) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        for word in VALID {
+            let _ = black_box(decode(black_box(*word), isa));
+        }
+    }
+    report(name, ITERATIONS * VALID.len(), start.elapsed());
+}
+
+fn bench_random(name: &str, decode: DecodeWord, isa: &Isa) {
+    let words = ITERATIONS * VALID.len();
+    let mut word: u32 = 0x12345678;
+    let start = Instant::now();
+    for _ in 0..words {
+        // xorshift32
+        word ^= word << 13;
+        word ^= word >> 17;
+        word ^= word << 5;
+        let _ = black_box(decode(black_box(word | 0x3), isa));
+    }
+    report(name, words, start.elapsed());
+}
+
+fn bench_bytes(name: &str, decode: DecodeBytes, isa: &Isa) {
+    let bytes: Vec<u8> = VALID
+        .iter()
+        .flat_map(|word| {
+            vec![
+                *word as u8,
+                (*word >> 8) as u8,
+                (*word >> 16) as u8,
+                (*word >> 24) as u8,
+            ]
+        })
+        .collect();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        let mut offset = 0;
+        while offset < bytes.len() {
+            match decode(black_box(&bytes[offset..]), isa) {
+                Ok((_, length)) => offset += length,
+                Err(_) => offset += 2,
+            }
+        }
+    }
+    report(name, ITERATIONS * VALID.len(), start.elapsed());
+}
+
+fn main() {
+    let isa: Isa = "rv64gc".parse().unwrap();
+
+    bench_valid("valid table", baseline::decode_isa, &isa);
+    bench_valid("valid", decode_isa, &isa);
+    bench_random("random table", baseline::decode_isa, &isa);
+    bench_random("random", decode_isa, &isa);
+    bench_bytes("bytes table", baseline::decode_bytes, &isa);
+    bench_bytes("bytes", decode_bytes, &isa);
+}
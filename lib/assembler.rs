@@ -0,0 +1,1661 @@
+use std::collections::HashMap;
+
+use compressed::{decode_compressed, required_xlen};
+use decoder::decode_isa;
+use encoder::encode;
+use error::{AssembleError, DecodeError};
+use instruction::{default_rounding_mode, FLI_CONSTANTS};
+use vector::{self, Slot};
+use {Aqrl, Csr, Fence, FenceSet, Instruction, Isa, Op, Register, RoundingMode, VectorOp, Xlen};
+
+// Assembly is line based. A line holds any number of `label:` definitions
+// followed by at most one instruction, and anything after a `#` is a
+// comment. Labels are resolved in a second pass, once the address of every
+// instruction is known, so branches and jumps may refer forwards.
+
+/// Every op, for looking up mnemonics
+const OPS: &[Op] = &[
+    Op::Add,
+    Op::Addi,
+    Op::Addiw,
+    Op::AddUw,
+    Op::Addw,
+    Op::Aes32dsi,
+    Op::Aes32dsmi,
+    Op::Aes32esi,
+    Op::Aes32esmi,
+    Op::Aes64ds,
+    Op::Aes64dsm,
+    Op::Aes64es,
+    Op::Aes64esm,
+    Op::Aes64im,
+    Op::Aes64ks1i,
+    Op::Aes64ks2,
+    Op::AmoaddD,
+    Op::AmoaddW,
+    Op::AmoandD,
+    Op::AmoandW,
+    Op::AmomaxD,
+    Op::AmomaxW,
+    Op::AmomaxuD,
+    Op::AmomaxuW,
+    Op::AmominD,
+    Op::AmominW,
+    Op::AmominuD,
+    Op::AmominuW,
+    Op::AmoorD,
+    Op::AmoorW,
+    Op::AmoswapD,
+    Op::AmoswapW,
+    Op::AmoxorD,
+    Op::AmoxorW,
+    Op::And,
+    Op::Andi,
+    Op::Andn,
+    Op::Auipc,
+    Op::Bclr,
+    Op::Bclri,
+    Op::Beq,
+    Op::Bext,
+    Op::Bexti,
+    Op::Bge,
+    Op::Bgeu,
+    Op::Binv,
+    Op::Binvi,
+    Op::Blt,
+    Op::Bltu,
+    Op::Bne,
+    Op::Brev8,
+    Op::Bset,
+    Op::Bseti,
+    Op::CAdd,
+    Op::CAddi,
+    Op::CAddi16sp,
+    Op::CAddi4spn,
+    Op::CAddiw,
+    Op::CAddw,
+    Op::CAnd,
+    Op::CAndi,
+    Op::CBeqz,
+    Op::CBnez,
+    Op::CEbreak,
+    Op::CFld,
+    Op::CFldsp,
+    Op::CFlw,
+    Op::CFlwsp,
+    Op::CFsd,
+    Op::CFsdsp,
+    Op::CFsw,
+    Op::CFswsp,
+    Op::CJ,
+    Op::CJal,
+    Op::CJalr,
+    Op::CJr,
+    Op::CLd,
+    Op::CLdsp,
+    Op::CLi,
+    Op::CLui,
+    Op::CLw,
+    Op::CLwsp,
+    Op::CMv,
+    Op::CNop,
+    Op::CNtlAll,
+    Op::CNtlP1,
+    Op::CNtlPall,
+    Op::CNtlS1,
+    Op::COr,
+    Op::CSd,
+    Op::CSdsp,
+    Op::CSlli,
+    Op::CSrai,
+    Op::CSrli,
+    Op::CSub,
+    Op::CSubw,
+    Op::CSw,
+    Op::CSwsp,
+    Op::CXor,
+    Op::CboClean,
+    Op::CboFlush,
+    Op::CboInval,
+    Op::CboZero,
+    Op::Clmul,
+    Op::Clmulh,
+    Op::Clmulr,
+    Op::Clz,
+    Op::Clzw,
+    Op::Cpop,
+    Op::Cpopw,
+    Op::Csrrc,
+    Op::Csrrci,
+    Op::Csrrs,
+    Op::Csrrsi,
+    Op::Csrrw,
+    Op::Csrrwi,
+    Op::Ctz,
+    Op::Ctzw,
+    Op::Dret,
+    Op::Ebreak,
+    Op::Ecall,
+    Op::FaddD,
+    Op::FaddH,
+    Op::FaddQ,
+    Op::FaddS,
+    Op::FclassD,
+    Op::FclassH,
+    Op::FclassQ,
+    Op::FclassS,
+    Op::FcvtDH,
+    Op::FcvtDL,
+    Op::FcvtDLu,
+    Op::FcvtDQ,
+    Op::FcvtDS,
+    Op::FcvtDW,
+    Op::FcvtDWu,
+    Op::FcvtHD,
+    Op::FcvtHL,
+    Op::FcvtHLu,
+    Op::FcvtHQ,
+    Op::FcvtHS,
+    Op::FcvtHW,
+    Op::FcvtHWu,
+    Op::FcvtLD,
+    Op::FcvtLH,
+    Op::FcvtLQ,
+    Op::FcvtLS,
+    Op::FcvtLuD,
+    Op::FcvtLuH,
+    Op::FcvtLuQ,
+    Op::FcvtLuS,
+    Op::FcvtQD,
+    Op::FcvtQH,
+    Op::FcvtQL,
+    Op::FcvtQLu,
+    Op::FcvtQS,
+    Op::FcvtQW,
+    Op::FcvtQWu,
+    Op::FcvtSD,
+    Op::FcvtSH,
+    Op::FcvtSL,
+    Op::FcvtSLu,
+    Op::FcvtSQ,
+    Op::FcvtSW,
+    Op::FcvtSWu,
+    Op::FcvtWD,
+    Op::FcvtWH,
+    Op::FcvtWQ,
+    Op::FcvtWS,
+    Op::FcvtWuD,
+    Op::FcvtWuH,
+    Op::FcvtWuQ,
+    Op::FcvtWuS,
+    Op::FcvtmodWD,
+    Op::FdivD,
+    Op::FdivH,
+    Op::FdivQ,
+    Op::FdivS,
+    Op::FeqD,
+    Op::FeqH,
+    Op::FeqQ,
+    Op::FeqS,
+    Op::Fld,
+    Op::FleD,
+    Op::FleH,
+    Op::FleQ,
+    Op::FleS,
+    Op::FleqD,
+    Op::FleqH,
+    Op::FleqQ,
+    Op::FleqS,
+    Op::Flh,
+    Op::FliD,
+    Op::FliH,
+    Op::FliQ,
+    Op::FliS,
+    Op::Flq,
+    Op::FltD,
+    Op::FltH,
+    Op::FltQ,
+    Op::FltS,
+    Op::FltqD,
+    Op::FltqH,
+    Op::FltqQ,
+    Op::FltqS,
+    Op::Flw,
+    Op::FmaddD,
+    Op::FmaddH,
+    Op::FmaddQ,
+    Op::FmaddS,
+    Op::FmaxD,
+    Op::FmaxH,
+    Op::FmaxQ,
+    Op::FmaxS,
+    Op::FmaxmD,
+    Op::FmaxmH,
+    Op::FmaxmQ,
+    Op::FmaxmS,
+    Op::FminD,
+    Op::FminH,
+    Op::FminQ,
+    Op::FminS,
+    Op::FminmD,
+    Op::FminmH,
+    Op::FminmQ,
+    Op::FminmS,
+    Op::FmsubD,
+    Op::FmsubH,
+    Op::FmsubQ,
+    Op::FmsubS,
+    Op::FmulD,
+    Op::FmulH,
+    Op::FmulQ,
+    Op::FmulS,
+    Op::FmvDX,
+    Op::FmvHX,
+    Op::FmvWX,
+    Op::FmvXD,
+    Op::FmvXH,
+    Op::FmvXW,
+    Op::FmvhXD,
+    Op::FmvhXQ,
+    Op::FmvpDX,
+    Op::FmvpQX,
+    Op::FnmaddD,
+    Op::FnmaddH,
+    Op::FnmaddQ,
+    Op::FnmaddS,
+    Op::FnmsubD,
+    Op::FnmsubH,
+    Op::FnmsubQ,
+    Op::FnmsubS,
+    Op::FroundD,
+    Op::FroundH,
+    Op::FroundQ,
+    Op::FroundS,
+    Op::FroundnxD,
+    Op::FroundnxH,
+    Op::FroundnxQ,
+    Op::FroundnxS,
+    Op::Fsd,
+    Op::FsgnjD,
+    Op::FsgnjH,
+    Op::FsgnjQ,
+    Op::FsgnjS,
+    Op::FsgnjnD,
+    Op::FsgnjnH,
+    Op::FsgnjnQ,
+    Op::FsgnjnS,
+    Op::FsgnjxD,
+    Op::FsgnjxH,
+    Op::FsgnjxQ,
+    Op::FsgnjxS,
+    Op::Fsh,
+    Op::Fsq,
+    Op::FsqrtD,
+    Op::FsqrtH,
+    Op::FsqrtQ,
+    Op::FsqrtS,
+    Op::FsubD,
+    Op::FsubH,
+    Op::FsubQ,
+    Op::FsubS,
+    Op::Fsw,
+    Op::Fence,
+    Op::FenceI,
+    Op::FenceTso,
+    Op::HfenceGvma,
+    Op::HfenceVvma,
+    Op::HinvalGvma,
+    Op::HinvalVvma,
+    Op::HlvB,
+    Op::HlvBu,
+    Op::HlvD,
+    Op::HlvH,
+    Op::HlvHu,
+    Op::HlvW,
+    Op::HlvWu,
+    Op::HlvxHu,
+    Op::HlvxWu,
+    Op::HsvB,
+    Op::HsvD,
+    Op::HsvH,
+    Op::HsvW,
+    Op::Jal,
+    Op::Jalr,
+    Op::Lb,
+    Op::Lbu,
+    Op::Ld,
+    Op::Lh,
+    Op::Lhu,
+    Op::LrD,
+    Op::LrW,
+    Op::Lw,
+    Op::Lwu,
+    Op::Lui,
+    Op::Max,
+    Op::Maxu,
+    Op::Min,
+    Op::Minu,
+    Op::Mret,
+    Op::Mul,
+    Op::Mulh,
+    Op::Mulhsu,
+    Op::Mulhu,
+    Op::Mulw,
+    Op::Div,
+    Op::Divu,
+    Op::Divuw,
+    Op::Divw,
+    Op::Rem,
+    Op::Remu,
+    Op::Remuw,
+    Op::Remw,
+    Op::NtlAll,
+    Op::NtlP1,
+    Op::NtlPall,
+    Op::NtlS1,
+    Op::Or,
+    Op::Ori,
+    Op::OrcB,
+    Op::Orn,
+    Op::Pack,
+    Op::Packh,
+    Op::Packw,
+    Op::Pause,
+    Op::PrefetchI,
+    Op::PrefetchR,
+    Op::PrefetchW,
+    Op::RdCycle,
+    Op::RdCycleH,
+    Op::RdTime,
+    Op::RdTimeH,
+    Op::RdInstRet,
+    Op::RdInstRetH,
+    Op::Rev8,
+    Op::Rol,
+    Op::Rolw,
+    Op::Ror,
+    Op::Rori,
+    Op::Roriw,
+    Op::Rorw,
+    Op::Sb,
+    Op::ScD,
+    Op::ScW,
+    Op::Sd,
+    Op::SextB,
+    Op::SextH,
+    Op::SfenceInvalIr,
+    Op::SfenceVma,
+    Op::SfenceWInval,
+    Op::SinvalVma,
+    Op::Sh,
+    Op::Sh1add,
+    Op::Sh1addUw,
+    Op::Sh2add,
+    Op::Sh2addUw,
+    Op::Sh3add,
+    Op::Sh3addUw,
+    Op::Sha256sig0,
+    Op::Sha256sig1,
+    Op::Sha256sum0,
+    Op::Sha256sum1,
+    Op::Sha512sig0,
+    Op::Sha512sig0h,
+    Op::Sha512sig0l,
+    Op::Sha512sig1,
+    Op::Sha512sig1h,
+    Op::Sha512sig1l,
+    Op::Sha512sum0,
+    Op::Sha512sum0r,
+    Op::Sha512sum1,
+    Op::Sha512sum1r,
+    Op::Sw,
+    Op::Sll,
+    Op::Slli,
+    Op::SlliUw,
+    Op::Slliw,
+    Op::Sllw,
+    Op::Slt,
+    Op::Slti,
+    Op::Sltu,
+    Op::Sltiu,
+    Op::Sm3p0,
+    Op::Sm3p1,
+    Op::Sm4ed,
+    Op::Sm4ks,
+    Op::Sra,
+    Op::Srai,
+    Op::Sraiw,
+    Op::Sraw,
+    Op::Srl,
+    Op::Srli,
+    Op::Srliw,
+    Op::Srlw,
+    Op::Sret,
+    Op::Sub,
+    Op::Subw,
+    Op::Uret,
+    Op::Unzip,
+    Op::Wfi,
+    Op::Xnor,
+    Op::Xor,
+    Op::Xori,
+    Op::Xperm4,
+    Op::Xperm8,
+    Op::ZextH,
+    Op::Zip,
+];
+
+/// Looks up a mnemonic, returning an instruction with no operands to be
+/// filled in. The atomic instructions may carry an `.aq`, `.rl` or `.aqrl`
+/// suffix, and vector instructions their operand form or element width.
+/// `rev8` and `zext.h`, whose encodings depend on XLEN, take the encodings
+/// for `xlen`.
+fn lookup(mnemonic: &str, xlen: Xlen) -> Option<Instruction> {
+    let find = |mnemonic: &str| OPS.iter().find(|op| op.mnemonic() == mnemonic).cloned();
+    let is_atomic = |op: &Op| {
+        let mnemonic = op.mnemonic();
+        mnemonic.starts_with("amo") || mnemonic.starts_with("lr.") || mnemonic.starts_with("sc.")
+    };
+    let atomic = |op: Op, aq: bool, rl: bool| Instruction {
+        aqrl: Some(Aqrl { aq, rl }),
+        ..Instruction::new(op)
+    };
+    if let Some(op) = find(mnemonic) {
+        if is_atomic(&op) {
+            return Some(atomic(op, false, false));
+        }
+        if let Op::Rev8 | Op::ZextH = op {
+            return Some(Instruction {
+                xlen: Some(xlen),
+                ..Instruction::new(op)
+            });
+        }
+        return Some(Instruction::new(op));
+    }
+    for &(suffix, aq, rl) in &[
+        (".aqrl", true, true),
+        (".aq", true, false),
+        (".rl", false, true),
+    ] {
+        if let Some(op) = mnemonic.strip_suffix(suffix).and_then(find) {
+            if is_atomic(&op) {
+                return Some(atomic(op, aq, rl));
+            }
+        }
+    }
+    vector::lookup(mnemonic)
+}
+
+/// Rewrites a pseudo-instruction which stands for a single instruction, as
+/// `Instruction`'s alternate `Display` writes it, as that instruction's
+/// mnemonic and operands. `jal`, `jalr` and the counter reads are already
+/// the mnemonics of ops, which take the pseudo-instruction forms too.
+fn expand_pseudo<'a>(mnemonic: &str, operands: &[&'a str]) -> Option<(&'static str, Vec<&'a str>)> {
+    let expanded = match (mnemonic, operands) {
+        ("nop", []) => ("addi", vec!["zero", "zero", "0"]),
+        ("li", &[rd, immediate]) => ("addi", vec![rd, "zero", immediate]),
+        ("mv", &[rd, rs]) => ("addi", vec![rd, rs, "0"]),
+        ("not", &[rd, rs]) => ("xori", vec![rd, rs, "-1"]),
+        ("neg", &[rd, rs]) => ("sub", vec![rd, "zero", rs]),
+        ("negw", &[rd, rs]) => ("subw", vec![rd, "zero", rs]),
+        ("sext.w", &[rd, rs]) => ("addiw", vec![rd, rs, "0"]),
+        ("zext.w", &[rd, rs]) => ("add.uw", vec![rd, rs, "zero"]),
+        ("seqz", &[rd, rs]) => ("sltiu", vec![rd, rs, "1"]),
+        ("snez", &[rd, rs]) => ("sltu", vec![rd, "zero", rs]),
+        ("sltz", &[rd, rs]) => ("slt", vec![rd, rs, "zero"]),
+        ("sgtz", &[rd, rs]) => ("slt", vec![rd, "zero", rs]),
+        ("fmv.s", &[rd, rs]) => ("fsgnj.s", vec![rd, rs, rs]),
+        ("fabs.s", &[rd, rs]) => ("fsgnjx.s", vec![rd, rs, rs]),
+        ("fneg.s", &[rd, rs]) => ("fsgnjn.s", vec![rd, rs, rs]),
+        ("fmv.d", &[rd, rs]) => ("fsgnj.d", vec![rd, rs, rs]),
+        ("fabs.d", &[rd, rs]) => ("fsgnjx.d", vec![rd, rs, rs]),
+        ("fneg.d", &[rd, rs]) => ("fsgnjn.d", vec![rd, rs, rs]),
+        ("fmv.h", &[rd, rs]) => ("fsgnj.h", vec![rd, rs, rs]),
+        ("fabs.h", &[rd, rs]) => ("fsgnjx.h", vec![rd, rs, rs]),
+        ("fneg.h", &[rd, rs]) => ("fsgnjn.h", vec![rd, rs, rs]),
+        ("fmv.q", &[rd, rs]) => ("fsgnj.q", vec![rd, rs, rs]),
+        ("fabs.q", &[rd, rs]) => ("fsgnjx.q", vec![rd, rs, rs]),
+        ("fneg.q", &[rd, rs]) => ("fsgnjn.q", vec![rd, rs, rs]),
+        ("beqz", &[rs, target]) => ("beq", vec![rs, "zero", target]),
+        ("bnez", &[rs, target]) => ("bne", vec![rs, "zero", target]),
+        ("blez", &[rs, target]) => ("bge", vec!["zero", rs, target]),
+        ("bgez", &[rs, target]) => ("bge", vec![rs, "zero", target]),
+        ("bltz", &[rs, target]) => ("blt", vec![rs, "zero", target]),
+        ("bgtz", &[rs, target]) => ("blt", vec!["zero", rs, target]),
+        ("j", &[target]) => ("jal", vec!["zero", target]),
+        ("jr", &[target]) => ("jalr", vec!["zero", target]),
+        ("ret", []) => ("jalr", vec!["zero", "0(ra)"]),
+        ("csrr", &[rd, csr]) => ("csrrs", vec![rd, csr, "zero"]),
+        ("csrw", &[csr, rs]) => ("csrrw", vec!["zero", csr, rs]),
+        ("csrs", &[csr, rs]) => ("csrrs", vec!["zero", csr, rs]),
+        ("csrc", &[csr, rs]) => ("csrrc", vec!["zero", csr, rs]),
+        ("csrwi", &[csr, immediate]) => ("csrrwi", vec!["zero", csr, immediate]),
+        ("csrsi", &[csr, immediate]) => ("csrrsi", vec!["zero", csr, immediate]),
+        ("csrci", &[csr, immediate]) => ("csrrci", vec!["zero", csr, immediate]),
+        ("frcsr", &[rd]) => ("csrrs", vec![rd, "fcsr", "zero"]),
+        ("frrm", &[rd]) => ("csrrs", vec![rd, "frm", "zero"]),
+        ("frflags", &[rd]) => ("csrrs", vec![rd, "fflags", "zero"]),
+        // The old value is only written out when it is kept
+        ("fscsr", &[rs]) => ("csrrw", vec!["zero", "fcsr", rs]),
+        ("fscsr", &[rd, rs]) => ("csrrw", vec![rd, "fcsr", rs]),
+        ("fsrm", &[rs]) => ("csrrw", vec!["zero", "frm", rs]),
+        ("fsrm", &[rd, rs]) => ("csrrw", vec![rd, "frm", rs]),
+        ("fsflags", &[rs]) => ("csrrw", vec!["zero", "fflags", rs]),
+        ("fsflags", &[rd, rs]) => ("csrrw", vec![rd, "fflags", rs]),
+        ("fsrmi", &[immediate]) => ("csrrwi", vec!["zero", "frm", immediate]),
+        ("fsrmi", &[rd, immediate]) => ("csrrwi", vec![rd, "frm", immediate]),
+        ("fsflagsi", &[immediate]) => ("csrrwi", vec!["zero", "fflags", immediate]),
+        ("fsflagsi", &[rd, immediate]) => ("csrrwi", vec![rd, "fflags", immediate]),
+        _ => return None,
+    };
+    Some(expanded)
+}
+
+fn is_label(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.' || c == '$' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$')
+}
+
+/// Parses a decimal, `0x` hexadecimal or `0b` binary integer, with an
+/// optional sign
+fn number(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (radix, digits) = if let Some(hex) = digits.strip_prefix("0x") {
+        (16, hex)
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        (2, binary)
+    } else {
+        (10, digits)
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let value = i64::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// The instructions of a `call` or `tail`, an `auipc` of the upper part of
+/// the offset to the target and a `jalr` of the lower part
+enum Half {
+    Auipc,
+    Jalr,
+}
+
+/// An instruction found in the first pass, with the address it is placed
+/// at. Both instructions of a `call` or `tail` take the address of the
+/// `auipc`, which their target is relative to.
+struct Statement<'a> {
+    line: usize,
+    address: u64,
+    instruction: Instruction,
+    operands: Vec<&'a str>,
+    half: Option<Half>,
+}
+
+/// Parses the operands of one statement
+struct Parser<'a> {
+    line: usize,
+    address: u64,
+    labels: &'a HashMap<&'a str, u64>,
+}
+
+impl<'a> Parser<'a> {
+    fn invalid(&self, operand: &str) -> AssembleError {
+        AssembleError::InvalidOperand(self.line, operand.to_string())
+    }
+
+    fn count(&self, operands: &[&str], count: usize) -> Result<(), AssembleError> {
+        if operands.len() == count {
+            Ok(())
+        } else {
+            Err(AssembleError::OperandCount(self.line))
+        }
+    }
+
+    fn register(&self, operand: &str) -> Result<Register, AssembleError> {
+        Register::from_name(operand).ok_or_else(|| self.invalid(operand))
+    }
+
+    /// An immediate. Whether it fits the instruction is checked when it is
+    /// encoded.
+    fn immediate(&self, operand: &str) -> Result<i32, AssembleError> {
+        match number(operand) {
+            Some(value) if i64::from(i32::MIN) <= value && value <= i64::from(i32::MAX) => {
+                Ok(value as i32)
+            }
+            _ => Err(self.invalid(operand)),
+        }
+    }
+
+    /// The constant of `fli`, written as in the printed form, such as `min`
+    /// or `0.5`, or as any other number with the same value
+    fn fli_constant(&self, operand: &str) -> Result<i32, AssembleError> {
+        let value = operand.parse::<f64>().ok();
+        FLI_CONSTANTS
+            .iter()
+            .position(|&constant| {
+                constant == operand || (value.is_some() && constant.parse::<f64>().ok() == value)
+            })
+            .map(|index| index as i32)
+            .ok_or_else(|| self.invalid(operand))
+    }
+
+    fn shamt(&self, operand: &str) -> Result<usize, AssembleError> {
+        match number(operand) {
+            Some(value) if (0..=i64::from(u32::MAX)).contains(&value) => Ok(value as usize),
+            _ => Err(self.invalid(operand)),
+        }
+    }
+
+    /// A memory operand, `offset(base)` or `(base)`
+    fn memory(&self, operand: &str) -> Result<(i32, Register), AssembleError> {
+        let open = operand.find('(').ok_or_else(|| self.invalid(operand))?;
+        let base = operand[open + 1..]
+            .strip_suffix(')')
+            .ok_or_else(|| self.invalid(operand))?;
+        let offset = match operand[..open].trim() {
+            "" => 0,
+            offset => self.immediate(offset)?,
+        };
+        Ok((offset, self.register(base.trim())?))
+    }
+
+    /// The base register of an atomic instruction, `(base)`, which takes no
+    /// offset
+    fn atomic_address(&self, operand: &str) -> Result<Register, AssembleError> {
+        match self.memory(operand)? {
+            (0, base) => Ok(base),
+            _ => Err(self.invalid(operand)),
+        }
+    }
+
+    /// The target of a `jalr`, either `offset(base)` or a bare base
+    /// register
+    fn jump_target(&self, operand: &str) -> Result<(i32, Register), AssembleError> {
+        match operand.find('(') {
+            Some(_) => self.memory(operand),
+            None => Ok((0, self.register(operand)?)),
+        }
+    }
+
+    /// One instruction of a `call` or `tail`, whose operands are the link
+    /// register the `auipc` writes, the `rd` of the `jalr`, and the target
+    fn half(
+        &self,
+        template: Instruction,
+        operands: &[&str],
+        half: &Half,
+    ) -> Result<Instruction, AssembleError> {
+        let mut i = template;
+        let link = self.register(operands[0])?;
+        let offset = self.target(operands[2])?;
+        // The jalr sign-extends the lower 12 bits, so the upper part rounds
+        // to the nearest multiple of 4096
+        let low = (offset << 20) >> 20;
+        match half {
+            Half::Auipc => {
+                i.rd = link;
+                i.immediate = (offset.wrapping_sub(low) >> 12) & 0xfffff;
+            }
+            Half::Jalr => {
+                i.rd = self.register(operands[1])?;
+                i.rs1 = link;
+                i.immediate = low;
+            }
+        }
+        Ok(i)
+    }
+
+    /// A branch or jump target, either a label or an offset from the
+    /// address of the instruction
+    fn target(&self, operand: &str) -> Result<i32, AssembleError> {
+        if number(operand).is_some() {
+            return self.immediate(operand);
+        }
+        if !is_label(operand) {
+            return Err(self.invalid(operand));
+        }
+        match self.labels.get(operand) {
+            Some(&address) => Ok(address.wrapping_sub(self.address) as i32),
+            None => Err(AssembleError::UndefinedLabel(
+                self.line,
+                operand.to_string(),
+            )),
+        }
+    }
+
+    /// A CSR, by name or by address
+    fn csr(&self, operand: &str) -> Result<Csr, AssembleError> {
+        Csr::from_name(operand)
+            .or_else(|| {
+                number(operand)
+                    .filter(|n| *n >= 0)
+                    .and_then(|n| Csr::from_address(n as usize))
+            })
+            .ok_or_else(|| self.invalid(operand))
+    }
+
+    /// The rounding mode of a floating point instruction, which is the last
+    /// of `count + 1` operands or is left out and takes its default
+    fn rounding_mode(
+        &self,
+        op: &Op,
+        operands: &[&str],
+        count: usize,
+    ) -> Result<Option<RoundingMode>, AssembleError> {
+        if operands.len() == count {
+            return Ok(Some(default_rounding_mode(op)));
+        }
+        self.count(operands, count + 1)?;
+        let operand = operands[count];
+        (0..8)
+            .filter_map(RoundingMode::from_u32)
+            .find(|rm| rm.to_string() == operand)
+            .map(Some)
+            .ok_or_else(|| self.invalid(operand))
+    }
+
+    /// The predecessor or successor set of a fence, such as `rw` or `0`
+    fn fence_set(&self, operand: &str) -> Result<FenceSet, AssembleError> {
+        let mut set = FenceSet {
+            i: false,
+            o: false,
+            r: false,
+            w: false,
+        };
+        if operand == "0" {
+            return Ok(set);
+        }
+        for c in operand.chars() {
+            let bit = match c {
+                'i' => &mut set.i,
+                'o' => &mut set.o,
+                'r' => &mut set.r,
+                'w' => &mut set.w,
+                _ => return Err(self.invalid(operand)),
+            };
+            if *bit {
+                return Err(self.invalid(operand));
+            }
+            *bit = true;
+        }
+        Ok(set)
+    }
+
+    /// A vector instruction, whose operands are described by its slots. A
+    /// trailing `v0.t` masks the instruction.
+    fn vector(
+        &self,
+        mut i: Instruction,
+        op: VectorOp,
+        operands: &[&str],
+    ) -> Result<Instruction, AssembleError> {
+        let mut operands = operands;
+        if let Some((&"v0.t", rest)) = operands.split_last() {
+            if vector::is_maskable(&i, op) {
+                i.masked = true;
+                operands = rest;
+            }
+        }
+        let slots = vector::slots(&i, op);
+        // The vtype takes up the remaining operands
+        if slots.last() == Some(&Slot::Vtype) && operands.len() >= slots.len() {
+            let count = slots.len() - 1;
+            i.vtype = Some(
+                vector::parse_vtype(&operands[count..])
+                    .ok_or_else(|| self.invalid(&operands[count..].join(", ")))?,
+            );
+            operands = &operands[..count + 1];
+        }
+        self.count(operands, slots.len())?;
+
+        for (slot, &operand) in slots.iter().zip(operands) {
+            match *slot {
+                Slot::Rd(_) => i.rd = self.register(operand)?,
+                Slot::Rs1(_) => i.rs1 = self.register(operand)?,
+                Slot::Rs2(_) => i.rs2 = self.register(operand)?,
+                Slot::Rs3 => i.rs3 = self.register(operand)?,
+                Slot::Immediate(_) => i.immediate = self.immediate(operand)?,
+                Slot::Base => i.rs1 = self.atomic_address(operand)?,
+                Slot::Vtype => {}
+                Slot::V0 if operand == "v0" => {}
+                Slot::V0 => return Err(self.invalid(operand)),
+            }
+        }
+        Ok(i)
+    }
+
+    fn instruction(
+        &self,
+        template: Instruction,
+        operands: &[&str],
+    ) -> Result<Instruction, AssembleError> {
+        let mut i = template;
+        let op = i.op.clone();
+        match op {
+            Op::Add
+            | Op::Addw
+            | Op::And
+            | Op::Div
+            | Op::Divu
+            | Op::Divuw
+            | Op::Divw
+            | Op::Mul
+            | Op::Mulh
+            | Op::Mulhsu
+            | Op::Mulhu
+            | Op::Mulw
+            | Op::Or
+            | Op::Rem
+            | Op::Remu
+            | Op::Remuw
+            | Op::Remw
+            | Op::Sll
+            | Op::Sllw
+            | Op::Slt
+            | Op::Sltu
+            | Op::Sra
+            | Op::Sraw
+            | Op::Srl
+            | Op::Srlw
+            | Op::Sub
+            | Op::Subw
+            | Op::Xor
+            | Op::AddUw
+            | Op::Andn
+            | Op::Bclr
+            | Op::Bext
+            | Op::Binv
+            | Op::Bset
+            | Op::Clmul
+            | Op::Clmulh
+            | Op::Clmulr
+            | Op::Max
+            | Op::Maxu
+            | Op::Min
+            | Op::Minu
+            | Op::Orn
+            | Op::Rol
+            | Op::Rolw
+            | Op::Ror
+            | Op::Rorw
+            | Op::Sh1add
+            | Op::Sh1addUw
+            | Op::Sh2add
+            | Op::Sh2addUw
+            | Op::Sh3add
+            | Op::Sh3addUw
+            | Op::Xnor
+            | Op::Aes64ds
+            | Op::Aes64dsm
+            | Op::Aes64es
+            | Op::Aes64esm
+            | Op::Aes64ks2
+            | Op::Pack
+            | Op::Packh
+            | Op::Packw
+            | Op::Sha512sig0h
+            | Op::Sha512sig0l
+            | Op::Sha512sig1h
+            | Op::Sha512sig1l
+            | Op::Sha512sum0r
+            | Op::Sha512sum1r
+            | Op::Xperm4
+            | Op::Xperm8
+            | Op::FeqD
+            | Op::FeqH
+            | Op::FeqQ
+            | Op::FeqS
+            | Op::FleD
+            | Op::FleH
+            | Op::FleQ
+            | Op::FleS
+            | Op::FleqD
+            | Op::FleqH
+            | Op::FleqQ
+            | Op::FleqS
+            | Op::FltD
+            | Op::FltH
+            | Op::FltQ
+            | Op::FltS
+            | Op::FltqD
+            | Op::FltqH
+            | Op::FltqQ
+            | Op::FltqS
+            | Op::FmaxD
+            | Op::FmaxH
+            | Op::FmaxQ
+            | Op::FmaxS
+            | Op::FmaxmD
+            | Op::FmaxmH
+            | Op::FmaxmQ
+            | Op::FmaxmS
+            | Op::FminD
+            | Op::FminH
+            | Op::FminQ
+            | Op::FminS
+            | Op::FminmD
+            | Op::FminmH
+            | Op::FminmQ
+            | Op::FminmS
+            | Op::FmvpDX
+            | Op::FmvpQX
+            | Op::FsgnjD
+            | Op::FsgnjH
+            | Op::FsgnjQ
+            | Op::FsgnjS
+            | Op::FsgnjnD
+            | Op::FsgnjnH
+            | Op::FsgnjnQ
+            | Op::FsgnjnS
+            | Op::FsgnjxD
+            | Op::FsgnjxH
+            | Op::FsgnjxQ
+            | Op::FsgnjxS => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.rs2 = self.register(operands[2])?;
+            }
+            Op::Addi | Op::Addiw | Op::Andi | Op::Ori | Op::Slti | Op::Sltiu | Op::Xori => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.immediate = self.immediate(operands[2])?;
+            }
+            Op::Slli
+            | Op::Slliw
+            | Op::Srai
+            | Op::Sraiw
+            | Op::Srli
+            | Op::Srliw
+            | Op::Bclri
+            | Op::Bexti
+            | Op::Binvi
+            | Op::Bseti
+            | Op::Rori
+            | Op::Roriw
+            | Op::SlliUw => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.shamt = self.shamt(operands[2])?;
+            }
+            Op::Aes32dsi | Op::Aes32dsmi | Op::Aes32esi | Op::Aes32esmi | Op::Sm4ed | Op::Sm4ks => {
+                self.count(operands, 4)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.rs2 = self.register(operands[2])?;
+                i.immediate = self.immediate(operands[3])?;
+            }
+            Op::Aes64ks1i => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.immediate = self.immediate(operands[2])?;
+            }
+            Op::FliD | Op::FliH | Op::FliQ | Op::FliS => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.immediate = self.fli_constant(operands[1])?;
+            }
+            // The rounding mode of fcvtmod.w.d is fixed, but still written
+            Op::FcvtmodWD => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                if operands[2] != "rtz" {
+                    return Err(self.invalid(operands[2]));
+                }
+                i.rm = Some(RoundingMode::Rtz);
+            }
+            Op::Auipc | Op::Lui | Op::CLui => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.immediate = self.immediate(operands[1])?;
+            }
+            Op::Beq | Op::Bge | Op::Bgeu | Op::Blt | Op::Bltu | Op::Bne => {
+                self.count(operands, 3)?;
+                i.rs1 = self.register(operands[0])?;
+                i.rs2 = self.register(operands[1])?;
+                i.immediate = self.target(operands[2])?;
+            }
+            // `jal target` links ra
+            Op::Jal => {
+                let target = match operands.len() {
+                    1 => {
+                        i.rd = Register::Ra;
+                        operands[0]
+                    }
+                    _ => {
+                        self.count(operands, 2)?;
+                        i.rd = self.register(operands[0])?;
+                        operands[1]
+                    }
+                };
+                i.immediate = self.target(target)?;
+            }
+            // `jalr rs1` links ra, and the target may also be written as
+            // `rs1, offset`
+            Op::Jalr => match operands.len() {
+                1 => {
+                    i.rd = Register::Ra;
+                    let (offset, base) = self.jump_target(operands[0])?;
+                    i.immediate = offset;
+                    i.rs1 = base;
+                }
+                2 => {
+                    i.rd = self.register(operands[0])?;
+                    let (offset, base) = self.jump_target(operands[1])?;
+                    i.immediate = offset;
+                    i.rs1 = base;
+                }
+                _ => {
+                    self.count(operands, 3)?;
+                    i.rd = self.register(operands[0])?;
+                    i.rs1 = self.register(operands[1])?;
+                    i.immediate = self.immediate(operands[2])?;
+                }
+            },
+            Op::Fld
+            | Op::Flh
+            | Op::Flq
+            | Op::Flw
+            | Op::Lb
+            | Op::Lbu
+            | Op::Ld
+            | Op::Lh
+            | Op::Lhu
+            | Op::Lw
+            | Op::Lwu
+            | Op::CFld
+            | Op::CFldsp
+            | Op::CFlw
+            | Op::CFlwsp
+            | Op::CLd
+            | Op::CLdsp
+            | Op::CLw
+            | Op::CLwsp => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                let (offset, base) = self.memory(operands[1])?;
+                i.immediate = offset;
+                i.rs1 = base;
+            }
+            Op::Fsd
+            | Op::Fsh
+            | Op::Fsq
+            | Op::Fsw
+            | Op::Sb
+            | Op::Sd
+            | Op::Sh
+            | Op::Sw
+            | Op::CFsd
+            | Op::CFsdsp
+            | Op::CFsw
+            | Op::CFswsp
+            | Op::CSd
+            | Op::CSdsp
+            | Op::CSw
+            | Op::CSwsp => {
+                self.count(operands, 2)?;
+                i.rs2 = self.register(operands[0])?;
+                let (offset, base) = self.memory(operands[1])?;
+                i.immediate = offset;
+                i.rs1 = base;
+            }
+            Op::Csrrc | Op::Csrrs | Op::Csrrw => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.csr = Some(self.csr(operands[1])?);
+                i.rs1 = self.register(operands[2])?;
+            }
+            Op::Csrrci | Op::Csrrsi | Op::Csrrwi => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.csr = Some(self.csr(operands[1])?);
+                i.immediate = self.immediate(operands[2])?;
+            }
+            Op::Ebreak
+            | Op::Ecall
+            | Op::CEbreak
+            | Op::Dret
+            | Op::Mret
+            | Op::Sret
+            | Op::Uret
+            | Op::Wfi
+            | Op::SfenceInvalIr
+            | Op::SfenceWInval
+            | Op::Pause
+            | Op::NtlAll
+            | Op::NtlP1
+            | Op::NtlPall
+            | Op::NtlS1
+            | Op::CNtlAll
+            | Op::CNtlP1
+            | Op::CNtlPall
+            | Op::CNtlS1 => self.count(operands, 0)?,
+            Op::CboClean | Op::CboFlush | Op::CboInval | Op::CboZero => {
+                self.count(operands, 1)?;
+                i.rs1 = self.atomic_address(operands[0])?;
+            }
+            Op::PrefetchI | Op::PrefetchR | Op::PrefetchW => {
+                self.count(operands, 1)?;
+                let (offset, base) = self.memory(operands[0])?;
+                i.immediate = offset;
+                i.rs1 = base;
+            }
+            // The address and ASID default to x0, which covers every
+            // address or every address space
+            Op::SfenceVma
+            | Op::SinvalVma
+            | Op::HfenceGvma
+            | Op::HfenceVvma
+            | Op::HinvalGvma
+            | Op::HinvalVvma => {
+                if operands.len() > 2 {
+                    return Err(AssembleError::OperandCount(self.line));
+                }
+                i.rd = Register::Zero;
+                i.rs1 = match operands.first() {
+                    Some(operand) => self.register(operand)?,
+                    None => Register::Zero,
+                };
+                i.rs2 = match operands.get(1) {
+                    Some(operand) => self.register(operand)?,
+                    None => Register::Zero,
+                };
+            }
+            Op::FenceI => {
+                self.count(operands, 0)?;
+                i.rd = Register::Zero;
+                i.rs1 = Register::Zero;
+                i.fence = Some(Fence {
+                    fm: 0,
+                    pi: false,
+                    po: false,
+                    pr: false,
+                    pw: false,
+                    si: false,
+                    so: false,
+                    sr: false,
+                    sw: false,
+                });
+            }
+            Op::FenceTso => {
+                self.count(operands, 0)?;
+                i.rd = Register::Zero;
+                i.rs1 = Register::Zero;
+                i.fence = Some(Fence {
+                    fm: 0b1000,
+                    pi: false,
+                    po: false,
+                    pr: true,
+                    pw: true,
+                    si: false,
+                    so: false,
+                    sr: true,
+                    sw: true,
+                });
+            }
+            // A bare `fence` orders everything
+            Op::Fence => {
+                let (predecessor, successor) = match operands.len() {
+                    0 => ("iorw", "iorw"),
+                    _ => {
+                        self.count(operands, 2)?;
+                        (operands[0], operands[1])
+                    }
+                };
+                let predecessor = self.fence_set(predecessor)?;
+                let successor = self.fence_set(successor)?;
+                i.rd = Register::Zero;
+                i.rs1 = Register::Zero;
+                i.fence = Some(Fence {
+                    fm: 0,
+                    pi: predecessor.i,
+                    po: predecessor.o,
+                    pr: predecessor.r,
+                    pw: predecessor.w,
+                    si: successor.i,
+                    so: successor.o,
+                    sr: successor.r,
+                    sw: successor.w,
+                });
+            }
+            Op::RdCycle
+            | Op::RdCycleH
+            | Op::RdInstRet
+            | Op::RdInstRetH
+            | Op::RdTime
+            | Op::RdTimeH => {
+                self.count(operands, 1)?;
+                i.rd = self.register(operands[0])?;
+            }
+            Op::HlvB
+            | Op::HlvBu
+            | Op::HlvD
+            | Op::HlvH
+            | Op::HlvHu
+            | Op::HlvW
+            | Op::HlvWu
+            | Op::HlvxHu
+            | Op::HlvxWu => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.atomic_address(operands[1])?;
+            }
+            Op::HsvB | Op::HsvD | Op::HsvH | Op::HsvW => {
+                self.count(operands, 2)?;
+                i.rs2 = self.register(operands[0])?;
+                i.rs1 = self.atomic_address(operands[1])?;
+            }
+            Op::LrD | Op::LrW => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.atomic_address(operands[1])?;
+            }
+            Op::ScD
+            | Op::ScW
+            | Op::AmoaddD
+            | Op::AmoaddW
+            | Op::AmoandD
+            | Op::AmoandW
+            | Op::AmomaxD
+            | Op::AmomaxW
+            | Op::AmomaxuD
+            | Op::AmomaxuW
+            | Op::AmominD
+            | Op::AmominW
+            | Op::AmominuD
+            | Op::AmominuW
+            | Op::AmoorD
+            | Op::AmoorW
+            | Op::AmoswapD
+            | Op::AmoswapW
+            | Op::AmoxorD
+            | Op::AmoxorW => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs2 = self.register(operands[1])?;
+                i.rs1 = self.atomic_address(operands[2])?;
+            }
+            Op::FaddD
+            | Op::FaddH
+            | Op::FaddQ
+            | Op::FaddS
+            | Op::FdivD
+            | Op::FdivH
+            | Op::FdivQ
+            | Op::FdivS
+            | Op::FmulD
+            | Op::FmulH
+            | Op::FmulQ
+            | Op::FmulS
+            | Op::FsubD
+            | Op::FsubH
+            | Op::FsubQ
+            | Op::FsubS => {
+                i.rm = self.rounding_mode(&op, operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.rs2 = self.register(operands[2])?;
+            }
+            Op::FmaddD
+            | Op::FmaddH
+            | Op::FmaddQ
+            | Op::FmaddS
+            | Op::FmsubD
+            | Op::FmsubH
+            | Op::FmsubQ
+            | Op::FmsubS
+            | Op::FnmaddD
+            | Op::FnmaddH
+            | Op::FnmaddQ
+            | Op::FnmaddS
+            | Op::FnmsubD
+            | Op::FnmsubH
+            | Op::FnmsubQ
+            | Op::FnmsubS => {
+                i.rm = self.rounding_mode(&op, operands, 4)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.rs2 = self.register(operands[2])?;
+                i.rs3 = self.register(operands[3])?;
+            }
+            Op::FcvtDH
+            | Op::FcvtDL
+            | Op::FcvtDLu
+            | Op::FcvtDQ
+            | Op::FcvtDS
+            | Op::FcvtDW
+            | Op::FcvtDWu
+            | Op::FcvtHD
+            | Op::FcvtHL
+            | Op::FcvtHLu
+            | Op::FcvtHQ
+            | Op::FcvtHS
+            | Op::FcvtHW
+            | Op::FcvtHWu
+            | Op::FcvtLD
+            | Op::FcvtLH
+            | Op::FcvtLQ
+            | Op::FcvtLS
+            | Op::FcvtLuD
+            | Op::FcvtLuH
+            | Op::FcvtLuQ
+            | Op::FcvtLuS
+            | Op::FcvtQD
+            | Op::FcvtQH
+            | Op::FcvtQL
+            | Op::FcvtQLu
+            | Op::FcvtQS
+            | Op::FcvtQW
+            | Op::FcvtQWu
+            | Op::FcvtSD
+            | Op::FcvtSH
+            | Op::FcvtSL
+            | Op::FcvtSLu
+            | Op::FcvtSQ
+            | Op::FcvtSW
+            | Op::FcvtSWu
+            | Op::FcvtWD
+            | Op::FcvtWH
+            | Op::FcvtWQ
+            | Op::FcvtWS
+            | Op::FcvtWuD
+            | Op::FcvtWuH
+            | Op::FcvtWuQ
+            | Op::FcvtWuS
+            | Op::FroundD
+            | Op::FroundH
+            | Op::FroundQ
+            | Op::FroundS
+            | Op::FroundnxD
+            | Op::FroundnxH
+            | Op::FroundnxQ
+            | Op::FroundnxS
+            | Op::FsqrtD
+            | Op::FsqrtH
+            | Op::FsqrtQ
+            | Op::FsqrtS => {
+                i.rm = self.rounding_mode(&op, operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+            }
+            Op::FclassD
+            | Op::FclassH
+            | Op::FclassQ
+            | Op::FclassS
+            | Op::FmvDX
+            | Op::FmvHX
+            | Op::FmvWX
+            | Op::FmvXD
+            | Op::FmvXH
+            | Op::FmvXW
+            | Op::FmvhXD
+            | Op::FmvhXQ
+            | Op::Clz
+            | Op::Clzw
+            | Op::Cpop
+            | Op::Cpopw
+            | Op::Ctz
+            | Op::Ctzw
+            | Op::OrcB
+            | Op::Rev8
+            | Op::SextB
+            | Op::SextH
+            | Op::ZextH
+            | Op::Aes64im
+            | Op::Brev8
+            | Op::Sha256sig0
+            | Op::Sha256sig1
+            | Op::Sha256sum0
+            | Op::Sha256sum1
+            | Op::Sha512sig0
+            | Op::Sha512sig1
+            | Op::Sha512sum0
+            | Op::Sha512sum1
+            | Op::Sm3p0
+            | Op::Sm3p1
+            | Op::Unzip
+            | Op::Zip => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+            }
+            Op::CAddi4spn => {
+                self.count(operands, 3)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = self.register(operands[1])?;
+                i.immediate = self.immediate(operands[2])?;
+            }
+            Op::CAddi | Op::CAddi16sp | Op::CAddiw | Op::CAndi | Op::CLi => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = match op {
+                    Op::CLi => Register::Zero,
+                    _ => i.rd.clone(),
+                };
+                i.immediate = self.immediate(operands[1])?;
+            }
+            Op::CNop => {
+                if !operands.is_empty() {
+                    self.count(operands, 1)?;
+                    i.immediate = self.immediate(operands[0])?;
+                }
+                i.rd = Register::Zero;
+                i.rs1 = Register::Zero;
+            }
+            Op::CSlli | Op::CSrai | Op::CSrli => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = i.rd.clone();
+                i.shamt = self.shamt(operands[1])?;
+            }
+            Op::CAdd
+            | Op::CAddw
+            | Op::CAnd
+            | Op::CMv
+            | Op::COr
+            | Op::CSub
+            | Op::CSubw
+            | Op::CXor => {
+                self.count(operands, 2)?;
+                i.rd = self.register(operands[0])?;
+                i.rs1 = match op {
+                    Op::CMv => Register::Zero,
+                    _ => i.rd.clone(),
+                };
+                i.rs2 = self.register(operands[1])?;
+            }
+            Op::CJ | Op::CJal => {
+                self.count(operands, 1)?;
+                i.rd = match op {
+                    Op::CJal => Register::Ra,
+                    _ => Register::Zero,
+                };
+                i.immediate = self.target(operands[0])?;
+            }
+            Op::CJalr | Op::CJr => {
+                self.count(operands, 1)?;
+                i.rd = match op {
+                    Op::CJalr => Register::Ra,
+                    _ => Register::Zero,
+                };
+                i.rs1 = self.register(operands[0])?;
+            }
+            Op::CBeqz | Op::CBnez => {
+                self.count(operands, 2)?;
+                i.rs1 = self.register(operands[0])?;
+                i.rs2 = Register::Zero;
+                i.immediate = self.target(operands[1])?;
+            }
+            Op::Vector(op) => return self.vector(i, op, operands),
+        }
+        Ok(i)
+    }
+}
+
+/// Checks that `isa` has an instruction, by decoding its encoding. The
+/// compressed instructions which only exist for one XLEN decode as other
+/// instructions for the other, so those are checked against the XLEN too.
+fn check_isa(instruction: &Instruction, word: u32, isa: &Isa) -> Result<(), DecodeError> {
+    if !instruction.op().is_compressed() {
+        return decode_isa(word, isa).map(|_| ());
+    }
+    decode_compressed(word as u16, isa)?;
+    match required_xlen(instruction.op()) {
+        Some(xlen) if xlen != isa.xlen() => Err(DecodeError::ExtensionDisabled),
+        _ => Ok(()),
+    }
+}
+
+/// Assembles `source` into instructions and their encodings
+fn assemble_encoded(source: &str, isa: &Isa) -> Result<Vec<(Instruction, u32)>, AssembleError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+    let mut address = 0;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let mut text = match text.find('#') {
+            Some(comment) => &text[..comment],
+            None => text,
+        }
+        .trim();
+
+        while let Some(colon) = text.find(':') {
+            let label = text[..colon].trim();
+            if !is_label(label) {
+                return Err(AssembleError::InvalidOperand(line, label.to_string()));
+            }
+            if labels.insert(label, address).is_some() {
+                return Err(AssembleError::DuplicateLabel(line, label.to_string()));
+            }
+            text = text[colon + 1..].trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = match text.find(char::is_whitespace) {
+            Some(space) => (&text[..space], text[space..].trim()),
+            None => (text, ""),
+        };
+        let operands: Vec<&str> = match operands {
+            "" => Vec::new(),
+            operands => operands.split(',').map(str::trim).collect(),
+        };
+
+        // call and tail are an auipc and a jalr through the link register
+        let pair = match (mnemonic, &operands[..]) {
+            ("call", &[target]) => Some(vec!["ra", "ra", target]),
+            ("tail", &[target]) => Some(vec!["t1", "zero", target]),
+            _ => None,
+        };
+        if let Some(operands) = pair {
+            for (op, half) in [(Op::Auipc, Half::Auipc), (Op::Jalr, Half::Jalr)] {
+                statements.push(Statement {
+                    line,
+                    address,
+                    instruction: Instruction::new(op),
+                    operands: operands.clone(),
+                    half: Some(half),
+                });
+            }
+            address += 8;
+            continue;
+        }
+
+        let (mnemonic, operands) =
+            expand_pseudo(mnemonic, &operands).unwrap_or((mnemonic, operands));
+        let instruction = lookup(mnemonic, isa.xlen())
+            .ok_or_else(|| AssembleError::UnknownMnemonic(line, mnemonic.to_string()))?;
+        let length = instruction.length() as u64;
+        statements.push(Statement {
+            line,
+            address,
+            instruction,
+            operands,
+            half: None,
+        });
+        address += length;
+    }
+
+    statements
+        .into_iter()
+        .map(|statement| {
+            let line = statement.line;
+            let parser = Parser {
+                line: statement.line,
+                address: statement.address,
+                labels: &labels,
+            };
+            let instruction = match statement.half {
+                Some(ref half) => parser.half(statement.instruction, &statement.operands, half)?,
+                None => parser.instruction(statement.instruction, &statement.operands)?,
+            };
+            let word = encode(&instruction).map_err(|error| AssembleError::Encode(line, error))?;
+            check_isa(&instruction, word, isa)
+                .map_err(|error| AssembleError::NotInIsa(line, error))?;
+            Ok((instruction, word))
+        })
+        .collect()
+}
+
+/// Assemble GNU-style RISC-V assembly into instructions, for RV64 with
+/// every extension other than E. See `assemble_isa`.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, AssembleError> {
+    assemble_isa(source, &Isa::all(Xlen::Rv64))
+}
+
+/// Assemble GNU-style RISC-V assembly, as accepted by `assemble`, into
+/// little-endian machine code
+pub fn assemble_bytes(source: &str) -> Result<Vec<u8>, AssembleError> {
+    assemble_bytes_isa(source, &Isa::all(Xlen::Rv64))
+}
+
+/// Assemble GNU-style RISC-V assembly into instructions for `isa`.
+///
+/// Each line holds an instruction, such as `addi a0, a1, -3`, optionally
+/// preceded by `label:` definitions, and `#` starts a comment. Registers
+/// are written by ABI name or as `x0`-`x31` and `f0`-`f31`, immediates in
+/// decimal, `0x` hexadecimal or `0b` binary, memory operands as
+/// `offset(base)`, and CSRs by name or address. A branch or jump target is
+/// either a label or a number, which is taken as an offset from the
+/// instruction, as `Instruction`'s `Display` prints it.
+///
+/// Pseudo-instructions are accepted as `Instruction`'s alternate `Display`
+/// writes them, such as `mv a0, a1` or `ret`, and `call` and `tail`
+/// assemble to an `auipc` followed by a `jalr`.
+///
+/// The first instruction is placed at address 0, and each takes 4 bytes,
+/// or 2 for compressed instructions. Every instruction is checked by
+/// encoding it, and then by decoding the encoding for `isa`, so that
+/// instructions `decode_isa` would reject are rejected with
+/// `AssembleError::NotInIsa`.
+pub fn assemble_isa(source: &str, isa: &Isa) -> Result<Vec<Instruction>, AssembleError> {
+    Ok(assemble_encoded(source, isa)?
+        .into_iter()
+        .map(|(instruction, _)| instruction)
+        .collect())
+}
+
+/// Assemble GNU-style RISC-V assembly for `isa`, as accepted by
+/// `assemble_isa`, into little-endian machine code
+pub fn assemble_bytes_isa(source: &str, isa: &Isa) -> Result<Vec<u8>, AssembleError> {
+    let mut bytes = Vec::new();
+    for (instruction, word) in assemble_encoded(source, isa)? {
+        let length = instruction.length();
+        bytes.extend_from_slice(&word.to_le_bytes()[..length]);
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,1682 @@
+use compressed::decode_compressed;
+use decoder::{check_registers, instruction_length};
+use error::DecodeError;
+use isa::{Extension, Isa, Xlen};
+use {Aqrl, Csr, Fence, Instruction, Op, Register, RoundingMode};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OpType {
+    B,
+    R,
+    I,
+    S,
+    U,
+    Uj,
+    Shift,
+    ShiftW,
+    Amo,
+    FLoad,
+    FStore,
+    R4,
+    /// Floating point R-type with funct3 selecting the operation
+    Fp,
+    /// Floating point R-type with funct3 holding the rounding mode
+    FpRm,
+    /// Floating point with a single source, rs2 selecting the operation
+    FpUnary(u32),
+    /// As `FpUnary`, with funct3 holding the rounding mode
+    FpUnaryRm(u32),
+    Special,
+}
+
+/// An opcode table, and the extension and base integer width it requires
+struct OpcodeTable {
+    extension: Extension,
+    rv64_only: bool,
+    opcodes: &'static [Opcode],
+}
+
+/// This struct is used to hold instruction information for decoding
+#[derive(Clone, Debug)]
+struct Opcode {
+    funct3: u32,
+    funct7: u32,
+    opcode: u32,
+    op: Op,
+    op_type: OpType,
+}
+
+use self::OpType::*;
+use Op::*;
+
+const RV32I_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0,
+        funct3: 0,
+        opcode: 0b0110111,
+        op: Lui,
+        op_type: U,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0,
+        opcode: 0b0010111,
+        op: Auipc,
+        op_type: U,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0,
+        opcode: 0b1101111,
+        op: Jal,
+        op_type: Uj,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b1100111,
+        op: Jalr,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b1100011,
+        op: Beq,
+        op_type: OpType::B,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b001,
+        opcode: 0b1100011,
+        op: Bne,
+        op_type: OpType::B,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b100,
+        opcode: 0b1100011,
+        op: Blt,
+        op_type: OpType::B,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b101,
+        opcode: 0b1100011,
+        op: Bge,
+        op_type: OpType::B,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b110,
+        opcode: 0b1100011,
+        op: Bltu,
+        op_type: OpType::B,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b111,
+        opcode: 0b1100011,
+        op: Bgeu,
+        op_type: OpType::B,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b0000011,
+        op: Lb,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b001,
+        opcode: 0b0000011,
+        op: Lh,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b010,
+        opcode: 0b0000011,
+        op: Lw,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b100,
+        opcode: 0b0000011,
+        op: Lbu,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b101,
+        opcode: 0b0000011,
+        op: Lhu,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b0100011,
+        op: Op::Sb,
+        op_type: OpType::S,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b001,
+        opcode: 0b0100011,
+        op: Sh,
+        op_type: OpType::S,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b010,
+        opcode: 0b0100011,
+        op: Sw,
+        op_type: OpType::S,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b0010011,
+        op: Addi,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b010,
+        opcode: 0b0010011,
+        op: Slti,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b011,
+        opcode: 0b0010011,
+        op: Sltiu,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b100,
+        opcode: 0b0010011,
+        op: Xori,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b110,
+        opcode: 0b0010011,
+        op: Ori,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b111,
+        opcode: 0b0010011,
+        op: Andi,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b001,
+        opcode: 0b0010011,
+        op: Slli,
+        op_type: Shift,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b101,
+        opcode: 0b0010011,
+        op: Srli,
+        op_type: Shift,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b101,
+        opcode: 0b0010011,
+        op: Srai,
+        op_type: Shift,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b000,
+        opcode: 0b0110011,
+        op: Add,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b000,
+        opcode: 0b0110011,
+        op: Sub,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b001,
+        opcode: 0b0110011,
+        op: Sll,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b010,
+        opcode: 0b0110011,
+        op: Slt,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b011,
+        opcode: 0b0110011,
+        op: Sltu,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b100,
+        opcode: 0b0110011,
+        op: Xor,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b101,
+        opcode: 0b0110011,
+        op: Srl,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b101,
+        opcode: 0b0110011,
+        op: Sra,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b110,
+        opcode: 0b0110011,
+        op: Or,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b111,
+        opcode: 0b0110011,
+        op: And,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b0001111,
+        op: Fence,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b1110011,
+        op: Ecall,
+        op_type: Special,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b1110011,
+        op: Ebreak,
+        op_type: Special,
+    },
+];
+
+/// Integer multiply and divide encodings
+const RV32M_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b000,
+        opcode: 0b0110011,
+        op: Mul,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b001,
+        opcode: 0b0110011,
+        op: Mulh,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b010,
+        opcode: 0b0110011,
+        op: Mulhsu,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b011,
+        opcode: 0b0110011,
+        op: Mulhu,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b100,
+        opcode: 0b0110011,
+        op: Div,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b101,
+        opcode: 0b0110011,
+        op: Divu,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b110,
+        opcode: 0b0110011,
+        op: Rem,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b111,
+        opcode: 0b0110011,
+        op: Remu,
+        op_type: R,
+    },
+];
+
+/// Atomic memory operation encodings
+const RV32A_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0b0001000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: LrW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0001100,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: ScW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0000100,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmoswapW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmoaddW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0010000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmoxorW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0110000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmoandW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmoorW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1000000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmominW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1010000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmomaxW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1100000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmominuW,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1110000,
+        funct3: 0b010,
+        opcode: 0b0101111,
+        op: AmomaxuW,
+        op_type: Amo,
+    },
+];
+
+/// Control and status register encodings
+const ZICSR_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0,
+        funct3: 0b001,
+        opcode: 0b1110011,
+        op: Csrrw,
+        op_type: Special,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b010,
+        opcode: 0b1110011,
+        op: Csrrs,
+        op_type: Special,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b011,
+        opcode: 0b1110011,
+        op: Csrrc,
+        op_type: Special,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b101,
+        opcode: 0b1110011,
+        op: Csrrwi,
+        op_type: Special,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b110,
+        opcode: 0b1110011,
+        op: Csrrsi,
+        op_type: Special,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b111,
+        opcode: 0b1110011,
+        op: Csrrci,
+        op_type: Special,
+    },
+];
+
+/// Instruction-fetch fence encodings
+const ZIFENCEI_OPCODES: &[Opcode] = &[Opcode {
+    funct7: 0,
+    funct3: 0b001,
+    opcode: 0b0001111,
+    op: FenceI,
+    op_type: I,
+}];
+
+/// Encodings which only exist when XLEN is 64
+const RV64I_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0,
+        funct3: 0b110,
+        opcode: 0b0000011,
+        op: Lwu,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b011,
+        opcode: 0b0000011,
+        op: Ld,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b011,
+        opcode: 0b0100011,
+        op: Sd,
+        op_type: OpType::S,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b000,
+        opcode: 0b0011011,
+        op: Addiw,
+        op_type: I,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b001,
+        opcode: 0b0011011,
+        op: Slliw,
+        op_type: ShiftW,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b101,
+        opcode: 0b0011011,
+        op: Srliw,
+        op_type: ShiftW,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b101,
+        opcode: 0b0011011,
+        op: Sraiw,
+        op_type: ShiftW,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b000,
+        opcode: 0b0111011,
+        op: Addw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b000,
+        opcode: 0b0111011,
+        op: Subw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b001,
+        opcode: 0b0111011,
+        op: Sllw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b101,
+        opcode: 0b0111011,
+        op: Srlw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b101,
+        opcode: 0b0111011,
+        op: Sraw,
+        op_type: R,
+    },
+];
+
+/// Integer multiply and divide encodings which only exist when XLEN is 64
+const RV64M_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b000,
+        opcode: 0b0111011,
+        op: Mulw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b100,
+        opcode: 0b0111011,
+        op: Divw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b101,
+        opcode: 0b0111011,
+        op: Divuw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b110,
+        opcode: 0b0111011,
+        op: Remw,
+        op_type: R,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0b111,
+        opcode: 0b0111011,
+        op: Remuw,
+        op_type: R,
+    },
+];
+
+/// Atomic memory operation encodings which only exist when XLEN is 64
+const RV64A_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0b0001000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: LrD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0001100,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: ScD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0000100,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmoswapD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmoaddD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0010000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmoxorD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0110000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmoandD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmoorD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1000000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmominD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1010000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmomaxD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1100000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmominuD,
+        op_type: Amo,
+    },
+    Opcode {
+        funct7: 0b1110000,
+        funct3: 0b011,
+        opcode: 0b0101111,
+        op: AmomaxuD,
+        op_type: Amo,
+    },
+];
+
+/// Single-precision floating point encodings
+const RV32F_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0,
+        funct3: 0b010,
+        opcode: 0b0000111,
+        op: Flw,
+        op_type: FLoad,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b010,
+        opcode: 0b0100111,
+        op: Fsw,
+        op_type: FStore,
+    },
+    Opcode {
+        funct7: 0b00,
+        funct3: 0,
+        opcode: 0b1000011,
+        op: FmaddS,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b00,
+        funct3: 0,
+        opcode: 0b1000111,
+        op: FmsubS,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b00,
+        funct3: 0,
+        opcode: 0b1001011,
+        op: FnmsubS,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b00,
+        funct3: 0,
+        opcode: 0b1001111,
+        op: FnmaddS,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b0000000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FaddS,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0000100,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FsubS,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0001000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FmulS,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0001100,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FdivS,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0101100,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FsqrtS,
+        op_type: FpUnaryRm(0),
+    },
+    Opcode {
+        funct7: 0b0010000,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FsgnjS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010000,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FsgnjnS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010000,
+        funct3: 0b010,
+        opcode: 0b1010011,
+        op: FsgnjxS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010100,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FminS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010100,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FmaxS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1010000,
+        funct3: 0b010,
+        opcode: 0b1010011,
+        op: FeqS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1010000,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FltS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1010000,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FleS,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1110000,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FclassS,
+        op_type: FpUnary(0),
+    },
+    Opcode {
+        funct7: 0b1100000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtWS,
+        op_type: FpUnaryRm(0),
+    },
+    Opcode {
+        funct7: 0b1100000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtWuS,
+        op_type: FpUnaryRm(1),
+    },
+    Opcode {
+        funct7: 0b1101000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtSW,
+        op_type: FpUnaryRm(0),
+    },
+    Opcode {
+        funct7: 0b1101000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtSWu,
+        op_type: FpUnaryRm(1),
+    },
+    Opcode {
+        funct7: 0b1110000,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FmvXW,
+        op_type: FpUnary(0),
+    },
+    Opcode {
+        funct7: 0b1111000,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FmvWX,
+        op_type: FpUnary(0),
+    },
+];
+
+/// Double-precision floating point encodings
+const RV32D_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0,
+        funct3: 0b011,
+        opcode: 0b0000111,
+        op: Fld,
+        op_type: FLoad,
+    },
+    Opcode {
+        funct7: 0,
+        funct3: 0b011,
+        opcode: 0b0100111,
+        op: Fsd,
+        op_type: FStore,
+    },
+    Opcode {
+        funct7: 0b01,
+        funct3: 0,
+        opcode: 0b1000011,
+        op: FmaddD,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b01,
+        funct3: 0,
+        opcode: 0b1000111,
+        op: FmsubD,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b01,
+        funct3: 0,
+        opcode: 0b1001011,
+        op: FnmsubD,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b01,
+        funct3: 0,
+        opcode: 0b1001111,
+        op: FnmaddD,
+        op_type: R4,
+    },
+    Opcode {
+        funct7: 0b0000001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FaddD,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0000101,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FsubD,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0001001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FmulD,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0001101,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FdivD,
+        op_type: FpRm,
+    },
+    Opcode {
+        funct7: 0b0101101,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FsqrtD,
+        op_type: FpUnaryRm(0),
+    },
+    Opcode {
+        funct7: 0b0010001,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FsgnjD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010001,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FsgnjnD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010001,
+        funct3: 0b010,
+        opcode: 0b1010011,
+        op: FsgnjxD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010101,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FminD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b0010101,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FmaxD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1010001,
+        funct3: 0b010,
+        opcode: 0b1010011,
+        op: FeqD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1010001,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FltD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1010001,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FleD,
+        op_type: Fp,
+    },
+    Opcode {
+        funct7: 0b1110001,
+        funct3: 0b001,
+        opcode: 0b1010011,
+        op: FclassD,
+        op_type: FpUnary(0),
+    },
+    Opcode {
+        funct7: 0b1100001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtWD,
+        op_type: FpUnaryRm(0),
+    },
+    Opcode {
+        funct7: 0b1100001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtWuD,
+        op_type: FpUnaryRm(1),
+    },
+    Opcode {
+        funct7: 0b1101001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtDW,
+        op_type: FpUnaryRm(0),
+    },
+    Opcode {
+        funct7: 0b1101001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtDWu,
+        op_type: FpUnaryRm(1),
+    },
+    Opcode {
+        funct7: 0b0100000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtSD,
+        op_type: FpUnaryRm(1),
+    },
+    Opcode {
+        funct7: 0b0100001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtDS,
+        op_type: FpUnaryRm(0),
+    },
+];
+
+/// Single-precision floating point encodings which only exist when XLEN is 64
+const RV64F_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0b1100000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtLS,
+        op_type: FpUnaryRm(2),
+    },
+    Opcode {
+        funct7: 0b1100000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtLuS,
+        op_type: FpUnaryRm(3),
+    },
+    Opcode {
+        funct7: 0b1101000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtSL,
+        op_type: FpUnaryRm(2),
+    },
+    Opcode {
+        funct7: 0b1101000,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtSLu,
+        op_type: FpUnaryRm(3),
+    },
+];
+
+/// Double-precision floating point encodings which only exist when XLEN is 64
+const RV64D_OPCODES: &[Opcode] = &[
+    Opcode {
+        funct7: 0b1100001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtLD,
+        op_type: FpUnaryRm(2),
+    },
+    Opcode {
+        funct7: 0b1100001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtLuD,
+        op_type: FpUnaryRm(3),
+    },
+    Opcode {
+        funct7: 0b1101001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtDL,
+        op_type: FpUnaryRm(2),
+    },
+    Opcode {
+        funct7: 0b1101001,
+        funct3: 0,
+        opcode: 0b1010011,
+        op: FcvtDLu,
+        op_type: FpUnaryRm(3),
+    },
+    Opcode {
+        funct7: 0b1110001,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FmvXD,
+        op_type: FpUnary(0),
+    },
+    Opcode {
+        funct7: 0b1111001,
+        funct3: 0b000,
+        opcode: 0b1010011,
+        op: FmvDX,
+        op_type: FpUnary(0),
+    },
+];
+
+fn decode_r(opcode: &Opcode, word: u32) -> Instruction {
+    Instruction {
+        rs2: Register::from_u32((word >> 20) & 0x1f),
+        rs1: Register::from_u32((word >> 15) & 0x1f),
+        rd: Register::from_u32((word >> 7) & 0x1f),
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+fn decode_i(opcode: &Opcode, word: u32) -> Instruction {
+    Instruction {
+        rs1: Register::from_u32((word >> 15) & 0x1f),
+        rd: Register::from_u32((word >> 7) & 0x1f),
+        immediate: (word as i32) >> 20,
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+/// The funct7 bits of a shift immediate which are not part of a
+/// `shamt_bits` wide shift amount
+fn shift_funct_mask(shamt_bits: u32) -> u32 {
+    0x7f & !((1 << (shamt_bits - 5)) - 1)
+}
+
+fn decode_shift(opcode: &Opcode, word: u32, shamt_bits: u32) -> Instruction {
+    Instruction {
+        rs1: Register::from_u32((word >> 15) & 0x1f),
+        rd: Register::from_u32((word >> 7) & 0x1f),
+        shamt: ((word >> 20) & ((1 << shamt_bits) - 1)) as usize,
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+/// The aq and rl bits occupy the low two bits of funct7, and `lr` requires
+/// rs2 to be zero.
+fn decode_amo(opcode: &Opcode, word: u32) -> Result<Instruction, DecodeError> {
+    let rs2 = (word >> 20) & 0x1f;
+    let rs2 = match opcode.op {
+        Op::LrW | Op::LrD => {
+            if rs2 != 0 {
+                return Err(DecodeError::Reserved);
+            }
+            Register::Invalid
+        }
+        _ => Register::from_u32(rs2),
+    };
+
+    Ok(Instruction {
+        rs2,
+        rs1: Register::from_u32((word >> 15) & 0x1f),
+        rd: Register::from_u32((word >> 7) & 0x1f),
+        aqrl: Some(Aqrl {
+            aq: (word >> 26) & 1 == 1,
+            rl: (word >> 25) & 1 == 1,
+        }),
+        ..Instruction::new(opcode.op.clone())
+    })
+}
+
+/// Returns whether the rd, rs1 and rs2 fields of a floating point
+/// instruction name floating point registers, as opposed to integer ones.
+fn float_registers(op: &Op) -> (bool, bool, bool) {
+    match op {
+        Op::Flw | Op::Fld => (true, false, false),
+        Op::Fsw | Op::Fsd => (false, false, true),
+        Op::FcvtWS
+        | Op::FcvtWuS
+        | Op::FcvtLS
+        | Op::FcvtLuS
+        | Op::FcvtWD
+        | Op::FcvtWuD
+        | Op::FcvtLD
+        | Op::FcvtLuD
+        | Op::FmvXW
+        | Op::FmvXD
+        | Op::FclassS
+        | Op::FclassD
+        | Op::FeqS
+        | Op::FeqD
+        | Op::FltS
+        | Op::FltD
+        | Op::FleS
+        | Op::FleD => (false, true, true),
+        Op::FcvtSW
+        | Op::FcvtSWu
+        | Op::FcvtSL
+        | Op::FcvtSLu
+        | Op::FcvtDW
+        | Op::FcvtDWu
+        | Op::FcvtDL
+        | Op::FcvtDLu
+        | Op::FmvWX
+        | Op::FmvDX => (true, false, false),
+        _ => (true, true, true),
+    }
+}
+
+fn register(u: u32, float: bool) -> Register {
+    if float {
+        Register::float_from_u32(u)
+    } else {
+        Register::from_u32(u)
+    }
+}
+
+fn rounding_mode(word: u32) -> Result<RoundingMode, DecodeError> {
+    RoundingMode::from_u32((word >> 12) & 0x7).ok_or(DecodeError::Reserved)
+}
+
+/// Decodes the OP-FP major opcode. When `rm` is set funct3 holds the
+/// rounding mode, and reserved rounding modes are rejected. Unary
+/// instructions have no rs2 operand.
+fn decode_fp(
+    opcode: &Opcode,
+    word: u32,
+    rm: bool,
+    unary: bool,
+) -> Result<Instruction, DecodeError> {
+    let (rd_float, rs1_float, rs2_float) = float_registers(&opcode.op);
+    let rm = if rm { Some(rounding_mode(word)?) } else { None };
+
+    Ok(Instruction {
+        rs2: if unary {
+            Register::Invalid
+        } else {
+            register((word >> 20) & 0x1f, rs2_float)
+        },
+        rs1: register((word >> 15) & 0x1f, rs1_float),
+        rd: register((word >> 7) & 0x1f, rd_float),
+        rm,
+        ..Instruction::new(opcode.op.clone())
+    })
+}
+
+/// Decodes the fused multiply-add family, which take a third source
+/// register in bits 31:27.
+fn decode_r4(opcode: &Opcode, word: u32) -> Result<Instruction, DecodeError> {
+    Ok(Instruction {
+        rs3: Register::float_from_u32((word >> 27) & 0x1f),
+        rs2: Register::float_from_u32((word >> 20) & 0x1f),
+        rs1: Register::float_from_u32((word >> 15) & 0x1f),
+        rd: Register::float_from_u32((word >> 7) & 0x1f),
+        rm: Some(rounding_mode(word)?),
+        ..Instruction::new(opcode.op.clone())
+    })
+}
+
+fn decode_b(opcode: &Opcode, word: u32) -> Instruction {
+    let mut immediate = ((word >> 7) & 1) << 11;
+    immediate |= ((word >> 8) & 0xf) << 1;
+    immediate |= ((word >> 25) & 0x3f) << 5;
+    immediate |= ((word >> 31) & 1) << 12;
+    if immediate & 0x1000 > 0 {
+        immediate |= 0xfffff000;
+    }
+    Instruction {
+        rs2: Register::from_u32((word >> 20) & 0x1f),
+        rs1: Register::from_u32((word >> 15) & 0x1f),
+        immediate: immediate as i32,
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+fn decode_s(opcode: &Opcode, word: u32) -> Instruction {
+    let mut immediate = (word >> 7) & 0x1f;
+    immediate |= ((word >> 25) & 0x7f) << 5;
+    if immediate & 0x800 > 0 {
+        immediate |= 0xfffff000;
+    }
+    Instruction {
+        rs2: Register::from_u32((word >> 20) & 0x1f),
+        rs1: Register::from_u32((word >> 15) & 0x1f),
+        immediate: immediate as i32,
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+fn decode_u(opcode: &Opcode, word: u32) -> Instruction {
+    Instruction {
+        rd: Register::from_u32((word >> 7) & 0x1f),
+        immediate: (word >> 12) as i32,
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+fn decode_uj(opcode: &Opcode, word: u32) -> Instruction {
+    let mut immediate = word & 0x000f_f000;
+    immediate |= ((word >> 20) & 1) << 11;
+    immediate |= ((word >> 21) & 0x3ff) << 1;
+    immediate |= ((word >> 31) & 1) << 20;
+    if immediate & 0x100000 > 0 {
+        immediate |= 0xffe00000;
+    }
+    Instruction {
+        rd: Register::from_u32((word >> 7) & 0x1f),
+        immediate: immediate as i32,
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+fn decode_fence(opcode: &Opcode, word: u32) -> Instruction {
+    let fence = Fence {
+        fm: word >> 28,
+        pi: (word >> 27) & 1 == 1,
+        po: (word >> 26) & 1 == 1,
+        pr: (word >> 25) & 1 == 1,
+        pw: (word >> 24) & 1 == 1,
+        si: (word >> 23) & 1 == 1,
+        so: (word >> 22) & 1 == 1,
+        sr: (word >> 21) & 1 == 1,
+        sw: (word >> 20) & 1 == 1,
+    };
+    Instruction {
+        rs1: Register::from_u32((word >> 15) & 0x1f),
+        rd: Register::from_u32((word >> 7) & 0x1f),
+        fence: Some(fence),
+        ..Instruction::new(opcode.op.clone())
+    }
+}
+
+fn decode_special(opcode: &Opcode, word: u32) -> Result<Instruction, DecodeError> {
+    let csr = Some(Csr::from_u32(word >> 20));
+    match opcode.op {
+        Op::Ecall | Op::Ebreak => {
+            // Every field other than the immediate selecting the call is zero
+            let op = match word {
+                0x00000073 => Op::Ecall,
+                0x00100073 => Op::Ebreak,
+                _ => return Err(DecodeError::Reserved),
+            };
+            Ok(Instruction::new(op))
+        }
+        Op::Csrrc | Op::Csrrs | Op::Csrrw => Ok(Instruction {
+            rs1: Register::from_u32((word >> 15) & 0x1f),
+            rd: Register::from_u32((word >> 7) & 0x1f),
+            csr,
+            ..Instruction::new(opcode.op.clone())
+        }),
+        Op::Csrrci | Op::Csrrsi | Op::Csrrwi => Ok(Instruction {
+            rd: Register::from_u32((word >> 7) & 0x1f),
+            csr,
+            immediate: ((word >> 15) & 0x1f) as i32,
+            ..Instruction::new(opcode.op.clone())
+        }),
+        _ => Err(DecodeError::Reserved),
+    }
+}
+
+const OPCODE_TABLES: &[OpcodeTable] = &[
+    OpcodeTable {
+        extension: Extension::I,
+        rv64_only: false,
+        opcodes: RV32I_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::M,
+        rv64_only: false,
+        opcodes: RV32M_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::A,
+        rv64_only: false,
+        opcodes: RV32A_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::F,
+        rv64_only: false,
+        opcodes: RV32F_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::D,
+        rv64_only: false,
+        opcodes: RV32D_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::Zicsr,
+        rv64_only: false,
+        opcodes: ZICSR_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::Zifencei,
+        rv64_only: false,
+        opcodes: ZIFENCEI_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::I,
+        rv64_only: true,
+        opcodes: RV64I_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::M,
+        rv64_only: true,
+        opcodes: RV64M_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::A,
+        rv64_only: true,
+        opcodes: RV64A_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::F,
+        rv64_only: true,
+        opcodes: RV64F_OPCODES,
+    },
+    OpcodeTable {
+        extension: Extension::D,
+        rv64_only: true,
+        opcodes: RV64D_OPCODES,
+    },
+];
+
+/// Decode a 32-bit instruction by scanning the opcode tables of the
+/// extensions enabled in `isa`
+pub fn decode_isa(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    // The all-zero and all-ones words are defined to be illegal
+    if word == 0 || word == 0xffff_ffff {
+        return Err(DecodeError::Illegal);
+    }
+
+    if let Some(result) = lookup(word, isa) {
+        return result.and_then(|instruction| check_registers(instruction, isa));
+    }
+
+    if lookup(word, &Isa::all(Xlen::Rv64)).is_some() {
+        return Err(DecodeError::ExtensionDisabled);
+    }
+
+    let opcode = word & 0x7f;
+    if OPCODE_TABLES
+        .iter()
+        .flat_map(|table| table.opcodes.iter())
+        .any(|o| o.opcode == opcode)
+    {
+        Err(DecodeError::Reserved)
+    } else {
+        Err(DecodeError::UnknownOpcode(opcode))
+    }
+}
+
+/// Finds the opcode table entry matching `word` and decodes it, or returns
+/// `None` if no entry matches.
+fn lookup(word: u32, isa: &Isa) -> Option<Result<Instruction, DecodeError>> {
+    let shamt_bits = match isa.xlen() {
+        Xlen::Rv32 => 5,
+        Xlen::Rv64 => 6,
+    };
+
+    let opcodes = OPCODE_TABLES
+        .iter()
+        .filter(|table| {
+            let enabled = isa.has(table.extension)
+                || (table.extension == Extension::I && isa.has(Extension::E));
+            enabled && (!table.rv64_only || isa.xlen() == Xlen::Rv64)
+        })
+        .flat_map(|table| table.opcodes.iter());
+
+    let opcode = word & 0x7f;
+    let funct3 = (word >> 12) & 0x7;
+    let funct7 = (word >> 25) & 0x7f;
+
+    for o in opcodes {
+        match o.op_type {
+            OpType::R => {
+                if opcode == o.opcode && funct3 == o.funct3 && funct7 == o.funct7 {
+                    return Some(Ok(decode_r(o, word)));
+                }
+            }
+            OpType::I => {
+                if opcode == o.opcode && funct3 == o.funct3 {
+                    if o.op == Fence || o.op == FenceI {
+                        return Some(Ok(decode_fence(o, word)));
+                    }
+                    return Some(Ok(decode_i(o, word)));
+                }
+            }
+            OpType::B => {
+                if opcode == o.opcode && funct3 == o.funct3 {
+                    return Some(Ok(decode_b(o, word)));
+                }
+            }
+            OpType::S => {
+                if opcode == o.opcode && funct3 == o.funct3 {
+                    return Some(Ok(decode_s(o, word)));
+                }
+            }
+            OpType::U => {
+                if opcode == o.opcode {
+                    return Some(Ok(decode_u(o, word)));
+                }
+            }
+            OpType::Uj => {
+                if opcode == o.opcode {
+                    return Some(Ok(decode_uj(o, word)));
+                }
+            }
+            OpType::Shift => {
+                if opcode == o.opcode
+                    && funct3 == o.funct3
+                    && funct7 & shift_funct_mask(shamt_bits) == o.funct7
+                {
+                    return Some(Ok(decode_shift(o, word, shamt_bits)));
+                }
+            }
+            OpType::ShiftW => {
+                if opcode == o.opcode && funct3 == o.funct3 && funct7 == o.funct7 {
+                    return Some(Ok(decode_shift(o, word, 5)));
+                }
+            }
+            OpType::Amo => {
+                if opcode == o.opcode && funct3 == o.funct3 && funct7 >> 2 == o.funct7 >> 2 {
+                    return Some(decode_amo(o, word));
+                }
+            }
+            OpType::FLoad => {
+                if opcode == o.opcode && funct3 == o.funct3 {
+                    let mut instruction = decode_i(o, word);
+                    instruction.rd = Register::float_from_u32((word >> 7) & 0x1f);
+                    return Some(Ok(instruction));
+                }
+            }
+            OpType::FStore => {
+                if opcode == o.opcode && funct3 == o.funct3 {
+                    let mut instruction = decode_s(o, word);
+                    instruction.rs2 = Register::float_from_u32((word >> 20) & 0x1f);
+                    return Some(Ok(instruction));
+                }
+            }
+            OpType::R4 => {
+                if opcode == o.opcode && funct7 & 0x3 == o.funct7 {
+                    return Some(decode_r4(o, word));
+                }
+            }
+            OpType::Fp => {
+                if opcode == o.opcode && funct3 == o.funct3 && funct7 == o.funct7 {
+                    return Some(decode_fp(o, word, false, false));
+                }
+            }
+            OpType::FpRm => {
+                if opcode == o.opcode && funct7 == o.funct7 {
+                    return Some(decode_fp(o, word, true, false));
+                }
+            }
+            OpType::FpUnary(rs2) => {
+                if opcode == o.opcode
+                    && funct3 == o.funct3
+                    && funct7 == o.funct7
+                    && (word >> 20) & 0x1f == rs2
+                {
+                    return Some(decode_fp(o, word, false, true));
+                }
+            }
+            OpType::FpUnaryRm(rs2) => {
+                if opcode == o.opcode && funct7 == o.funct7 && (word >> 20) & 0x1f == rs2 {
+                    return Some(decode_fp(o, word, true, true));
+                }
+            }
+            OpType::Special => {
+                if opcode == o.opcode && funct3 == o.funct3 {
+                    return Some(decode_special(o, word));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Decode the instruction at the start of `bytes` as `decode_bytes` does,
+/// with 32-bit instructions decoded by the table decoder
+pub fn decode_bytes(bytes: &[u8], isa: &Isa) -> Result<(Instruction, usize), DecodeError> {
+    if bytes.len() < 2 {
+        return Err(DecodeError::Truncated(2));
+    }
+    let parcel = u16::from(bytes[0]) | u16::from(bytes[1]) << 8;
+
+    // Encodings of 192 bits or more are reserved, and include the illegal
+    // all-ones parcel
+    let length = instruction_length(parcel).ok_or(DecodeError::Illegal)?;
+    if length > 4 {
+        return Err(DecodeError::UnsupportedLength(length));
+    }
+    if bytes.len() < length {
+        return Err(DecodeError::Truncated(length));
+    }
+
+    let instruction = if length == 2 {
+        decode_compressed(parcel, isa)
+    } else {
+        let word = u32::from(parcel) | u32::from(bytes[2]) << 16 | u32::from(bytes[3]) << 24;
+        decode_isa(word, isa)
+    };
+
+    instruction.map(|instruction| (instruction, length))
+}
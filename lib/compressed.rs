@@ -0,0 +1,521 @@
+use decoder::check_registers;
+use error::DecodeError;
+use isa::{Extension, Isa, Xlen};
+use {Instruction, Op, Register};
+
+/// Registers x8-x15, addressed by the 3-bit register fields of the CIW, CL,
+/// CS, CA and CB formats
+fn register_prime(u: u16) -> Register {
+    Register::from_u32(8 + (u as u32 & 0x7))
+}
+
+fn float_register_prime(u: u16) -> Register {
+    Register::float_from_u32(8 + (u as u32 & 0x7))
+}
+
+fn register(u: u16) -> Register {
+    Register::from_u32(u as u32 & 0x1f)
+}
+
+fn float_register(u: u16) -> Register {
+    Register::float_from_u32(u as u32 & 0x1f)
+}
+
+/// Sign-extends the low `bits` bits of `value`
+fn sign_extend(value: u32, bits: u32) -> u32 {
+    let shift = 32 - bits;
+    (((value << shift) as i32) >> shift) as u32
+}
+
+/// Extracts bit `from` of `halfword` and places it at bit `to`
+fn bit(halfword: u16, from: u32, to: u32) -> u32 {
+    ((halfword as u32 >> from) & 1) << to
+}
+
+/// The 6-bit immediate of the CI format, imm[5] in bit 12 and imm[4:0] in
+/// bits 6:2
+fn ci_immediate(halfword: u16) -> u32 {
+    bit(halfword, 12, 5) | ((halfword as u32 >> 2) & 0x1f)
+}
+
+fn cj_immediate(halfword: u16) -> u32 {
+    let immediate = bit(halfword, 12, 11)
+        | bit(halfword, 11, 4)
+        | bit(halfword, 10, 9)
+        | bit(halfword, 9, 8)
+        | bit(halfword, 8, 10)
+        | bit(halfword, 7, 6)
+        | bit(halfword, 6, 7)
+        | bit(halfword, 5, 3)
+        | bit(halfword, 4, 2)
+        | bit(halfword, 3, 1)
+        | bit(halfword, 2, 5);
+    sign_extend(immediate, 12)
+}
+
+fn cb_immediate(halfword: u16) -> u32 {
+    let immediate = bit(halfword, 12, 8)
+        | bit(halfword, 11, 4)
+        | bit(halfword, 10, 3)
+        | bit(halfword, 6, 7)
+        | bit(halfword, 5, 6)
+        | bit(halfword, 4, 2)
+        | bit(halfword, 3, 1)
+        | bit(halfword, 2, 5);
+    sign_extend(immediate, 9)
+}
+
+/// Offset for word-sized CL/CS loads and stores
+fn clw_immediate(halfword: u16) -> u32 {
+    bit(halfword, 12, 5)
+        | bit(halfword, 11, 4)
+        | bit(halfword, 10, 3)
+        | bit(halfword, 6, 2)
+        | bit(halfword, 5, 6)
+}
+
+/// Offset for doubleword-sized CL/CS loads and stores
+fn cld_immediate(halfword: u16) -> u32 {
+    bit(halfword, 12, 5)
+        | bit(halfword, 11, 4)
+        | bit(halfword, 10, 3)
+        | bit(halfword, 6, 7)
+        | bit(halfword, 5, 6)
+}
+
+fn instruction(op: Op, rd: Register, rs1: Register, rs2: Register, immediate: u32) -> Instruction {
+    Instruction {
+        rs2,
+        rs1,
+        rd,
+        immediate: immediate as i32,
+        ..Instruction::new(op)
+    }
+}
+
+fn shift(op: Op, rd: Register, halfword: u16, xlen: Xlen) -> Result<Instruction, DecodeError> {
+    let shamt = ci_immediate(halfword);
+    // shamt[5] is reserved for custom extensions in RV32C
+    if xlen == Xlen::Rv32 && shamt & 0x20 != 0 {
+        return Err(DecodeError::Reserved);
+    }
+    let mut instruction = instruction(op, rd.clone(), rd, Register::Invalid, 0);
+    instruction.shamt = shamt as usize;
+    Ok(instruction)
+}
+
+fn decode_quadrant0(halfword: u16, xlen: Xlen) -> Result<Instruction, DecodeError> {
+    let funct3 = halfword >> 13;
+    let rd = (halfword >> 2) & 0x7;
+    let rs1 = (halfword >> 7) & 0x7;
+
+    match funct3 {
+        0b000 => {
+            let immediate = bit(halfword, 12, 5)
+                | bit(halfword, 11, 4)
+                | bit(halfword, 10, 9)
+                | bit(halfword, 9, 8)
+                | bit(halfword, 8, 7)
+                | bit(halfword, 7, 6)
+                | bit(halfword, 6, 2)
+                | bit(halfword, 5, 3);
+            if halfword == 0 {
+                return Err(DecodeError::Illegal);
+            }
+            if immediate == 0 {
+                return Err(DecodeError::Reserved);
+            }
+            Ok(instruction(
+                Op::CAddi4spn,
+                register_prime(rd),
+                Register::Sp,
+                Register::Invalid,
+                immediate,
+            ))
+        }
+        0b001 => Ok(instruction(
+            Op::CFld,
+            float_register_prime(rd),
+            register_prime(rs1),
+            Register::Invalid,
+            cld_immediate(halfword),
+        )),
+        0b010 => Ok(instruction(
+            Op::CLw,
+            register_prime(rd),
+            register_prime(rs1),
+            Register::Invalid,
+            clw_immediate(halfword),
+        )),
+        0b011 => match xlen {
+            Xlen::Rv32 => Ok(instruction(
+                Op::CFlw,
+                float_register_prime(rd),
+                register_prime(rs1),
+                Register::Invalid,
+                clw_immediate(halfword),
+            )),
+            Xlen::Rv64 => Ok(instruction(
+                Op::CLd,
+                register_prime(rd),
+                register_prime(rs1),
+                Register::Invalid,
+                cld_immediate(halfword),
+            )),
+        },
+        0b101 => Ok(instruction(
+            Op::CFsd,
+            Register::Invalid,
+            register_prime(rs1),
+            float_register_prime(rd),
+            cld_immediate(halfword),
+        )),
+        0b110 => Ok(instruction(
+            Op::CSw,
+            Register::Invalid,
+            register_prime(rs1),
+            register_prime(rd),
+            clw_immediate(halfword),
+        )),
+        0b111 => match xlen {
+            Xlen::Rv32 => Ok(instruction(
+                Op::CFsw,
+                Register::Invalid,
+                register_prime(rs1),
+                float_register_prime(rd),
+                clw_immediate(halfword),
+            )),
+            Xlen::Rv64 => Ok(instruction(
+                Op::CSd,
+                Register::Invalid,
+                register_prime(rs1),
+                register_prime(rd),
+                cld_immediate(halfword),
+            )),
+        },
+        _ => Err(DecodeError::Reserved),
+    }
+}
+
+fn decode_quadrant1(halfword: u16, xlen: Xlen) -> Result<Instruction, DecodeError> {
+    let funct3 = halfword >> 13;
+    let rd = (halfword >> 7) & 0x1f;
+
+    match funct3 {
+        0b000 => {
+            let op = if rd == 0 { Op::CNop } else { Op::CAddi };
+            Ok(instruction(
+                op,
+                register(rd),
+                register(rd),
+                Register::Invalid,
+                sign_extend(ci_immediate(halfword), 6),
+            ))
+        }
+        0b001 => match xlen {
+            Xlen::Rv32 => Ok(instruction(
+                Op::CJal,
+                Register::Ra,
+                Register::Invalid,
+                Register::Invalid,
+                cj_immediate(halfword),
+            )),
+            Xlen::Rv64 => {
+                if rd == 0 {
+                    return Err(DecodeError::Reserved);
+                }
+                Ok(instruction(
+                    Op::CAddiw,
+                    register(rd),
+                    register(rd),
+                    Register::Invalid,
+                    sign_extend(ci_immediate(halfword), 6),
+                ))
+            }
+        },
+        0b010 => Ok(instruction(
+            Op::CLi,
+            register(rd),
+            Register::Zero,
+            Register::Invalid,
+            sign_extend(ci_immediate(halfword), 6),
+        )),
+        0b011 => {
+            if rd == 2 {
+                let immediate = bit(halfword, 12, 9)
+                    | bit(halfword, 6, 4)
+                    | bit(halfword, 5, 6)
+                    | bit(halfword, 4, 8)
+                    | bit(halfword, 3, 7)
+                    | bit(halfword, 2, 5);
+                if immediate == 0 {
+                    return Err(DecodeError::Reserved);
+                }
+                Ok(instruction(
+                    Op::CAddi16sp,
+                    Register::Sp,
+                    Register::Sp,
+                    Register::Invalid,
+                    sign_extend(immediate, 10),
+                ))
+            } else {
+                let immediate = ci_immediate(halfword);
+                if immediate == 0 {
+                    return Err(DecodeError::Reserved);
+                }
+                // Held as the upper 20 bits, the same as lui
+                Ok(instruction(
+                    Op::CLui,
+                    register(rd),
+                    Register::Invalid,
+                    Register::Invalid,
+                    sign_extend(immediate, 6) & 0xfffff,
+                ))
+            }
+        }
+        0b100 => {
+            let rd = register_prime(rd);
+            let rs2 = register_prime(halfword >> 2);
+            match (halfword >> 10) & 0x3 {
+                0b00 => shift(Op::CSrli, rd, halfword, xlen),
+                0b01 => shift(Op::CSrai, rd, halfword, xlen),
+                0b10 => Ok(instruction(
+                    Op::CAndi,
+                    rd.clone(),
+                    rd,
+                    Register::Invalid,
+                    sign_extend(ci_immediate(halfword), 6),
+                )),
+                _ => {
+                    let op = match ((halfword >> 12) & 1, (halfword >> 5) & 0x3, xlen) {
+                        (0, 0b00, _) => Op::CSub,
+                        (0, 0b01, _) => Op::CXor,
+                        (0, 0b10, _) => Op::COr,
+                        (0, 0b11, _) => Op::CAnd,
+                        (1, 0b00, Xlen::Rv64) => Op::CSubw,
+                        (1, 0b01, Xlen::Rv64) => Op::CAddw,
+                        _ => return Err(DecodeError::Reserved),
+                    };
+                    Ok(instruction(op, rd.clone(), rd, rs2, 0))
+                }
+            }
+        }
+        0b101 => Ok(instruction(
+            Op::CJ,
+            Register::Zero,
+            Register::Invalid,
+            Register::Invalid,
+            cj_immediate(halfword),
+        )),
+        0b110 => Ok(instruction(
+            Op::CBeqz,
+            Register::Invalid,
+            register_prime(halfword >> 7),
+            Register::Zero,
+            cb_immediate(halfword),
+        )),
+        _ => Ok(instruction(
+            Op::CBnez,
+            Register::Invalid,
+            register_prime(halfword >> 7),
+            Register::Zero,
+            cb_immediate(halfword),
+        )),
+    }
+}
+
+fn decode_quadrant2(halfword: u16, xlen: Xlen) -> Result<Instruction, DecodeError> {
+    let funct3 = halfword >> 13;
+    let rd = (halfword >> 7) & 0x1f;
+    let rs2 = (halfword >> 2) & 0x1f;
+
+    // Offsets for the stack-pointer relative loads and stores
+    let lwsp_immediate = bit(halfword, 12, 5)
+        | bit(halfword, 6, 4)
+        | bit(halfword, 5, 3)
+        | bit(halfword, 4, 2)
+        | bit(halfword, 3, 7)
+        | bit(halfword, 2, 6);
+    let ldsp_immediate = bit(halfword, 12, 5)
+        | bit(halfword, 6, 4)
+        | bit(halfword, 5, 3)
+        | bit(halfword, 4, 8)
+        | bit(halfword, 3, 7)
+        | bit(halfword, 2, 6);
+    let swsp_immediate = ((halfword as u32 >> 9) & 0xf) << 2 | ((halfword as u32 >> 7) & 0x3) << 6;
+    let sdsp_immediate = ((halfword as u32 >> 10) & 0x7) << 3 | ((halfword as u32 >> 7) & 0x7) << 6;
+
+    match funct3 {
+        0b000 => shift(Op::CSlli, register(rd), halfword, xlen),
+        0b001 => Ok(instruction(
+            Op::CFldsp,
+            float_register(rd),
+            Register::Sp,
+            Register::Invalid,
+            ldsp_immediate,
+        )),
+        0b010 => {
+            if rd == 0 {
+                return Err(DecodeError::Reserved);
+            }
+            Ok(instruction(
+                Op::CLwsp,
+                register(rd),
+                Register::Sp,
+                Register::Invalid,
+                lwsp_immediate,
+            ))
+        }
+        0b011 => match xlen {
+            Xlen::Rv32 => Ok(instruction(
+                Op::CFlwsp,
+                float_register(rd),
+                Register::Sp,
+                Register::Invalid,
+                lwsp_immediate,
+            )),
+            Xlen::Rv64 => {
+                if rd == 0 {
+                    return Err(DecodeError::Reserved);
+                }
+                Ok(instruction(
+                    Op::CLdsp,
+                    register(rd),
+                    Register::Sp,
+                    Register::Invalid,
+                    ldsp_immediate,
+                ))
+            }
+        },
+        0b100 => match ((halfword >> 12) & 1, rd, rs2) {
+            (0, 0, 0) => Err(DecodeError::Reserved),
+            (0, _, 0) => Ok(instruction(
+                Op::CJr,
+                Register::Zero,
+                register(rd),
+                Register::Invalid,
+                0,
+            )),
+            (0, _, _) => Ok(instruction(
+                Op::CMv,
+                register(rd),
+                Register::Zero,
+                register(rs2),
+                0,
+            )),
+            (_, 0, 0) => Ok(instruction(
+                Op::CEbreak,
+                Register::Invalid,
+                Register::Invalid,
+                Register::Invalid,
+                0,
+            )),
+            (_, _, 0) => Ok(instruction(
+                Op::CJalr,
+                Register::Ra,
+                register(rd),
+                Register::Invalid,
+                0,
+            )),
+            (_, _, _) => Ok(instruction(
+                Op::CAdd,
+                register(rd),
+                register(rd),
+                register(rs2),
+                0,
+            )),
+        },
+        0b101 => Ok(instruction(
+            Op::CFsdsp,
+            Register::Invalid,
+            Register::Sp,
+            float_register(rs2),
+            sdsp_immediate,
+        )),
+        0b110 => Ok(instruction(
+            Op::CSwsp,
+            Register::Invalid,
+            Register::Sp,
+            register(rs2),
+            swsp_immediate,
+        )),
+        _ => match xlen {
+            Xlen::Rv32 => Ok(instruction(
+                Op::CFswsp,
+                Register::Invalid,
+                Register::Sp,
+                float_register(rs2),
+                swsp_immediate,
+            )),
+            Xlen::Rv64 => Ok(instruction(
+                Op::CSdsp,
+                Register::Invalid,
+                Register::Sp,
+                register(rs2),
+                sdsp_immediate,
+            )),
+        },
+    }
+}
+
+/// The extension, besides C, which a compressed instruction requires
+fn required_extension(op: &Op) -> Option<Extension> {
+    match op {
+        Op::CFlw | Op::CFlwsp | Op::CFsw | Op::CFswsp => Some(Extension::F),
+        Op::CFld | Op::CFldsp | Op::CFsd | Op::CFsdsp => Some(Extension::D),
+        _ => None,
+    }
+}
+
+/// The XLEN a compressed instruction requires, for those which only exist
+/// for one. Their encodings hold other instructions for the other XLEN.
+pub(crate) fn required_xlen(op: &Op) -> Option<Xlen> {
+    match op {
+        Op::CJal | Op::CFlw | Op::CFlwsp | Op::CFsw | Op::CFswsp => Some(Xlen::Rv32),
+        Op::CAddiw | Op::CAddw | Op::CSubw | Op::CLd | Op::CLdsp | Op::CSd | Op::CSdsp => {
+            Some(Xlen::Rv64)
+        }
+        _ => None,
+    }
+}
+
+/// Decode a 16-bit compressed instruction, which requires the C extension
+/// to be enabled in `isa`.
+///
+/// The operand fields of the returned `Instruction` are those of the base
+/// instruction it expands to, so `c.addi a0, 1` has both `rd` and `rs1` set
+/// to `a0`. Use `Instruction::expand` to get the base instruction itself.
+pub fn decode_compressed(halfword: u16, isa: &Isa) -> Result<Instruction, DecodeError> {
+    if halfword & 0x3 != 0x3 && !isa.has(Extension::C) {
+        return Err(DecodeError::ExtensionDisabled);
+    }
+
+    // c.add with rd x0 is a hint, which Zihintntl uses for c.ntl.*
+    if isa.has(Extension::Zihintntl) {
+        let hint = match halfword {
+            0x900a => Some(Op::CNtlP1),
+            0x900e => Some(Op::CNtlPall),
+            0x9012 => Some(Op::CNtlS1),
+            0x9016 => Some(Op::CNtlAll),
+            _ => None,
+        };
+        if let Some(op) = hint {
+            return Ok(Instruction::new(op));
+        }
+    }
+
+    let instruction = match halfword & 0x3 {
+        0b00 => decode_quadrant0(halfword, isa.xlen()),
+        0b01 => decode_quadrant1(halfword, isa.xlen()),
+        0b10 => decode_quadrant2(halfword, isa.xlen()),
+        _ => Err(DecodeError::UnknownOpcode(u32::from(halfword) & 0x7f)),
+    }?;
+
+    if let Some(extension) = required_extension(instruction.op()) {
+        if !isa.has(extension) {
+            return Err(DecodeError::ExtensionDisabled);
+        }
+    }
+
+    check_registers(instruction, isa)
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+
+/// A control and status register, by its 12-bit address
+///
+/// The top four bits of the address encode how the CSR may be accessed: bits
+/// 11:10 are `0b11` for read-only CSRs, and bits 9:8 hold the lowest
+/// privilege level which can access it.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Csr(u16);
+
+/// The privilege level a CSR requires, as returned by `Csr::privilege`
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Privilege {
+    User,
+    Supervisor,
+    /// Hypervisor-extended supervisor mode, HS-mode
+    Hypervisor,
+    Machine,
+    /// Debug mode, which alone can access `dcsr`, `dpc` and the other CSRs
+    /// at 0x7b0 to 0x7bf
+    Debug,
+}
+
+pub(crate) const FFLAGS: Csr = Csr(0x001);
+pub(crate) const FRM: Csr = Csr(0x002);
+pub(crate) const FCSR: Csr = Csr(0x003);
+pub(crate) const CYCLE: Csr = Csr(0xc00);
+pub(crate) const TIME: Csr = Csr(0xc01);
+pub(crate) const INSTRET: Csr = Csr(0xc02);
+pub(crate) const CYCLEH: Csr = Csr(0xc80);
+pub(crate) const TIMEH: Csr = Csr(0xc81);
+pub(crate) const INSTRETH: Csr = Csr(0xc82);
+pub(crate) const VL: Csr = Csr(0xc20);
+pub(crate) const VTYPE: Csr = Csr(0xc21);
+pub(crate) const USTATUS: Csr = Csr(0x000);
+pub(crate) const UEPC: Csr = Csr(0x041);
+pub(crate) const SSTATUS: Csr = Csr(0x100);
+pub(crate) const SEPC: Csr = Csr(0x141);
+pub(crate) const MSTATUS: Csr = Csr(0x300);
+pub(crate) const MEPC: Csr = Csr(0x341);
+pub(crate) const DCSR: Csr = Csr(0x7b0);
+pub(crate) const DPC: Csr = Csr(0x7b1);
+
+impl Csr {
+    /// The CSR at `address`, or `None` if the address does not fit in 12
+    /// bits
+    pub fn from_address(address: usize) -> Option<Csr> {
+        if address < 0x1000 {
+            Some(Csr(address as u16))
+        } else {
+            None
+        }
+    }
+
+    /// The CSR addressed by bits 11:0 of `u`
+    pub(crate) fn from_u32(u: u32) -> Csr {
+        Csr((u & 0xfff) as u16)
+    }
+
+    /// The CSR with the given name, such as `mstatus` or `pmpaddr3`
+    pub fn from_name(name: &str) -> Option<Csr> {
+        if let Some(&(address, _)) = NAMES.iter().find(|&&(_, n)| n == name) {
+            return Some(Csr(address));
+        }
+        FAMILIES
+            .iter()
+            .filter_map(|&(base, first, last, prefix, suffix)| {
+                name.strip_prefix(prefix)
+                    .and_then(|n| n.strip_suffix(suffix))
+                    .filter(|n| n.bytes().all(|b| b.is_ascii_digit()))
+                    .filter(|n| n.len() == 1 || !n.starts_with('0'))
+                    .and_then(|n| n.parse::<u16>().ok())
+                    .filter(|n| (first..=last).contains(n))
+                    .map(|n| Csr(base + n))
+            })
+            .next()
+    }
+
+    pub fn address(&self) -> usize {
+        self.0 as usize
+    }
+
+    /// The name of this CSR, or `None` if its address is not one of the
+    /// standard CSRs
+    pub fn name(&self) -> Option<String> {
+        match self.fixed_name() {
+            Some(name) => Some(name.to_string()),
+            None => self
+                .family()
+                .map(|(prefix, n, suffix)| format!("{}{}{}", prefix, n, suffix)),
+        }
+    }
+
+    /// The name of a CSR which is not one of a numbered family
+    fn fixed_name(&self) -> Option<&'static str> {
+        NAMES
+            .iter()
+            .find(|&&(address, _)| address == self.0)
+            .map(|&(_, name)| name)
+    }
+
+    /// The prefix, index and suffix of the name of a CSR which is one of a
+    /// numbered family, such as `pmpcfg2` or `mhpmcounter3h`
+    fn family(&self) -> Option<(&'static str, u16, &'static str)> {
+        FAMILIES
+            .iter()
+            .find(|&&(base, first, last, _, _)| (base + first..=base + last).contains(&self.0))
+            .map(|&(base, _, _, prefix, suffix)| (prefix, self.0 - base, suffix))
+    }
+
+    /// The lowest privilege level which can access this CSR
+    pub fn privilege(&self) -> Privilege {
+        match self.0 {
+            0x7b0..=0x7bf => Privilege::Debug,
+            _ => match (self.0 >> 8) & 0b11 {
+                0b00 => Privilege::User,
+                0b01 => Privilege::Supervisor,
+                0b10 => Privilege::Hypervisor,
+                _ => Privilege::Machine,
+            },
+        }
+    }
+
+    /// Returns true if writing this CSR raises an illegal instruction
+    /// exception
+    pub fn is_read_only(&self) -> bool {
+        self.0 >> 10 == 0b11
+    }
+}
+
+/// Displays the name of a CSR where it has one, and its address in hex
+/// otherwise
+impl fmt::Display for Csr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(name) = self.fixed_name() {
+            return write!(f, "{}", name);
+        }
+        match self.family() {
+            Some((prefix, n, suffix)) => write!(f, "{}{}{}", prefix, n, suffix),
+            None => write!(f, "0x{:x}", self.0),
+        }
+    }
+}
+
+/// The standard CSRs which have a name of their own
+const NAMES: &[(u16, &str)] = &[
+    // Unprivileged floating point, vector and entropy source CSRs
+    (0x001, "fflags"),
+    (0x002, "frm"),
+    (0x003, "fcsr"),
+    (0x008, "vstart"),
+    (0x009, "vxsat"),
+    (0x00a, "vxrm"),
+    (0x00f, "vcsr"),
+    (0x015, "seed"),
+    (0xc20, "vl"),
+    (0xc21, "vtype"),
+    (0xc22, "vlenb"),
+    // Unprivileged counters and timers
+    (0xc00, "cycle"),
+    (0xc01, "time"),
+    (0xc02, "instret"),
+    (0xc80, "cycleh"),
+    (0xc81, "timeh"),
+    (0xc82, "instreth"),
+    // User trap handling, from the withdrawn N extension
+    (0x000, "ustatus"),
+    (0x004, "uie"),
+    (0x005, "utvec"),
+    (0x040, "uscratch"),
+    (0x041, "uepc"),
+    (0x042, "ucause"),
+    (0x043, "utval"),
+    (0x044, "uip"),
+    // Supervisor
+    (0x100, "sstatus"),
+    (0x104, "sie"),
+    (0x105, "stvec"),
+    (0x106, "scounteren"),
+    (0x10a, "senvcfg"),
+    (0x140, "sscratch"),
+    (0x141, "sepc"),
+    (0x142, "scause"),
+    (0x143, "stval"),
+    (0x144, "sip"),
+    (0x14d, "stimecmp"),
+    (0x15d, "stimecmph"),
+    (0x180, "satp"),
+    (0x5a8, "scontext"),
+    // Hypervisor
+    (0x600, "hstatus"),
+    (0x602, "hedeleg"),
+    (0x603, "hideleg"),
+    (0x604, "hie"),
+    (0x605, "htimedelta"),
+    (0x606, "hcounteren"),
+    (0x607, "hgeie"),
+    (0x60a, "henvcfg"),
+    (0x615, "htimedeltah"),
+    (0x61a, "henvcfgh"),
+    (0x643, "htval"),
+    (0x644, "hip"),
+    (0x645, "hvip"),
+    (0x64a, "htinst"),
+    (0x680, "hgatp"),
+    (0x6a8, "hcontext"),
+    (0xe12, "hgeip"),
+    // Virtual supervisor
+    (0x200, "vsstatus"),
+    (0x204, "vsie"),
+    (0x205, "vstvec"),
+    (0x240, "vsscratch"),
+    (0x241, "vsepc"),
+    (0x242, "vscause"),
+    (0x243, "vstval"),
+    (0x244, "vsip"),
+    (0x280, "vsatp"),
+    // Machine information
+    (0xf11, "mvendorid"),
+    (0xf12, "marchid"),
+    (0xf13, "mimpid"),
+    (0xf14, "mhartid"),
+    (0xf15, "mconfigptr"),
+    // Machine trap setup and handling
+    (0x300, "mstatus"),
+    (0x301, "misa"),
+    (0x302, "medeleg"),
+    (0x303, "mideleg"),
+    (0x304, "mie"),
+    (0x305, "mtvec"),
+    (0x306, "mcounteren"),
+    (0x310, "mstatush"),
+    (0x340, "mscratch"),
+    (0x341, "mepc"),
+    (0x342, "mcause"),
+    (0x343, "mtval"),
+    (0x344, "mip"),
+    (0x34a, "mtinst"),
+    (0x34b, "mtval2"),
+    // Machine configuration
+    (0x30a, "menvcfg"),
+    (0x31a, "menvcfgh"),
+    (0x747, "mseccfg"),
+    (0x757, "mseccfgh"),
+    // Machine counters
+    (0xb00, "mcycle"),
+    (0xb02, "minstret"),
+    (0xb80, "mcycleh"),
+    (0xb82, "minstreth"),
+    (0x320, "mcountinhibit"),
+    // Debug triggers and debug mode
+    (0x7a0, "tselect"),
+    (0x7a1, "tdata1"),
+    (0x7a2, "tdata2"),
+    (0x7a3, "tdata3"),
+    (0x7a4, "tinfo"),
+    (0x7a5, "tcontrol"),
+    (0x7a8, "mcontext"),
+    (0x7b0, "dcsr"),
+    (0x7b1, "dpc"),
+    (0x7b2, "dscratch0"),
+    (0x7b3, "dscratch1"),
+];
+
+/// The numbered families of CSRs, as the address of member 0, the first and
+/// last members, and the prefix and suffix around the number in their names
+const FAMILIES: &[(u16, u16, u16, &str, &str)] = &[
+    (0x3a0, 0, 15, "pmpcfg", ""),
+    (0x3b0, 0, 63, "pmpaddr", ""),
+    (0xc00, 3, 31, "hpmcounter", ""),
+    (0xc80, 3, 31, "hpmcounter", "h"),
+    (0xb00, 3, 31, "mhpmcounter", ""),
+    (0xb80, 3, 31, "mhpmcounter", "h"),
+    (0x320, 3, 31, "mhpmevent", ""),
+    (0x720, 3, 31, "mhpmevent", "h"),
+];
@@ -1,671 +1,1287 @@
-use {Fence, Instruction, Op, Register};
-
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum OpType {
-    B,
-    R,
-    I,
-    S,
-    U,
-    Uj,
-    Special,
-}
-
-/// This struct is used to hold instruction information for decoding
-#[derive(Clone, Debug)]
-struct Opcode {
-    funct3: u32,
-    funct7: u32,
-    opcode: u32,
-    op: Op,
-    op_type: OpType,
-}
-
-use self::OpType::*;
-use Op::*;
-
-const RV32I_OPCODES: &'static [Opcode] = &[
-    Opcode {
-        funct7: 0,
-        funct3: 0,
-        opcode: 0b0110111,
-        op: Lui,
-        op_type: U,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0,
-        opcode: 0b0010111,
-        op: Auipc,
-        op_type: U,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0,
-        opcode: 0b1101111,
-        op: Jal,
-        op_type: Uj,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b1100111,
-        op: Jalr,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b1100011,
-        op: Beq,
-        op_type: OpType::B,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b001,
-        opcode: 0b1100011,
-        op: Bne,
-        op_type: OpType::B,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b100,
-        opcode: 0b1100011,
-        op: Blt,
-        op_type: OpType::B,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b101,
-        opcode: 0b1100011,
-        op: Bge,
-        op_type: OpType::B,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b110,
-        opcode: 0b1100011,
-        op: Bltu,
-        op_type: OpType::B,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b111,
-        opcode: 0b1100011,
-        op: Bgeu,
-        op_type: OpType::B,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b0000011,
-        op: Lb,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b001,
-        opcode: 0b0000011,
-        op: Lh,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b010,
-        opcode: 0b0000011,
-        op: Lw,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b100,
-        opcode: 0b0000011,
-        op: Lbu,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b101,
-        opcode: 0b0000011,
-        op: Lhu,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b0100011,
-        op: Op::Sb,
-        op_type: OpType::S,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b001,
-        opcode: 0b0100011,
-        op: Sh,
-        op_type: OpType::S,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b010,
-        opcode: 0b0100011,
-        op: Sw,
-        op_type: OpType::S,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b0010011,
-        op: Addi,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b010,
-        opcode: 0b0010011,
-        op: Slti,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b011,
-        opcode: 0b0010011,
-        op: Sltiu,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b100,
-        opcode: 0b0010011,
-        op: Xori,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b110,
-        opcode: 0b0010011,
-        op: Ori,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b111,
-        opcode: 0b0010011,
-        op: Andi,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b001,
-        opcode: 0b0010011,
-        op: Slli,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b101,
-        opcode: 0b0010011,
-        op: Srli,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0b0100000,
-        funct3: 0b101,
-        opcode: 0b0010011,
-        op: Srai,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b000,
-        opcode: 0b0110011,
-        op: Add,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0100000,
-        funct3: 0b000,
-        opcode: 0b0110011,
-        op: Sub,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b001,
-        opcode: 0b0110011,
-        op: Sll,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b010,
-        opcode: 0b0110011,
-        op: Slt,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b011,
-        opcode: 0b0110011,
-        op: Sltu,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b100,
-        opcode: 0b0110011,
-        op: Xor,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b101,
-        opcode: 0b0010011,
-        op: Srl,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0100000,
-        funct3: 0b101,
-        opcode: 0b0010011,
-        op: Sra,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b110,
-        opcode: 0b0110011,
-        op: Or,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0b0000000,
-        funct3: 0b111,
-        opcode: 0b0110011,
-        op: And,
-        op_type: R,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b0001111,
-        op: Fence,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b001,
-        opcode: 0b0001111,
-        op: FenceI,
-        op_type: I,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b1110011,
-        op: Ecall,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b000,
-        opcode: 0b1110011,
-        op: Ebreak,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b001,
-        opcode: 0b1110011,
-        op: Csrrw,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b010,
-        opcode: 0b1110011,
-        op: Csrrs,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b011,
-        opcode: 0b1110011,
-        op: Csrrc,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b101,
-        opcode: 0b1110011,
-        op: Csrrwi,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b110,
-        opcode: 0b1110011,
-        op: Csrrsi,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0,
-        funct3: 0b111,
-        opcode: 0b1110011,
-        op: Csrrci,
-        op_type: Special,
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b000,
-        opcode: 0b0110011,
-        op: Mul,
-        op_type: R
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b001,
-        opcode: 0b0110011,
-        op: Mulh,
-        op_type: R
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b010,
-        opcode: 0b0110011,
-        op: Mulhsu,
-        op_type: R
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b011,
-        opcode: 0b0110011,
-        op: Mulhu,
-        op_type: R
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b100,
-        opcode: 0b0110011,
-        op: Div,
-        op_type: R
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b101,
-        opcode: 0b0110011,
-        op: Divu,
-        op_type: R
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b110,
-        opcode: 0b0110011,
-        op: Rem,
-        op_type: R
-    },
-    Opcode {
-        funct7: 0b0000001,
-        funct3: 0b111,
-        opcode: 0b0110011,
-        op: Remu,
-        op_type: R
-    }
-];
-
-fn decode_r(opcode: &Opcode, word: u32) -> Instruction {
-    let rs2 = (word >> 20) & 0x1f;
-    let rs1 = (word >> 15) & 0x1f;
-    let rd = (word >> 7) & 0x1f;
+use compressed::decode_compressed;
+use error::DecodeError;
+use isa::{Extension, Isa, Xlen};
+use vector::{decode_memory, decode_op_v, is_vector_width};
+use {Aqrl, Csr, Fence, FloatFormat, Instruction, Op, Register, RoundingMode};
 
-    Instruction {
-        rs2: Register::from_u32(rs2),
-        rs1: Register::from_u32(rs1),
-        rd: Register::from_u32(rd),
-        csr: 0,
-        op: opcode.op.clone(),
-        shamt: 0,
-        immediate: 0,
-        fence: None,
+// Decoding dispatches on the major opcode in bits 6:0, and then on funct3,
+// funct7 and whichever other fields tell apart the instructions sharing that
+// major opcode, so the cost of decoding a word does not grow with the number
+// of instructions supported. Each arm checks that the extension the
+// instruction belongs to is enabled in the `Isa` being decoded for.
+
+pub(crate) fn rd(word: u32) -> u32 {
+    (word >> 7) & 0x1f
+}
+
+pub(crate) fn rs1(word: u32) -> u32 {
+    (word >> 15) & 0x1f
+}
+
+pub(crate) fn rs2(word: u32) -> u32 {
+    (word >> 20) & 0x1f
+}
+
+fn rs3(word: u32) -> u32 {
+    (word >> 27) & 0x1f
+}
+
+pub(crate) fn funct3(word: u32) -> u32 {
+    (word >> 12) & 0x7
+}
+
+pub(crate) fn funct7(word: u32) -> u32 {
+    (word >> 25) & 0x7f
+}
+
+/// Rejects instructions from an extension which is not enabled. The base
+/// integer instructions are also available under E.
+pub(crate) fn require(isa: &Isa, extension: Extension) -> Result<(), DecodeError> {
+    if isa.has(extension) || (extension == Extension::I && isa.has(Extension::E)) {
+        Ok(())
+    } else {
+        Err(DecodeError::ExtensionDisabled)
     }
 }
 
-fn decode_i(opcode: &Opcode, word: u32) -> Instruction {
-    let rs1 = (word >> 15) & 0x1f;
-    let rd = (word >> 7) & 0x1f;
+/// Rejects an op unless `extension`, or another extension which also
+/// includes it, is enabled. The scalar cryptography extensions take some of
+/// their ops from the bit-manipulation extensions, the AES key schedule ops
+/// belong to both Zknd and Zkne, and Zfhmin has the half-precision loads,
+/// stores, moves and conversions of Zfh.
+fn require_op(isa: &Isa, op: &Op, extension: Extension) -> Result<(), DecodeError> {
+    let shared = match op {
+        Op::Andn
+        | Op::Orn
+        | Op::Xnor
+        | Op::Rol
+        | Op::Rolw
+        | Op::Ror
+        | Op::Rori
+        | Op::Roriw
+        | Op::Rorw
+        | Op::Rev8 => Some(Extension::Zbkb),
+        Op::Clmul | Op::Clmulh => Some(Extension::Zbkc),
+        Op::Aes64ks1i | Op::Aes64ks2 => Some(Extension::Zkne),
+        Op::Flh
+        | Op::Fsh
+        | Op::FmvXH
+        | Op::FmvHX
+        | Op::FcvtSH
+        | Op::FcvtHS
+        | Op::FcvtDH
+        | Op::FcvtHD
+        | Op::FcvtQH
+        | Op::FcvtHQ => Some(Extension::Zfhmin),
+        _ => None,
+    };
+    match shared {
+        Some(shared) if isa.has(shared) => Ok(()),
+        _ => require(isa, extension),
+    }
+}
 
-    let mut immediate = (word >> 20) & 0xfff;
-    if immediate & 0x800 > 0 {
-        immediate = immediate | 0xfffff000;
+/// Rejects instructions which only exist when XLEN is 64
+fn require_rv64(isa: &Isa) -> Result<(), DecodeError> {
+    match isa.xlen() {
+        Xlen::Rv64 => Ok(()),
+        Xlen::Rv32 => Err(DecodeError::ExtensionDisabled),
+    }
+}
+
+/// Rejects instructions which only exist when XLEN is 32
+fn require_rv32(isa: &Isa) -> Result<(), DecodeError> {
+    match isa.xlen() {
+        Xlen::Rv32 => Ok(()),
+        Xlen::Rv64 => Err(DecodeError::ExtensionDisabled),
+    }
+}
+
+fn decode_r(op: Op, word: u32) -> Instruction {
+    Instruction {
+        rs2: Register::from_u32(rs2(word)),
+        rs1: Register::from_u32(rs1(word)),
+        rd: Register::from_u32(rd(word)),
+        ..Instruction::new(op)
     }
+}
 
+fn decode_i(op: Op, word: u32) -> Instruction {
     Instruction {
-        rs2: Register::Invalid,
-        rs1: Register::from_u32(rs1),
-        rd: Register::from_u32(rd),
-        csr: 0,
-        op: opcode.op.clone(),
-        shamt: 0,
-        immediate: immediate,
-        fence: None,
+        rs1: Register::from_u32(rs1(word)),
+        rd: Register::from_u32(rd(word)),
+        immediate: (word as i32) >> 20,
+        ..Instruction::new(op)
     }
 }
 
-fn decode_b(opcode: &Opcode, word: u32) -> Instruction {
-    let rs1 = (word >> 15) & 0x1f;
-    let rs2 = (word >> 20) & 0x1f;
+fn decode_shift(op: Op, word: u32, shamt: u32) -> Instruction {
+    Instruction {
+        rs1: Register::from_u32(rs1(word)),
+        rd: Register::from_u32(rd(word)),
+        shamt: shamt as usize,
+        ..Instruction::new(op)
+    }
+}
 
+/// An instruction with a single source register, where the rest of the
+/// immediate or the rs2 field selects the operation
+fn decode_unary(op: Op, word: u32) -> Instruction {
+    Instruction {
+        rs1: Register::from_u32(rs1(word)),
+        rd: Register::from_u32(rd(word)),
+        ..Instruction::new(op)
+    }
+}
+
+fn decode_b(op: Op, word: u32) -> Instruction {
     let mut immediate = ((word >> 7) & 1) << 11;
     immediate |= ((word >> 8) & 0xf) << 1;
     immediate |= ((word >> 25) & 0x3f) << 5;
     immediate |= ((word >> 31) & 1) << 12;
-    if immediate & 0x800 > 0 {
-        immediate = 0xfffff000 | immediate;
+    if immediate & 0x1000 > 0 {
+        immediate |= 0xfffff000;
     }
+
     Instruction {
-        rs2: Register::from_u32(rs2),
-        rs1: Register::from_u32(rs1),
-        rd: Register::Invalid,
-        csr: 0,
-        op: opcode.op.clone(),
-        shamt: 0,
-        immediate: immediate,
-        fence: None,
+        rs2: Register::from_u32(rs2(word)),
+        rs1: Register::from_u32(rs1(word)),
+        immediate: immediate as i32,
+        ..Instruction::new(op)
     }
 }
 
-fn decode_s(opcode: &Opcode, word: u32) -> Instruction {
-    let rs1 = (word >> 15) & 0x1f;
-    let rs2 = (word >> 20) & 0x1f;
-
+fn decode_s(op: Op, word: u32) -> Instruction {
     let mut immediate = (word >> 7) & 0x1f;
     immediate |= ((word >> 25) & 0x7f) << 5;
     if immediate & 0x800 > 0 {
-        immediate = 0xfffff000 | immediate;
+        immediate |= 0xfffff000;
     }
+
     Instruction {
-        rs2: Register::from_u32(rs2),
-        rs1: Register::from_u32(rs1),
-        rd: Register::Invalid,
-        csr: 0,
-        op: opcode.op.clone(),
-        shamt: 0,
-        immediate: immediate,
-        fence: None,
+        rs2: Register::from_u32(rs2(word)),
+        rs1: Register::from_u32(rs1(word)),
+        immediate: immediate as i32,
+        ..Instruction::new(op)
     }
 }
 
-fn decode_u(opcode: &Opcode, word: u32) -> Instruction {
-    let rd = (word >> 7) & 0x1f;
-
-    let immediate = word >> 12;
+fn decode_u(op: Op, word: u32) -> Instruction {
     Instruction {
-        rs2: Register::Invalid,
-        rs1: Register::Invalid,
-        rd: Register::from_u32(rd),
-        csr: 0,
-        op: opcode.op.clone(),
-        shamt: 0,
-        immediate: immediate,
-        fence: None,
+        rd: Register::from_u32(rd(word)),
+        immediate: (word >> 12) as i32,
+        ..Instruction::new(op)
     }
 }
 
-fn decode_uj(opcode: &Opcode, word: u32) -> Instruction {
-    let rd = (word >> 7) & 0x1f;
-
+fn decode_uj(op: Op, word: u32) -> Instruction {
     let mut immediate = word & 0x000f_f000;
     immediate |= ((word >> 20) & 1) << 11;
     immediate |= ((word >> 21) & 0x3ff) << 1;
     immediate |= ((word >> 31) & 1) << 20;
     if immediate & 0x100000 > 0 {
-        immediate = 0xffe00000 | immediate;
+        immediate |= 0xffe00000;
     }
 
     Instruction {
-        rs2: Register::Invalid,
-        rs1: Register::Invalid,
-        rd: Register::from_u32(rd),
-        csr: 0,
-        op: opcode.op.clone(),
-        shamt: 0,
-        immediate: immediate,
-        fence: None,
+        rd: Register::from_u32(rd(word)),
+        immediate: immediate as i32,
+        ..Instruction::new(op)
     }
 }
 
-fn decode_fence(opcode: &Opcode, word: u32) -> Instruction {
-    let rs1 = (word >> 15) & 0x1f;
-    let rd = (word >> 7) & 0x1f;
+fn decode_jalr(word: u32) -> Result<Instruction, DecodeError> {
+    match funct3(word) {
+        0b000 => Ok(decode_i(Op::Jalr, word)),
+        _ => Err(DecodeError::Reserved),
+    }
+}
 
-    let fence = Fence {
-        pi: if ((word >> 27) & 1) == 1 { true } else { false },
-        po: if ((word >> 26) & 1) == 1 { true } else { false },
-        pr: if ((word >> 25) & 1) == 1 { true } else { false },
-        pw: if ((word >> 24) & 1) == 1 { true } else { false },
-        si: if ((word >> 23) & 1) == 1 { true } else { false },
-        so: if ((word >> 22) & 1) == 1 { true } else { false },
-        sr: if ((word >> 21) & 1) == 1 { true } else { false },
-        sw: if ((word >> 20) & 1) == 1 { true } else { false },
+fn decode_branch(word: u32) -> Result<Instruction, DecodeError> {
+    let op = match funct3(word) {
+        0b000 => Op::Beq,
+        0b001 => Op::Bne,
+        0b100 => Op::Blt,
+        0b101 => Op::Bge,
+        0b110 => Op::Bltu,
+        0b111 => Op::Bgeu,
+        _ => return Err(DecodeError::Reserved),
+    };
+    Ok(decode_b(op, word))
+}
+
+fn decode_load(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let op = match funct3(word) {
+        0b000 => Op::Lb,
+        0b001 => Op::Lh,
+        0b010 => Op::Lw,
+        0b011 => Op::Ld,
+        0b100 => Op::Lbu,
+        0b101 => Op::Lhu,
+        0b110 => Op::Lwu,
+        _ => return Err(DecodeError::Reserved),
     };
+    if let Op::Ld | Op::Lwu = op {
+        require_rv64(isa)?;
+    }
+    Ok(decode_i(op, word))
+}
 
-    Instruction {
-        rs2: Register::Invalid,
-        rs1: Register::from_u32(rs1),
-        rd: Register::from_u32(rd),
-        csr: 0,
-        op: opcode.op.clone(),
-        shamt: 0,
-        immediate: 0,
-        fence: Some(fence),
+fn decode_store(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let op = match funct3(word) {
+        0b000 => Op::Sb,
+        0b001 => Op::Sh,
+        0b010 => Op::Sw,
+        0b011 => Op::Sd,
+        _ => return Err(DecodeError::Reserved),
+    };
+    if op == Op::Sd {
+        require_rv64(isa)?;
     }
+    Ok(decode_s(op, word))
 }
 
-fn decode_special(opcode: &Opcode, word: u32) -> Instruction {
-    match opcode.op {
-        Op::Ecall | Op::Ebreak => {
-            let op = if (word >> 20) & 0xfff == 0 {
-                Op::Ecall
+/// The unary bit-manipulation and cryptography ops under OP-IMM, which
+/// hold a fixed value in the whole of imm[11:0]. `rev8` is encoded as a
+/// rotate by XLEN-8, so its encoding depends on XLEN, and `zip` and `unzip`
+/// only exist when XLEN is 32.
+fn unary_op_imm(word: u32, xlen: Xlen) -> Option<(Op, Extension)> {
+    let unary = match (funct3(word), word >> 20, xlen) {
+        (0b001, 0x600, _) => (Op::Clz, Extension::Zbb),
+        (0b001, 0x601, _) => (Op::Ctz, Extension::Zbb),
+        (0b001, 0x602, _) => (Op::Cpop, Extension::Zbb),
+        (0b001, 0x604, _) => (Op::SextB, Extension::Zbb),
+        (0b001, 0x605, _) => (Op::SextH, Extension::Zbb),
+        (0b101, 0x287, _) => (Op::OrcB, Extension::Zbb),
+        (0b101, 0x698, Xlen::Rv32) | (0b101, 0x6b8, Xlen::Rv64) => (Op::Rev8, Extension::Zbb),
+        (0b101, 0x687, _) => (Op::Brev8, Extension::Zbkb),
+        (0b001, 0x08f, _) => (Op::Zip, Extension::Zbkb),
+        (0b101, 0x08f, _) => (Op::Unzip, Extension::Zbkb),
+        (0b001, 0x300, _) => (Op::Aes64im, Extension::Zknd),
+        (0b001, 0x100, _) => (Op::Sha256sum0, Extension::Zknh),
+        (0b001, 0x101, _) => (Op::Sha256sum1, Extension::Zknh),
+        (0b001, 0x102, _) => (Op::Sha256sig0, Extension::Zknh),
+        (0b001, 0x103, _) => (Op::Sha256sig1, Extension::Zknh),
+        (0b001, 0x104, _) => (Op::Sha512sum0, Extension::Zknh),
+        (0b001, 0x105, _) => (Op::Sha512sum1, Extension::Zknh),
+        (0b001, 0x106, _) => (Op::Sha512sig0, Extension::Zknh),
+        (0b001, 0x107, _) => (Op::Sha512sig1, Extension::Zknh),
+        (0b001, 0x108, _) => (Op::Sm3p0, Extension::Zksh),
+        (0b001, 0x109, _) => (Op::Sm3p1, Extension::Zksh),
+        _ => return None,
+    };
+    Some(unary)
+}
+
+/// `aes64ks1i` takes a 4-bit round number in imm[3:0], where values above
+/// 0xa are reserved
+fn decode_aes64ks1i(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let rnum = (word >> 20) & 0xf;
+    if rnum > 0xa {
+        return Err(DecodeError::Reserved);
+    }
+    require_op(isa, &Op::Aes64ks1i, Extension::Zknd)?;
+    require_rv64(isa)?;
+    Ok(Instruction {
+        immediate: rnum as i32,
+        ..decode_unary(Op::Aes64ks1i, word)
+    })
+}
+
+/// The ops under OP-IMM which are not shifts, and are selected by a fixed
+/// value in the shift amount field too
+fn decode_op_imm_unary(word: u32, isa: &Isa) -> Option<Result<Instruction, DecodeError>> {
+    if funct3(word) == 0b001 && word >> 24 == 0x31 {
+        return Some(decode_aes64ks1i(word, isa));
+    }
+    let (op, extension) = unary_op_imm(word, isa.xlen())?;
+    let instruction = require_op(isa, &op, extension).and_then(|()| {
+        match op {
+            Op::Aes64im | Op::Sha512sum0 | Op::Sha512sum1 | Op::Sha512sig0 | Op::Sha512sig1 => {
+                require_rv64(isa)?
+            }
+            Op::Zip | Op::Unzip => require_rv32(isa)?,
+            _ => {}
+        }
+        Ok(Instruction {
+            xlen: if op == Op::Rev8 {
+                Some(isa.xlen())
             } else {
-                Op::Ebreak
+                None
+            },
+            ..decode_unary(op, word)
+        })
+    });
+    Some(instruction)
+}
+
+/// The Zicbop prefetches are `ori` hints with rd x0. The low five bits of
+/// the immediate select the prefetch, and the rest hold an offset which is
+/// a multiple of 32.
+fn decode_prefetch(word: u32, isa: &Isa) -> Option<Instruction> {
+    if funct3(word) != 0b110 || rd(word) != 0 || !isa.has(Extension::Zicbop) {
+        return None;
+    }
+    let op = match (word >> 20) & 0x1f {
+        0b00000 => Op::PrefetchI,
+        0b00001 => Op::PrefetchR,
+        0b00011 => Op::PrefetchW,
+        _ => return None,
+    };
+    Some(Instruction {
+        rs1: Register::from_u32(rs1(word)),
+        immediate: ((word as i32) >> 20) & !0x1f,
+        ..Instruction::new(op)
+    })
+}
+
+/// Shifts by an immediate take a 6-bit shamt when XLEN is 64, and a 5-bit
+/// one when XLEN is 32, with bits 31:26 selecting the operation. The
+/// immediate forms of the Zbs single-bit ops and `rori` share the shift
+/// encoding.
+fn decode_op_imm(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    if let Some(instruction) = decode_prefetch(word, isa) {
+        return Ok(instruction);
+    }
+    let op = match funct3(word) {
+        0b000 => Op::Addi,
+        0b010 => Op::Slti,
+        0b011 => Op::Sltiu,
+        0b100 => Op::Xori,
+        0b110 => Op::Ori,
+        0b111 => Op::Andi,
+        funct3 => {
+            if let Some(instruction) = decode_op_imm_unary(word, isa) {
+                return instruction;
+            }
+            let (op, extension) = match (funct3, word >> 26) {
+                (0b001, 0b000000) => (Op::Slli, Extension::I),
+                (0b101, 0b000000) => (Op::Srli, Extension::I),
+                (0b101, 0b010000) => (Op::Srai, Extension::I),
+                (0b101, 0b011000) => (Op::Rori, Extension::Zbb),
+                (0b001, 0b010010) => (Op::Bclri, Extension::Zbs),
+                (0b101, 0b010010) => (Op::Bexti, Extension::Zbs),
+                (0b001, 0b011010) => (Op::Binvi, Extension::Zbs),
+                (0b001, 0b001010) => (Op::Bseti, Extension::Zbs),
+                _ => return Err(DecodeError::Reserved),
             };
-            Instruction {
-                rs2: Register::Invalid,
-                rs1: Register::Invalid,
-                rd: Register::Invalid,
-                csr: 0,
-                op: op,
-                shamt: 0,
-                immediate: 0,
-                fence: None,
+            require_op(isa, &op, extension)?;
+            let shamt = (word >> 20) & 0x3f;
+            if shamt & 0x20 != 0 {
+                require_rv64(isa)?;
             }
+            return Ok(decode_shift(op, word, shamt));
         }
-        Op::Csrrc | Op::Csrrs | Op::Csrrw => {
-            let rs1 = (word >> 15) & 0x1f;
-            let rd = (word >> 7) & 0x1f;
-            let csr = (word >> 20) & 0xfff;
-            Instruction {
-                rs2: Register::Invalid,
-                rs1: Register::from_u32(rs1),
-                rd: Register::from_u32(rd),
-                csr: csr as usize,
-                op: opcode.op.clone(),
-                shamt: 0,
-                immediate: 0,
-                fence: None,
-            }
+    };
+    Ok(decode_i(op, word))
+}
+
+/// `slli.uw` takes a 6-bit shamt, with bits 31:26 selecting it, and the
+/// other shifts a 5-bit one. The Zbb unary word ops select the operation
+/// with the rs2 field.
+fn decode_op_imm_32(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let (instruction, extension) = match (funct3(word), funct7(word), rs2(word)) {
+        (0b000, _, _) => (decode_i(Op::Addiw, word), Extension::I),
+        (0b001, 0b0000000, shamt) => (decode_shift(Op::Slliw, word, shamt), Extension::I),
+        (0b101, 0b0000000, shamt) => (decode_shift(Op::Srliw, word, shamt), Extension::I),
+        (0b101, 0b0100000, shamt) => (decode_shift(Op::Sraiw, word, shamt), Extension::I),
+        (0b101, 0b0110000, shamt) => (decode_shift(Op::Roriw, word, shamt), Extension::Zbb),
+        (0b001, 0b0110000, 0b00000) => (decode_unary(Op::Clzw, word), Extension::Zbb),
+        (0b001, 0b0110000, 0b00001) => (decode_unary(Op::Ctzw, word), Extension::Zbb),
+        (0b001, 0b0110000, 0b00010) => (decode_unary(Op::Cpopw, word), Extension::Zbb),
+        (0b001, 0b0000100, _) | (0b001, 0b0000101, _) => {
+            let shamt = (word >> 20) & 0x3f;
+            (decode_shift(Op::SlliUw, word, shamt), Extension::Zba)
         }
-        Op::Csrrci | Op::Csrrsi | Op::Csrrwi => {
-            let immediate = (word >> 15) & 0x1f;
-            let rd = (word >> 7) & 0x1f;
-            let csr = (word >> 20) & 0xfff;
-            Instruction {
-                rs2: Register::Invalid,
-                rs1: Register::Invalid,
-                rd: Register::from_u32(rd),
-                csr: csr as usize,
-                op: opcode.op.clone(),
-                shamt: 0,
-                immediate: immediate,
-                fence: None,
-            }
+        _ => return Err(DecodeError::Reserved),
+    };
+    require_op(isa, instruction.op(), extension)?;
+    require_rv64(isa)?;
+    Ok(instruction)
+}
+
+/// `zext.h` is `pack` with rs2 zero when XLEN is 32, and `packw` when XLEN
+/// is 64, and is decoded as such whenever Zbb is enabled
+fn decode_zext_h(word: u32, isa: &Isa) -> Instruction {
+    Instruction {
+        xlen: Some(isa.xlen()),
+        ..decode_unary(Op::ZextH, word)
+    }
+}
+
+/// The AES and SM4 ops which select a byte of rs2 hold the byte select in
+/// bits 31:30, above a 5-bit funct5, with funct3 zero
+fn byte_select_op(word: u32) -> Option<(Op, Extension)> {
+    let op = match (word >> 25) & 0x1f {
+        0b10001 => (Op::Aes32esi, Extension::Zkne),
+        0b10011 => (Op::Aes32esmi, Extension::Zkne),
+        0b10101 => (Op::Aes32dsi, Extension::Zknd),
+        0b10111 => (Op::Aes32dsmi, Extension::Zknd),
+        0b11000 => (Op::Sm4ed, Extension::Zksed),
+        0b11010 => (Op::Sm4ks, Extension::Zksed),
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// The Zihintntl hints are `add` with rd and rs1 x0, and rs2 x2 to x5
+/// selecting the hint
+fn ntl_hint(word: u32, isa: &Isa) -> Option<Op> {
+    if !isa.has(Extension::Zihintntl) {
+        return None;
+    }
+    match word {
+        0x00200033 => Some(Op::NtlP1),
+        0x00300033 => Some(Op::NtlPall),
+        0x00400033 => Some(Op::NtlS1),
+        0x00500033 => Some(Op::NtlAll),
+        _ => None,
+    }
+}
+
+fn decode_op(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    if let Some(op) = ntl_hint(word, isa) {
+        return Ok(Instruction::new(op));
+    }
+    let rv32 = isa.xlen() == Xlen::Rv32;
+    let (op, extension) = match (funct7(word), funct3(word)) {
+        (0b0000000, 0b000) => (Op::Add, Extension::I),
+        (0b0100000, 0b000) => (Op::Sub, Extension::I),
+        (0b0000000, 0b001) => (Op::Sll, Extension::I),
+        (0b0000000, 0b010) => (Op::Slt, Extension::I),
+        (0b0000000, 0b011) => (Op::Sltu, Extension::I),
+        (0b0000000, 0b100) => (Op::Xor, Extension::I),
+        (0b0000000, 0b101) => (Op::Srl, Extension::I),
+        (0b0100000, 0b101) => (Op::Sra, Extension::I),
+        (0b0000000, 0b110) => (Op::Or, Extension::I),
+        (0b0000000, 0b111) => (Op::And, Extension::I),
+        (0b0000001, 0b000) => (Op::Mul, Extension::M),
+        (0b0000001, 0b001) => (Op::Mulh, Extension::M),
+        (0b0000001, 0b010) => (Op::Mulhsu, Extension::M),
+        (0b0000001, 0b011) => (Op::Mulhu, Extension::M),
+        (0b0000001, 0b100) => (Op::Div, Extension::M),
+        (0b0000001, 0b101) => (Op::Divu, Extension::M),
+        (0b0000001, 0b110) => (Op::Rem, Extension::M),
+        (0b0000001, 0b111) => (Op::Remu, Extension::M),
+        (0b0010000, 0b010) => (Op::Sh1add, Extension::Zba),
+        (0b0010000, 0b100) => (Op::Sh2add, Extension::Zba),
+        (0b0010000, 0b110) => (Op::Sh3add, Extension::Zba),
+        (0b0100000, 0b111) => (Op::Andn, Extension::Zbb),
+        (0b0100000, 0b110) => (Op::Orn, Extension::Zbb),
+        (0b0100000, 0b100) => (Op::Xnor, Extension::Zbb),
+        (0b0000101, 0b110) => (Op::Max, Extension::Zbb),
+        (0b0000101, 0b111) => (Op::Maxu, Extension::Zbb),
+        (0b0000101, 0b100) => (Op::Min, Extension::Zbb),
+        (0b0000101, 0b101) => (Op::Minu, Extension::Zbb),
+        (0b0110000, 0b001) => (Op::Rol, Extension::Zbb),
+        (0b0110000, 0b101) => (Op::Ror, Extension::Zbb),
+        (0b0000100, 0b100) if rs2(word) == 0 && rv32 && isa.has(Extension::Zbb) => {
+            (Op::ZextH, Extension::Zbb)
         }
-        _ => panic!("decode_special called with an invalid opcode"),
-    }
-}
-
-pub fn decode(word: u32) -> Option<Instruction> {
-    let opcode = word & 0x7f;
-    let funct3 = (word >> 12) & 0x7;
-    let funct7 = (word >> 25) & 0x7f;
-
-    for o in RV32I_OPCODES {
-        // println!(
-        //     "{:?} {:b}=={:b} {:b}=={:b} {:b}=={:b}",
-        //     o.op,
-        //     opcode, o.opcode,
-        //     funct3, o.funct3,
-        //     funct7, o.funct7);
-        match o.op_type {
-            OpType::R => {
-                if opcode == o.opcode && funct3 == o.funct3 && funct7 == o.funct7 {
-                    return Some(decode_r(o, word));
-                }
-            }
-            OpType::I => {
-                if opcode == o.opcode && funct3 == o.funct3 {
-                    if o.op == Fence || o.op == FenceI {
-                        return Some(decode_fence(o, word));
-                    }
-                    return Some(decode_i(o, word));
-                }
-            }
-            OpType::B => {
-                if opcode == o.opcode && funct3 == o.funct3 {
-                    return Some(decode_b(o, word));
-                }
-            }
-            OpType::S => {
-                if opcode == o.opcode && funct3 == o.funct3 {
-                    return Some(decode_s(o, word));
-                }
+        (0b0000101, 0b001) => (Op::Clmul, Extension::Zbc),
+        (0b0000101, 0b011) => (Op::Clmulh, Extension::Zbc),
+        (0b0000101, 0b010) => (Op::Clmulr, Extension::Zbc),
+        (0b0100100, 0b001) => (Op::Bclr, Extension::Zbs),
+        (0b0100100, 0b101) => (Op::Bext, Extension::Zbs),
+        (0b0110100, 0b001) => (Op::Binv, Extension::Zbs),
+        (0b0010100, 0b001) => (Op::Bset, Extension::Zbs),
+        (0b0000100, 0b100) => (Op::Pack, Extension::Zbkb),
+        (0b0000100, 0b111) => (Op::Packh, Extension::Zbkb),
+        (0b0010100, 0b010) => (Op::Xperm4, Extension::Zbkx),
+        (0b0010100, 0b100) => (Op::Xperm8, Extension::Zbkx),
+        (0b0011001, 0b000) => (Op::Aes64es, Extension::Zkne),
+        (0b0011011, 0b000) => (Op::Aes64esm, Extension::Zkne),
+        (0b0011101, 0b000) => (Op::Aes64ds, Extension::Zknd),
+        (0b0011111, 0b000) => (Op::Aes64dsm, Extension::Zknd),
+        (0b0111111, 0b000) => (Op::Aes64ks2, Extension::Zknd),
+        (0b0101000, 0b000) => (Op::Sha512sum0r, Extension::Zknh),
+        (0b0101001, 0b000) => (Op::Sha512sum1r, Extension::Zknh),
+        (0b0101010, 0b000) => (Op::Sha512sig0l, Extension::Zknh),
+        (0b0101011, 0b000) => (Op::Sha512sig1l, Extension::Zknh),
+        (0b0101110, 0b000) => (Op::Sha512sig0h, Extension::Zknh),
+        (0b0101111, 0b000) => (Op::Sha512sig1h, Extension::Zknh),
+        (_, 0b000) => byte_select_op(word).ok_or(DecodeError::Reserved)?,
+        _ => return Err(DecodeError::Reserved),
+    };
+    require_op(isa, &op, extension)?;
+    match op {
+        Op::Aes64es | Op::Aes64esm | Op::Aes64ds | Op::Aes64dsm | Op::Aes64ks2 => {
+            require_rv64(isa)?
+        }
+        Op::Aes32esi
+        | Op::Aes32esmi
+        | Op::Aes32dsi
+        | Op::Aes32dsmi
+        | Op::Sha512sum0r
+        | Op::Sha512sum1r
+        | Op::Sha512sig0l
+        | Op::Sha512sig1l
+        | Op::Sha512sig0h
+        | Op::Sha512sig1h => require_rv32(isa)?,
+        _ => {}
+    }
+
+    match op {
+        Op::ZextH => Ok(decode_zext_h(word, isa)),
+        Op::Aes32esi | Op::Aes32esmi | Op::Aes32dsi | Op::Aes32dsmi | Op::Sm4ed | Op::Sm4ks => {
+            Ok(Instruction {
+                immediate: (word >> 30) as i32,
+                ..decode_r(op, word)
+            })
+        }
+        _ => Ok(decode_r(op, word)),
+    }
+}
+
+fn decode_op_32(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let (op, extension) = match (funct7(word), funct3(word)) {
+        (0b0000000, 0b000) => (Op::Addw, Extension::I),
+        (0b0100000, 0b000) => (Op::Subw, Extension::I),
+        (0b0000000, 0b001) => (Op::Sllw, Extension::I),
+        (0b0000000, 0b101) => (Op::Srlw, Extension::I),
+        (0b0100000, 0b101) => (Op::Sraw, Extension::I),
+        (0b0000001, 0b000) => (Op::Mulw, Extension::M),
+        (0b0000001, 0b100) => (Op::Divw, Extension::M),
+        (0b0000001, 0b101) => (Op::Divuw, Extension::M),
+        (0b0000001, 0b110) => (Op::Remw, Extension::M),
+        (0b0000001, 0b111) => (Op::Remuw, Extension::M),
+        (0b0000100, 0b000) => (Op::AddUw, Extension::Zba),
+        (0b0010000, 0b010) => (Op::Sh1addUw, Extension::Zba),
+        (0b0010000, 0b100) => (Op::Sh2addUw, Extension::Zba),
+        (0b0010000, 0b110) => (Op::Sh3addUw, Extension::Zba),
+        (0b0110000, 0b001) => (Op::Rolw, Extension::Zbb),
+        (0b0110000, 0b101) => (Op::Rorw, Extension::Zbb),
+        (0b0000100, 0b100) if rs2(word) == 0 && isa.has(Extension::Zbb) => {
+            (Op::ZextH, Extension::Zbb)
+        }
+        (0b0000100, 0b100) => (Op::Packw, Extension::Zbkb),
+        _ => return Err(DecodeError::Reserved),
+    };
+    require_op(isa, &op, extension)?;
+    require_rv64(isa)?;
+    match op {
+        Op::ZextH => Ok(decode_zext_h(word, isa)),
+        _ => Ok(decode_r(op, word)),
+    }
+}
+
+/// The cache-block operations take their base address in rs1 alone, with
+/// the immediate selecting the operation
+fn decode_cbo(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let (op, extension) = match word >> 20 {
+        0b000 => (Op::CboInval, Extension::Zicbom),
+        0b001 => (Op::CboClean, Extension::Zicbom),
+        0b010 => (Op::CboFlush, Extension::Zicbom),
+        0b100 => (Op::CboZero, Extension::Zicboz),
+        _ => return Err(DecodeError::Reserved),
+    };
+    if rd(word) != 0 {
+        return Err(DecodeError::Reserved);
+    }
+    require(isa, extension)?;
+    Ok(Instruction {
+        rs1: Register::from_u32(rs1(word)),
+        ..Instruction::new(op)
+    })
+}
+
+fn decode_misc_mem(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let op = match funct3(word) {
+        // pause is a fence hint, ordering prior writes before nothing
+        0b000 if word == 0x0100000f && isa.has(Extension::Zihintpause) => {
+            return Ok(Instruction::new(Op::Pause))
+        }
+        0b000 => Op::Fence,
+        0b001 => {
+            require(isa, Extension::Zifencei)?;
+            Op::FenceI
+        }
+        0b010 => return decode_cbo(word, isa),
+        _ => return Err(DecodeError::Reserved),
+    };
+
+    let fence = Fence {
+        fm: word >> 28,
+        pi: (word >> 27) & 1 == 1,
+        po: (word >> 26) & 1 == 1,
+        pr: (word >> 25) & 1 == 1,
+        pw: (word >> 24) & 1 == 1,
+        si: (word >> 23) & 1 == 1,
+        so: (word >> 22) & 1 == 1,
+        sr: (word >> 21) & 1 == 1,
+        sw: (word >> 20) & 1 == 1,
+    };
+
+    // fence.tso is the one fence mode defined, and only orders rw,rw
+    let op = match op {
+        Op::Fence if word >> 20 == 0x833 => Op::FenceTso,
+        op => op,
+    };
+
+    Ok(Instruction {
+        rs1: Register::from_u32(rs1(word)),
+        rd: Register::from_u32(rd(word)),
+        fence: Some(fence),
+        ..Instruction::new(op)
+    })
+}
+
+fn decode_system(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let op = match funct3(word) {
+        0b000 => return decode_privileged(word, isa),
+        0b100 => return decode_hypervisor_memory(word, isa),
+        0b001 => Op::Csrrw,
+        0b010 => Op::Csrrs,
+        0b011 => Op::Csrrc,
+        0b101 => Op::Csrrwi,
+        0b110 => Op::Csrrsi,
+        0b111 => Op::Csrrci,
+        _ => return Err(DecodeError::Reserved),
+    };
+    require(isa, Extension::Zicsr)?;
+
+    let csr = Some(Csr::from_u32(word >> 20));
+    let rd = Register::from_u32(rd(word));
+    match op {
+        // The immediate forms hold a 5-bit zero-extended immediate in place
+        // of rs1
+        Op::Csrrwi | Op::Csrrsi | Op::Csrrci => Ok(Instruction {
+            rd,
+            csr,
+            immediate: rs1(word) as i32,
+            ..Instruction::new(op)
+        }),
+        _ => Ok(Instruction {
+            rs1: Register::from_u32(rs1(word)),
+            rd,
+            csr,
+            ..Instruction::new(op)
+        }),
+    }
+}
+
+/// The SYSTEM instructions with funct3 zero: the environment calls, the
+/// trap returns, `wfi`, and the address-translation fences. None of them
+/// write rd, and all but the fences which take an address and an ASID are
+/// a single fixed encoding.
+fn decode_privileged(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let op = match word {
+        0x00000073 => Op::Ecall,
+        0x00100073 => Op::Ebreak,
+        0x00200073 => Op::Uret,
+        0x10200073 => Op::Sret,
+        0x30200073 => Op::Mret,
+        0x7b200073 => Op::Dret,
+        0x10500073 => Op::Wfi,
+        0x18000073 => Op::SfenceWInval,
+        0x18100073 => Op::SfenceInvalIr,
+        _ if rd(word) != 0 => return Err(DecodeError::Reserved),
+        _ => match funct7(word) {
+            0b0001001 => Op::SfenceVma,
+            0b0001011 => Op::SinvalVma,
+            0b0010001 => Op::HfenceVvma,
+            0b0110001 => Op::HfenceGvma,
+            0b0010011 => Op::HinvalVvma,
+            0b0110011 => Op::HinvalGvma,
+            _ => return Err(DecodeError::Reserved),
+        },
+    };
+    match op {
+        Op::HfenceVvma | Op::HfenceGvma => require(isa, Extension::H)?,
+        Op::HinvalVvma | Op::HinvalGvma => {
+            require(isa, Extension::H)?;
+            require(isa, Extension::Svinval)?
+        }
+        Op::SinvalVma | Op::SfenceWInval | Op::SfenceInvalIr => require(isa, Extension::Svinval)?,
+        _ => {}
+    }
+
+    match op {
+        Op::SfenceVma
+        | Op::SinvalVma
+        | Op::HfenceVvma
+        | Op::HfenceGvma
+        | Op::HinvalVvma
+        | Op::HinvalGvma => Ok(decode_r(op, word)),
+        _ => Ok(Instruction::new(op)),
+    }
+}
+
+/// The hypervisor virtual-machine loads and stores, under funct3 0b100 of
+/// the SYSTEM opcode. funct7 selects the width, and for loads the rs2 field
+/// selects between the signed, unsigned and execute-permission forms.
+fn decode_hypervisor_memory(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let op = match (funct7(word), rs2(word)) {
+        (0b0110000, 0b00000) => Op::HlvB,
+        (0b0110000, 0b00001) => Op::HlvBu,
+        (0b0110010, 0b00000) => Op::HlvH,
+        (0b0110010, 0b00001) => Op::HlvHu,
+        (0b0110010, 0b00011) => Op::HlvxHu,
+        (0b0110100, 0b00000) => Op::HlvW,
+        (0b0110100, 0b00001) => Op::HlvWu,
+        (0b0110100, 0b00011) => Op::HlvxWu,
+        (0b0110110, 0b00000) => Op::HlvD,
+        (0b0110001, _) => Op::HsvB,
+        (0b0110011, _) => Op::HsvH,
+        (0b0110101, _) => Op::HsvW,
+        (0b0110111, _) => Op::HsvD,
+        _ => return Err(DecodeError::Reserved),
+    };
+    require(isa, Extension::H)?;
+    if let Op::HlvWu | Op::HlvD | Op::HsvD = op {
+        require_rv64(isa)?;
+    }
+
+    let rs1 = Register::from_u32(rs1(word));
+    match op {
+        // Stores have no destination
+        Op::HsvB | Op::HsvH | Op::HsvW | Op::HsvD if rd(word) != 0 => Err(DecodeError::Reserved),
+        Op::HsvB | Op::HsvH | Op::HsvW | Op::HsvD => Ok(Instruction {
+            rs2: Register::from_u32(rs2(word)),
+            rs1,
+            ..Instruction::new(op)
+        }),
+        _ => Ok(Instruction {
+            rs1,
+            rd: Register::from_u32(rd(word)),
+            ..Instruction::new(op)
+        }),
+    }
+}
+
+/// The aq and rl bits occupy the low two bits of funct7, and `lr` requires
+/// rs2 to be zero.
+fn decode_amo(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let doubleword = match funct3(word) {
+        0b010 => false,
+        0b011 => true,
+        _ => return Err(DecodeError::Reserved),
+    };
+
+    let op = match (word >> 27, doubleword) {
+        (0b00010, false) => Op::LrW,
+        (0b00010, true) => Op::LrD,
+        (0b00011, false) => Op::ScW,
+        (0b00011, true) => Op::ScD,
+        (0b00001, false) => Op::AmoswapW,
+        (0b00001, true) => Op::AmoswapD,
+        (0b00000, false) => Op::AmoaddW,
+        (0b00000, true) => Op::AmoaddD,
+        (0b00100, false) => Op::AmoxorW,
+        (0b00100, true) => Op::AmoxorD,
+        (0b01100, false) => Op::AmoandW,
+        (0b01100, true) => Op::AmoandD,
+        (0b01000, false) => Op::AmoorW,
+        (0b01000, true) => Op::AmoorD,
+        (0b10000, false) => Op::AmominW,
+        (0b10000, true) => Op::AmominD,
+        (0b10100, false) => Op::AmomaxW,
+        (0b10100, true) => Op::AmomaxD,
+        (0b11000, false) => Op::AmominuW,
+        (0b11000, true) => Op::AmominuD,
+        (0b11100, false) => Op::AmomaxuW,
+        (0b11100, true) => Op::AmomaxuD,
+        _ => return Err(DecodeError::Reserved),
+    };
+    require(isa, Extension::A)?;
+    if doubleword {
+        require_rv64(isa)?;
+    }
+
+    let rs2 = match op {
+        Op::LrW | Op::LrD => {
+            if rs2(word) != 0 {
+                return Err(DecodeError::Reserved);
             }
-            OpType::U => {
-                if opcode == o.opcode {
-                    return Some(decode_u(o, word));
-                }
+            Register::Invalid
+        }
+        _ => Register::from_u32(rs2(word)),
+    };
+
+    let aqrl = Aqrl {
+        aq: (word >> 26) & 1 == 1,
+        rl: (word >> 25) & 1 == 1,
+    };
+
+    Ok(Instruction {
+        rs2,
+        rs1: Register::from_u32(rs1(word)),
+        rd: Register::from_u32(rd(word)),
+        aqrl: Some(aqrl),
+        ..Instruction::new(op)
+    })
+}
+
+/// The floating point format selected by the two-bit fmt field
+fn float_format(fmt: u32) -> FloatFormat {
+    match fmt & 0x3 {
+        0b00 => FloatFormat::S,
+        0b01 => FloatFormat::D,
+        0b10 => FloatFormat::H,
+        _ => FloatFormat::Q,
+    }
+}
+
+/// The extension holding the arithmetic of a floating point format
+fn format_extension(format: FloatFormat) -> Extension {
+    match format {
+        FloatFormat::S => Extension::F,
+        FloatFormat::D => Extension::D,
+        FloatFormat::H => Extension::Zfh,
+        FloatFormat::Q => Extension::Q,
+    }
+}
+
+/// Selects between the single, double, half and quad precision forms of an
+/// op
+fn select(format: FloatFormat, s: Op, d: Op, h: Op, q: Op) -> Op {
+    match format {
+        FloatFormat::S => s,
+        FloatFormat::D => d,
+        FloatFormat::H => h,
+        FloatFormat::Q => q,
+    }
+}
+
+/// The conversion from one floating point format to another
+fn conversion(to: FloatFormat, from: FloatFormat) -> Option<Op> {
+    match (to, from) {
+        (FloatFormat::S, FloatFormat::D) => Some(Op::FcvtSD),
+        (FloatFormat::S, FloatFormat::H) => Some(Op::FcvtSH),
+        (FloatFormat::S, FloatFormat::Q) => Some(Op::FcvtSQ),
+        (FloatFormat::D, FloatFormat::S) => Some(Op::FcvtDS),
+        (FloatFormat::D, FloatFormat::H) => Some(Op::FcvtDH),
+        (FloatFormat::D, FloatFormat::Q) => Some(Op::FcvtDQ),
+        (FloatFormat::H, FloatFormat::S) => Some(Op::FcvtHS),
+        (FloatFormat::H, FloatFormat::D) => Some(Op::FcvtHD),
+        (FloatFormat::H, FloatFormat::Q) => Some(Op::FcvtHQ),
+        (FloatFormat::Q, FloatFormat::S) => Some(Op::FcvtQS),
+        (FloatFormat::Q, FloatFormat::D) => Some(Op::FcvtQD),
+        (FloatFormat::Q, FloatFormat::H) => Some(Op::FcvtQH),
+        _ => None,
+    }
+}
+
+/// Returns whether the rd, rs1 and rs2 fields of a floating point
+/// instruction name floating point registers, as opposed to integer ones.
+pub(crate) fn float_registers(op: &Op) -> (bool, bool, bool) {
+    match op {
+        Op::Flw | Op::Fld => (true, false, false),
+        Op::Fsw | Op::Fsd => (false, false, true),
+        Op::FcvtWS
+        | Op::FcvtWuS
+        | Op::FcvtLS
+        | Op::FcvtLuS
+        | Op::FcvtWD
+        | Op::FcvtWuD
+        | Op::FcvtLD
+        | Op::FcvtLuD
+        | Op::FmvXW
+        | Op::FmvXD
+        | Op::FclassS
+        | Op::FclassD
+        | Op::FeqS
+        | Op::FeqD
+        | Op::FltS
+        | Op::FltD
+        | Op::FleS
+        | Op::FleD
+        | Op::FclassH
+        | Op::FclassQ
+        | Op::FcvtLH
+        | Op::FcvtLQ
+        | Op::FcvtLuH
+        | Op::FcvtLuQ
+        | Op::FcvtWH
+        | Op::FcvtWQ
+        | Op::FcvtWuH
+        | Op::FcvtWuQ
+        | Op::FcvtmodWD
+        | Op::FeqH
+        | Op::FeqQ
+        | Op::FleH
+        | Op::FleQ
+        | Op::FleqD
+        | Op::FleqH
+        | Op::FleqQ
+        | Op::FleqS
+        | Op::FltH
+        | Op::FltQ
+        | Op::FltqD
+        | Op::FltqH
+        | Op::FltqQ
+        | Op::FltqS
+        | Op::FmvXH
+        | Op::FmvhXD
+        | Op::FmvhXQ => (false, true, true),
+        Op::FcvtSW
+        | Op::FcvtSWu
+        | Op::FcvtSL
+        | Op::FcvtSLu
+        | Op::FcvtDW
+        | Op::FcvtDWu
+        | Op::FcvtDL
+        | Op::FcvtDLu
+        | Op::FmvWX
+        | Op::FmvDX
+        | Op::FcvtHL
+        | Op::FcvtHLu
+        | Op::FcvtHW
+        | Op::FcvtHWu
+        | Op::FcvtQL
+        | Op::FcvtQLu
+        | Op::FcvtQW
+        | Op::FcvtQWu
+        | Op::FmvHX
+        | Op::FmvpDX
+        | Op::FmvpQX => (true, false, false),
+        _ => (true, true, true),
+    }
+}
+
+fn register(u: u32, float: bool) -> Register {
+    if float {
+        Register::float_from_u32(u)
+    } else {
+        Register::from_u32(u)
+    }
+}
+
+fn rounding_mode(word: u32) -> Result<RoundingMode, DecodeError> {
+    RoundingMode::from_u32(funct3(word)).ok_or(DecodeError::Reserved)
+}
+
+/// LOAD-FP and STORE-FP give the width in funct3
+fn fp_width(word: u32) -> Result<FloatFormat, DecodeError> {
+    match funct3(word) {
+        0b001 => Ok(FloatFormat::H),
+        0b010 => Ok(FloatFormat::S),
+        0b011 => Ok(FloatFormat::D),
+        0b100 => Ok(FloatFormat::Q),
+        _ => Err(DecodeError::Reserved),
+    }
+}
+
+fn decode_load_fp(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    if is_vector_width(word) {
+        return decode_memory(word, isa);
+    }
+    let format = fp_width(word)?;
+    let op = select(format, Op::Flw, Op::Fld, Op::Flh, Op::Flq);
+    require_op(isa, &op, format_extension(format))?;
+
+    Ok(Instruction {
+        rd: Register::float_from_u32(rd(word)),
+        ..decode_i(op, word)
+    })
+}
+
+fn decode_store_fp(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    if is_vector_width(word) {
+        return decode_memory(word, isa);
+    }
+    let format = fp_width(word)?;
+    let op = select(format, Op::Fsw, Op::Fsd, Op::Fsh, Op::Fsq);
+    require_op(isa, &op, format_extension(format))?;
+
+    Ok(Instruction {
+        rs2: Register::float_from_u32(rs2(word)),
+        ..decode_s(op, word)
+    })
+}
+
+/// Decodes the fused multiply-add family, which take a third source
+/// register in bits 31:27.
+fn decode_r4(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let format = float_format(funct7(word));
+    let f = |s, d, h, q| select(format, s, d, h, q);
+    let op = match word & 0x7f {
+        0b1000011 => f(Op::FmaddS, Op::FmaddD, Op::FmaddH, Op::FmaddQ),
+        0b1000111 => f(Op::FmsubS, Op::FmsubD, Op::FmsubH, Op::FmsubQ),
+        0b1001011 => f(Op::FnmsubS, Op::FnmsubD, Op::FnmsubH, Op::FnmsubQ),
+        _ => f(Op::FnmaddS, Op::FnmaddD, Op::FnmaddH, Op::FnmaddQ),
+    };
+    require(isa, format_extension(format))?;
+    let rm = rounding_mode(word)?;
+
+    Ok(Instruction {
+        rs3: Register::float_from_u32(rs3(word)),
+        rs2: Register::float_from_u32(rs2(word)),
+        rs1: Register::float_from_u32(rs1(word)),
+        rd: Register::float_from_u32(rd(word)),
+        rm: Some(rm),
+        ..Instruction::new(op)
+    })
+}
+
+/// The Zfa additions to OP-FP, which take encodings left free by the
+/// other floating point extensions
+fn zfa_op(word: u32) -> Option<Op> {
+    let format = float_format(funct7(word));
+    let f = |s, d, h, q| select(format, s, d, h, q);
+    let d_or_q = |d, q| match format {
+        FloatFormat::D => Some(d),
+        FloatFormat::Q => Some(q),
+        _ => None,
+    };
+
+    let op = match (funct7(word) >> 2, funct3(word), rs2(word)) {
+        (0b11110, 0b000, 1) => f(Op::FliS, Op::FliD, Op::FliH, Op::FliQ),
+        (0b00101, 0b010, _) => f(Op::FminmS, Op::FminmD, Op::FminmH, Op::FminmQ),
+        (0b00101, 0b011, _) => f(Op::FmaxmS, Op::FmaxmD, Op::FmaxmH, Op::FmaxmQ),
+        (0b01000, _, 4) => f(Op::FroundS, Op::FroundD, Op::FroundH, Op::FroundQ),
+        (0b01000, _, 5) => f(Op::FroundnxS, Op::FroundnxD, Op::FroundnxH, Op::FroundnxQ),
+        (0b10100, 0b100, _) => f(Op::FleqS, Op::FleqD, Op::FleqH, Op::FleqQ),
+        (0b10100, 0b101, _) => f(Op::FltqS, Op::FltqD, Op::FltqH, Op::FltqQ),
+        // fcvtmod.w.d always rounds towards zero
+        (0b11000, 0b001, 8) if format == FloatFormat::D => Op::FcvtmodWD,
+        (0b11100, 0b000, 1) => d_or_q(Op::FmvhXD, Op::FmvhXQ)?,
+        (0b10110, 0b000, _) => d_or_q(Op::FmvpDX, Op::FmvpQX)?,
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// Decodes the OP-FP major opcode, dispatching on funct5 and the format.
+/// Where funct3 holds the rounding mode reserved rounding modes are
+/// rejected, and where rs2 selects the operation it is not an operand.
+fn decode_op_fp(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    let format = float_format(funct7(word));
+    let f = |s, d, h, q| select(format, s, d, h, q);
+    let funct5 = funct7(word) >> 2;
+
+    let op = if let Some(op) = zfa_op(word) {
+        require(isa, Extension::Zfa)?;
+        op
+    } else {
+        match (funct5, funct3(word), rs2(word)) {
+            (0b00000, _, _) => f(Op::FaddS, Op::FaddD, Op::FaddH, Op::FaddQ),
+            (0b00001, _, _) => f(Op::FsubS, Op::FsubD, Op::FsubH, Op::FsubQ),
+            (0b00010, _, _) => f(Op::FmulS, Op::FmulD, Op::FmulH, Op::FmulQ),
+            (0b00011, _, _) => f(Op::FdivS, Op::FdivD, Op::FdivH, Op::FdivQ),
+            (0b01011, _, 0) => f(Op::FsqrtS, Op::FsqrtD, Op::FsqrtH, Op::FsqrtQ),
+            (0b00100, 0b000, _) => f(Op::FsgnjS, Op::FsgnjD, Op::FsgnjH, Op::FsgnjQ),
+            (0b00100, 0b001, _) => f(Op::FsgnjnS, Op::FsgnjnD, Op::FsgnjnH, Op::FsgnjnQ),
+            (0b00100, 0b010, _) => f(Op::FsgnjxS, Op::FsgnjxD, Op::FsgnjxH, Op::FsgnjxQ),
+            (0b00101, 0b000, _) => f(Op::FminS, Op::FminD, Op::FminH, Op::FminQ),
+            (0b00101, 0b001, _) => f(Op::FmaxS, Op::FmaxD, Op::FmaxH, Op::FmaxQ),
+            (0b10100, 0b010, _) => f(Op::FeqS, Op::FeqD, Op::FeqH, Op::FeqQ),
+            (0b10100, 0b001, _) => f(Op::FltS, Op::FltD, Op::FltH, Op::FltQ),
+            (0b10100, 0b000, _) => f(Op::FleS, Op::FleD, Op::FleH, Op::FleQ),
+            (0b11100, 0b001, 0) => f(Op::FclassS, Op::FclassD, Op::FclassH, Op::FclassQ),
+            // Q has no moves to and from the integer registers, leaving
+            // them to the Zfa moves of the two halves
+            (0b11100, 0b000, 0) | (0b11110, 0b000, 0) if format == FloatFormat::Q => {
+                return Err(DecodeError::Reserved)
             }
-            OpType::Uj => {
-                if opcode == o.opcode {
-                    return Some(decode_uj(o, word));
-                }
+            (0b11100, 0b000, 0) => f(Op::FmvXW, Op::FmvXD, Op::FmvXH, Op::FmvXD),
+            (0b11110, 0b000, 0) => f(Op::FmvWX, Op::FmvDX, Op::FmvHX, Op::FmvDX),
+            (0b11000, _, 0) => f(Op::FcvtWS, Op::FcvtWD, Op::FcvtWH, Op::FcvtWQ),
+            (0b11000, _, 1) => f(Op::FcvtWuS, Op::FcvtWuD, Op::FcvtWuH, Op::FcvtWuQ),
+            (0b11000, _, 2) => f(Op::FcvtLS, Op::FcvtLD, Op::FcvtLH, Op::FcvtLQ),
+            (0b11000, _, 3) => f(Op::FcvtLuS, Op::FcvtLuD, Op::FcvtLuH, Op::FcvtLuQ),
+            (0b11010, _, 0) => f(Op::FcvtSW, Op::FcvtDW, Op::FcvtHW, Op::FcvtQW),
+            (0b11010, _, 1) => f(Op::FcvtSWu, Op::FcvtDWu, Op::FcvtHWu, Op::FcvtQWu),
+            (0b11010, _, 2) => f(Op::FcvtSL, Op::FcvtDL, Op::FcvtHL, Op::FcvtQL),
+            (0b11010, _, 3) => f(Op::FcvtSLu, Op::FcvtDLu, Op::FcvtHLu, Op::FcvtQLu),
+            // Conversions between formats give the source format in rs2
+            (0b01000, _, source) if source < 4 => {
+                conversion(format, float_format(source)).ok_or(DecodeError::Reserved)?
             }
-            OpType::Special => {
-                if opcode == o.opcode && funct3 == o.funct3 {
-                    return Some(decode_special(o, word));
-                }
+            _ => return Err(DecodeError::Reserved),
+        }
+    };
+
+    // Conversions between formats belong to the wider format, save that
+    // Zfhmin has those with half precision alongside the other format
+    match op {
+        Op::FcvtSD | Op::FcvtDS => require(isa, Extension::D)?,
+        Op::FcvtSQ | Op::FcvtQS | Op::FcvtDQ | Op::FcvtQD => require(isa, Extension::Q)?,
+        Op::FcvtSH | Op::FcvtHS => require_op(isa, &op, Extension::Zfh)?,
+        Op::FcvtDH | Op::FcvtHD => {
+            require_op(isa, &op, Extension::Zfh)?;
+            require(isa, Extension::D)?
+        }
+        Op::FcvtQH | Op::FcvtHQ => {
+            require_op(isa, &op, Extension::Zfh)?;
+            require(isa, Extension::Q)?
+        }
+        _ => require_op(isa, &op, format_extension(format))?,
+    }
+    match op {
+        Op::FcvtLS
+        | Op::FcvtLuS
+        | Op::FcvtSL
+        | Op::FcvtSLu
+        | Op::FcvtLD
+        | Op::FcvtLuD
+        | Op::FcvtDL
+        | Op::FcvtDLu
+        | Op::FmvXD
+        | Op::FmvDX
+        | Op::FcvtLH
+        | Op::FcvtLuH
+        | Op::FcvtHL
+        | Op::FcvtHLu
+        | Op::FcvtLQ
+        | Op::FcvtLuQ
+        | Op::FcvtQL
+        | Op::FcvtQLu
+        | Op::FmvhXQ
+        | Op::FmvpQX => require_rv64(isa)?,
+        // The halves of a double only need moving separately on RV32
+        Op::FmvhXD | Op::FmvpDX => require_rv32(isa)?,
+        _ => {}
+    }
+
+    // funct3 holds the rounding mode of arithmetic and conversions, and the
+    // ops with a single source use rs2 to select the operation
+    let rm = match funct5 {
+        0b00000..=0b00011 | 0b01011 | 0b01000 | 0b11000 | 0b11010 => Some(rounding_mode(word)?),
+        _ => None,
+    };
+    let unary = matches!(
+        funct5,
+        0b01011 | 0b01000 | 0b11000 | 0b11010 | 0b11100 | 0b11110
+    );
+    let (rd_float, rs1_float, rs2_float) = float_registers(&op);
+
+    // fli takes the index of its constant in the rs1 field
+    if let Op::FliS | Op::FliD | Op::FliH | Op::FliQ = op {
+        return Ok(Instruction {
+            rd: Register::float_from_u32(rd(word)),
+            immediate: rs1(word) as i32,
+            ..Instruction::new(op)
+        });
+    }
+
+    Ok(Instruction {
+        rs2: if unary {
+            Register::Invalid
+        } else {
+            register(rs2(word), rs2_float)
+        },
+        rs1: register(rs1(word), rs1_float),
+        rd: register(rd(word), rd_float),
+        rm,
+        ..Instruction::new(op)
+    })
+}
+
+/// Decode a 32-bit instruction with the default `Isa::rv32im`
+pub fn decode(word: u32) -> Result<Instruction, DecodeError> {
+    decode_isa(word, &Isa::rv32im())
+}
+
+/// Decode a 32-bit instruction, accepting only encodings from the base
+/// integer width and extensions enabled in `isa`.
+///
+/// Encodings which belong to a disabled extension, or only exist for the
+/// other XLEN, are rejected with `DecodeError::ExtensionDisabled`.
+pub fn decode_isa(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    // The all-zero and all-ones words are defined to be illegal
+    if word == 0 || word == 0xffff_ffff {
+        return Err(DecodeError::Illegal);
+    }
+
+    let instruction = match word & 0x7f {
+        0b0110111 => decode_u(Op::Lui, word),
+        0b0010111 => decode_u(Op::Auipc, word),
+        0b1101111 => decode_uj(Op::Jal, word),
+        0b1100111 => decode_jalr(word)?,
+        0b1100011 => decode_branch(word)?,
+        0b0000011 => decode_load(word, isa)?,
+        0b0100011 => decode_store(word, isa)?,
+        0b0010011 => decode_op_imm(word, isa)?,
+        0b0011011 => decode_op_imm_32(word, isa)?,
+        0b0110011 => decode_op(word, isa)?,
+        0b0111011 => decode_op_32(word, isa)?,
+        0b0001111 => decode_misc_mem(word, isa)?,
+        0b1110011 => decode_system(word, isa)?,
+        0b0101111 => decode_amo(word, isa)?,
+        0b0000111 => decode_load_fp(word, isa)?,
+        0b0100111 => decode_store_fp(word, isa)?,
+        0b1000011 | 0b1000111 | 0b1001011 | 0b1001111 => decode_r4(word, isa)?,
+        0b1010011 => decode_op_fp(word, isa)?,
+        0b1010111 => decode_op_v(word, isa)?,
+        opcode => return Err(DecodeError::UnknownOpcode(opcode)),
+    };
+
+    check_registers(instruction, isa)
+}
+
+/// RV32E and RV64E only have the integer registers x0-x15
+pub(crate) fn check_registers(
+    instruction: Instruction,
+    isa: &Isa,
+) -> Result<Instruction, DecodeError> {
+    if isa.has(Extension::E) {
+        for register in &[instruction.rd(), instruction.rs1(), instruction.rs2()] {
+            if !register.is_float()
+                && !register.is_vector()
+                && register.index().is_some_and(|index| index >= 16)
+            {
+                return Err(DecodeError::Reserved);
             }
         }
     }
+    Ok(instruction)
+}
+
+/// Decode the instruction held in the low bits of `word`, which may be a
+/// 16-bit compressed instruction or a 32-bit instruction, returning it with
+/// its length in bytes.
+pub fn decode_with_length(word: u32, isa: &Isa) -> Result<(Instruction, usize), DecodeError> {
+    if word & 0x3 == 0x3 {
+        decode_isa(word, isa).map(|instruction| (instruction, 4))
+    } else {
+        decode_compressed(word as u16, isa).map(|instruction| (instruction, 2))
+    }
+}
+
+/// Determines the length in bytes of an instruction from its first 16-bit
+/// parcel, using the standard variable-length encoding scheme. Returns
+/// `None` for the encodings reserved for instructions of 192 bits or more.
+pub fn instruction_length(parcel: u16) -> Option<usize> {
+    if parcel & 0x3 != 0x3 {
+        Some(2)
+    } else if parcel & 0x1c != 0x1c {
+        Some(4)
+    } else if parcel & 0x3f == 0x1f {
+        Some(6)
+    } else if parcel & 0x7f == 0x3f {
+        Some(8)
+    } else if (parcel >> 12) & 0x7 != 0x7 {
+        Some(10 + 2 * ((parcel as usize >> 12) & 0x7))
+    } else {
+        None
+    }
+}
+
+/// Decode the instruction at the start of `bytes`, which holds instruction
+/// parcels in little-endian order, returning it with its length in bytes.
+pub fn decode_bytes(bytes: &[u8], isa: &Isa) -> Result<(Instruction, usize), DecodeError> {
+    if bytes.len() < 2 {
+        return Err(DecodeError::Truncated(2));
+    }
+    let parcel = u16::from(bytes[0]) | u16::from(bytes[1]) << 8;
+
+    // Encodings of 192 bits or more are reserved, and include the illegal
+    // all-ones parcel
+    let length = instruction_length(parcel).ok_or(DecodeError::Illegal)?;
+    if length > 4 {
+        return Err(DecodeError::UnsupportedLength(length));
+    }
+    if bytes.len() < length {
+        return Err(DecodeError::Truncated(length));
+    }
+
+    let instruction = if length == 2 {
+        decode_compressed(parcel, isa)
+    } else {
+        let word = u32::from(parcel) | u32::from(bytes[2]) << 16 | u32::from(bytes[3]) << 24;
+        decode_isa(word, isa)
+    };
 
-    None
+    instruction.map(|instruction| (instruction, length))
 }
@@ -0,0 +1,1111 @@
+use csr::{CYCLE, CYCLEH, INSTRET, INSTRETH, TIME, TIMEH};
+use decoder::float_registers;
+use error::EncodeError;
+use vector::{opcode_bits, slots, Class, Slot};
+use {Csr, Instruction, Op, Register, RoundingMode, VectorOp, Xlen};
+
+// Major opcodes
+const LOAD: u32 = 0b0000011;
+const LOAD_FP: u32 = 0b0000111;
+const MISC_MEM: u32 = 0b0001111;
+const OP_IMM: u32 = 0b0010011;
+const AUIPC: u32 = 0b0010111;
+const OP_IMM_32: u32 = 0b0011011;
+const STORE: u32 = 0b0100011;
+const STORE_FP: u32 = 0b0100111;
+const AMO: u32 = 0b0101111;
+const OP: u32 = 0b0110011;
+const LUI: u32 = 0b0110111;
+const OP_32: u32 = 0b0111011;
+const MADD: u32 = 0b1000011;
+const MSUB: u32 = 0b1000111;
+const NMSUB: u32 = 0b1001011;
+const NMADD: u32 = 0b1001111;
+const OP_FP: u32 = 0b1010011;
+const BRANCH: u32 = 0b1100011;
+const JALR: u32 = 0b1100111;
+const JAL: u32 = 0b1101111;
+const SYSTEM: u32 = 0b1110011;
+
+/// The number of an integer register
+fn x(register: &Register) -> Result<u32, EncodeError> {
+    match register.index() {
+        Some(index) if !register.is_float() && !register.is_vector() => Ok(index),
+        _ => Err(EncodeError::InvalidRegister),
+    }
+}
+
+/// The number of a floating point register
+fn f(register: &Register) -> Result<u32, EncodeError> {
+    match register.index() {
+        Some(index) if register.is_float() => Ok(index),
+        _ => Err(EncodeError::InvalidRegister),
+    }
+}
+
+/// The number of a vector register
+fn v(register: &Register) -> Result<u32, EncodeError> {
+    match register.index() {
+        Some(index) if register.is_vector() => Ok(index),
+        _ => Err(EncodeError::InvalidRegister),
+    }
+}
+
+fn register(register: &Register, float: bool) -> Result<u32, EncodeError> {
+    if float {
+        f(register)
+    } else {
+        x(register)
+    }
+}
+
+/// Checks that `value` is a multiple of `align` and fits in a `bits` wide
+/// two's complement field, returning the field
+fn signed(value: i32, bits: u32, align: i32) -> Result<u32, EncodeError> {
+    let value = i64::from(value);
+    if value < -(1 << (bits - 1)) || value >= 1 << (bits - 1) {
+        return Err(EncodeError::ImmediateOutOfRange);
+    }
+    if value % i64::from(align) != 0 {
+        return Err(EncodeError::MisalignedImmediate);
+    }
+    Ok(value as u32 & ((1 << bits) - 1))
+}
+
+/// Checks that `value` is a multiple of `align` and fits in a `bits` wide
+/// unsigned field
+fn unsigned(value: i64, bits: u32, align: i64) -> Result<u32, EncodeError> {
+    if value < 0 || value >= 1 << bits {
+        return Err(EncodeError::ImmediateOutOfRange);
+    }
+    if value % align != 0 {
+        return Err(EncodeError::MisalignedImmediate);
+    }
+    Ok(value as u32)
+}
+
+fn rm(instruction: &Instruction) -> u32 {
+    match instruction.rm.unwrap_or(RoundingMode::Dyn) {
+        RoundingMode::Rne => 0b000,
+        RoundingMode::Rtz => 0b001,
+        RoundingMode::Rdn => 0b010,
+        RoundingMode::Rup => 0b011,
+        RoundingMode::Rmm => 0b100,
+        RoundingMode::Dyn => 0b111,
+    }
+}
+
+fn r_type(opcode: u32, funct3: u32, funct7: u32, rd: u32, rs1: u32, rs2: u32) -> u32 {
+    funct7 << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn i_type(opcode: u32, funct3: u32, rd: u32, rs1: u32, immediate: u32) -> u32 {
+    immediate << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn s_type(opcode: u32, funct3: u32, rs1: u32, rs2: u32, immediate: u32) -> u32 {
+    (immediate >> 5) << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | (immediate & 0x1f) << 7 | opcode
+}
+
+/// An integer register-register instruction
+fn encode_r(
+    instruction: &Instruction,
+    opcode: u32,
+    funct3: u32,
+    funct7: u32,
+) -> Result<u32, EncodeError> {
+    Ok(r_type(
+        opcode,
+        funct3,
+        funct7,
+        x(&instruction.rd)?,
+        x(&instruction.rs1)?,
+        x(&instruction.rs2)?,
+    ))
+}
+
+/// An instruction with a 12-bit signed immediate, such as `addi`, `jalr` or
+/// a load
+fn encode_i(instruction: &Instruction, opcode: u32, funct3: u32) -> Result<u32, EncodeError> {
+    let (rd_float, _, _) = float_registers(&instruction.op);
+    Ok(i_type(
+        opcode,
+        funct3,
+        register(&instruction.rd, rd_float && opcode == LOAD_FP)?,
+        x(&instruction.rs1)?,
+        signed(instruction.immediate, 12, 1)?,
+    ))
+}
+
+fn encode_s(instruction: &Instruction, opcode: u32, funct3: u32) -> Result<u32, EncodeError> {
+    Ok(s_type(
+        opcode,
+        funct3,
+        x(&instruction.rs1)?,
+        register(&instruction.rs2, opcode == STORE_FP)?,
+        signed(instruction.immediate, 12, 1)?,
+    ))
+}
+
+/// Shifts by an immediate, where the shift amount is `bits` wide
+fn encode_shift(
+    instruction: &Instruction,
+    opcode: u32,
+    funct3: u32,
+    funct6: u32,
+    bits: u32,
+) -> Result<u32, EncodeError> {
+    let shamt = unsigned(instruction.shamt as i64, bits, 1)?;
+    Ok(i_type(
+        opcode,
+        funct3,
+        x(&instruction.rd)?,
+        x(&instruction.rs1)?,
+        funct6 << 6 | shamt,
+    ))
+}
+
+/// An instruction with one source register, where `selector` fills the
+/// rest of the immediate
+fn encode_unary(
+    instruction: &Instruction,
+    opcode: u32,
+    funct3: u32,
+    selector: u32,
+) -> Result<u32, EncodeError> {
+    Ok(i_type(
+        opcode,
+        funct3,
+        x(&instruction.rd)?,
+        x(&instruction.rs1)?,
+        selector,
+    ))
+}
+
+/// The AES and SM4 ops which take a byte select in bits 31:30
+fn encode_byte_select(instruction: &Instruction, funct5: u32) -> Result<u32, EncodeError> {
+    let bs = unsigned(i64::from(instruction.immediate), 2, 1)?;
+    encode_r(instruction, OP, 0b000, bs << 5 | funct5)
+}
+
+/// `aes64ks1i`, whose round number only goes up to 0xa
+fn encode_aes64ks1i(instruction: &Instruction) -> Result<u32, EncodeError> {
+    let rnum = unsigned(i64::from(instruction.immediate), 4, 1)?;
+    if rnum > 0xa {
+        return Err(EncodeError::ImmediateOutOfRange);
+    }
+    encode_unary(instruction, OP_IMM, 0b001, 0x310 | rnum)
+}
+
+fn encode_u(instruction: &Instruction, opcode: u32) -> Result<u32, EncodeError> {
+    let immediate = unsigned(i64::from(instruction.immediate), 20, 1)?;
+    Ok(immediate << 12 | x(&instruction.rd)? << 7 | opcode)
+}
+
+fn encode_b(instruction: &Instruction, funct3: u32) -> Result<u32, EncodeError> {
+    let immediate = signed(instruction.immediate, 13, 2)?;
+    Ok(((immediate >> 12) & 1) << 31
+        | ((immediate >> 5) & 0x3f) << 25
+        | x(&instruction.rs2)? << 20
+        | x(&instruction.rs1)? << 15
+        | funct3 << 12
+        | ((immediate >> 1) & 0xf) << 8
+        | ((immediate >> 11) & 1) << 7
+        | BRANCH)
+}
+
+fn encode_jal(instruction: &Instruction) -> Result<u32, EncodeError> {
+    let immediate = signed(instruction.immediate, 21, 2)?;
+    Ok(((immediate >> 20) & 1) << 31
+        | ((immediate >> 1) & 0x3ff) << 21
+        | ((immediate >> 11) & 1) << 20
+        | ((immediate >> 12) & 0xff) << 12
+        | x(&instruction.rd)? << 7
+        | JAL)
+}
+
+fn encode_fence(instruction: &Instruction, funct3: u32) -> Result<u32, EncodeError> {
+    let fence = match instruction.fence {
+        Some(ref fence) => {
+            let bits = [
+                fence.pi, fence.po, fence.pr, fence.pw, fence.si, fence.so, fence.sr, fence.sw,
+            ];
+            // The fence mode sits above the predecessor and successor sets
+            bits.iter()
+                .fold(fence.fm, |set, bit| set << 1 | *bit as u32)
+        }
+        None => 0,
+    };
+    Ok(i_type(
+        MISC_MEM,
+        funct3,
+        x(&instruction.rd)?,
+        x(&instruction.rs1)?,
+        fence,
+    ))
+}
+
+/// The cache-block operations select the operation with the immediate,
+/// and take their base address in rs1 alone
+fn encode_cbo(instruction: &Instruction, selector: u32) -> Result<u32, EncodeError> {
+    Ok(i_type(MISC_MEM, 0b010, 0, x(&instruction.rs1)?, selector))
+}
+
+/// The prefetches are `ori` with rd x0, with an offset which is a multiple
+/// of 32 above the five bits selecting the prefetch
+fn encode_prefetch(instruction: &Instruction, selector: u32) -> Result<u32, EncodeError> {
+    Ok(i_type(
+        OP_IMM,
+        0b110,
+        0,
+        x(&instruction.rs1)?,
+        signed(instruction.immediate, 12, 32)? | selector,
+    ))
+}
+
+fn encode_csr(instruction: &Instruction, funct3: u32) -> Result<u32, EncodeError> {
+    let csr = instruction.csr.ok_or(EncodeError::Reserved)?.address() as u32;
+    let source = match instruction.op {
+        Op::Csrrwi | Op::Csrrsi | Op::Csrrci => unsigned(i64::from(instruction.immediate), 5, 1)?,
+        _ => x(&instruction.rs1)?,
+    };
+    Ok(i_type(SYSTEM, funct3, x(&instruction.rd)?, source, csr))
+}
+
+/// The counter reads are `csrrs rd, csr, x0`
+fn encode_counter(instruction: &Instruction, csr: Csr) -> Result<u32, EncodeError> {
+    Ok(i_type(
+        SYSTEM,
+        0b010,
+        x(&instruction.rd)?,
+        0,
+        csr.address() as u32,
+    ))
+}
+
+/// The hypervisor loads select their form with the rs2 field
+fn encode_hlv(instruction: &Instruction, funct7: u32, selector: u32) -> Result<u32, EncodeError> {
+    Ok(r_type(
+        SYSTEM,
+        0b100,
+        funct7,
+        x(&instruction.rd)?,
+        x(&instruction.rs1)?,
+        selector,
+    ))
+}
+
+fn encode_hsv(instruction: &Instruction, funct7: u32) -> Result<u32, EncodeError> {
+    Ok(r_type(
+        SYSTEM,
+        0b100,
+        funct7,
+        0,
+        x(&instruction.rs1)?,
+        x(&instruction.rs2)?,
+    ))
+}
+
+fn encode_amo(instruction: &Instruction, funct5: u32, funct3: u32) -> Result<u32, EncodeError> {
+    let (aq, rl) = match instruction.aqrl {
+        Some(ref aqrl) => (aqrl.aq as u32, aqrl.rl as u32),
+        None => (0, 0),
+    };
+    let rs2 = match instruction.op {
+        Op::LrW | Op::LrD => 0,
+        _ => x(&instruction.rs2)?,
+    };
+    Ok(r_type(
+        AMO,
+        funct3,
+        funct5 << 2 | aq << 1 | rl,
+        x(&instruction.rd)?,
+        x(&instruction.rs1)?,
+        rs2,
+    ))
+}
+
+fn encode_r4(instruction: &Instruction, opcode: u32, fmt: u32) -> Result<u32, EncodeError> {
+    Ok(r_type(
+        opcode,
+        rm(instruction),
+        f(&instruction.rs3)? << 2 | fmt,
+        f(&instruction.rd)?,
+        f(&instruction.rs1)?,
+        f(&instruction.rs2)?,
+    ))
+}
+
+/// An OP-FP instruction. `funct3` is the fixed value of the funct3 field,
+/// or `None` where it holds the rounding mode, and `rs2` likewise for
+/// instructions where the rs2 field selects the operation.
+fn encode_op_fp(
+    instruction: &Instruction,
+    funct5: u32,
+    fmt: u32,
+    funct3: Option<u32>,
+    rs2: Option<u32>,
+) -> Result<u32, EncodeError> {
+    let (rd_float, rs1_float, rs2_float) = float_registers(&instruction.op);
+    let rs2 = match rs2 {
+        Some(rs2) => rs2,
+        None => register(&instruction.rs2, rs2_float)?,
+    };
+    Ok(r_type(
+        OP_FP,
+        funct3.unwrap_or_else(|| rm(instruction)),
+        funct5 << 2 | fmt,
+        register(&instruction.rd, rd_float)?,
+        register(&instruction.rs1, rs1_float)?,
+        rs2,
+    ))
+}
+
+/// `fli`, whose rs1 field holds the index of the constant it loads
+fn encode_fli(instruction: &Instruction, fmt: u32) -> Result<u32, EncodeError> {
+    let index = unsigned(i64::from(instruction.immediate), 5, 1)?;
+    Ok(r_type(
+        OP_FP,
+        0b000,
+        0b11110 << 2 | fmt,
+        f(&instruction.rd)?,
+        index,
+        1,
+    ))
+}
+
+/// Encodes a vector instruction by placing each of its operands in the
+/// field its slot names
+fn encode_vector(instruction: &Instruction, op: VectorOp) -> Result<u32, EncodeError> {
+    let i = instruction;
+    let mut word = opcode_bits(i, op).ok_or(EncodeError::Reserved)?;
+    let register = |register: &Register, class: Class| match class {
+        Class::X => x(register),
+        Class::F => f(register),
+        Class::V => v(register),
+    };
+    for slot in slots(i, op) {
+        word |= match slot {
+            Slot::Rd(class) => register(&i.rd, class)? << 7,
+            Slot::Rs1(class) => register(&i.rs1, class)? << 15,
+            Slot::Rs2(class) => register(&i.rs2, class)? << 20,
+            Slot::Rs3 => v(&i.rs3)? << 7,
+            Slot::Immediate(true) => signed(i.immediate, 5, 1)? << 15,
+            Slot::Immediate(false) => unsigned(i64::from(i.immediate), 5, 1)? << 15,
+            Slot::Base => x(&i.rs1)? << 15,
+            Slot::Vtype => i.vtype.ok_or(EncodeError::Reserved)?.bits() << 20,
+            Slot::V0 => 0,
+        };
+    }
+    Ok(word)
+}
+
+/// Encode an instruction as a machine word, the inverse of `decode`.
+///
+/// Compressed instructions are encoded as their 16-bit form, in the low
+/// half of the word. Immediates are checked against the range and
+/// alignment of their encoding, and registers against the register file
+/// and, for compressed instructions, the subset of registers the encoding
+/// can address. A missing rounding mode encodes the dynamic rounding mode.
+pub fn encode(instruction: &Instruction) -> Result<u32, EncodeError> {
+    const S: u32 = 0b00;
+    const D: u32 = 0b01;
+    const H: u32 = 0b10;
+    const Q: u32 = 0b11;
+
+    let i = instruction;
+    match i.op {
+        Op::Lui => encode_u(i, LUI),
+        Op::Auipc => encode_u(i, AUIPC),
+        Op::Jal => encode_jal(i),
+        Op::Jalr => encode_i(i, JALR, 0b000),
+        Op::Beq => encode_b(i, 0b000),
+        Op::Bne => encode_b(i, 0b001),
+        Op::Blt => encode_b(i, 0b100),
+        Op::Bge => encode_b(i, 0b101),
+        Op::Bltu => encode_b(i, 0b110),
+        Op::Bgeu => encode_b(i, 0b111),
+        Op::Lb => encode_i(i, LOAD, 0b000),
+        Op::Lh => encode_i(i, LOAD, 0b001),
+        Op::Lw => encode_i(i, LOAD, 0b010),
+        Op::Ld => encode_i(i, LOAD, 0b011),
+        Op::Lbu => encode_i(i, LOAD, 0b100),
+        Op::Lhu => encode_i(i, LOAD, 0b101),
+        Op::Lwu => encode_i(i, LOAD, 0b110),
+        Op::Sb => encode_s(i, STORE, 0b000),
+        Op::Sh => encode_s(i, STORE, 0b001),
+        Op::Sw => encode_s(i, STORE, 0b010),
+        Op::Sd => encode_s(i, STORE, 0b011),
+        Op::Addi => encode_i(i, OP_IMM, 0b000),
+        Op::Slti => encode_i(i, OP_IMM, 0b010),
+        Op::Sltiu => encode_i(i, OP_IMM, 0b011),
+        Op::Xori => encode_i(i, OP_IMM, 0b100),
+        Op::Ori => encode_i(i, OP_IMM, 0b110),
+        Op::Andi => encode_i(i, OP_IMM, 0b111),
+        Op::Slli => encode_shift(i, OP_IMM, 0b001, 0b000000, 6),
+        Op::Srli => encode_shift(i, OP_IMM, 0b101, 0b000000, 6),
+        Op::Srai => encode_shift(i, OP_IMM, 0b101, 0b010000, 6),
+        Op::Addiw => encode_i(i, OP_IMM_32, 0b000),
+        Op::Slliw => encode_shift(i, OP_IMM_32, 0b001, 0b000000, 5),
+        Op::Srliw => encode_shift(i, OP_IMM_32, 0b101, 0b000000, 5),
+        Op::Sraiw => encode_shift(i, OP_IMM_32, 0b101, 0b010000, 5),
+        Op::Add => encode_r(i, OP, 0b000, 0b0000000),
+        Op::Sub => encode_r(i, OP, 0b000, 0b0100000),
+        Op::Sll => encode_r(i, OP, 0b001, 0b0000000),
+        Op::Slt => encode_r(i, OP, 0b010, 0b0000000),
+        Op::Sltu => encode_r(i, OP, 0b011, 0b0000000),
+        Op::Xor => encode_r(i, OP, 0b100, 0b0000000),
+        Op::Srl => encode_r(i, OP, 0b101, 0b0000000),
+        Op::Sra => encode_r(i, OP, 0b101, 0b0100000),
+        Op::Or => encode_r(i, OP, 0b110, 0b0000000),
+        Op::And => encode_r(i, OP, 0b111, 0b0000000),
+        Op::Mul => encode_r(i, OP, 0b000, 0b0000001),
+        Op::Mulh => encode_r(i, OP, 0b001, 0b0000001),
+        Op::Mulhsu => encode_r(i, OP, 0b010, 0b0000001),
+        Op::Mulhu => encode_r(i, OP, 0b011, 0b0000001),
+        Op::Div => encode_r(i, OP, 0b100, 0b0000001),
+        Op::Divu => encode_r(i, OP, 0b101, 0b0000001),
+        Op::Rem => encode_r(i, OP, 0b110, 0b0000001),
+        Op::Remu => encode_r(i, OP, 0b111, 0b0000001),
+        Op::Addw => encode_r(i, OP_32, 0b000, 0b0000000),
+        Op::Subw => encode_r(i, OP_32, 0b000, 0b0100000),
+        Op::Sllw => encode_r(i, OP_32, 0b001, 0b0000000),
+        Op::Srlw => encode_r(i, OP_32, 0b101, 0b0000000),
+        Op::Sraw => encode_r(i, OP_32, 0b101, 0b0100000),
+        Op::Mulw => encode_r(i, OP_32, 0b000, 0b0000001),
+        Op::Divw => encode_r(i, OP_32, 0b100, 0b0000001),
+        Op::Divuw => encode_r(i, OP_32, 0b101, 0b0000001),
+        Op::Remw => encode_r(i, OP_32, 0b110, 0b0000001),
+        Op::Remuw => encode_r(i, OP_32, 0b111, 0b0000001),
+        Op::Sh1add => encode_r(i, OP, 0b010, 0b0010000),
+        Op::Sh2add => encode_r(i, OP, 0b100, 0b0010000),
+        Op::Sh3add => encode_r(i, OP, 0b110, 0b0010000),
+        Op::AddUw => encode_r(i, OP_32, 0b000, 0b0000100),
+        Op::Sh1addUw => encode_r(i, OP_32, 0b010, 0b0010000),
+        Op::Sh2addUw => encode_r(i, OP_32, 0b100, 0b0010000),
+        Op::Sh3addUw => encode_r(i, OP_32, 0b110, 0b0010000),
+        Op::SlliUw => encode_shift(i, OP_IMM_32, 0b001, 0b000010, 6),
+        Op::Andn => encode_r(i, OP, 0b111, 0b0100000),
+        Op::Orn => encode_r(i, OP, 0b110, 0b0100000),
+        Op::Xnor => encode_r(i, OP, 0b100, 0b0100000),
+        Op::Clz => encode_unary(i, OP_IMM, 0b001, 0x600),
+        Op::Ctz => encode_unary(i, OP_IMM, 0b001, 0x601),
+        Op::Cpop => encode_unary(i, OP_IMM, 0b001, 0x602),
+        Op::SextB => encode_unary(i, OP_IMM, 0b001, 0x604),
+        Op::SextH => encode_unary(i, OP_IMM, 0b001, 0x605),
+        Op::Clzw => encode_unary(i, OP_IMM_32, 0b001, 0x600),
+        Op::Ctzw => encode_unary(i, OP_IMM_32, 0b001, 0x601),
+        Op::Cpopw => encode_unary(i, OP_IMM_32, 0b001, 0x602),
+        Op::Max => encode_r(i, OP, 0b110, 0b0000101),
+        Op::Maxu => encode_r(i, OP, 0b111, 0b0000101),
+        Op::Min => encode_r(i, OP, 0b100, 0b0000101),
+        Op::Minu => encode_r(i, OP, 0b101, 0b0000101),
+        Op::Rol => encode_r(i, OP, 0b001, 0b0110000),
+        Op::Ror => encode_r(i, OP, 0b101, 0b0110000),
+        Op::Rori => encode_shift(i, OP_IMM, 0b101, 0b011000, 6),
+        Op::Rolw => encode_r(i, OP_32, 0b001, 0b0110000),
+        Op::Rorw => encode_r(i, OP_32, 0b101, 0b0110000),
+        Op::Roriw => encode_shift(i, OP_IMM_32, 0b101, 0b011000, 5),
+        Op::OrcB => encode_unary(i, OP_IMM, 0b101, 0x287),
+        // rev8 rotates by XLEN-8, and zext.h is pack or packw with rs2 zero
+        Op::Rev8 => match i.xlen {
+            Some(Xlen::Rv32) => encode_unary(i, OP_IMM, 0b101, 0x698),
+            Some(Xlen::Rv64) => encode_unary(i, OP_IMM, 0b101, 0x6b8),
+            None => Err(EncodeError::Reserved),
+        },
+        Op::ZextH => match i.xlen {
+            Some(Xlen::Rv32) => encode_unary(i, OP, 0b100, 0x080),
+            Some(Xlen::Rv64) => encode_unary(i, OP_32, 0b100, 0x080),
+            None => Err(EncodeError::Reserved),
+        },
+        Op::Clmul => encode_r(i, OP, 0b001, 0b0000101),
+        Op::Clmulh => encode_r(i, OP, 0b011, 0b0000101),
+        Op::Clmulr => encode_r(i, OP, 0b010, 0b0000101),
+        Op::Bclr => encode_r(i, OP, 0b001, 0b0100100),
+        Op::Bext => encode_r(i, OP, 0b101, 0b0100100),
+        Op::Binv => encode_r(i, OP, 0b001, 0b0110100),
+        Op::Bset => encode_r(i, OP, 0b001, 0b0010100),
+        Op::Bclri => encode_shift(i, OP_IMM, 0b001, 0b010010, 6),
+        Op::Bexti => encode_shift(i, OP_IMM, 0b101, 0b010010, 6),
+        Op::Binvi => encode_shift(i, OP_IMM, 0b001, 0b011010, 6),
+        Op::Bseti => encode_shift(i, OP_IMM, 0b001, 0b001010, 6),
+        Op::Pack => encode_r(i, OP, 0b100, 0b0000100),
+        Op::Packh => encode_r(i, OP, 0b111, 0b0000100),
+        Op::Packw => encode_r(i, OP_32, 0b100, 0b0000100),
+        Op::Brev8 => encode_unary(i, OP_IMM, 0b101, 0x687),
+        Op::Zip => encode_unary(i, OP_IMM, 0b001, 0x08f),
+        Op::Unzip => encode_unary(i, OP_IMM, 0b101, 0x08f),
+        Op::Xperm4 => encode_r(i, OP, 0b010, 0b0010100),
+        Op::Xperm8 => encode_r(i, OP, 0b100, 0b0010100),
+        Op::Aes32esi => encode_byte_select(i, 0b10001),
+        Op::Aes32esmi => encode_byte_select(i, 0b10011),
+        Op::Aes32dsi => encode_byte_select(i, 0b10101),
+        Op::Aes32dsmi => encode_byte_select(i, 0b10111),
+        Op::Aes64es => encode_r(i, OP, 0b000, 0b0011001),
+        Op::Aes64esm => encode_r(i, OP, 0b000, 0b0011011),
+        Op::Aes64ds => encode_r(i, OP, 0b000, 0b0011101),
+        Op::Aes64dsm => encode_r(i, OP, 0b000, 0b0011111),
+        Op::Aes64im => encode_unary(i, OP_IMM, 0b001, 0x300),
+        Op::Aes64ks1i => encode_aes64ks1i(i),
+        Op::Aes64ks2 => encode_r(i, OP, 0b000, 0b0111111),
+        Op::Sha256sum0 => encode_unary(i, OP_IMM, 0b001, 0x100),
+        Op::Sha256sum1 => encode_unary(i, OP_IMM, 0b001, 0x101),
+        Op::Sha256sig0 => encode_unary(i, OP_IMM, 0b001, 0x102),
+        Op::Sha256sig1 => encode_unary(i, OP_IMM, 0b001, 0x103),
+        Op::Sha512sum0 => encode_unary(i, OP_IMM, 0b001, 0x104),
+        Op::Sha512sum1 => encode_unary(i, OP_IMM, 0b001, 0x105),
+        Op::Sha512sig0 => encode_unary(i, OP_IMM, 0b001, 0x106),
+        Op::Sha512sig1 => encode_unary(i, OP_IMM, 0b001, 0x107),
+        Op::Sha512sum0r => encode_r(i, OP, 0b000, 0b0101000),
+        Op::Sha512sum1r => encode_r(i, OP, 0b000, 0b0101001),
+        Op::Sha512sig0l => encode_r(i, OP, 0b000, 0b0101010),
+        Op::Sha512sig1l => encode_r(i, OP, 0b000, 0b0101011),
+        Op::Sha512sig0h => encode_r(i, OP, 0b000, 0b0101110),
+        Op::Sha512sig1h => encode_r(i, OP, 0b000, 0b0101111),
+        Op::Sm4ed => encode_byte_select(i, 0b11000),
+        Op::Sm4ks => encode_byte_select(i, 0b11010),
+        Op::Sm3p0 => encode_unary(i, OP_IMM, 0b001, 0x108),
+        Op::Sm3p1 => encode_unary(i, OP_IMM, 0b001, 0x109),
+        Op::Fence => encode_fence(i, 0b000),
+        Op::FenceI => encode_fence(i, 0b001),
+        Op::FenceTso => encode_fence(i, 0b000),
+        Op::Pause => Ok(0x0100000f),
+        Op::CboInval => encode_cbo(i, 0b000),
+        Op::CboClean => encode_cbo(i, 0b001),
+        Op::CboFlush => encode_cbo(i, 0b010),
+        Op::CboZero => encode_cbo(i, 0b100),
+        Op::PrefetchI => encode_prefetch(i, 0b00000),
+        Op::PrefetchR => encode_prefetch(i, 0b00001),
+        Op::PrefetchW => encode_prefetch(i, 0b00011),
+        Op::NtlP1 => Ok(0x00200033),
+        Op::NtlPall => Ok(0x00300033),
+        Op::NtlS1 => Ok(0x00400033),
+        Op::NtlAll => Ok(0x00500033),
+        Op::Ecall => Ok(0x00000073),
+        Op::Ebreak => Ok(0x00100073),
+        Op::Uret => Ok(0x00200073),
+        Op::Sret => Ok(0x10200073),
+        Op::Mret => Ok(0x30200073),
+        Op::Dret => Ok(0x7b200073),
+        Op::Wfi => Ok(0x10500073),
+        Op::SfenceVma => encode_r(i, SYSTEM, 0b000, 0b0001001),
+        Op::SinvalVma => encode_r(i, SYSTEM, 0b000, 0b0001011),
+        Op::SfenceWInval => Ok(0x18000073),
+        Op::SfenceInvalIr => Ok(0x18100073),
+        Op::HfenceVvma => encode_r(i, SYSTEM, 0b000, 0b0010001),
+        Op::HfenceGvma => encode_r(i, SYSTEM, 0b000, 0b0110001),
+        Op::HinvalVvma => encode_r(i, SYSTEM, 0b000, 0b0010011),
+        Op::HinvalGvma => encode_r(i, SYSTEM, 0b000, 0b0110011),
+        Op::HlvB => encode_hlv(i, 0b0110000, 0b00000),
+        Op::HlvBu => encode_hlv(i, 0b0110000, 0b00001),
+        Op::HlvH => encode_hlv(i, 0b0110010, 0b00000),
+        Op::HlvHu => encode_hlv(i, 0b0110010, 0b00001),
+        Op::HlvxHu => encode_hlv(i, 0b0110010, 0b00011),
+        Op::HlvW => encode_hlv(i, 0b0110100, 0b00000),
+        Op::HlvWu => encode_hlv(i, 0b0110100, 0b00001),
+        Op::HlvxWu => encode_hlv(i, 0b0110100, 0b00011),
+        Op::HlvD => encode_hlv(i, 0b0110110, 0b00000),
+        Op::HsvB => encode_hsv(i, 0b0110001),
+        Op::HsvH => encode_hsv(i, 0b0110011),
+        Op::HsvW => encode_hsv(i, 0b0110101),
+        Op::HsvD => encode_hsv(i, 0b0110111),
+        Op::Csrrw => encode_csr(i, 0b001),
+        Op::Csrrs => encode_csr(i, 0b010),
+        Op::Csrrc => encode_csr(i, 0b011),
+        Op::Csrrwi => encode_csr(i, 0b101),
+        Op::Csrrsi => encode_csr(i, 0b110),
+        Op::Csrrci => encode_csr(i, 0b111),
+        Op::RdCycle => encode_counter(i, CYCLE),
+        Op::RdTime => encode_counter(i, TIME),
+        Op::RdInstRet => encode_counter(i, INSTRET),
+        Op::RdCycleH => encode_counter(i, CYCLEH),
+        Op::RdTimeH => encode_counter(i, TIMEH),
+        Op::RdInstRetH => encode_counter(i, INSTRETH),
+        Op::LrW => encode_amo(i, 0b00010, 0b010),
+        Op::ScW => encode_amo(i, 0b00011, 0b010),
+        Op::AmoswapW => encode_amo(i, 0b00001, 0b010),
+        Op::AmoaddW => encode_amo(i, 0b00000, 0b010),
+        Op::AmoxorW => encode_amo(i, 0b00100, 0b010),
+        Op::AmoandW => encode_amo(i, 0b01100, 0b010),
+        Op::AmoorW => encode_amo(i, 0b01000, 0b010),
+        Op::AmominW => encode_amo(i, 0b10000, 0b010),
+        Op::AmomaxW => encode_amo(i, 0b10100, 0b010),
+        Op::AmominuW => encode_amo(i, 0b11000, 0b010),
+        Op::AmomaxuW => encode_amo(i, 0b11100, 0b010),
+        Op::LrD => encode_amo(i, 0b00010, 0b011),
+        Op::ScD => encode_amo(i, 0b00011, 0b011),
+        Op::AmoswapD => encode_amo(i, 0b00001, 0b011),
+        Op::AmoaddD => encode_amo(i, 0b00000, 0b011),
+        Op::AmoxorD => encode_amo(i, 0b00100, 0b011),
+        Op::AmoandD => encode_amo(i, 0b01100, 0b011),
+        Op::AmoorD => encode_amo(i, 0b01000, 0b011),
+        Op::AmominD => encode_amo(i, 0b10000, 0b011),
+        Op::AmomaxD => encode_amo(i, 0b10100, 0b011),
+        Op::AmominuD => encode_amo(i, 0b11000, 0b011),
+        Op::AmomaxuD => encode_amo(i, 0b11100, 0b011),
+        Op::Flw => encode_i(i, LOAD_FP, 0b010),
+        Op::Fld => encode_i(i, LOAD_FP, 0b011),
+        Op::Fsw => encode_s(i, STORE_FP, 0b010),
+        Op::Fsd => encode_s(i, STORE_FP, 0b011),
+        Op::FmaddS => encode_r4(i, MADD, S),
+        Op::FmsubS => encode_r4(i, MSUB, S),
+        Op::FnmsubS => encode_r4(i, NMSUB, S),
+        Op::FnmaddS => encode_r4(i, NMADD, S),
+        Op::FmaddD => encode_r4(i, MADD, D),
+        Op::FmsubD => encode_r4(i, MSUB, D),
+        Op::FnmsubD => encode_r4(i, NMSUB, D),
+        Op::FnmaddD => encode_r4(i, NMADD, D),
+        Op::FaddS => encode_op_fp(i, 0b00000, S, None, None),
+        Op::FsubS => encode_op_fp(i, 0b00001, S, None, None),
+        Op::FmulS => encode_op_fp(i, 0b00010, S, None, None),
+        Op::FdivS => encode_op_fp(i, 0b00011, S, None, None),
+        Op::FsqrtS => encode_op_fp(i, 0b01011, S, None, Some(0)),
+        Op::FsgnjS => encode_op_fp(i, 0b00100, S, Some(0b000), None),
+        Op::FsgnjnS => encode_op_fp(i, 0b00100, S, Some(0b001), None),
+        Op::FsgnjxS => encode_op_fp(i, 0b00100, S, Some(0b010), None),
+        Op::FminS => encode_op_fp(i, 0b00101, S, Some(0b000), None),
+        Op::FmaxS => encode_op_fp(i, 0b00101, S, Some(0b001), None),
+        Op::FeqS => encode_op_fp(i, 0b10100, S, Some(0b010), None),
+        Op::FltS => encode_op_fp(i, 0b10100, S, Some(0b001), None),
+        Op::FleS => encode_op_fp(i, 0b10100, S, Some(0b000), None),
+        Op::FmvXW => encode_op_fp(i, 0b11100, S, Some(0b000), Some(0)),
+        Op::FclassS => encode_op_fp(i, 0b11100, S, Some(0b001), Some(0)),
+        Op::FmvWX => encode_op_fp(i, 0b11110, S, Some(0b000), Some(0)),
+        Op::FcvtWS => encode_op_fp(i, 0b11000, S, None, Some(0)),
+        Op::FcvtWuS => encode_op_fp(i, 0b11000, S, None, Some(1)),
+        Op::FcvtLS => encode_op_fp(i, 0b11000, S, None, Some(2)),
+        Op::FcvtLuS => encode_op_fp(i, 0b11000, S, None, Some(3)),
+        Op::FcvtSW => encode_op_fp(i, 0b11010, S, None, Some(0)),
+        Op::FcvtSWu => encode_op_fp(i, 0b11010, S, None, Some(1)),
+        Op::FcvtSL => encode_op_fp(i, 0b11010, S, None, Some(2)),
+        Op::FcvtSLu => encode_op_fp(i, 0b11010, S, None, Some(3)),
+        Op::FcvtSD => encode_op_fp(i, 0b01000, S, None, Some(1)),
+        Op::FcvtSH => encode_op_fp(i, 0b01000, S, None, Some(2)),
+        Op::FcvtSQ => encode_op_fp(i, 0b01000, S, None, Some(3)),
+        Op::FaddD => encode_op_fp(i, 0b00000, D, None, None),
+        Op::FsubD => encode_op_fp(i, 0b00001, D, None, None),
+        Op::FmulD => encode_op_fp(i, 0b00010, D, None, None),
+        Op::FdivD => encode_op_fp(i, 0b00011, D, None, None),
+        Op::FsqrtD => encode_op_fp(i, 0b01011, D, None, Some(0)),
+        Op::FsgnjD => encode_op_fp(i, 0b00100, D, Some(0b000), None),
+        Op::FsgnjnD => encode_op_fp(i, 0b00100, D, Some(0b001), None),
+        Op::FsgnjxD => encode_op_fp(i, 0b00100, D, Some(0b010), None),
+        Op::FminD => encode_op_fp(i, 0b00101, D, Some(0b000), None),
+        Op::FmaxD => encode_op_fp(i, 0b00101, D, Some(0b001), None),
+        Op::FeqD => encode_op_fp(i, 0b10100, D, Some(0b010), None),
+        Op::FltD => encode_op_fp(i, 0b10100, D, Some(0b001), None),
+        Op::FleD => encode_op_fp(i, 0b10100, D, Some(0b000), None),
+        Op::FmvXD => encode_op_fp(i, 0b11100, D, Some(0b000), Some(0)),
+        Op::FclassD => encode_op_fp(i, 0b11100, D, Some(0b001), Some(0)),
+        Op::FmvDX => encode_op_fp(i, 0b11110, D, Some(0b000), Some(0)),
+        Op::FcvtWD => encode_op_fp(i, 0b11000, D, None, Some(0)),
+        Op::FcvtWuD => encode_op_fp(i, 0b11000, D, None, Some(1)),
+        Op::FcvtLD => encode_op_fp(i, 0b11000, D, None, Some(2)),
+        Op::FcvtLuD => encode_op_fp(i, 0b11000, D, None, Some(3)),
+        Op::FcvtDW => encode_op_fp(i, 0b11010, D, None, Some(0)),
+        Op::FcvtDWu => encode_op_fp(i, 0b11010, D, None, Some(1)),
+        Op::FcvtDL => encode_op_fp(i, 0b11010, D, None, Some(2)),
+        Op::FcvtDLu => encode_op_fp(i, 0b11010, D, None, Some(3)),
+        Op::FcvtDS => encode_op_fp(i, 0b01000, D, None, Some(0)),
+        Op::FcvtDH => encode_op_fp(i, 0b01000, D, None, Some(2)),
+        Op::FcvtDQ => encode_op_fp(i, 0b01000, D, None, Some(3)),
+        Op::Flh => encode_i(i, LOAD_FP, 0b001),
+        Op::Flq => encode_i(i, LOAD_FP, 0b100),
+        Op::Fsh => encode_s(i, STORE_FP, 0b001),
+        Op::Fsq => encode_s(i, STORE_FP, 0b100),
+        Op::FmaddH => encode_r4(i, MADD, H),
+        Op::FmsubH => encode_r4(i, MSUB, H),
+        Op::FnmsubH => encode_r4(i, NMSUB, H),
+        Op::FnmaddH => encode_r4(i, NMADD, H),
+        Op::FaddH => encode_op_fp(i, 0b00000, H, None, None),
+        Op::FsubH => encode_op_fp(i, 0b00001, H, None, None),
+        Op::FmulH => encode_op_fp(i, 0b00010, H, None, None),
+        Op::FdivH => encode_op_fp(i, 0b00011, H, None, None),
+        Op::FsqrtH => encode_op_fp(i, 0b01011, H, None, Some(0)),
+        Op::FsgnjH => encode_op_fp(i, 0b00100, H, Some(0b000), None),
+        Op::FsgnjnH => encode_op_fp(i, 0b00100, H, Some(0b001), None),
+        Op::FsgnjxH => encode_op_fp(i, 0b00100, H, Some(0b010), None),
+        Op::FminH => encode_op_fp(i, 0b00101, H, Some(0b000), None),
+        Op::FmaxH => encode_op_fp(i, 0b00101, H, Some(0b001), None),
+        Op::FeqH => encode_op_fp(i, 0b10100, H, Some(0b010), None),
+        Op::FltH => encode_op_fp(i, 0b10100, H, Some(0b001), None),
+        Op::FleH => encode_op_fp(i, 0b10100, H, Some(0b000), None),
+        Op::FmvXH => encode_op_fp(i, 0b11100, H, Some(0b000), Some(0)),
+        Op::FclassH => encode_op_fp(i, 0b11100, H, Some(0b001), Some(0)),
+        Op::FmvHX => encode_op_fp(i, 0b11110, H, Some(0b000), Some(0)),
+        Op::FcvtWH => encode_op_fp(i, 0b11000, H, None, Some(0)),
+        Op::FcvtWuH => encode_op_fp(i, 0b11000, H, None, Some(1)),
+        Op::FcvtLH => encode_op_fp(i, 0b11000, H, None, Some(2)),
+        Op::FcvtLuH => encode_op_fp(i, 0b11000, H, None, Some(3)),
+        Op::FcvtHW => encode_op_fp(i, 0b11010, H, None, Some(0)),
+        Op::FcvtHWu => encode_op_fp(i, 0b11010, H, None, Some(1)),
+        Op::FcvtHL => encode_op_fp(i, 0b11010, H, None, Some(2)),
+        Op::FcvtHLu => encode_op_fp(i, 0b11010, H, None, Some(3)),
+        Op::FcvtHS => encode_op_fp(i, 0b01000, H, None, Some(0)),
+        Op::FcvtHD => encode_op_fp(i, 0b01000, H, None, Some(1)),
+        Op::FcvtHQ => encode_op_fp(i, 0b01000, H, None, Some(3)),
+        Op::FmaddQ => encode_r4(i, MADD, Q),
+        Op::FmsubQ => encode_r4(i, MSUB, Q),
+        Op::FnmsubQ => encode_r4(i, NMSUB, Q),
+        Op::FnmaddQ => encode_r4(i, NMADD, Q),
+        Op::FaddQ => encode_op_fp(i, 0b00000, Q, None, None),
+        Op::FsubQ => encode_op_fp(i, 0b00001, Q, None, None),
+        Op::FmulQ => encode_op_fp(i, 0b00010, Q, None, None),
+        Op::FdivQ => encode_op_fp(i, 0b00011, Q, None, None),
+        Op::FsqrtQ => encode_op_fp(i, 0b01011, Q, None, Some(0)),
+        Op::FsgnjQ => encode_op_fp(i, 0b00100, Q, Some(0b000), None),
+        Op::FsgnjnQ => encode_op_fp(i, 0b00100, Q, Some(0b001), None),
+        Op::FsgnjxQ => encode_op_fp(i, 0b00100, Q, Some(0b010), None),
+        Op::FminQ => encode_op_fp(i, 0b00101, Q, Some(0b000), None),
+        Op::FmaxQ => encode_op_fp(i, 0b00101, Q, Some(0b001), None),
+        Op::FeqQ => encode_op_fp(i, 0b10100, Q, Some(0b010), None),
+        Op::FltQ => encode_op_fp(i, 0b10100, Q, Some(0b001), None),
+        Op::FleQ => encode_op_fp(i, 0b10100, Q, Some(0b000), None),
+        Op::FclassQ => encode_op_fp(i, 0b11100, Q, Some(0b001), Some(0)),
+        Op::FcvtWQ => encode_op_fp(i, 0b11000, Q, None, Some(0)),
+        Op::FcvtWuQ => encode_op_fp(i, 0b11000, Q, None, Some(1)),
+        Op::FcvtLQ => encode_op_fp(i, 0b11000, Q, None, Some(2)),
+        Op::FcvtLuQ => encode_op_fp(i, 0b11000, Q, None, Some(3)),
+        Op::FcvtQW => encode_op_fp(i, 0b11010, Q, None, Some(0)),
+        Op::FcvtQWu => encode_op_fp(i, 0b11010, Q, None, Some(1)),
+        Op::FcvtQL => encode_op_fp(i, 0b11010, Q, None, Some(2)),
+        Op::FcvtQLu => encode_op_fp(i, 0b11010, Q, None, Some(3)),
+        Op::FcvtQS => encode_op_fp(i, 0b01000, Q, None, Some(0)),
+        Op::FcvtQD => encode_op_fp(i, 0b01000, Q, None, Some(1)),
+        Op::FcvtQH => encode_op_fp(i, 0b01000, Q, None, Some(2)),
+        Op::FliS => encode_fli(i, S),
+        Op::FminmS => encode_op_fp(i, 0b00101, S, Some(0b010), None),
+        Op::FmaxmS => encode_op_fp(i, 0b00101, S, Some(0b011), None),
+        Op::FroundS => encode_op_fp(i, 0b01000, S, None, Some(4)),
+        Op::FroundnxS => encode_op_fp(i, 0b01000, S, None, Some(5)),
+        Op::FleqS => encode_op_fp(i, 0b10100, S, Some(0b100), None),
+        Op::FltqS => encode_op_fp(i, 0b10100, S, Some(0b101), None),
+        Op::FliD => encode_fli(i, D),
+        Op::FminmD => encode_op_fp(i, 0b00101, D, Some(0b010), None),
+        Op::FmaxmD => encode_op_fp(i, 0b00101, D, Some(0b011), None),
+        Op::FroundD => encode_op_fp(i, 0b01000, D, None, Some(4)),
+        Op::FroundnxD => encode_op_fp(i, 0b01000, D, None, Some(5)),
+        Op::FleqD => encode_op_fp(i, 0b10100, D, Some(0b100), None),
+        Op::FltqD => encode_op_fp(i, 0b10100, D, Some(0b101), None),
+        Op::FliH => encode_fli(i, H),
+        Op::FminmH => encode_op_fp(i, 0b00101, H, Some(0b010), None),
+        Op::FmaxmH => encode_op_fp(i, 0b00101, H, Some(0b011), None),
+        Op::FroundH => encode_op_fp(i, 0b01000, H, None, Some(4)),
+        Op::FroundnxH => encode_op_fp(i, 0b01000, H, None, Some(5)),
+        Op::FleqH => encode_op_fp(i, 0b10100, H, Some(0b100), None),
+        Op::FltqH => encode_op_fp(i, 0b10100, H, Some(0b101), None),
+        Op::FliQ => encode_fli(i, Q),
+        Op::FminmQ => encode_op_fp(i, 0b00101, Q, Some(0b010), None),
+        Op::FmaxmQ => encode_op_fp(i, 0b00101, Q, Some(0b011), None),
+        Op::FroundQ => encode_op_fp(i, 0b01000, Q, None, Some(4)),
+        Op::FroundnxQ => encode_op_fp(i, 0b01000, Q, None, Some(5)),
+        Op::FleqQ => encode_op_fp(i, 0b10100, Q, Some(0b100), None),
+        Op::FltqQ => encode_op_fp(i, 0b10100, Q, Some(0b101), None),
+        Op::FcvtmodWD => encode_op_fp(i, 0b11000, D, Some(0b001), Some(8)),
+        Op::FmvhXD => encode_op_fp(i, 0b11100, D, Some(0b000), Some(1)),
+        Op::FmvhXQ => encode_op_fp(i, 0b11100, Q, Some(0b000), Some(1)),
+        Op::FmvpDX => encode_op_fp(i, 0b10110, D, Some(0b000), None),
+        Op::FmvpQX => encode_op_fp(i, 0b10110, Q, Some(0b000), None),
+        Op::Vector(op) => encode_vector(i, op),
+        _ => encode_compressed(i).map(u32::from),
+    }
+}
+
+// Where each immediate bit of the compressed formats is held, as pairs of
+// (bit in the immediate, bit in the halfword)
+const CI: &[(u32, u32)] = &[(5, 12), (4, 6), (3, 5), (2, 4), (1, 3), (0, 2)];
+const CIW: &[(u32, u32)] = &[
+    (5, 12),
+    (4, 11),
+    (9, 10),
+    (8, 9),
+    (7, 8),
+    (6, 7),
+    (2, 6),
+    (3, 5),
+];
+const CL_WORD: &[(u32, u32)] = &[(5, 12), (4, 11), (3, 10), (2, 6), (6, 5)];
+const CL_DOUBLE: &[(u32, u32)] = &[(5, 12), (4, 11), (3, 10), (7, 6), (6, 5)];
+const CJ: &[(u32, u32)] = &[
+    (11, 12),
+    (4, 11),
+    (9, 10),
+    (8, 9),
+    (10, 8),
+    (6, 7),
+    (7, 6),
+    (3, 5),
+    (2, 4),
+    (1, 3),
+    (5, 2),
+];
+const CB: &[(u32, u32)] = &[
+    (8, 12),
+    (4, 11),
+    (3, 10),
+    (7, 6),
+    (6, 5),
+    (2, 4),
+    (1, 3),
+    (5, 2),
+];
+const ADDI16SP: &[(u32, u32)] = &[(9, 12), (4, 6), (6, 5), (8, 4), (7, 3), (5, 2)];
+const LWSP: &[(u32, u32)] = &[(5, 12), (4, 6), (3, 5), (2, 4), (7, 3), (6, 2)];
+const LDSP: &[(u32, u32)] = &[(5, 12), (4, 6), (3, 5), (8, 4), (7, 3), (6, 2)];
+const SWSP: &[(u32, u32)] = &[(5, 12), (4, 11), (3, 10), (2, 9), (7, 8), (6, 7)];
+const SDSP: &[(u32, u32)] = &[(5, 12), (4, 11), (3, 10), (8, 9), (7, 8), (6, 7)];
+
+/// Places the bits of `immediate` in a halfword according to `layout`
+fn scatter(immediate: u32, layout: &[(u32, u32)]) -> u16 {
+    layout.iter().fold(0, |halfword, &(from, to)| {
+        halfword | (((immediate >> from) & 1) << to) as u16
+    })
+}
+
+/// The 3-bit field for one of the registers x8-x15 or f8-f15
+fn prime(register: &Register, float: bool) -> Result<u16, EncodeError> {
+    let index = self::register(register, float)?;
+    if !(8..=15).contains(&index) {
+        return Err(EncodeError::InvalidRegister);
+    }
+    Ok((index - 8) as u16)
+}
+
+/// The 5-bit field for any register
+fn full(register: &Register, float: bool) -> Result<u16, EncodeError> {
+    Ok(self::register(register, float)? as u16)
+}
+
+/// Checks that a register which the compressed encoding implies, such as
+/// sp for `c.lwsp` or rs1 being the same as rd, has the expected value
+fn implied(register: &Register, expected: &Register) -> Result<(), EncodeError> {
+    if register == expected {
+        Ok(())
+    } else {
+        Err(EncodeError::InvalidRegister)
+    }
+}
+
+fn not_zero(register: &Register) -> Result<(), EncodeError> {
+    if *register == Register::Zero {
+        Err(EncodeError::InvalidRegister)
+    } else {
+        Ok(())
+    }
+}
+
+/// A CL or CS format load or store: rd or rs2 at 4:2 and rs1 at 9:7
+fn cl(
+    funct3: u16,
+    instruction: &Instruction,
+    data: &Register,
+    float: bool,
+    layout: &[(u32, u32)],
+    bits: u32,
+    align: i64,
+) -> Result<u16, EncodeError> {
+    let offset = unsigned(i64::from(instruction.immediate), bits, align)?;
+    Ok(funct3 << 13
+        | scatter(offset, layout)
+        | prime(&instruction.rs1, false)? << 7
+        | prime(data, float)? << 2)
+}
+
+/// A stack-pointer relative load, with rd at 11:7
+fn ci_sp(
+    funct3: u16,
+    instruction: &Instruction,
+    float: bool,
+    layout: &[(u32, u32)],
+    bits: u32,
+    align: i64,
+) -> Result<u16, EncodeError> {
+    implied(&instruction.rs1, &Register::Sp)?;
+    let offset = unsigned(i64::from(instruction.immediate), bits, align)?;
+    Ok(funct3 << 13 | scatter(offset, layout) | full(&instruction.rd, float)? << 7 | 0b10)
+}
+
+/// A stack-pointer relative store, with rs2 at 6:2
+fn css(
+    funct3: u16,
+    instruction: &Instruction,
+    float: bool,
+    layout: &[(u32, u32)],
+    bits: u32,
+    align: i64,
+) -> Result<u16, EncodeError> {
+    implied(&instruction.rs1, &Register::Sp)?;
+    let offset = unsigned(i64::from(instruction.immediate), bits, align)?;
+    Ok(funct3 << 13 | scatter(offset, layout) | full(&instruction.rs2, float)? << 2 | 0b10)
+}
+
+/// A CI format instruction with a 6-bit signed immediate and rd at 11:7
+fn ci(funct3: u16, quadrant: u16, rd: &Register, immediate: i32) -> Result<u16, EncodeError> {
+    let immediate = signed(immediate, 6, 1)?;
+    Ok(funct3 << 13 | scatter(immediate, CI) | full(rd, false)? << 7 | quadrant)
+}
+
+/// A shift by an immediate in the CI or CB format
+fn c_shift(instruction: &Instruction, high: u16, quadrant: u16) -> Result<u16, EncodeError> {
+    implied(&instruction.rs1, &instruction.rd)?;
+    let shamt = unsigned(instruction.shamt as i64, 6, 1)?;
+    let rd = if quadrant == 0b01 {
+        prime(&instruction.rd, false)?
+    } else {
+        full(&instruction.rd, false)?
+    };
+    Ok(high << 10 | scatter(shamt, CI) | rd << 7 | quadrant)
+}
+
+/// A CA format register-register instruction
+fn ca(instruction: &Instruction, funct6: u16, funct2: u16) -> Result<u16, EncodeError> {
+    implied(&instruction.rs1, &instruction.rd)?;
+    Ok(funct6 << 10
+        | prime(&instruction.rd, false)? << 7
+        | funct2 << 5
+        | prime(&instruction.rs2, false)? << 2
+        | 0b01)
+}
+
+fn cj(funct3: u16, instruction: &Instruction) -> Result<u16, EncodeError> {
+    let offset = signed(instruction.immediate, 12, 2)?;
+    Ok(funct3 << 13 | scatter(offset, CJ) | 0b01)
+}
+
+fn cb(funct3: u16, instruction: &Instruction) -> Result<u16, EncodeError> {
+    implied(&instruction.rs2, &Register::Zero)?;
+    let offset = signed(instruction.immediate, 9, 2)?;
+    Ok(funct3 << 13 | scatter(offset, CB) | prime(&instruction.rs1, false)? << 7 | 0b01)
+}
+
+fn encode_compressed(instruction: &Instruction) -> Result<u16, EncodeError> {
+    let i = instruction;
+    match i.op {
+        Op::CAddi4spn => {
+            implied(&i.rs1, &Register::Sp)?;
+            let immediate = unsigned(i64::from(i.immediate), 10, 4)?;
+            if immediate == 0 {
+                return Err(EncodeError::Reserved);
+            }
+            Ok(scatter(immediate, CIW) | prime(&i.rd, false)? << 2)
+        }
+        Op::CFld => cl(0b001, i, &i.rd, true, CL_DOUBLE, 8, 8),
+        Op::CLw => cl(0b010, i, &i.rd, false, CL_WORD, 7, 4),
+        Op::CFlw => cl(0b011, i, &i.rd, true, CL_WORD, 7, 4),
+        Op::CLd => cl(0b011, i, &i.rd, false, CL_DOUBLE, 8, 8),
+        Op::CFsd => cl(0b101, i, &i.rs2, true, CL_DOUBLE, 8, 8),
+        Op::CSw => cl(0b110, i, &i.rs2, false, CL_WORD, 7, 4),
+        Op::CFsw => cl(0b111, i, &i.rs2, true, CL_WORD, 7, 4),
+        Op::CSd => cl(0b111, i, &i.rs2, false, CL_DOUBLE, 8, 8),
+        Op::CNop => {
+            implied(&i.rd, &Register::Zero)?;
+            implied(&i.rs1, &Register::Zero)?;
+            ci(0b000, 0b01, &i.rd, i.immediate)
+        }
+        Op::CAddi => {
+            not_zero(&i.rd)?;
+            implied(&i.rs1, &i.rd)?;
+            ci(0b000, 0b01, &i.rd, i.immediate)
+        }
+        Op::CJal => {
+            implied(&i.rd, &Register::Ra)?;
+            cj(0b001, i)
+        }
+        Op::CAddiw => {
+            not_zero(&i.rd)?;
+            implied(&i.rs1, &i.rd)?;
+            ci(0b001, 0b01, &i.rd, i.immediate)
+        }
+        Op::CLi => {
+            implied(&i.rs1, &Register::Zero)?;
+            ci(0b010, 0b01, &i.rd, i.immediate)
+        }
+        Op::CAddi16sp => {
+            implied(&i.rd, &Register::Sp)?;
+            implied(&i.rs1, &Register::Sp)?;
+            let immediate = signed(i.immediate, 10, 16)?;
+            if immediate == 0 {
+                return Err(EncodeError::Reserved);
+            }
+            Ok(0b011 << 13 | scatter(immediate, ADDI16SP) | 2 << 7 | 0b01)
+        }
+        Op::CLui => {
+            if i.rd == Register::Sp {
+                return Err(EncodeError::InvalidRegister);
+            }
+            // Held as the upper 20 bits of a sign-extended 6-bit immediate
+            let immediate = match i.immediate {
+                0 => return Err(EncodeError::Reserved),
+                1..=0x1f | 0xfffe0..=0xfffff => i.immediate as u32 & 0x3f,
+                _ => return Err(EncodeError::ImmediateOutOfRange),
+            };
+            Ok(0b011 << 13 | scatter(immediate, CI) | full(&i.rd, false)? << 7 | 0b01)
+        }
+        Op::CSrli => c_shift(i, 0b10_0000, 0b01),
+        Op::CSrai => c_shift(i, 0b10_0001, 0b01),
+        Op::CAndi => {
+            implied(&i.rs1, &i.rd)?;
+            let immediate = signed(i.immediate, 6, 1)?;
+            Ok(
+                0b100 << 13
+                    | 0b10 << 10
+                    | scatter(immediate, CI)
+                    | prime(&i.rd, false)? << 7
+                    | 0b01,
+            )
+        }
+        Op::CSub => ca(i, 0b100011, 0b00),
+        Op::CXor => ca(i, 0b100011, 0b01),
+        Op::COr => ca(i, 0b100011, 0b10),
+        Op::CAnd => ca(i, 0b100011, 0b11),
+        Op::CSubw => ca(i, 0b100111, 0b00),
+        Op::CAddw => ca(i, 0b100111, 0b01),
+        Op::CJ => {
+            implied(&i.rd, &Register::Zero)?;
+            cj(0b101, i)
+        }
+        Op::CBeqz => cb(0b110, i),
+        Op::CBnez => cb(0b111, i),
+        Op::CSlli => c_shift(i, 0b00_0000, 0b10),
+        Op::CFldsp => ci_sp(0b001, i, true, LDSP, 9, 8),
+        Op::CLwsp => {
+            not_zero(&i.rd)?;
+            ci_sp(0b010, i, false, LWSP, 8, 4)
+        }
+        Op::CFlwsp => ci_sp(0b011, i, true, LWSP, 8, 4),
+        Op::CLdsp => {
+            not_zero(&i.rd)?;
+            ci_sp(0b011, i, false, LDSP, 9, 8)
+        }
+        Op::CJr => {
+            implied(&i.rd, &Register::Zero)?;
+            not_zero(&i.rs1)?;
+            Ok(0b1000 << 12 | full(&i.rs1, false)? << 7 | 0b10)
+        }
+        Op::CMv => {
+            implied(&i.rs1, &Register::Zero)?;
+            not_zero(&i.rs2)?;
+            Ok(0b1000 << 12 | full(&i.rd, false)? << 7 | full(&i.rs2, false)? << 2 | 0b10)
+        }
+        Op::CEbreak => Ok(0x9002),
+        Op::CNtlP1 => Ok(0x900a),
+        Op::CNtlPall => Ok(0x900e),
+        Op::CNtlS1 => Ok(0x9012),
+        Op::CNtlAll => Ok(0x9016),
+        Op::CJalr => {
+            implied(&i.rd, &Register::Ra)?;
+            not_zero(&i.rs1)?;
+            Ok(0b1001 << 12 | full(&i.rs1, false)? << 7 | 0b10)
+        }
+        Op::CAdd => {
+            implied(&i.rs1, &i.rd)?;
+            not_zero(&i.rs2)?;
+            Ok(0b1001 << 12 | full(&i.rd, false)? << 7 | full(&i.rs2, false)? << 2 | 0b10)
+        }
+        Op::CFsdsp => css(0b101, i, true, SDSP, 9, 8),
+        Op::CSwsp => css(0b110, i, false, SWSP, 8, 4),
+        Op::CFswsp => css(0b111, i, true, SWSP, 8, 4),
+        Op::CSdsp => css(0b111, i, false, SDSP, 9, 8),
+        _ => Err(EncodeError::Reserved),
+    }
+}
@@ -0,0 +1,162 @@
+use std::error::Error;
+use std::fmt;
+
+/// The reason an instruction could not be decoded
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The major opcode, held here, does not belong to any supported
+    /// instruction
+    UnknownOpcode(u32),
+    /// The major opcode is known, but the combination of function fields,
+    /// or the value of another field such as the rounding mode, is reserved
+    Reserved,
+    /// The instruction belongs to an extension, or base integer width, which
+    /// the decoder was not asked to decode
+    ExtensionDisabled,
+    /// The instruction is one of the encodings defined to be illegal, such
+    /// as all zeros or all ones
+    Illegal,
+    /// The input ends before the instruction does. Holds the length in
+    /// bytes of the instruction, so that streaming callers know how much
+    /// more input is required.
+    Truncated(usize),
+    /// The instruction is of a length, such as 48 or 64 bits, for which no
+    /// instructions are decoded. Holds the length in bytes so the caller
+    /// can skip over it.
+    UnsupportedLength(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::UnknownOpcode(opcode) => write!(f, "unknown opcode 0b{:07b}", opcode),
+            DecodeError::Reserved => write!(f, "reserved instruction encoding"),
+            DecodeError::ExtensionDisabled => {
+                write!(f, "instruction belongs to a disabled extension")
+            }
+            DecodeError::Illegal => write!(f, "illegal instruction"),
+            DecodeError::Truncated(length) => {
+                write!(f, "input truncated, instruction is {} bytes", length)
+            }
+            DecodeError::UnsupportedLength(length) => {
+                write!(f, "unsupported {}-byte instruction", length)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// The reason an instruction could not be encoded
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EncodeError {
+    /// A register operand is missing, belongs to the wrong register file,
+    /// or is not one the encoding can address, such as a register outside
+    /// x8-x15 in a compressed instruction
+    InvalidRegister,
+    /// An immediate, offset or shift amount does not fit in the encoding
+    ImmediateOutOfRange,
+    /// An offset is not a multiple of the alignment the encoding requires,
+    /// such as an odd branch offset
+    MisalignedImmediate,
+    /// The operands would form a reserved encoding, such as `c.addi4spn`
+    /// with a zero immediate
+    Reserved,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::InvalidRegister => write!(f, "invalid register operand"),
+            EncodeError::ImmediateOutOfRange => write!(f, "immediate out of range"),
+            EncodeError::MisalignedImmediate => write!(f, "misaligned immediate"),
+            EncodeError::Reserved => write!(f, "operands form a reserved encoding"),
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
+/// The reason assembly could not be assembled. Each variant holds the
+/// number of the line, counting from 1, on which the problem was found.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AssembleError {
+    /// The mnemonic, held here, does not name a supported instruction
+    UnknownMnemonic(usize, String),
+    /// The operand, held here, is not a valid operand in its position
+    InvalidOperand(usize, String),
+    /// The instruction is given the wrong number of operands
+    OperandCount(usize),
+    /// A branch or jump names a label, held here, which is never defined
+    UndefinedLabel(usize, String),
+    /// A label, held here, is defined more than once
+    DuplicateLabel(usize, String),
+    /// The operands parsed, but do not fit the instruction's encoding
+    Encode(usize, EncodeError),
+    /// The instruction is not part of the target `Isa`, and `decode_isa`
+    /// rejects its encoding with the error held here
+    NotInIsa(usize, DecodeError),
+}
+
+impl AssembleError {
+    /// The line on which the problem was found, counting from 1
+    pub fn line(&self) -> usize {
+        match self {
+            AssembleError::UnknownMnemonic(line, _)
+            | AssembleError::InvalidOperand(line, _)
+            | AssembleError::OperandCount(line)
+            | AssembleError::UndefinedLabel(line, _)
+            | AssembleError::DuplicateLabel(line, _)
+            | AssembleError::Encode(line, _)
+            | AssembleError::NotInIsa(line, _) => *line,
+        }
+    }
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line())?;
+        match self {
+            AssembleError::UnknownMnemonic(_, mnemonic) => {
+                write!(f, "unknown mnemonic \"{}\"", mnemonic)
+            }
+            AssembleError::InvalidOperand(_, operand) => {
+                write!(f, "invalid operand \"{}\"", operand)
+            }
+            AssembleError::OperandCount(_) => write!(f, "wrong number of operands"),
+            AssembleError::UndefinedLabel(_, label) => write!(f, "undefined label \"{}\"", label),
+            AssembleError::DuplicateLabel(_, label) => {
+                write!(f, "label \"{}\" is already defined", label)
+            }
+            AssembleError::Encode(_, error) => write!(f, "{}", error),
+            AssembleError::NotInIsa(_, error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for AssembleError {}
+
+/// The reason an ISA string could not be parsed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseIsaError {
+    /// The string does not start with `rv32` or `rv64` followed by one of
+    /// the base instruction sets `i`, `e` or `g`
+    InvalidBase(String),
+    /// The string names an extension this crate does not know
+    UnknownExtension(String),
+    /// A version number does not directly follow the name of an extension,
+    /// or has a `p` with no minor version after it, such as `rv32i2p`
+    InvalidVersion(String),
+}
+
+impl fmt::Display for ParseIsaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseIsaError::InvalidBase(s) => write!(f, "invalid base ISA in \"{}\"", s),
+            ParseIsaError::UnknownExtension(name) => write!(f, "unknown extension \"{}\"", name),
+            ParseIsaError::InvalidVersion(s) => write!(f, "invalid version number in \"{}\"", s),
+        }
+    }
+}
+
+impl Error for ParseIsaError {}
@@ -0,0 +1,31 @@
+/// How an instruction affects control flow, as returned by
+/// `Instruction::flow`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Flow {
+    /// Execution continues with the next instruction
+    Sequential,
+    /// A conditional branch to a PC-relative target, such as `beq`
+    ConditionalBranch,
+    /// An unconditional jump to a PC-relative target, such as `jal x0`
+    Jump,
+    /// An unconditional jump to a target held in a register, such as
+    /// `jalr x0, 0(a0)`
+    IndirectJump,
+    /// A jump to a PC-relative target which links the return address, such
+    /// as `jal ra`
+    Call,
+    /// A jump to a target held in a register which links the return
+    /// address, such as `jalr ra, 0(a0)`
+    IndirectCall,
+    /// A return through a link register, such as `jalr x0, 0(ra)`
+    Return,
+    /// A return through one link register which links the return address
+    /// in the other, such as `jalr t0, 0(ra)`, swapping between coroutines
+    CoroutineSwap,
+    /// A transfer to the execution environment, such as `ecall` or
+    /// `ebreak`
+    Trap,
+    /// A return from a trap handler to the pc saved when the trap was
+    /// taken, such as `mret`
+    TrapReturn,
+}
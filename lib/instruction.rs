@@ -1,73 +1,1259 @@
+use csr::{
+    CYCLE, CYCLEH, DCSR, DPC, FCSR, FFLAGS, FRM, INSTRET, INSTRETH, MEPC, MSTATUS, SEPC, SSTATUS,
+    TIME, TIMEH, UEPC, USTATUS, VL, VTYPE,
+};
 use std::fmt;
-use Register;
+use vector;
+use {
+    AccessKind, Csr, Extend, Flow, Location, MemoryAccess, Operand, Pseudo, Register, VectorForm,
+    VectorOp, Vtype, Xlen,
+};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Op {
     Add,
     Addi,
+    Addiw,
+    AddUw,
+    Addw,
+    Aes32dsi,
+    Aes32dsmi,
+    Aes32esi,
+    Aes32esmi,
+    Aes64ds,
+    Aes64dsm,
+    Aes64es,
+    Aes64esm,
+    Aes64im,
+    Aes64ks1i,
+    Aes64ks2,
+    AmoaddD,
+    AmoaddW,
+    AmoandD,
+    AmoandW,
+    AmomaxD,
+    AmomaxW,
+    AmomaxuD,
+    AmomaxuW,
+    AmominD,
+    AmominW,
+    AmominuD,
+    AmominuW,
+    AmoorD,
+    AmoorW,
+    AmoswapD,
+    AmoswapW,
+    AmoxorD,
+    AmoxorW,
     And,
     Andi,
+    Andn,
     Auipc,
+    Bclr,
+    Bclri,
     Beq,
+    Bext,
+    Bexti,
     Bge,
     Bgeu,
+    Binv,
+    Binvi,
     Blt,
     Bltu,
     Bne,
+    Brev8,
+    Bset,
+    Bseti,
+    CAdd,
+    CAddi,
+    CAddi16sp,
+    CAddi4spn,
+    CAddiw,
+    CAddw,
+    CAnd,
+    CAndi,
+    CBeqz,
+    CBnez,
+    CEbreak,
+    CFld,
+    CFldsp,
+    CFlw,
+    CFlwsp,
+    CFsd,
+    CFsdsp,
+    CFsw,
+    CFswsp,
+    CJ,
+    CJal,
+    CJalr,
+    CJr,
+    CLd,
+    CLdsp,
+    CLi,
+    CLui,
+    CLw,
+    CLwsp,
+    CMv,
+    CNop,
+    CNtlAll,
+    CNtlP1,
+    CNtlPall,
+    CNtlS1,
+    COr,
+    CSd,
+    CSdsp,
+    CSlli,
+    CSrai,
+    CSrli,
+    CSub,
+    CSubw,
+    CSw,
+    CSwsp,
+    CXor,
+    CboClean,
+    CboFlush,
+    CboInval,
+    CboZero,
+    Clmul,
+    Clmulh,
+    Clmulr,
+    Clz,
+    Clzw,
+    Cpop,
+    Cpopw,
     Csrrc,
     Csrrci,
     Csrrs,
     Csrrsi,
     Csrrw,
     Csrrwi,
+    Ctz,
+    Ctzw,
+    Dret,
     Ebreak,
     Ecall,
+    FaddD,
+    FaddH,
+    FaddQ,
+    FaddS,
+    FclassD,
+    FclassH,
+    FclassQ,
+    FclassS,
+    FcvtDH,
+    FcvtDL,
+    FcvtDLu,
+    FcvtDQ,
+    FcvtDS,
+    FcvtDW,
+    FcvtDWu,
+    FcvtHD,
+    FcvtHL,
+    FcvtHLu,
+    FcvtHQ,
+    FcvtHS,
+    FcvtHW,
+    FcvtHWu,
+    FcvtLD,
+    FcvtLH,
+    FcvtLQ,
+    FcvtLS,
+    FcvtLuD,
+    FcvtLuH,
+    FcvtLuQ,
+    FcvtLuS,
+    FcvtQD,
+    FcvtQH,
+    FcvtQL,
+    FcvtQLu,
+    FcvtQS,
+    FcvtQW,
+    FcvtQWu,
+    FcvtSD,
+    FcvtSH,
+    FcvtSL,
+    FcvtSLu,
+    FcvtSQ,
+    FcvtSW,
+    FcvtSWu,
+    FcvtWD,
+    FcvtWH,
+    FcvtWQ,
+    FcvtWS,
+    FcvtWuD,
+    FcvtWuH,
+    FcvtWuQ,
+    FcvtWuS,
+    FcvtmodWD,
+    FdivD,
+    FdivH,
+    FdivQ,
+    FdivS,
+    FeqD,
+    FeqH,
+    FeqQ,
+    FeqS,
+    Fld,
+    FleD,
+    FleH,
+    FleQ,
+    FleS,
+    FleqD,
+    FleqH,
+    FleqQ,
+    FleqS,
+    Flh,
+    FliD,
+    FliH,
+    FliQ,
+    FliS,
+    Flq,
+    FltD,
+    FltH,
+    FltQ,
+    FltS,
+    FltqD,
+    FltqH,
+    FltqQ,
+    FltqS,
+    Flw,
+    FmaddD,
+    FmaddH,
+    FmaddQ,
+    FmaddS,
+    FmaxD,
+    FmaxH,
+    FmaxQ,
+    FmaxS,
+    FmaxmD,
+    FmaxmH,
+    FmaxmQ,
+    FmaxmS,
+    FminD,
+    FminH,
+    FminQ,
+    FminS,
+    FminmD,
+    FminmH,
+    FminmQ,
+    FminmS,
+    FmsubD,
+    FmsubH,
+    FmsubQ,
+    FmsubS,
+    FmulD,
+    FmulH,
+    FmulQ,
+    FmulS,
+    FmvDX,
+    FmvHX,
+    FmvWX,
+    FmvXD,
+    FmvXH,
+    FmvXW,
+    FmvhXD,
+    FmvhXQ,
+    FmvpDX,
+    FmvpQX,
+    FnmaddD,
+    FnmaddH,
+    FnmaddQ,
+    FnmaddS,
+    FnmsubD,
+    FnmsubH,
+    FnmsubQ,
+    FnmsubS,
+    FroundD,
+    FroundH,
+    FroundQ,
+    FroundS,
+    FroundnxD,
+    FroundnxH,
+    FroundnxQ,
+    FroundnxS,
+    Fsd,
+    FsgnjD,
+    FsgnjH,
+    FsgnjQ,
+    FsgnjS,
+    FsgnjnD,
+    FsgnjnH,
+    FsgnjnQ,
+    FsgnjnS,
+    FsgnjxD,
+    FsgnjxH,
+    FsgnjxQ,
+    FsgnjxS,
+    Fsh,
+    Fsq,
+    FsqrtD,
+    FsqrtH,
+    FsqrtQ,
+    FsqrtS,
+    FsubD,
+    FsubH,
+    FsubQ,
+    FsubS,
+    Fsw,
     Fence,
     FenceI,
+    FenceTso,
+    HfenceGvma,
+    HfenceVvma,
+    HinvalGvma,
+    HinvalVvma,
+    HlvB,
+    HlvBu,
+    HlvD,
+    HlvH,
+    HlvHu,
+    HlvW,
+    HlvWu,
+    HlvxHu,
+    HlvxWu,
+    HsvB,
+    HsvD,
+    HsvH,
+    HsvW,
     Jal,
     Jalr,
     Lb,
     Lbu,
+    Ld,
     Lh,
     Lhu,
+    LrD,
+    LrW,
     Lw,
+    Lwu,
     Lui,
+    Max,
+    Maxu,
+    Min,
+    Minu,
+    Mret,
     Mul,
     Mulh,
     Mulhsu,
     Mulhu,
+    Mulw,
     Div,
     Divu,
+    Divuw,
+    Divw,
     Rem,
     Remu,
+    Remuw,
+    Remw,
+    NtlAll,
+    NtlP1,
+    NtlPall,
+    NtlS1,
     Or,
     Ori,
+    OrcB,
+    Orn,
+    Pack,
+    Packh,
+    Packw,
+    Pause,
+    PrefetchI,
+    PrefetchR,
+    PrefetchW,
     RdCycle,
     RdCycleH,
     RdTime,
     RdTimeH,
     RdInstRet,
     RdInstRetH,
+    Rev8,
+    Rol,
+    Rolw,
+    Ror,
+    Rori,
+    Roriw,
+    Rorw,
     Sb,
+    ScD,
+    ScW,
+    Sd,
+    SextB,
+    SextH,
+    SfenceInvalIr,
+    SfenceVma,
+    SfenceWInval,
+    SinvalVma,
     Sh,
+    Sh1add,
+    Sh1addUw,
+    Sh2add,
+    Sh2addUw,
+    Sh3add,
+    Sh3addUw,
+    Sha256sig0,
+    Sha256sig1,
+    Sha256sum0,
+    Sha256sum1,
+    Sha512sig0,
+    Sha512sig0h,
+    Sha512sig0l,
+    Sha512sig1,
+    Sha512sig1h,
+    Sha512sig1l,
+    Sha512sum0,
+    Sha512sum0r,
+    Sha512sum1,
+    Sha512sum1r,
     Sw,
     Sll,
     Slli,
+    SlliUw,
+    Slliw,
+    Sllw,
     Slt,
     Slti,
     Sltu,
     Sltiu,
+    Sm3p0,
+    Sm3p1,
+    Sm4ed,
+    Sm4ks,
     Sra,
     Srai,
+    Sraiw,
+    Sraw,
     Srl,
     Srli,
+    Srliw,
+    Srlw,
+    Sret,
     Sub,
+    Subw,
+    Uret,
+    Unzip,
+    /// An instruction from the V extension
+    Vector(VectorOp),
+    Wfi,
+    Xnor,
     Xor,
     Xori,
+    Xperm4,
+    Xperm8,
+    ZextH,
+    Zip,
 }
 
-#[derive(Clone, Debug)]
+impl Op {
+    /// The assembler mnemonic of this op, such as `addi` or `fcvt.w.s`
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Op::Add => "add",
+            Op::Addi => "addi",
+            Op::Addiw => "addiw",
+            Op::AddUw => "add.uw",
+            Op::Addw => "addw",
+            Op::Aes32dsi => "aes32dsi",
+            Op::Aes32dsmi => "aes32dsmi",
+            Op::Aes32esi => "aes32esi",
+            Op::Aes32esmi => "aes32esmi",
+            Op::Aes64ds => "aes64ds",
+            Op::Aes64dsm => "aes64dsm",
+            Op::Aes64es => "aes64es",
+            Op::Aes64esm => "aes64esm",
+            Op::Aes64im => "aes64im",
+            Op::Aes64ks1i => "aes64ks1i",
+            Op::Aes64ks2 => "aes64ks2",
+            Op::AmoaddD => "amoadd.d",
+            Op::AmoaddW => "amoadd.w",
+            Op::AmoandD => "amoand.d",
+            Op::AmoandW => "amoand.w",
+            Op::AmomaxD => "amomax.d",
+            Op::AmomaxW => "amomax.w",
+            Op::AmomaxuD => "amomaxu.d",
+            Op::AmomaxuW => "amomaxu.w",
+            Op::AmominD => "amomin.d",
+            Op::AmominW => "amomin.w",
+            Op::AmominuD => "amominu.d",
+            Op::AmominuW => "amominu.w",
+            Op::AmoorD => "amoor.d",
+            Op::AmoorW => "amoor.w",
+            Op::AmoswapD => "amoswap.d",
+            Op::AmoswapW => "amoswap.w",
+            Op::AmoxorD => "amoxor.d",
+            Op::AmoxorW => "amoxor.w",
+            Op::And => "and",
+            Op::Andi => "andi",
+            Op::Andn => "andn",
+            Op::Auipc => "auipc",
+            Op::Bclr => "bclr",
+            Op::Bclri => "bclri",
+            Op::Beq => "beq",
+            Op::Bext => "bext",
+            Op::Bexti => "bexti",
+            Op::Bge => "bge",
+            Op::Bgeu => "bgeu",
+            Op::Binv => "binv",
+            Op::Binvi => "binvi",
+            Op::Blt => "blt",
+            Op::Bltu => "bltu",
+            Op::Bne => "bne",
+            Op::Brev8 => "brev8",
+            Op::Bset => "bset",
+            Op::Bseti => "bseti",
+            Op::CAdd => "c.add",
+            Op::CAddi => "c.addi",
+            Op::CAddi16sp => "c.addi16sp",
+            Op::CAddi4spn => "c.addi4spn",
+            Op::CAddiw => "c.addiw",
+            Op::CAddw => "c.addw",
+            Op::CAnd => "c.and",
+            Op::CAndi => "c.andi",
+            Op::CBeqz => "c.beqz",
+            Op::CBnez => "c.bnez",
+            Op::CEbreak => "c.ebreak",
+            Op::CFld => "c.fld",
+            Op::CFldsp => "c.fldsp",
+            Op::CFlw => "c.flw",
+            Op::CFlwsp => "c.flwsp",
+            Op::CFsd => "c.fsd",
+            Op::CFsdsp => "c.fsdsp",
+            Op::CFsw => "c.fsw",
+            Op::CFswsp => "c.fswsp",
+            Op::CJ => "c.j",
+            Op::CJal => "c.jal",
+            Op::CJalr => "c.jalr",
+            Op::CJr => "c.jr",
+            Op::CLd => "c.ld",
+            Op::CLdsp => "c.ldsp",
+            Op::CLi => "c.li",
+            Op::CLui => "c.lui",
+            Op::CLw => "c.lw",
+            Op::CLwsp => "c.lwsp",
+            Op::CMv => "c.mv",
+            Op::CNop => "c.nop",
+            Op::CNtlAll => "c.ntl.all",
+            Op::CNtlP1 => "c.ntl.p1",
+            Op::CNtlPall => "c.ntl.pall",
+            Op::CNtlS1 => "c.ntl.s1",
+            Op::COr => "c.or",
+            Op::CSd => "c.sd",
+            Op::CSdsp => "c.sdsp",
+            Op::CSlli => "c.slli",
+            Op::CSrai => "c.srai",
+            Op::CSrli => "c.srli",
+            Op::CSub => "c.sub",
+            Op::CSubw => "c.subw",
+            Op::CSw => "c.sw",
+            Op::CSwsp => "c.swsp",
+            Op::CXor => "c.xor",
+            Op::CboClean => "cbo.clean",
+            Op::CboFlush => "cbo.flush",
+            Op::CboInval => "cbo.inval",
+            Op::CboZero => "cbo.zero",
+            Op::Clmul => "clmul",
+            Op::Clmulh => "clmulh",
+            Op::Clmulr => "clmulr",
+            Op::Clz => "clz",
+            Op::Clzw => "clzw",
+            Op::Cpop => "cpop",
+            Op::Cpopw => "cpopw",
+            Op::Csrrc => "csrrc",
+            Op::Csrrci => "csrrci",
+            Op::Csrrs => "csrrs",
+            Op::Csrrsi => "csrrsi",
+            Op::Csrrw => "csrrw",
+            Op::Csrrwi => "csrrwi",
+            Op::Ctz => "ctz",
+            Op::Ctzw => "ctzw",
+            Op::Dret => "dret",
+            Op::Ebreak => "ebreak",
+            Op::Ecall => "ecall",
+            Op::FaddD => "fadd.d",
+            Op::FaddH => "fadd.h",
+            Op::FaddQ => "fadd.q",
+            Op::FaddS => "fadd.s",
+            Op::FclassD => "fclass.d",
+            Op::FclassH => "fclass.h",
+            Op::FclassQ => "fclass.q",
+            Op::FclassS => "fclass.s",
+            Op::FcvtDH => "fcvt.d.h",
+            Op::FcvtDL => "fcvt.d.l",
+            Op::FcvtDLu => "fcvt.d.lu",
+            Op::FcvtDQ => "fcvt.d.q",
+            Op::FcvtDS => "fcvt.d.s",
+            Op::FcvtDW => "fcvt.d.w",
+            Op::FcvtDWu => "fcvt.d.wu",
+            Op::FcvtHD => "fcvt.h.d",
+            Op::FcvtHL => "fcvt.h.l",
+            Op::FcvtHLu => "fcvt.h.lu",
+            Op::FcvtHQ => "fcvt.h.q",
+            Op::FcvtHS => "fcvt.h.s",
+            Op::FcvtHW => "fcvt.h.w",
+            Op::FcvtHWu => "fcvt.h.wu",
+            Op::FcvtLD => "fcvt.l.d",
+            Op::FcvtLH => "fcvt.l.h",
+            Op::FcvtLQ => "fcvt.l.q",
+            Op::FcvtLS => "fcvt.l.s",
+            Op::FcvtLuD => "fcvt.lu.d",
+            Op::FcvtLuH => "fcvt.lu.h",
+            Op::FcvtLuQ => "fcvt.lu.q",
+            Op::FcvtLuS => "fcvt.lu.s",
+            Op::FcvtQD => "fcvt.q.d",
+            Op::FcvtQH => "fcvt.q.h",
+            Op::FcvtQL => "fcvt.q.l",
+            Op::FcvtQLu => "fcvt.q.lu",
+            Op::FcvtQS => "fcvt.q.s",
+            Op::FcvtQW => "fcvt.q.w",
+            Op::FcvtQWu => "fcvt.q.wu",
+            Op::FcvtSD => "fcvt.s.d",
+            Op::FcvtSH => "fcvt.s.h",
+            Op::FcvtSL => "fcvt.s.l",
+            Op::FcvtSLu => "fcvt.s.lu",
+            Op::FcvtSQ => "fcvt.s.q",
+            Op::FcvtSW => "fcvt.s.w",
+            Op::FcvtSWu => "fcvt.s.wu",
+            Op::FcvtWD => "fcvt.w.d",
+            Op::FcvtWH => "fcvt.w.h",
+            Op::FcvtWQ => "fcvt.w.q",
+            Op::FcvtWS => "fcvt.w.s",
+            Op::FcvtWuD => "fcvt.wu.d",
+            Op::FcvtWuH => "fcvt.wu.h",
+            Op::FcvtWuQ => "fcvt.wu.q",
+            Op::FcvtWuS => "fcvt.wu.s",
+            Op::FcvtmodWD => "fcvtmod.w.d",
+            Op::FdivD => "fdiv.d",
+            Op::FdivH => "fdiv.h",
+            Op::FdivQ => "fdiv.q",
+            Op::FdivS => "fdiv.s",
+            Op::FeqD => "feq.d",
+            Op::FeqH => "feq.h",
+            Op::FeqQ => "feq.q",
+            Op::FeqS => "feq.s",
+            Op::Fld => "fld",
+            Op::FleD => "fle.d",
+            Op::FleH => "fle.h",
+            Op::FleQ => "fle.q",
+            Op::FleS => "fle.s",
+            Op::FleqD => "fleq.d",
+            Op::FleqH => "fleq.h",
+            Op::FleqQ => "fleq.q",
+            Op::FleqS => "fleq.s",
+            Op::Flh => "flh",
+            Op::FliD => "fli.d",
+            Op::FliH => "fli.h",
+            Op::FliQ => "fli.q",
+            Op::FliS => "fli.s",
+            Op::Flq => "flq",
+            Op::FltD => "flt.d",
+            Op::FltH => "flt.h",
+            Op::FltQ => "flt.q",
+            Op::FltS => "flt.s",
+            Op::FltqD => "fltq.d",
+            Op::FltqH => "fltq.h",
+            Op::FltqQ => "fltq.q",
+            Op::FltqS => "fltq.s",
+            Op::Flw => "flw",
+            Op::FmaddD => "fmadd.d",
+            Op::FmaddH => "fmadd.h",
+            Op::FmaddQ => "fmadd.q",
+            Op::FmaddS => "fmadd.s",
+            Op::FmaxD => "fmax.d",
+            Op::FmaxH => "fmax.h",
+            Op::FmaxQ => "fmax.q",
+            Op::FmaxS => "fmax.s",
+            Op::FmaxmD => "fmaxm.d",
+            Op::FmaxmH => "fmaxm.h",
+            Op::FmaxmQ => "fmaxm.q",
+            Op::FmaxmS => "fmaxm.s",
+            Op::FminD => "fmin.d",
+            Op::FminH => "fmin.h",
+            Op::FminQ => "fmin.q",
+            Op::FminS => "fmin.s",
+            Op::FminmD => "fminm.d",
+            Op::FminmH => "fminm.h",
+            Op::FminmQ => "fminm.q",
+            Op::FminmS => "fminm.s",
+            Op::FmsubD => "fmsub.d",
+            Op::FmsubH => "fmsub.h",
+            Op::FmsubQ => "fmsub.q",
+            Op::FmsubS => "fmsub.s",
+            Op::FmulD => "fmul.d",
+            Op::FmulH => "fmul.h",
+            Op::FmulQ => "fmul.q",
+            Op::FmulS => "fmul.s",
+            Op::FmvDX => "fmv.d.x",
+            Op::FmvHX => "fmv.h.x",
+            Op::FmvWX => "fmv.w.x",
+            Op::FmvXD => "fmv.x.d",
+            Op::FmvXH => "fmv.x.h",
+            Op::FmvXW => "fmv.x.w",
+            Op::FmvhXD => "fmvh.x.d",
+            Op::FmvhXQ => "fmvh.x.q",
+            Op::FmvpDX => "fmvp.d.x",
+            Op::FmvpQX => "fmvp.q.x",
+            Op::FnmaddD => "fnmadd.d",
+            Op::FnmaddH => "fnmadd.h",
+            Op::FnmaddQ => "fnmadd.q",
+            Op::FnmaddS => "fnmadd.s",
+            Op::FnmsubD => "fnmsub.d",
+            Op::FnmsubH => "fnmsub.h",
+            Op::FnmsubQ => "fnmsub.q",
+            Op::FnmsubS => "fnmsub.s",
+            Op::FroundD => "fround.d",
+            Op::FroundH => "fround.h",
+            Op::FroundQ => "fround.q",
+            Op::FroundS => "fround.s",
+            Op::FroundnxD => "froundnx.d",
+            Op::FroundnxH => "froundnx.h",
+            Op::FroundnxQ => "froundnx.q",
+            Op::FroundnxS => "froundnx.s",
+            Op::Fsd => "fsd",
+            Op::FsgnjD => "fsgnj.d",
+            Op::FsgnjH => "fsgnj.h",
+            Op::FsgnjQ => "fsgnj.q",
+            Op::FsgnjS => "fsgnj.s",
+            Op::FsgnjnD => "fsgnjn.d",
+            Op::FsgnjnH => "fsgnjn.h",
+            Op::FsgnjnQ => "fsgnjn.q",
+            Op::FsgnjnS => "fsgnjn.s",
+            Op::FsgnjxD => "fsgnjx.d",
+            Op::FsgnjxH => "fsgnjx.h",
+            Op::FsgnjxQ => "fsgnjx.q",
+            Op::FsgnjxS => "fsgnjx.s",
+            Op::Fsh => "fsh",
+            Op::Fsq => "fsq",
+            Op::FsqrtD => "fsqrt.d",
+            Op::FsqrtH => "fsqrt.h",
+            Op::FsqrtQ => "fsqrt.q",
+            Op::FsqrtS => "fsqrt.s",
+            Op::FsubD => "fsub.d",
+            Op::FsubH => "fsub.h",
+            Op::FsubQ => "fsub.q",
+            Op::FsubS => "fsub.s",
+            Op::Fsw => "fsw",
+            Op::Fence => "fence",
+            Op::FenceI => "fence.i",
+            Op::FenceTso => "fence.tso",
+            Op::HfenceGvma => "hfence.gvma",
+            Op::HfenceVvma => "hfence.vvma",
+            Op::HinvalGvma => "hinval.gvma",
+            Op::HinvalVvma => "hinval.vvma",
+            Op::HlvB => "hlv.b",
+            Op::HlvBu => "hlv.bu",
+            Op::HlvD => "hlv.d",
+            Op::HlvH => "hlv.h",
+            Op::HlvHu => "hlv.hu",
+            Op::HlvW => "hlv.w",
+            Op::HlvWu => "hlv.wu",
+            Op::HlvxHu => "hlvx.hu",
+            Op::HlvxWu => "hlvx.wu",
+            Op::HsvB => "hsv.b",
+            Op::HsvD => "hsv.d",
+            Op::HsvH => "hsv.h",
+            Op::HsvW => "hsv.w",
+            Op::Jal => "jal",
+            Op::Jalr => "jalr",
+            Op::Lb => "lb",
+            Op::Lbu => "lbu",
+            Op::Ld => "ld",
+            Op::Lh => "lh",
+            Op::Lhu => "lhu",
+            Op::LrD => "lr.d",
+            Op::LrW => "lr.w",
+            Op::Lw => "lw",
+            Op::Lwu => "lwu",
+            Op::Lui => "lui",
+            Op::Max => "max",
+            Op::Maxu => "maxu",
+            Op::Min => "min",
+            Op::Minu => "minu",
+            Op::Mret => "mret",
+            Op::Mul => "mul",
+            Op::Mulh => "mulh",
+            Op::Mulhsu => "mulhsu",
+            Op::Mulhu => "mulhu",
+            Op::Mulw => "mulw",
+            Op::Div => "div",
+            Op::Divu => "divu",
+            Op::Divuw => "divuw",
+            Op::Divw => "divw",
+            Op::Rem => "rem",
+            Op::Remu => "remu",
+            Op::Remuw => "remuw",
+            Op::Remw => "remw",
+            Op::NtlAll => "ntl.all",
+            Op::NtlP1 => "ntl.p1",
+            Op::NtlPall => "ntl.pall",
+            Op::NtlS1 => "ntl.s1",
+            Op::Or => "or",
+            Op::Ori => "ori",
+            Op::OrcB => "orc.b",
+            Op::Orn => "orn",
+            Op::Pack => "pack",
+            Op::Packh => "packh",
+            Op::Packw => "packw",
+            Op::Pause => "pause",
+            Op::PrefetchI => "prefetch.i",
+            Op::PrefetchR => "prefetch.r",
+            Op::PrefetchW => "prefetch.w",
+            Op::RdCycle => "rdcycle",
+            Op::RdCycleH => "rdcycleh",
+            Op::RdTime => "rdtime",
+            Op::RdTimeH => "rdtimeh",
+            Op::RdInstRet => "rdinstret",
+            Op::RdInstRetH => "rdinstreth",
+            Op::Rev8 => "rev8",
+            Op::Rol => "rol",
+            Op::Rolw => "rolw",
+            Op::Ror => "ror",
+            Op::Rori => "rori",
+            Op::Roriw => "roriw",
+            Op::Rorw => "rorw",
+            Op::Sb => "sb",
+            Op::ScD => "sc.d",
+            Op::ScW => "sc.w",
+            Op::Sd => "sd",
+            Op::SextB => "sext.b",
+            Op::SextH => "sext.h",
+            Op::SfenceInvalIr => "sfence.inval.ir",
+            Op::SfenceVma => "sfence.vma",
+            Op::SfenceWInval => "sfence.w.inval",
+            Op::SinvalVma => "sinval.vma",
+            Op::Sh => "sh",
+            Op::Sh1add => "sh1add",
+            Op::Sh1addUw => "sh1add.uw",
+            Op::Sh2add => "sh2add",
+            Op::Sh2addUw => "sh2add.uw",
+            Op::Sh3add => "sh3add",
+            Op::Sh3addUw => "sh3add.uw",
+            Op::Sha256sig0 => "sha256sig0",
+            Op::Sha256sig1 => "sha256sig1",
+            Op::Sha256sum0 => "sha256sum0",
+            Op::Sha256sum1 => "sha256sum1",
+            Op::Sha512sig0 => "sha512sig0",
+            Op::Sha512sig0h => "sha512sig0h",
+            Op::Sha512sig0l => "sha512sig0l",
+            Op::Sha512sig1 => "sha512sig1",
+            Op::Sha512sig1h => "sha512sig1h",
+            Op::Sha512sig1l => "sha512sig1l",
+            Op::Sha512sum0 => "sha512sum0",
+            Op::Sha512sum0r => "sha512sum0r",
+            Op::Sha512sum1 => "sha512sum1",
+            Op::Sha512sum1r => "sha512sum1r",
+            Op::Sw => "sw",
+            Op::Sll => "sll",
+            Op::Slli => "slli",
+            Op::SlliUw => "slli.uw",
+            Op::Slliw => "slliw",
+            Op::Sllw => "sllw",
+            Op::Slt => "slt",
+            Op::Slti => "slti",
+            Op::Sltu => "sltu",
+            Op::Sltiu => "sltiu",
+            Op::Sm3p0 => "sm3p0",
+            Op::Sm3p1 => "sm3p1",
+            Op::Sm4ed => "sm4ed",
+            Op::Sm4ks => "sm4ks",
+            Op::Sra => "sra",
+            Op::Srai => "srai",
+            Op::Sraiw => "sraiw",
+            Op::Sraw => "sraw",
+            Op::Srl => "srl",
+            Op::Srli => "srli",
+            Op::Srliw => "srliw",
+            Op::Srlw => "srlw",
+            Op::Sret => "sret",
+            Op::Sub => "sub",
+            Op::Subw => "subw",
+            Op::Uret => "uret",
+            Op::Unzip => "unzip",
+            Op::Vector(op) => op.mnemonic(),
+            Op::Wfi => "wfi",
+            Op::Xnor => "xnor",
+            Op::Xor => "xor",
+            Op::Xori => "xori",
+            Op::Xperm4 => "xperm4",
+            Op::Xperm8 => "xperm8",
+            Op::ZextH => "zext.h",
+            Op::Zip => "zip",
+        }
+    }
+
+    /// Returns true if this is a 16-bit compressed instruction
+    pub fn is_compressed(&self) -> bool {
+        self.expanded().is_some()
+    }
+
+    /// Returns true if this op can raise floating point exceptions, which
+    /// accrue in `fflags`
+    fn sets_fflags(&self) -> bool {
+        matches!(
+            self,
+            Op::FaddD
+                | Op::FaddH
+                | Op::FaddQ
+                | Op::FaddS
+                | Op::FcvtDH
+                | Op::FcvtDL
+                | Op::FcvtDLu
+                | Op::FcvtDQ
+                | Op::FcvtDS
+                | Op::FcvtDW
+                | Op::FcvtDWu
+                | Op::FcvtHD
+                | Op::FcvtHL
+                | Op::FcvtHLu
+                | Op::FcvtHQ
+                | Op::FcvtHS
+                | Op::FcvtHW
+                | Op::FcvtHWu
+                | Op::FcvtLD
+                | Op::FcvtLH
+                | Op::FcvtLQ
+                | Op::FcvtLS
+                | Op::FcvtLuD
+                | Op::FcvtLuH
+                | Op::FcvtLuQ
+                | Op::FcvtLuS
+                | Op::FcvtQD
+                | Op::FcvtQH
+                | Op::FcvtQL
+                | Op::FcvtQLu
+                | Op::FcvtQS
+                | Op::FcvtQW
+                | Op::FcvtQWu
+                | Op::FcvtSD
+                | Op::FcvtSH
+                | Op::FcvtSL
+                | Op::FcvtSLu
+                | Op::FcvtSQ
+                | Op::FcvtSW
+                | Op::FcvtSWu
+                | Op::FcvtWD
+                | Op::FcvtWH
+                | Op::FcvtWQ
+                | Op::FcvtWS
+                | Op::FcvtWuD
+                | Op::FcvtWuH
+                | Op::FcvtWuQ
+                | Op::FcvtWuS
+                | Op::FcvtmodWD
+                | Op::FdivD
+                | Op::FdivH
+                | Op::FdivQ
+                | Op::FdivS
+                | Op::FeqD
+                | Op::FeqH
+                | Op::FeqQ
+                | Op::FeqS
+                | Op::FleD
+                | Op::FleH
+                | Op::FleQ
+                | Op::FleS
+                | Op::FleqD
+                | Op::FleqH
+                | Op::FleqQ
+                | Op::FleqS
+                | Op::FltD
+                | Op::FltH
+                | Op::FltQ
+                | Op::FltS
+                | Op::FltqD
+                | Op::FltqH
+                | Op::FltqQ
+                | Op::FltqS
+                | Op::FmaddD
+                | Op::FmaddH
+                | Op::FmaddQ
+                | Op::FmaddS
+                | Op::FmaxD
+                | Op::FmaxH
+                | Op::FmaxQ
+                | Op::FmaxS
+                | Op::FmaxmD
+                | Op::FmaxmH
+                | Op::FmaxmQ
+                | Op::FmaxmS
+                | Op::FminD
+                | Op::FminH
+                | Op::FminQ
+                | Op::FminS
+                | Op::FminmD
+                | Op::FminmH
+                | Op::FminmQ
+                | Op::FminmS
+                | Op::FmsubD
+                | Op::FmsubH
+                | Op::FmsubQ
+                | Op::FmsubS
+                | Op::FmulD
+                | Op::FmulH
+                | Op::FmulQ
+                | Op::FmulS
+                | Op::FnmaddD
+                | Op::FnmaddH
+                | Op::FnmaddQ
+                | Op::FnmaddS
+                | Op::FnmsubD
+                | Op::FnmsubH
+                | Op::FnmsubQ
+                | Op::FnmsubS
+                | Op::FroundD
+                | Op::FroundH
+                | Op::FroundQ
+                | Op::FroundS
+                | Op::FroundnxD
+                | Op::FroundnxH
+                | Op::FroundnxQ
+                | Op::FroundnxS
+                | Op::FsqrtD
+                | Op::FsqrtH
+                | Op::FsqrtQ
+                | Op::FsqrtS
+                | Op::FsubD
+                | Op::FsubH
+                | Op::FsubQ
+                | Op::FsubS
+        )
+    }
+
+    /// The floating point format this op works in, as held in the fmt field
+    /// of its encoding or, for loads and stores, the width. A conversion
+    /// between floating point formats works in the format it converts to.
+    fn float_format(&self) -> Option<FloatFormat> {
+        match self {
+            Op::CFlw
+            | Op::CFlwsp
+            | Op::CFsw
+            | Op::CFswsp
+            | Op::FaddS
+            | Op::FclassS
+            | Op::FcvtLS
+            | Op::FcvtLuS
+            | Op::FcvtSD
+            | Op::FcvtSH
+            | Op::FcvtSL
+            | Op::FcvtSLu
+            | Op::FcvtSQ
+            | Op::FcvtSW
+            | Op::FcvtSWu
+            | Op::FcvtWS
+            | Op::FcvtWuS
+            | Op::FdivS
+            | Op::FeqS
+            | Op::FleS
+            | Op::FleqS
+            | Op::FliS
+            | Op::FltS
+            | Op::FltqS
+            | Op::Flw
+            | Op::FmaddS
+            | Op::FmaxS
+            | Op::FmaxmS
+            | Op::FminS
+            | Op::FminmS
+            | Op::FmsubS
+            | Op::FmulS
+            | Op::FmvWX
+            | Op::FmvXW
+            | Op::FnmaddS
+            | Op::FnmsubS
+            | Op::FroundS
+            | Op::FroundnxS
+            | Op::FsgnjS
+            | Op::FsgnjnS
+            | Op::FsgnjxS
+            | Op::FsqrtS
+            | Op::FsubS
+            | Op::Fsw => Some(FloatFormat::S),
+            Op::CFld
+            | Op::CFldsp
+            | Op::CFsd
+            | Op::CFsdsp
+            | Op::FaddD
+            | Op::FclassD
+            | Op::FcvtDH
+            | Op::FcvtDL
+            | Op::FcvtDLu
+            | Op::FcvtDQ
+            | Op::FcvtDS
+            | Op::FcvtDW
+            | Op::FcvtDWu
+            | Op::FcvtLD
+            | Op::FcvtLuD
+            | Op::FcvtWD
+            | Op::FcvtWuD
+            | Op::FcvtmodWD
+            | Op::FdivD
+            | Op::FeqD
+            | Op::Fld
+            | Op::FleD
+            | Op::FleqD
+            | Op::FliD
+            | Op::FltD
+            | Op::FltqD
+            | Op::FmaddD
+            | Op::FmaxD
+            | Op::FmaxmD
+            | Op::FminD
+            | Op::FminmD
+            | Op::FmsubD
+            | Op::FmulD
+            | Op::FmvDX
+            | Op::FmvXD
+            | Op::FmvhXD
+            | Op::FmvpDX
+            | Op::FnmaddD
+            | Op::FnmsubD
+            | Op::FroundD
+            | Op::FroundnxD
+            | Op::Fsd
+            | Op::FsgnjD
+            | Op::FsgnjnD
+            | Op::FsgnjxD
+            | Op::FsqrtD
+            | Op::FsubD => Some(FloatFormat::D),
+            Op::FaddH
+            | Op::FclassH
+            | Op::FcvtHD
+            | Op::FcvtHL
+            | Op::FcvtHLu
+            | Op::FcvtHQ
+            | Op::FcvtHS
+            | Op::FcvtHW
+            | Op::FcvtHWu
+            | Op::FcvtLH
+            | Op::FcvtLuH
+            | Op::FcvtWH
+            | Op::FcvtWuH
+            | Op::FdivH
+            | Op::FeqH
+            | Op::FleH
+            | Op::FleqH
+            | Op::Flh
+            | Op::FliH
+            | Op::FltH
+            | Op::FltqH
+            | Op::FmaddH
+            | Op::FmaxH
+            | Op::FmaxmH
+            | Op::FminH
+            | Op::FminmH
+            | Op::FmsubH
+            | Op::FmulH
+            | Op::FmvHX
+            | Op::FmvXH
+            | Op::FnmaddH
+            | Op::FnmsubH
+            | Op::FroundH
+            | Op::FroundnxH
+            | Op::FsgnjH
+            | Op::FsgnjnH
+            | Op::FsgnjxH
+            | Op::Fsh
+            | Op::FsqrtH
+            | Op::FsubH => Some(FloatFormat::H),
+            Op::FaddQ
+            | Op::FclassQ
+            | Op::FcvtLQ
+            | Op::FcvtLuQ
+            | Op::FcvtQD
+            | Op::FcvtQH
+            | Op::FcvtQL
+            | Op::FcvtQLu
+            | Op::FcvtQS
+            | Op::FcvtQW
+            | Op::FcvtQWu
+            | Op::FcvtWQ
+            | Op::FcvtWuQ
+            | Op::FdivQ
+            | Op::FeqQ
+            | Op::FleQ
+            | Op::FleqQ
+            | Op::FliQ
+            | Op::Flq
+            | Op::FltQ
+            | Op::FltqQ
+            | Op::FmaddQ
+            | Op::FmaxQ
+            | Op::FmaxmQ
+            | Op::FminQ
+            | Op::FminmQ
+            | Op::FmsubQ
+            | Op::FmulQ
+            | Op::FmvhXQ
+            | Op::FmvpQX
+            | Op::FnmaddQ
+            | Op::FnmsubQ
+            | Op::FroundQ
+            | Op::FroundnxQ
+            | Op::FsgnjQ
+            | Op::FsgnjnQ
+            | Op::FsgnjxQ
+            | Op::Fsq
+            | Op::FsqrtQ
+            | Op::FsubQ => Some(FloatFormat::Q),
+            _ => None,
+        }
+    }
+
+    /// The base instruction a compressed instruction expands to
+    fn expanded(&self) -> Option<Op> {
+        match self {
+            Op::CAdd => Some(Op::Add),
+            Op::CAddi => Some(Op::Addi),
+            Op::CAddi16sp => Some(Op::Addi),
+            Op::CAddi4spn => Some(Op::Addi),
+            Op::CAddiw => Some(Op::Addiw),
+            Op::CAddw => Some(Op::Addw),
+            Op::CAnd => Some(Op::And),
+            Op::CAndi => Some(Op::Andi),
+            Op::CBeqz => Some(Op::Beq),
+            Op::CBnez => Some(Op::Bne),
+            Op::CEbreak => Some(Op::Ebreak),
+            Op::CFld => Some(Op::Fld),
+            Op::CFldsp => Some(Op::Fld),
+            Op::CFlw => Some(Op::Flw),
+            Op::CFlwsp => Some(Op::Flw),
+            Op::CFsd => Some(Op::Fsd),
+            Op::CFsdsp => Some(Op::Fsd),
+            Op::CFsw => Some(Op::Fsw),
+            Op::CFswsp => Some(Op::Fsw),
+            Op::CJ => Some(Op::Jal),
+            Op::CJal => Some(Op::Jal),
+            Op::CJalr => Some(Op::Jalr),
+            Op::CJr => Some(Op::Jalr),
+            Op::CLd => Some(Op::Ld),
+            Op::CLdsp => Some(Op::Ld),
+            Op::CLi => Some(Op::Addi),
+            Op::CLui => Some(Op::Lui),
+            Op::CLw => Some(Op::Lw),
+            Op::CLwsp => Some(Op::Lw),
+            Op::CMv => Some(Op::Add),
+            Op::CNop => Some(Op::Addi),
+            Op::CNtlAll => Some(Op::NtlAll),
+            Op::CNtlP1 => Some(Op::NtlP1),
+            Op::CNtlPall => Some(Op::NtlPall),
+            Op::CNtlS1 => Some(Op::NtlS1),
+            Op::COr => Some(Op::Or),
+            Op::CSd => Some(Op::Sd),
+            Op::CSdsp => Some(Op::Sd),
+            Op::CSlli => Some(Op::Slli),
+            Op::CSrai => Some(Op::Srai),
+            Op::CSrli => Some(Op::Srli),
+            Op::CSub => Some(Op::Sub),
+            Op::CSubw => Some(Op::Subw),
+            Op::CSw => Some(Op::Sw),
+            Op::CSwsp => Some(Op::Sw),
+            Op::CXor => Some(Op::Xor),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Fence {
+    pub(crate) fm: u32,
     pub(crate) pi: bool,
     pub(crate) po: bool,
     pub(crate) pr: bool,
@@ -79,6 +1265,11 @@ pub struct Fence {
 }
 
 impl Fence {
+    /// The fence mode, which is 0 for a normal fence and `0b1000` for
+    /// `fence.tso`. Other modes are reserved, and order as a normal fence.
+    pub fn fm(&self) -> u32 {
+        self.fm
+    }
     pub fn pi(&self) -> bool {
         self.pi
     }
@@ -103,22 +1294,240 @@ impl Fence {
     pub fn sw(&self) -> bool {
         self.sw
     }
+
+    /// The operations ordered before the fence
+    pub fn predecessor(&self) -> FenceSet {
+        FenceSet {
+            i: self.pi,
+            o: self.po,
+            r: self.pr,
+            w: self.pw,
+        }
+    }
+
+    /// The operations ordered after the fence
+    pub fn successor(&self) -> FenceSet {
+        FenceSet {
+            i: self.si,
+            o: self.so,
+            r: self.sr,
+            w: self.sw,
+        }
+    }
+}
+
+/// One side of a fence: device input, device output, memory reads and
+/// memory writes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FenceSet {
+    pub(crate) i: bool,
+    pub(crate) o: bool,
+    pub(crate) r: bool,
+    pub(crate) w: bool,
+}
+
+impl FenceSet {
+    pub fn i(&self) -> bool {
+        self.i
+    }
+    pub fn o(&self) -> bool {
+        self.o
+    }
+    pub fn r(&self) -> bool {
+        self.r
+    }
+    pub fn w(&self) -> bool {
+        self.w
+    }
+}
+
+/// The acquire and release ordering bits of an atomic memory operation
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Aqrl {
+    pub(crate) aq: bool,
+    pub(crate) rl: bool,
+}
+
+impl Aqrl {
+    pub fn aq(&self) -> bool {
+        self.aq
+    }
+    pub fn rl(&self) -> bool {
+        self.rl
+    }
+}
+
+impl fmt::Display for Aqrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.aq, self.rl) {
+            (false, false) => Ok(()),
+            (true, false) => write!(f, ".aq"),
+            (false, true) => write!(f, ".rl"),
+            (true, true) => write!(f, ".aqrl"),
+        }
+    }
+}
+
+/// The rounding mode of a floating point instruction
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RoundingMode {
+    /// Round to nearest, ties to even
+    Rne,
+    /// Round towards zero
+    Rtz,
+    /// Round down, towards negative infinity
+    Rdn,
+    /// Round up, towards positive infinity
+    Rup,
+    /// Round to nearest, ties to max magnitude
+    Rmm,
+    /// Use the dynamic rounding mode in the frm register
+    Dyn,
+}
+
+impl RoundingMode {
+    pub(crate) fn from_u32(u: u32) -> Option<RoundingMode> {
+        match u {
+            0b000 => Some(RoundingMode::Rne),
+            0b001 => Some(RoundingMode::Rtz),
+            0b010 => Some(RoundingMode::Rdn),
+            0b011 => Some(RoundingMode::Rup),
+            0b100 => Some(RoundingMode::Rmm),
+            0b111 => Some(RoundingMode::Dyn),
+            _ => None,
+        }
+    }
 }
 
+impl fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoundingMode::Rne => write!(f, "rne"),
+            RoundingMode::Rtz => write!(f, "rtz"),
+            RoundingMode::Rdn => write!(f, "rdn"),
+            RoundingMode::Rup => write!(f, "rup"),
+            RoundingMode::Rmm => write!(f, "rmm"),
+            RoundingMode::Dyn => write!(f, "dyn"),
+        }
+    }
+}
+
+/// The format of a floating point instruction, one of the four precisions
+/// the fmt field of an encoding selects
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FloatFormat {
+    /// Single precision, from F
+    S,
+    /// Double precision, from D
+    D,
+    /// Half precision, from Zfh
+    H,
+    /// Quad precision, from Q
+    Q,
+}
+
+impl FloatFormat {
+    /// The width of a value of this format in bits
+    pub fn bits(&self) -> usize {
+        match self {
+            FloatFormat::S => 32,
+            FloatFormat::D => 64,
+            FloatFormat::H => 16,
+            FloatFormat::Q => 128,
+        }
+    }
+}
+
+/// The constants `fli` loads, indexed by its rs1 field, as they are written
+/// in assembly. `min` is the smallest positive normal number of the format.
+pub(crate) const FLI_CONSTANTS: [&str; 32] = [
+    "-1.0",
+    "min",
+    "1.52587890625e-05",
+    "3.0517578125e-05",
+    "0.00390625",
+    "0.0078125",
+    "0.0625",
+    "0.125",
+    "0.25",
+    "0.3125",
+    "0.375",
+    "0.4375",
+    "0.5",
+    "0.625",
+    "0.75",
+    "0.875",
+    "1.0",
+    "1.25",
+    "1.5",
+    "1.75",
+    "2.0",
+    "2.5",
+    "3.0",
+    "4.0",
+    "8.0",
+    "16.0",
+    "128.0",
+    "256.0",
+    "32768.0",
+    "65536.0",
+    "inf",
+    "nan",
+];
+
 /// A decoded instruction
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Instruction {
+    pub(crate) rs3: Register,
     pub(crate) rs2: Register,
     pub(crate) rs1: Register,
     pub(crate) rd: Register,
-    pub(crate) csr: usize,
+    pub(crate) csr: Option<Csr>,
     pub(crate) op: Op,
     pub(crate) shamt: usize,
-    pub(crate) immediate: u32,
+    pub(crate) immediate: i32,
     pub(crate) fence: Option<Fence>,
+    pub(crate) aqrl: Option<Aqrl>,
+    pub(crate) rm: Option<RoundingMode>,
+    pub(crate) format: Option<FloatFormat>,
+    pub(crate) masked: bool,
+    pub(crate) vtype: Option<Vtype>,
+    pub(crate) form: Option<VectorForm>,
+    pub(crate) eew: usize,
+    pub(crate) nf: usize,
+    pub(crate) xlen: Option<Xlen>,
 }
 
 impl Instruction {
+    /// An instruction with the given op and no operands, to be filled in by
+    /// the decoder
+    pub(crate) fn new(op: Op) -> Instruction {
+        let format = op.float_format();
+        Instruction {
+            rs3: Register::Invalid,
+            rs2: Register::Invalid,
+            rs1: Register::Invalid,
+            rd: Register::Invalid,
+            csr: None,
+            op,
+            shamt: 0,
+            immediate: 0,
+            fence: None,
+            aqrl: None,
+            rm: None,
+            format,
+            masked: false,
+            vtype: None,
+            form: None,
+            eew: 0,
+            nf: 0,
+            xlen: None,
+        }
+    }
+
+    pub fn rs3(&self) -> &Register {
+        &self.rs3
+    }
     pub fn rs2(&self) -> &Register {
         &self.rs2
     }
@@ -128,7 +1537,9 @@ impl Instruction {
     pub fn rd(&self) -> &Register {
         &self.rd
     }
-    pub fn csr(&self) -> usize {
+    /// The CSR a Zicsr instruction reads or writes, or `None` for other
+    /// instructions
+    pub fn csr(&self) -> Option<Csr> {
         self.csr
     }
     pub fn op(&self) -> &Op {
@@ -137,88 +1548,1551 @@ impl Instruction {
     pub fn shamt(&self) -> usize {
         self.shamt
     }
-    pub fn immediate(&self) -> u32 {
+    pub fn immediate(&self) -> i32 {
         self.immediate
     }
     pub fn fence(&self) -> Option<&Fence> {
         self.fence.as_ref()
     }
+    pub fn aqrl(&self) -> Option<&Aqrl> {
+        self.aqrl.as_ref()
+    }
+
+    pub fn rm(&self) -> Option<RoundingMode> {
+        self.rm
+    }
+
+    /// The floating point format of this instruction, as its fmt field or
+    /// load or store width encodes it, or `None` for instructions which are
+    /// not floating point. Conversions between floating point formats give
+    /// the format converted to.
+    pub fn float_format(&self) -> Option<FloatFormat> {
+        self.format
+    }
+
+    /// Returns true for a vector instruction masked by v0, written with a
+    /// trailing `v0.t`
+    pub fn is_masked(&self) -> bool {
+        self.masked
+    }
+
+    /// The vector type set by `vsetvli` or `vsetivli`
+    pub fn vtype(&self) -> Option<Vtype> {
+        self.vtype
+    }
+
+    /// The operand form of a vector arithmetic instruction, such as `.vx`
+    pub fn vector_form(&self) -> Option<VectorForm> {
+        self.form
+    }
+
+    /// The element width in bits of a vector load or store, which for the
+    /// indexed forms is the width of the indices, or 0 for other
+    /// instructions
+    pub fn eew(&self) -> usize {
+        self.eew
+    }
+
+    /// The number of fields of a vector segment load or store, which is 1
+    /// for other vector loads and stores, or the number of registers moved
+    /// by a whole register load or store. 0 for other instructions.
+    pub fn nf(&self) -> usize {
+        self.nf
+    }
+
+    /// The XLEN `rev8` and `zext.h` are encoded for, as their encodings
+    /// differ between RV32 and RV64, or `None` for other instructions
+    pub fn xlen(&self) -> Option<Xlen> {
+        self.xlen
+    }
+
+    /// The length of this instruction's encoding in bytes
+    pub fn length(&self) -> usize {
+        if self.op.is_compressed() {
+            2
+        } else {
+            4
+        }
+    }
+
+    /// Expands a compressed instruction to its equivalent base instruction.
+    /// Instructions which are not compressed are returned unchanged.
+    pub fn expand(&self) -> Instruction {
+        let mut instruction = self.clone();
+        if let Some(op) = self.op.expanded() {
+            instruction.op = op;
+        }
+        instruction
+    }
+
+    /// The CSR operand as it is written in assembly, which only Zicsr
+    /// instructions have
+    fn csr_name(&self) -> String {
+        self.csr.map(|csr| csr.to_string()).unwrap_or_default()
+    }
+
+    /// The operands of this instruction, in the order they are written in
+    /// assembly. The rounding mode is included whenever the instruction has
+    /// one, even where the printed form leaves out the default.
+    pub fn operands(&self) -> Vec<Operand> {
+        let rd = Operand::Register(self.rd.clone());
+        let rs1 = Operand::Register(self.rs1.clone());
+        let rs2 = Operand::Register(self.rs2.clone());
+        let rs3 = Operand::Register(self.rs3.clone());
+        let signed = Operand::SignedImmediate(self.immediate);
+        let unsigned = Operand::UnsignedImmediate(self.immediate as u32);
+        let shamt = Operand::UnsignedImmediate(self.shamt as u32);
+        let target = Operand::PcRelative(self.immediate);
+        let memory = Operand::Memory {
+            base: self.rs1.clone(),
+            offset: self.immediate,
+        };
+        // Atomics take a base register with no offset
+        let address = Operand::Memory {
+            base: self.rs1.clone(),
+            offset: 0,
+        };
+
+        let mut operands = match self.op {
+            Op::Add
+            | Op::Addw
+            | Op::And
+            | Op::Div
+            | Op::Divu
+            | Op::Divuw
+            | Op::Divw
+            | Op::Mul
+            | Op::Mulh
+            | Op::Mulhsu
+            | Op::Mulhu
+            | Op::Mulw
+            | Op::Or
+            | Op::Rem
+            | Op::Remu
+            | Op::Remuw
+            | Op::Remw
+            | Op::Sll
+            | Op::Sllw
+            | Op::Slt
+            | Op::Sltu
+            | Op::Sra
+            | Op::Sraw
+            | Op::Srl
+            | Op::Srlw
+            | Op::Sub
+            | Op::Subw
+            | Op::Xor
+            | Op::AddUw
+            | Op::Andn
+            | Op::Bclr
+            | Op::Bext
+            | Op::Binv
+            | Op::Bset
+            | Op::Clmul
+            | Op::Clmulh
+            | Op::Clmulr
+            | Op::Max
+            | Op::Maxu
+            | Op::Min
+            | Op::Minu
+            | Op::Orn
+            | Op::Rol
+            | Op::Rolw
+            | Op::Ror
+            | Op::Rorw
+            | Op::Sh1add
+            | Op::Sh1addUw
+            | Op::Sh2add
+            | Op::Sh2addUw
+            | Op::Sh3add
+            | Op::Sh3addUw
+            | Op::Xnor
+            | Op::Aes64ds
+            | Op::Aes64dsm
+            | Op::Aes64es
+            | Op::Aes64esm
+            | Op::Aes64ks2
+            | Op::Pack
+            | Op::Packh
+            | Op::Packw
+            | Op::Sha512sig0h
+            | Op::Sha512sig0l
+            | Op::Sha512sig1h
+            | Op::Sha512sig1l
+            | Op::Sha512sum0r
+            | Op::Sha512sum1r
+            | Op::Xperm4
+            | Op::Xperm8
+            | Op::FaddD
+            | Op::FaddH
+            | Op::FaddQ
+            | Op::FaddS
+            | Op::FdivD
+            | Op::FdivH
+            | Op::FdivQ
+            | Op::FdivS
+            | Op::FeqD
+            | Op::FeqH
+            | Op::FeqQ
+            | Op::FeqS
+            | Op::FleD
+            | Op::FleH
+            | Op::FleQ
+            | Op::FleS
+            | Op::FleqD
+            | Op::FleqH
+            | Op::FleqQ
+            | Op::FleqS
+            | Op::FltD
+            | Op::FltH
+            | Op::FltQ
+            | Op::FltS
+            | Op::FltqD
+            | Op::FltqH
+            | Op::FltqQ
+            | Op::FltqS
+            | Op::FmaxD
+            | Op::FmaxH
+            | Op::FmaxQ
+            | Op::FmaxS
+            | Op::FmaxmD
+            | Op::FmaxmH
+            | Op::FmaxmQ
+            | Op::FmaxmS
+            | Op::FminD
+            | Op::FminH
+            | Op::FminQ
+            | Op::FminS
+            | Op::FminmD
+            | Op::FminmH
+            | Op::FminmQ
+            | Op::FminmS
+            | Op::FmulD
+            | Op::FmulH
+            | Op::FmulQ
+            | Op::FmulS
+            | Op::FmvpDX
+            | Op::FmvpQX
+            | Op::FsgnjD
+            | Op::FsgnjH
+            | Op::FsgnjQ
+            | Op::FsgnjS
+            | Op::FsgnjnD
+            | Op::FsgnjnH
+            | Op::FsgnjnQ
+            | Op::FsgnjnS
+            | Op::FsgnjxD
+            | Op::FsgnjxH
+            | Op::FsgnjxQ
+            | Op::FsgnjxS
+            | Op::FsubD
+            | Op::FsubH
+            | Op::FsubQ
+            | Op::FsubS => vec![rd, rs1, rs2],
+            Op::Addi
+            | Op::Addiw
+            | Op::Andi
+            | Op::Jalr
+            | Op::Ori
+            | Op::Slti
+            | Op::Sltiu
+            | Op::Xori => vec![rd, rs1, signed],
+            Op::Slli
+            | Op::Slliw
+            | Op::Srai
+            | Op::Sraiw
+            | Op::Srli
+            | Op::Srliw
+            | Op::Bclri
+            | Op::Bexti
+            | Op::Binvi
+            | Op::Bseti
+            | Op::Rori
+            | Op::Roriw
+            | Op::SlliUw => vec![rd, rs1, shamt],
+            Op::Auipc | Op::Lui | Op::CLui => vec![rd, unsigned],
+            Op::Beq | Op::Bge | Op::Bgeu | Op::Blt | Op::Bltu | Op::Bne => vec![rs1, rs2, target],
+            Op::Jal => vec![rd, target],
+            Op::Fld
+            | Op::Flh
+            | Op::Flq
+            | Op::Flw
+            | Op::Lb
+            | Op::Lbu
+            | Op::Ld
+            | Op::Lh
+            | Op::Lhu
+            | Op::Lw
+            | Op::Lwu
+            | Op::CFld
+            | Op::CFldsp
+            | Op::CFlw
+            | Op::CFlwsp
+            | Op::CLd
+            | Op::CLdsp
+            | Op::CLw
+            | Op::CLwsp => vec![rd, memory],
+            Op::Fsd
+            | Op::Fsh
+            | Op::Fsq
+            | Op::Fsw
+            | Op::Sb
+            | Op::Sd
+            | Op::Sh
+            | Op::Sw
+            | Op::CFsd
+            | Op::CFsdsp
+            | Op::CFsw
+            | Op::CFswsp
+            | Op::CSd
+            | Op::CSdsp
+            | Op::CSw
+            | Op::CSwsp => vec![rs2, memory],
+            Op::Csrrc | Op::Csrrs | Op::Csrrw => match self.csr {
+                Some(csr) => vec![rd, Operand::Csr(csr), rs1],
+                None => vec![rd, rs1],
+            },
+            Op::Csrrci | Op::Csrrsi | Op::Csrrwi => match self.csr {
+                Some(csr) => vec![rd, Operand::Csr(csr), unsigned],
+                None => vec![rd, unsigned],
+            },
+            Op::Ebreak
+            | Op::Ecall
+            | Op::FenceI
+            | Op::FenceTso
+            | Op::CEbreak
+            | Op::Dret
+            | Op::Mret
+            | Op::Sret
+            | Op::Uret
+            | Op::Wfi
+            | Op::SfenceInvalIr
+            | Op::SfenceWInval
+            | Op::Pause
+            | Op::NtlAll
+            | Op::NtlP1
+            | Op::NtlPall
+            | Op::NtlS1
+            | Op::CNtlAll
+            | Op::CNtlP1
+            | Op::CNtlPall
+            | Op::CNtlS1 => Vec::new(),
+            Op::CboClean | Op::CboFlush | Op::CboInval | Op::CboZero => vec![address],
+            Op::PrefetchI | Op::PrefetchR | Op::PrefetchW => vec![memory],
+            Op::SfenceVma
+            | Op::SinvalVma
+            | Op::HfenceGvma
+            | Op::HfenceVvma
+            | Op::HinvalGvma
+            | Op::HinvalVvma => vec![rs1, rs2],
+            Op::HlvB
+            | Op::HlvBu
+            | Op::HlvD
+            | Op::HlvH
+            | Op::HlvHu
+            | Op::HlvW
+            | Op::HlvWu
+            | Op::HlvxHu
+            | Op::HlvxWu => vec![rd, address],
+            Op::HsvB | Op::HsvD | Op::HsvH | Op::HsvW => vec![rs2, address],
+            Op::Fence => match self.fence {
+                Some(ref fence) => vec![
+                    Operand::FenceSet(fence.predecessor()),
+                    Operand::FenceSet(fence.successor()),
+                ],
+                None => Vec::new(),
+            },
+            Op::RdCycle
+            | Op::RdCycleH
+            | Op::RdInstRet
+            | Op::RdInstRetH
+            | Op::RdTime
+            | Op::RdTimeH => vec![rd],
+            Op::LrD | Op::LrW => vec![rd, address],
+            Op::ScD
+            | Op::ScW
+            | Op::AmoaddD
+            | Op::AmoaddW
+            | Op::AmoandD
+            | Op::AmoandW
+            | Op::AmomaxD
+            | Op::AmomaxW
+            | Op::AmomaxuD
+            | Op::AmomaxuW
+            | Op::AmominD
+            | Op::AmominW
+            | Op::AmominuD
+            | Op::AmominuW
+            | Op::AmoorD
+            | Op::AmoorW
+            | Op::AmoswapD
+            | Op::AmoswapW
+            | Op::AmoxorD
+            | Op::AmoxorW => vec![rd, rs2, address],
+            Op::FmaddD
+            | Op::FmaddH
+            | Op::FmaddQ
+            | Op::FmaddS
+            | Op::FmsubD
+            | Op::FmsubH
+            | Op::FmsubQ
+            | Op::FmsubS
+            | Op::FnmaddD
+            | Op::FnmaddH
+            | Op::FnmaddQ
+            | Op::FnmaddS
+            | Op::FnmsubD
+            | Op::FnmsubH
+            | Op::FnmsubQ
+            | Op::FnmsubS => vec![rd, rs1, rs2, rs3],
+            Op::FclassD
+            | Op::FclassH
+            | Op::FclassQ
+            | Op::FclassS
+            | Op::FcvtDH
+            | Op::FcvtDL
+            | Op::FcvtDLu
+            | Op::FcvtDQ
+            | Op::FcvtDS
+            | Op::FcvtDW
+            | Op::FcvtDWu
+            | Op::FcvtHD
+            | Op::FcvtHL
+            | Op::FcvtHLu
+            | Op::FcvtHQ
+            | Op::FcvtHS
+            | Op::FcvtHW
+            | Op::FcvtHWu
+            | Op::FcvtLD
+            | Op::FcvtLH
+            | Op::FcvtLQ
+            | Op::FcvtLS
+            | Op::FcvtLuD
+            | Op::FcvtLuH
+            | Op::FcvtLuQ
+            | Op::FcvtLuS
+            | Op::FcvtQD
+            | Op::FcvtQH
+            | Op::FcvtQL
+            | Op::FcvtQLu
+            | Op::FcvtQS
+            | Op::FcvtQW
+            | Op::FcvtQWu
+            | Op::FcvtSD
+            | Op::FcvtSH
+            | Op::FcvtSL
+            | Op::FcvtSLu
+            | Op::FcvtSQ
+            | Op::FcvtSW
+            | Op::FcvtSWu
+            | Op::FcvtWD
+            | Op::FcvtWH
+            | Op::FcvtWQ
+            | Op::FcvtWS
+            | Op::FcvtWuD
+            | Op::FcvtWuH
+            | Op::FcvtWuQ
+            | Op::FcvtWuS
+            | Op::FcvtmodWD
+            | Op::FmvDX
+            | Op::FmvHX
+            | Op::FmvWX
+            | Op::FmvXD
+            | Op::FmvXH
+            | Op::FmvXW
+            | Op::FmvhXD
+            | Op::FmvhXQ
+            | Op::FroundD
+            | Op::FroundH
+            | Op::FroundQ
+            | Op::FroundS
+            | Op::FroundnxD
+            | Op::FroundnxH
+            | Op::FroundnxQ
+            | Op::FroundnxS
+            | Op::FsqrtD
+            | Op::FsqrtH
+            | Op::FsqrtQ
+            | Op::FsqrtS
+            | Op::Clz
+            | Op::Clzw
+            | Op::Cpop
+            | Op::Cpopw
+            | Op::Ctz
+            | Op::Ctzw
+            | Op::OrcB
+            | Op::Rev8
+            | Op::SextB
+            | Op::SextH
+            | Op::ZextH
+            | Op::Aes64im
+            | Op::Brev8
+            | Op::Sha256sig0
+            | Op::Sha256sig1
+            | Op::Sha256sum0
+            | Op::Sha256sum1
+            | Op::Sha512sig0
+            | Op::Sha512sig1
+            | Op::Sha512sum0
+            | Op::Sha512sum1
+            | Op::Sm3p0
+            | Op::Sm3p1
+            | Op::Unzip
+            | Op::Zip => vec![rd, rs1],
+            Op::Aes32dsi | Op::Aes32dsmi | Op::Aes32esi | Op::Aes32esmi | Op::Sm4ed | Op::Sm4ks => {
+                vec![rd, rs1, rs2, unsigned]
+            }
+            Op::Aes64ks1i => vec![rd, rs1, unsigned],
+            Op::FliD | Op::FliH | Op::FliQ | Op::FliS => {
+                vec![rd, Operand::FloatImmediate(self.immediate as u32)]
+            }
+            Op::CAddi4spn => vec![rd, rs1, unsigned],
+            Op::CAddi | Op::CAddi16sp | Op::CAddiw | Op::CAndi | Op::CLi => vec![rd, signed],
+            // A non-zero immediate makes c.nop a hint
+            Op::CNop => match self.immediate {
+                0 => Vec::new(),
+                _ => vec![signed],
+            },
+            Op::CSlli | Op::CSrai | Op::CSrli => vec![rd, shamt],
+            Op::CAdd
+            | Op::CAddw
+            | Op::CAnd
+            | Op::CMv
+            | Op::COr
+            | Op::CSub
+            | Op::CSubw
+            | Op::CXor => vec![rd, rs2],
+            Op::CJ | Op::CJal => vec![target],
+            Op::CJalr | Op::CJr => vec![rs1],
+            Op::CBeqz | Op::CBnez => vec![rs1, target],
+            Op::Vector(op) => vector::operands(self, op),
+        };
+
+        if let Some(rm) = self.rm {
+            operands.push(Operand::RoundingMode(rm));
+        }
+        operands
+    }
+
+    /// How this instruction affects control flow.
+    ///
+    /// Calls and returns are recognised by the standard calling convention,
+    /// which links the return address in ra, or t0 as the alternate link
+    /// register: `jal ra` is a call, and `jalr x0, 0(ra)` is a return.
+    /// A `jalr` between the two link registers is a coroutine swap, while
+    /// one which links in the register it jumps through, such as
+    /// `jalr ra, 0(ra)`, is a call, as the return address stack hints in
+    /// the specification have it.
+    pub fn flow(&self) -> Flow {
+        match self.op {
+            Op::Beq | Op::Bge | Op::Bgeu | Op::Blt | Op::Bltu | Op::Bne | Op::CBeqz | Op::CBnez => {
+                Flow::ConditionalBranch
+            }
+            Op::Jal | Op::CJ | Op::CJal => {
+                if is_link(&self.rd) {
+                    Flow::Call
+                } else {
+                    Flow::Jump
+                }
+            }
+            Op::Jalr | Op::CJalr | Op::CJr => {
+                if is_link(&self.rd) && is_link(&self.rs1) && self.rd != self.rs1 {
+                    Flow::CoroutineSwap
+                } else if is_link(&self.rd) {
+                    Flow::IndirectCall
+                } else if self.rd == Register::Zero && is_link(&self.rs1) && self.immediate == 0 {
+                    Flow::Return
+                } else {
+                    Flow::IndirectJump
+                }
+            }
+            Op::Ebreak | Op::Ecall | Op::CEbreak => Flow::Trap,
+            Op::Dret | Op::Mret | Op::Sret | Op::Uret => Flow::TrapReturn,
+            _ => Flow::Sequential,
+        }
+    }
+
+    /// The address this instruction transfers control to, if it is a
+    /// branch or jump whose target is known without the contents of
+    /// registers, for an instruction at `pc`.
+    ///
+    /// This is the target of the branch when taken, and for `jalr` is only
+    /// known when the base register is x0. Addresses wrap at 64 bits, so for
+    /// RV32 the result should be truncated to 32 bits.
+    pub fn branch_target(&self, pc: u64) -> Option<u64> {
+        let offset = self.immediate as i64 as u64;
+        match self.flow() {
+            Flow::ConditionalBranch | Flow::Jump | Flow::Call => Some(pc.wrapping_add(offset)),
+            Flow::IndirectJump | Flow::IndirectCall if self.rs1 == Register::Zero => {
+                Some(offset & !1)
+            }
+            _ => None,
+        }
+    }
+
+    /// The pseudo-instruction this instruction is written as by assemblers
+    /// and disassemblers, such as `nop` for `addi zero, zero, 0`, or `None`
+    /// if it has no alias. Compressed instructions are recognised by the
+    /// instruction they expand to, so `c.jr ra` is a `ret`.
+    ///
+    /// `call` and `tail` span two instructions, and are recognised by
+    /// `pseudo_pair` instead.
+    pub fn pseudo(&self) -> Option<Pseudo> {
+        let i = self.expand();
+        let zero = Register::Zero;
+        let pseudo = match i.op {
+            // `c.mv` expands to `add rd, zero, rs`
+            _ if self.op == Op::CMv => Pseudo::Mv,
+            Op::Addi if i.rd == zero && i.rs1 == zero && i.immediate == 0 => Pseudo::Nop,
+            // Writes to x0 with a nonzero immediate are hints, not loads
+            Op::Addi if i.rd != zero && i.rs1 == zero => Pseudo::Li,
+            Op::Addi if i.immediate == 0 => Pseudo::Mv,
+            Op::Xori if i.immediate == -1 => Pseudo::Not,
+            Op::Sub if i.rs1 == zero => Pseudo::Neg,
+            Op::Subw if i.rs1 == zero => Pseudo::Negw,
+            Op::Addiw if i.immediate == 0 => Pseudo::SextW,
+            Op::AddUw if i.rs2 == zero => Pseudo::ZextW,
+            Op::Sltiu if i.immediate == 1 => Pseudo::Seqz,
+            Op::Sltu if i.rs1 == zero => Pseudo::Snez,
+            Op::Slt if i.rs2 == zero => Pseudo::Sltz,
+            Op::Slt if i.rs1 == zero => Pseudo::Sgtz,
+            Op::FsgnjS if i.rs1 == i.rs2 => Pseudo::FmvS,
+            Op::FsgnjxS if i.rs1 == i.rs2 => Pseudo::FabsS,
+            Op::FsgnjnS if i.rs1 == i.rs2 => Pseudo::FnegS,
+            Op::FsgnjD if i.rs1 == i.rs2 => Pseudo::FmvD,
+            Op::FsgnjxD if i.rs1 == i.rs2 => Pseudo::FabsD,
+            Op::FsgnjnD if i.rs1 == i.rs2 => Pseudo::FnegD,
+            Op::FsgnjH if i.rs1 == i.rs2 => Pseudo::FmvH,
+            Op::FsgnjxH if i.rs1 == i.rs2 => Pseudo::FabsH,
+            Op::FsgnjnH if i.rs1 == i.rs2 => Pseudo::FnegH,
+            Op::FsgnjQ if i.rs1 == i.rs2 => Pseudo::FmvQ,
+            Op::FsgnjxQ if i.rs1 == i.rs2 => Pseudo::FabsQ,
+            Op::FsgnjnQ if i.rs1 == i.rs2 => Pseudo::FnegQ,
+            Op::Beq if i.rs2 == zero => Pseudo::Beqz,
+            Op::Bne if i.rs2 == zero => Pseudo::Bnez,
+            Op::Bge if i.rs1 == zero => Pseudo::Blez,
+            Op::Bge if i.rs2 == zero => Pseudo::Bgez,
+            Op::Blt if i.rs2 == zero => Pseudo::Bltz,
+            Op::Blt if i.rs1 == zero => Pseudo::Bgtz,
+            Op::Jal if i.rd == zero => Pseudo::J,
+            Op::Jal if i.rd == Register::Ra => Pseudo::Jal,
+            Op::Jalr if i.rd == zero && i.rs1 == Register::Ra && i.immediate == 0 => Pseudo::Ret,
+            Op::Jalr if i.rd == zero => Pseudo::Jr,
+            Op::Jalr if i.rd == Register::Ra => Pseudo::Jalr,
+            Op::Csrrs if i.rs1 == zero => match i.csr {
+                Some(CYCLE) => Pseudo::Counter(Op::RdCycle),
+                Some(TIME) => Pseudo::Counter(Op::RdTime),
+                Some(INSTRET) => Pseudo::Counter(Op::RdInstRet),
+                Some(CYCLEH) => Pseudo::Counter(Op::RdCycleH),
+                Some(TIMEH) => Pseudo::Counter(Op::RdTimeH),
+                Some(INSTRETH) => Pseudo::Counter(Op::RdInstRetH),
+                Some(FCSR) => Pseudo::Frcsr,
+                Some(FRM) => Pseudo::Frrm,
+                Some(FFLAGS) => Pseudo::Frflags,
+                _ => Pseudo::Csrr,
+            },
+            Op::Csrrw if i.csr == Some(FCSR) => Pseudo::Fscsr,
+            Op::Csrrw if i.csr == Some(FRM) => Pseudo::Fsrm,
+            Op::Csrrw if i.csr == Some(FFLAGS) => Pseudo::Fsflags,
+            Op::Csrrwi if i.csr == Some(FRM) => Pseudo::Fsrmi,
+            Op::Csrrwi if i.csr == Some(FFLAGS) => Pseudo::Fsflagsi,
+            Op::Csrrw if i.rd == zero => Pseudo::Csrw,
+            Op::Csrrs if i.rd == zero => Pseudo::Csrs,
+            Op::Csrrc if i.rd == zero => Pseudo::Csrc,
+            Op::Csrrwi if i.rd == zero => Pseudo::Csrwi,
+            Op::Csrrsi if i.rd == zero => Pseudo::Csrsi,
+            Op::Csrrci if i.rd == zero => Pseudo::Csrci,
+            Op::RdCycle
+            | Op::RdCycleH
+            | Op::RdInstRet
+            | Op::RdInstRetH
+            | Op::RdTime
+            | Op::RdTimeH => Pseudo::Counter(i.op.clone()),
+            _ => return None,
+        };
+        Some(pseudo)
+    }
+
+    /// Recognises the `call` and `tail` pseudo-instructions, an `auipc`
+    /// followed by `next`, a `jalr` through the register it wrote. Returns
+    /// the pseudo-instruction and the offset of its target from the
+    /// `auipc`.
+    pub fn pseudo_pair(&self, next: &Instruction) -> Option<(Pseudo, i32)> {
+        if self.op != Op::Auipc || next.op != Op::Jalr || next.rs1 != self.rd {
+            return None;
+        }
+        let pseudo = match (&self.rd, &next.rd) {
+            (Register::Ra, Register::Ra) => Pseudo::Call,
+            (Register::T1, Register::Zero) => Pseudo::Tail,
+            _ => return None,
+        };
+        let offset = ((self.immediate as u32) << 12) as i32;
+        Some((pseudo, offset.wrapping_add(next.immediate)))
+    }
+
+    /// Writes the alias-aware form of this instruction, as selected by the
+    /// alternate flag, `{:#}`
+    fn fmt_pseudo(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let i = self.expand();
+        let pseudo = match self.pseudo() {
+            Some(pseudo) => pseudo,
+            None => return write!(f, "{}", i),
+        };
+        let mnemonic = pseudo.mnemonic();
+        match pseudo {
+            Pseudo::Nop | Pseudo::Ret => write!(f, "{}", mnemonic),
+            // call and tail span two instructions, so only pseudo_pair
+            // returns them
+            Pseudo::Call | Pseudo::Tail => write!(f, "{}", i),
+            Pseudo::Li => write!(f, "li {}, {}", i.rd, i.immediate),
+            Pseudo::Mv if i.op == Op::Add => write!(f, "mv {}, {}", i.rd, i.rs2),
+            Pseudo::Mv
+            | Pseudo::Not
+            | Pseudo::SextW
+            | Pseudo::ZextW
+            | Pseudo::Seqz
+            | Pseudo::Sltz
+            | Pseudo::FmvS
+            | Pseudo::FabsS
+            | Pseudo::FnegS
+            | Pseudo::FmvD
+            | Pseudo::FabsD
+            | Pseudo::FnegD
+            | Pseudo::FmvH
+            | Pseudo::FabsH
+            | Pseudo::FnegH
+            | Pseudo::FmvQ
+            | Pseudo::FabsQ
+            | Pseudo::FnegQ => write!(f, "{} {}, {}", mnemonic, i.rd, i.rs1),
+            Pseudo::Neg | Pseudo::Negw | Pseudo::Snez | Pseudo::Sgtz => {
+                write!(f, "{} {}, {}", mnemonic, i.rd, i.rs2)
+            }
+            Pseudo::Beqz | Pseudo::Bnez | Pseudo::Bgez | Pseudo::Bltz => {
+                write!(f, "{} {}, {}", mnemonic, i.rs1, i.immediate)
+            }
+            Pseudo::Blez | Pseudo::Bgtz => write!(f, "{} {}, {}", mnemonic, i.rs2, i.immediate),
+            Pseudo::J | Pseudo::Jal => write!(f, "{} {}", mnemonic, i.immediate),
+            Pseudo::Jr | Pseudo::Jalr => match i.immediate {
+                0 => write!(f, "{} {}", mnemonic, i.rs1),
+                offset => write!(f, "{} {}({})", mnemonic, offset, i.rs1),
+            },
+            Pseudo::Csrr => write!(f, "csrr {}, {}", i.rd, i.csr_name()),
+            Pseudo::Csrw | Pseudo::Csrs | Pseudo::Csrc => {
+                write!(f, "{} {}, {}", mnemonic, i.csr_name(), i.rs1)
+            }
+            Pseudo::Csrwi | Pseudo::Csrsi | Pseudo::Csrci => {
+                write!(f, "{} {}, {}", mnemonic, i.csr_name(), i.immediate)
+            }
+            Pseudo::Frcsr | Pseudo::Frrm | Pseudo::Frflags | Pseudo::Counter(_) => {
+                write!(f, "{} {}", mnemonic, i.rd)
+            }
+            // The old value is only written out when it is kept
+            Pseudo::Fscsr | Pseudo::Fsrm | Pseudo::Fsflags => match i.rd {
+                Register::Zero => write!(f, "{} {}", mnemonic, i.rs1),
+                _ => write!(f, "{} {}, {}", mnemonic, i.rd, i.rs1),
+            },
+            Pseudo::Fsrmi | Pseudo::Fsflagsi => match i.rd {
+                Register::Zero => write!(f, "{} {}", mnemonic, i.immediate),
+                _ => write!(f, "{} {}, {}", mnemonic, i.rd, i.immediate),
+            },
+        }
+    }
+
+    /// The memory this instruction accesses, or `None` if it does not
+    /// access memory.
+    ///
+    /// `cbo.zero` is a store to the whole cache block holding the address
+    /// in rs1, and has a width of 0, as the size of a cache block is up to
+    /// the implementation. The indexed vector loads and stores also have a
+    /// width of 0, as their elements are as wide as the SEW in `vtype`
+    /// rather than their eew. The other cache-block operations and the
+    /// prefetches are deliberately left out: they manage caches rather than
+    /// read or write data, although `cbo.inval` may discard stores which
+    /// were not yet written back.
+    pub fn memory_access(&self) -> Option<MemoryAccess> {
+        let (kind, width, extend) = match self.op {
+            Op::Lb => (AccessKind::Load, 1, Extend::Sign),
+            Op::Lbu => (AccessKind::Load, 1, Extend::Zero),
+            Op::Lh => (AccessKind::Load, 2, Extend::Sign),
+            Op::Lhu => (AccessKind::Load, 2, Extend::Zero),
+            Op::Lw | Op::CLw | Op::CLwsp => (AccessKind::Load, 4, Extend::Sign),
+            Op::Lwu => (AccessKind::Load, 4, Extend::Zero),
+            Op::Ld | Op::CLd | Op::CLdsp => (AccessKind::Load, 8, Extend::None),
+            Op::Flw | Op::CFlw | Op::CFlwsp => (AccessKind::Load, 4, Extend::None),
+            Op::Fld | Op::CFld | Op::CFldsp => (AccessKind::Load, 8, Extend::None),
+            Op::Flh => (AccessKind::Load, 2, Extend::None),
+            Op::Flq => (AccessKind::Load, 16, Extend::None),
+            Op::Sb => (AccessKind::Store, 1, Extend::None),
+            Op::Sh | Op::Fsh => (AccessKind::Store, 2, Extend::None),
+            Op::Sw | Op::CSw | Op::CSwsp | Op::Fsw | Op::CFsw | Op::CFswsp => {
+                (AccessKind::Store, 4, Extend::None)
+            }
+            Op::Sd | Op::CSd | Op::CSdsp | Op::Fsd | Op::CFsd | Op::CFsdsp => {
+                (AccessKind::Store, 8, Extend::None)
+            }
+            Op::Fsq => (AccessKind::Store, 16, Extend::None),
+            Op::CboZero => (AccessKind::Store, 0, Extend::None),
+            // The hypervisor loads and stores access guest virtual memory
+            // through rs1 alone
+            Op::HlvB => (AccessKind::Load, 1, Extend::Sign),
+            Op::HlvBu => (AccessKind::Load, 1, Extend::Zero),
+            Op::HlvH => (AccessKind::Load, 2, Extend::Sign),
+            Op::HlvHu | Op::HlvxHu => (AccessKind::Load, 2, Extend::Zero),
+            Op::HlvW => (AccessKind::Load, 4, Extend::Sign),
+            Op::HlvWu | Op::HlvxWu => (AccessKind::Load, 4, Extend::Zero),
+            Op::HlvD => (AccessKind::Load, 8, Extend::None),
+            Op::HsvB => (AccessKind::Store, 1, Extend::None),
+            Op::HsvH => (AccessKind::Store, 2, Extend::None),
+            Op::HsvW => (AccessKind::Store, 4, Extend::None),
+            Op::HsvD => (AccessKind::Store, 8, Extend::None),
+            Op::LrW => (AccessKind::LoadReserved, 4, Extend::Sign),
+            Op::LrD => (AccessKind::LoadReserved, 8, Extend::None),
+            Op::ScW => (AccessKind::StoreConditional, 4, Extend::None),
+            Op::ScD => (AccessKind::StoreConditional, 8, Extend::None),
+            Op::AmoaddW
+            | Op::AmoandW
+            | Op::AmomaxW
+            | Op::AmomaxuW
+            | Op::AmominW
+            | Op::AmominuW
+            | Op::AmoorW
+            | Op::AmoswapW
+            | Op::AmoxorW => (AccessKind::ReadModifyWrite, 4, Extend::Sign),
+            Op::AmoaddD
+            | Op::AmoandD
+            | Op::AmomaxD
+            | Op::AmomaxuD
+            | Op::AmominD
+            | Op::AmominuD
+            | Op::AmoorD
+            | Op::AmoswapD
+            | Op::AmoxorD => (AccessKind::ReadModifyWrite, 8, Extend::None),
+            // Vector accesses give the width of one element, which for the
+            // indexed accesses is set by vtype when they run
+            Op::Vector(op) => {
+                let width = match op {
+                    VectorOp::Vluxei | VectorOp::Vloxei | VectorOp::Vsuxei | VectorOp::Vsoxei => 0,
+                    _ => self.eew / 8,
+                };
+                match vector::access_kind(op) {
+                    Some(kind) => (kind, width, Extend::None),
+                    None => return None,
+                }
+            }
+            _ => return None,
+        };
+
+        // Atomics address memory through rs1 alone
+        let displacement = match kind {
+            AccessKind::Load | AccessKind::Store => self.immediate,
+            _ => 0,
+        };
+
+        Some(MemoryAccess {
+            kind,
+            width,
+            extend,
+            base: self.rs1.clone(),
+            displacement,
+        })
+    }
+
+    /// The registers this instruction reads, including implicit reads such
+    /// as the CSR of a Zicsr instruction, or `frm` for a floating point
+    /// instruction using the dynamic rounding mode. x0 is hardwired to zero,
+    /// so it never appears.
+    pub fn registers_read(&self) -> Vec<Location> {
+        let mut read = Vec::new();
+        match self.op {
+            // The rs1 and rd fields of fences are reserved
+            Op::Fence | Op::FenceI | Op::FenceTso => return read,
+            _ => {}
+        }
+
+        for register in &[&self.rs1, &self.rs2, &self.rs3] {
+            push_register(&mut read, register);
+        }
+
+        match self.op {
+            // csrrw and csrrwi do not read the CSR when rd is x0
+            Op::Csrrw | Op::Csrrwi if self.rd == Register::Zero => {}
+            Op::Csrrw | Op::Csrrwi | Op::Csrrc | Op::Csrrci | Op::Csrrs | Op::Csrrsi => {
+                read.extend(self.csr.map(Location::Csr))
+            }
+            Op::RdCycle => read.push(Location::Csr(CYCLE)),
+            Op::RdCycleH => read.push(Location::Csr(CYCLEH)),
+            Op::RdTime => read.push(Location::Csr(TIME)),
+            Op::RdTimeH => read.push(Location::Csr(TIMEH)),
+            Op::RdInstRet => read.push(Location::Csr(INSTRET)),
+            Op::RdInstRetH => read.push(Location::Csr(INSTRETH)),
+            // Trap returns restore the pc and the interrupt enable stack
+            Op::Mret => read.extend(vec![Location::Csr(MEPC), Location::Csr(MSTATUS)]),
+            Op::Sret => read.extend(vec![Location::Csr(SEPC), Location::Csr(SSTATUS)]),
+            Op::Uret => read.extend(vec![Location::Csr(UEPC), Location::Csr(USTATUS)]),
+            Op::Dret => read.extend(vec![Location::Csr(DPC), Location::Csr(DCSR)]),
+            Op::Vector(op) => {
+                for register in vector::implicit_reads(self, op) {
+                    push_register(&mut read, &register);
+                }
+            }
+            _ => {}
+        }
+        if self.rm == Some(RoundingMode::Dyn) {
+            read.push(Location::Csr(FRM));
+        }
+
+        read
+    }
+
+    /// The registers this instruction writes, including implicit writes
+    /// such as the CSR of a Zicsr instruction, or the accrued exception
+    /// flags in `fflags` for floating point arithmetic. Writes to x0 are
+    /// discarded, so it never appears.
+    pub fn registers_written(&self) -> Vec<Location> {
+        let mut written = Vec::new();
+        match self.op {
+            Op::Fence | Op::FenceI | Op::FenceTso => return written,
+            _ => {}
+        }
+
+        push_register(&mut written, &self.rd);
+
+        match self.op {
+            Op::Csrrw | Op::Csrrwi => written.extend(self.csr.map(Location::Csr)),
+            // csrrs and csrrc do not write the CSR when rs1 is x0, nor their
+            // immediate forms when the immediate is zero
+            Op::Csrrc | Op::Csrrs if self.rs1 != Register::Zero => {
+                written.extend(self.csr.map(Location::Csr))
+            }
+            Op::Csrrci | Op::Csrrsi if self.immediate != 0 => {
+                written.extend(self.csr.map(Location::Csr))
+            }
+            Op::Mret => written.push(Location::Csr(MSTATUS)),
+            Op::Sret => written.push(Location::Csr(SSTATUS)),
+            Op::Uret => written.push(Location::Csr(USTATUS)),
+            Op::Vector(VectorOp::Vsetvli)
+            | Op::Vector(VectorOp::Vsetivli)
+            | Op::Vector(VectorOp::Vsetvl) => {
+                written.extend(vec![Location::Csr(VL), Location::Csr(VTYPE)])
+            }
+            _ => {}
+        }
+        if self.op.sets_fflags() {
+            written.push(Location::Csr(FFLAGS));
+        }
+
+        written
+    }
+
+    fn aqrl_suffix(&self) -> String {
+        self.aqrl().map(|aqrl| aqrl.to_string()).unwrap_or_default()
+    }
+
+    /// The rounding mode operand, which is left out when it is the default
+    fn rm_suffix(&self) -> String {
+        match self.rm {
+            Some(rm) if rm != default_rounding_mode(&self.op) => format!(", {}", rm),
+            _ => String::new(),
+        }
+    }
+}
+
+/// The rounding mode an instruction is written with when assembly leaves it
+/// out. Conversions which are always exact default to rne, and everything
+/// else to the dynamic rounding mode.
+pub(crate) fn default_rounding_mode(op: &Op) -> RoundingMode {
+    match op {
+        Op::FcvtDS
+        | Op::FcvtDW
+        | Op::FcvtDWu
+        | Op::FcvtSH
+        | Op::FcvtDH
+        | Op::FcvtQH
+        | Op::FcvtQS
+        | Op::FcvtQD
+        | Op::FcvtQW
+        | Op::FcvtQWu
+        | Op::FcvtQL
+        | Op::FcvtQLu => RoundingMode::Rne,
+        _ => RoundingMode::Dyn,
+    }
+}
+
+/// Returns true for the registers the calling convention uses to link a
+/// return address
+fn is_link(register: &Register) -> bool {
+    *register == Register::Ra || *register == Register::T0
 }
 
+/// Adds `register` to a read or write set, leaving out x0 and registers
+/// already in the set
+fn push_register(set: &mut Vec<Location>, register: &Register) {
+    if *register == Register::Invalid || *register == Register::Zero {
+        return;
+    }
+    let location = Location::Register(register.clone());
+    if !set.contains(&location) {
+        set.push(location);
+    }
+}
+
+/// Instructions are written in the GNU assembler syntax, with every
+/// instruction under its own mnemonic. The alternate form, `{:#}`, writes
+/// the pseudo-instruction an instruction is an alias of where it has one,
+/// and compressed instructions as the instructions they expand to, as
+/// disassemblers do by default.
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_pseudo(f);
+        }
+        let mnemonic = self.op().mnemonic();
+        let immediate = self.immediate();
+
         match self.op() {
-            Op::Add => write!(f, "add {}, {}, {}", self.rd(), self.rs1(), self.rs2()),
-            Op::Addi => write!(
+            Op::Vector(op) => vector::fmt(self, *op, f),
+            Op::Add
+            | Op::Addw
+            | Op::And
+            | Op::Div
+            | Op::Divu
+            | Op::Divuw
+            | Op::Divw
+            | Op::Mul
+            | Op::Mulh
+            | Op::Mulhsu
+            | Op::Mulhu
+            | Op::Mulw
+            | Op::Or
+            | Op::Rem
+            | Op::Remu
+            | Op::Remuw
+            | Op::Remw
+            | Op::Sll
+            | Op::Sllw
+            | Op::Slt
+            | Op::Sltu
+            | Op::Sra
+            | Op::Sraw
+            | Op::Srl
+            | Op::Srlw
+            | Op::Sub
+            | Op::Subw
+            | Op::Xor
+            | Op::AddUw
+            | Op::Andn
+            | Op::Bclr
+            | Op::Bext
+            | Op::Binv
+            | Op::Bset
+            | Op::Clmul
+            | Op::Clmulh
+            | Op::Clmulr
+            | Op::Max
+            | Op::Maxu
+            | Op::Min
+            | Op::Minu
+            | Op::Orn
+            | Op::Rol
+            | Op::Rolw
+            | Op::Ror
+            | Op::Rorw
+            | Op::Sh1add
+            | Op::Sh1addUw
+            | Op::Sh2add
+            | Op::Sh2addUw
+            | Op::Sh3add
+            | Op::Sh3addUw
+            | Op::Xnor
+            | Op::Aes64ds
+            | Op::Aes64dsm
+            | Op::Aes64es
+            | Op::Aes64esm
+            | Op::Aes64ks2
+            | Op::Pack
+            | Op::Packh
+            | Op::Packw
+            | Op::Sha512sig0h
+            | Op::Sha512sig0l
+            | Op::Sha512sig1h
+            | Op::Sha512sig1l
+            | Op::Sha512sum0r
+            | Op::Sha512sum1r
+            | Op::Xperm4
+            | Op::Xperm8
+            | Op::FeqD
+            | Op::FeqH
+            | Op::FeqQ
+            | Op::FeqS
+            | Op::FleD
+            | Op::FleH
+            | Op::FleQ
+            | Op::FleS
+            | Op::FleqD
+            | Op::FleqH
+            | Op::FleqQ
+            | Op::FleqS
+            | Op::FltD
+            | Op::FltH
+            | Op::FltQ
+            | Op::FltS
+            | Op::FltqD
+            | Op::FltqH
+            | Op::FltqQ
+            | Op::FltqS
+            | Op::FmaxD
+            | Op::FmaxH
+            | Op::FmaxQ
+            | Op::FmaxS
+            | Op::FmaxmD
+            | Op::FmaxmH
+            | Op::FmaxmQ
+            | Op::FmaxmS
+            | Op::FminD
+            | Op::FminH
+            | Op::FminQ
+            | Op::FminS
+            | Op::FminmD
+            | Op::FminmH
+            | Op::FminmQ
+            | Op::FminmS
+            | Op::FmvpDX
+            | Op::FmvpQX
+            | Op::FsgnjD
+            | Op::FsgnjH
+            | Op::FsgnjQ
+            | Op::FsgnjS
+            | Op::FsgnjnD
+            | Op::FsgnjnH
+            | Op::FsgnjnQ
+            | Op::FsgnjnS
+            | Op::FsgnjxD
+            | Op::FsgnjxH
+            | Op::FsgnjxQ
+            | Op::FsgnjxS => write!(
                 f,
-                "addi {}, {}, {}",
+                "{} {}, {}, {}",
+                mnemonic,
                 self.rd(),
                 self.rs1(),
-                self.immediate()
+                self.rs2()
             ),
-            Op::And => write!(f, "and {}, {}, {}", self.rd(), self.rs1(), self.rs2()),
-            Op::Andi => write!(
+            Op::Addi | Op::Addiw | Op::Andi | Op::Ori | Op::Slti | Op::Sltiu | Op::Xori => write!(
                 f,
-                "andi {}, {}, {}",
+                "{} {}, {}, {}",
+                mnemonic,
                 self.rd(),
                 self.rs1(),
-                self.immediate()
+                immediate
             ),
-            Op::Auipc => write!(f, "auipc {}, 0x{:x}", self.rd(), self.immediate()),
-            Op::Beq => write!(
+            Op::Slli
+            | Op::Slliw
+            | Op::Srai
+            | Op::Sraiw
+            | Op::Srli
+            | Op::Srliw
+            | Op::Bclri
+            | Op::Bexti
+            | Op::Binvi
+            | Op::Bseti
+            | Op::Rori
+            | Op::Roriw
+            | Op::SlliUw => write!(
                 f,
-                "beq {}, {}, {}",
+                "{} {}, {}, 0x{:x}",
+                mnemonic,
+                self.rd(),
                 self.rs1(),
-                self.rs2(),
-                self.immediate()
+                self.shamt()
             ),
-            Op::Bge => write!(
+            Op::Auipc | Op::Lui => {
+                write!(f, "{} {}, 0x{:x}", mnemonic, self.rd(), self.immediate())
+            }
+            Op::Beq | Op::Bge | Op::Bgeu | Op::Blt | Op::Bltu | Op::Bne => write!(
                 f,
-                "bge {}, {}, {}",
+                "{} {}, {}, {}",
+                mnemonic,
                 self.rs1(),
                 self.rs2(),
-                self.immediate()
+                immediate
             ),
-            Op::Bgeu => write!(
+            Op::Jal => write!(f, "jal {}, {}", self.rd(), immediate),
+            Op::Fld
+            | Op::Flh
+            | Op::Flq
+            | Op::Flw
+            | Op::Jalr
+            | Op::Lb
+            | Op::Lbu
+            | Op::Ld
+            | Op::Lh
+            | Op::Lhu
+            | Op::Lw
+            | Op::Lwu
+            | Op::CFld
+            | Op::CFldsp
+            | Op::CFlw
+            | Op::CFlwsp
+            | Op::CLd
+            | Op::CLdsp
+            | Op::CLw
+            | Op::CLwsp => write!(
                 f,
-                "bgeu {}, {}, {}",
-                self.rs1(),
+                "{} {}, {}({})",
+                mnemonic,
+                self.rd(),
+                immediate,
+                self.rs1()
+            ),
+            Op::Fsd
+            | Op::Fsh
+            | Op::Fsq
+            | Op::Fsw
+            | Op::Sb
+            | Op::Sd
+            | Op::Sh
+            | Op::Sw
+            | Op::CFsd
+            | Op::CFsdsp
+            | Op::CFsw
+            | Op::CFswsp
+            | Op::CSd
+            | Op::CSdsp
+            | Op::CSw
+            | Op::CSwsp => write!(
+                f,
+                "{} {}, {}({})",
+                mnemonic,
                 self.rs2(),
+                immediate,
+                self.rs1()
+            ),
+            Op::Csrrc | Op::Csrrs | Op::Csrrw => write!(
+                f,
+                "{} {}, {}, {}",
+                mnemonic,
+                self.rd(),
+                self.csr_name(),
+                self.rs1()
+            ),
+            Op::Csrrci | Op::Csrrsi | Op::Csrrwi => write!(
+                f,
+                "{} {}, {}, {}",
+                mnemonic,
+                self.rd(),
+                self.csr_name(),
                 self.immediate()
             ),
-            Op::Blt => write!(
+            Op::Ebreak
+            | Op::Ecall
+            | Op::FenceI
+            | Op::FenceTso
+            | Op::CEbreak
+            | Op::Dret
+            | Op::Mret
+            | Op::Sret
+            | Op::Uret
+            | Op::Wfi
+            | Op::SfenceInvalIr
+            | Op::SfenceWInval
+            | Op::Pause
+            | Op::NtlAll
+            | Op::NtlP1
+            | Op::NtlPall
+            | Op::NtlS1
+            | Op::CNtlAll
+            | Op::CNtlP1
+            | Op::CNtlPall
+            | Op::CNtlS1 => write!(f, "{}", mnemonic),
+            Op::CboClean | Op::CboFlush | Op::CboInval | Op::CboZero => {
+                write!(f, "{} ({})", mnemonic, self.rs1())
+            }
+            Op::PrefetchI | Op::PrefetchR | Op::PrefetchW => {
+                write!(f, "{} {}({})", mnemonic, immediate, self.rs1())
+            }
+            Op::SfenceVma
+            | Op::SinvalVma
+            | Op::HfenceGvma
+            | Op::HfenceVvma
+            | Op::HinvalGvma
+            | Op::HinvalVvma => write!(f, "{} {}, {}", mnemonic, self.rs1(), self.rs2()),
+            Op::HlvB
+            | Op::HlvBu
+            | Op::HlvD
+            | Op::HlvH
+            | Op::HlvHu
+            | Op::HlvW
+            | Op::HlvWu
+            | Op::HlvxHu
+            | Op::HlvxWu => write!(f, "{} {}, ({})", mnemonic, self.rd(), self.rs1()),
+            Op::HsvB | Op::HsvD | Op::HsvH | Op::HsvW => {
+                write!(f, "{} {}, ({})", mnemonic, self.rs2(), self.rs1())
+            }
+            Op::Fence => match self.fence() {
+                Some(fence) => write!(f, "fence {}", fence),
+                None => write!(f, "fence"),
+            },
+            Op::RdCycle
+            | Op::RdCycleH
+            | Op::RdInstRet
+            | Op::RdInstRetH
+            | Op::RdTime
+            | Op::RdTimeH => write!(f, "{} {}", mnemonic, self.rd()),
+            Op::LrD | Op::LrW => write!(
                 f,
-                "blt {}, {}, {}",
-                self.rs1(),
+                "{}{} {}, ({})",
+                mnemonic,
+                self.aqrl_suffix(),
+                self.rd(),
+                self.rs1()
+            ),
+            Op::ScD
+            | Op::ScW
+            | Op::AmoaddD
+            | Op::AmoaddW
+            | Op::AmoandD
+            | Op::AmoandW
+            | Op::AmomaxD
+            | Op::AmomaxW
+            | Op::AmomaxuD
+            | Op::AmomaxuW
+            | Op::AmominD
+            | Op::AmominW
+            | Op::AmominuD
+            | Op::AmominuW
+            | Op::AmoorD
+            | Op::AmoorW
+            | Op::AmoswapD
+            | Op::AmoswapW
+            | Op::AmoxorD
+            | Op::AmoxorW => write!(
+                f,
+                "{}{} {}, {}, ({})",
+                mnemonic,
+                self.aqrl_suffix(),
+                self.rd(),
                 self.rs2(),
-                self.immediate()
+                self.rs1()
             ),
-            Op::Bltu => write!(
+            Op::FaddD
+            | Op::FaddH
+            | Op::FaddQ
+            | Op::FaddS
+            | Op::FdivD
+            | Op::FdivH
+            | Op::FdivQ
+            | Op::FdivS
+            | Op::FmulD
+            | Op::FmulH
+            | Op::FmulQ
+            | Op::FmulS
+            | Op::FsubD
+            | Op::FsubH
+            | Op::FsubQ
+            | Op::FsubS => write!(
                 f,
-                "bltu {}, {}, {}",
+                "{} {}, {}, {}{}",
+                mnemonic,
+                self.rd(),
                 self.rs1(),
                 self.rs2(),
-                self.immediate()
+                self.rm_suffix()
             ),
-            Op::Bne => write!(
+            Op::FmaddD
+            | Op::FmaddH
+            | Op::FmaddQ
+            | Op::FmaddS
+            | Op::FmsubD
+            | Op::FmsubH
+            | Op::FmsubQ
+            | Op::FmsubS
+            | Op::FnmaddD
+            | Op::FnmaddH
+            | Op::FnmaddQ
+            | Op::FnmaddS
+            | Op::FnmsubD
+            | Op::FnmsubH
+            | Op::FnmsubQ
+            | Op::FnmsubS => write!(
                 f,
-                "bne {}, {}, {}",
+                "{} {}, {}, {}, {}{}",
+                mnemonic,
+                self.rd(),
                 self.rs1(),
                 self.rs2(),
-                self.immediate()
+                self.rs3(),
+                self.rm_suffix()
+            ),
+            Op::FcvtDH
+            | Op::FcvtDL
+            | Op::FcvtDLu
+            | Op::FcvtDQ
+            | Op::FcvtDS
+            | Op::FcvtDW
+            | Op::FcvtDWu
+            | Op::FcvtHD
+            | Op::FcvtHL
+            | Op::FcvtHLu
+            | Op::FcvtHQ
+            | Op::FcvtHS
+            | Op::FcvtHW
+            | Op::FcvtHWu
+            | Op::FcvtLD
+            | Op::FcvtLH
+            | Op::FcvtLQ
+            | Op::FcvtLS
+            | Op::FcvtLuD
+            | Op::FcvtLuH
+            | Op::FcvtLuQ
+            | Op::FcvtLuS
+            | Op::FcvtQD
+            | Op::FcvtQH
+            | Op::FcvtQL
+            | Op::FcvtQLu
+            | Op::FcvtQS
+            | Op::FcvtQW
+            | Op::FcvtQWu
+            | Op::FcvtSD
+            | Op::FcvtSH
+            | Op::FcvtSL
+            | Op::FcvtSLu
+            | Op::FcvtSQ
+            | Op::FcvtSW
+            | Op::FcvtSWu
+            | Op::FcvtWD
+            | Op::FcvtWH
+            | Op::FcvtWQ
+            | Op::FcvtWS
+            | Op::FcvtWuD
+            | Op::FcvtWuH
+            | Op::FcvtWuQ
+            | Op::FcvtWuS
+            | Op::FcvtmodWD
+            | Op::FroundD
+            | Op::FroundH
+            | Op::FroundQ
+            | Op::FroundS
+            | Op::FroundnxD
+            | Op::FroundnxH
+            | Op::FroundnxQ
+            | Op::FroundnxS
+            | Op::FsqrtD
+            | Op::FsqrtH
+            | Op::FsqrtQ
+            | Op::FsqrtS => write!(
+                f,
+                "{} {}, {}{}",
+                mnemonic,
+                self.rd(),
+                self.rs1(),
+                self.rm_suffix()
             ),
-            Op::Csrrc => write!(f, "cssrc (unimplemented)"),
-            Op::Csrrci => write!(f, "cssrci (unimplemented)"),
-            Op::Csrrs => write!(f, "cssrs (unimplemented)"),
-            Op::Csrrsi => write!(f, "cssrsi (unimplemented)"),
-            Op::Csrrw => write!(f, "cssrw (unimplemented)"),
-            Op::Csrrwi => write!(f, "cssrwi (unimplemented"),
-            Op::Ebreak => write!(f, "ebreak"),
-            Op::Ecall => write!(f, "ecall"),
-            Op::Fence => write!(f, "fence"),
-            Op::FenceI => write!(f, "fencei"),
-            Op::Jal => write!(f, "jal 0x{:x}", self.immediate()),
-            _ => unimplemented!(),
+            Op::FclassD
+            | Op::FclassH
+            | Op::FclassQ
+            | Op::FclassS
+            | Op::FmvDX
+            | Op::FmvHX
+            | Op::FmvWX
+            | Op::FmvXD
+            | Op::FmvXH
+            | Op::FmvXW
+            | Op::FmvhXD
+            | Op::FmvhXQ
+            | Op::Clz
+            | Op::Clzw
+            | Op::Cpop
+            | Op::Cpopw
+            | Op::Ctz
+            | Op::Ctzw
+            | Op::OrcB
+            | Op::Rev8
+            | Op::SextB
+            | Op::SextH
+            | Op::ZextH
+            | Op::Aes64im
+            | Op::Brev8
+            | Op::Sha256sig0
+            | Op::Sha256sig1
+            | Op::Sha256sum0
+            | Op::Sha256sum1
+            | Op::Sha512sig0
+            | Op::Sha512sig1
+            | Op::Sha512sum0
+            | Op::Sha512sum1
+            | Op::Sm3p0
+            | Op::Sm3p1
+            | Op::Unzip
+            | Op::Zip => write!(f, "{} {}, {}", mnemonic, self.rd(), self.rs1()),
+            // The byte select and round number are written in decimal
+            Op::Aes32dsi | Op::Aes32dsmi | Op::Aes32esi | Op::Aes32esmi | Op::Sm4ed | Op::Sm4ks => {
+                write!(
+                    f,
+                    "{} {}, {}, {}, {}",
+                    mnemonic,
+                    self.rd(),
+                    self.rs1(),
+                    self.rs2(),
+                    immediate
+                )
+            }
+            Op::Aes64ks1i => write!(
+                f,
+                "{} {}, {}, {}",
+                mnemonic,
+                self.rd(),
+                self.rs1(),
+                immediate
+            ),
+            Op::FliD | Op::FliH | Op::FliQ | Op::FliS => {
+                let constant = FLI_CONSTANTS.get(immediate as usize).unwrap_or(&"?");
+                write!(f, "{} {}, {}", mnemonic, self.rd(), constant)
+            }
+            Op::CAddi4spn => write!(f, "c.addi4spn {}, sp, {}", self.rd(), immediate),
+            Op::CAddi | Op::CAddi16sp | Op::CAddiw | Op::CAndi | Op::CLi => {
+                write!(f, "{} {}, {}", mnemonic, self.rd(), immediate)
+            }
+            Op::CNop => match immediate {
+                0 => write!(f, "c.nop"),
+                _ => write!(f, "c.nop {}", immediate),
+            },
+            Op::CLui => write!(f, "c.lui {}, 0x{:x}", self.rd(), self.immediate()),
+            Op::CSlli | Op::CSrai | Op::CSrli => {
+                write!(f, "{} {}, 0x{:x}", mnemonic, self.rd(), self.shamt())
+            }
+            Op::CAdd
+            | Op::CAddw
+            | Op::CAnd
+            | Op::CMv
+            | Op::COr
+            | Op::CSub
+            | Op::CSubw
+            | Op::CXor => write!(f, "{} {}, {}", mnemonic, self.rd(), self.rs2()),
+            Op::CJ | Op::CJal => write!(f, "{} {}", mnemonic, immediate),
+            Op::CJalr | Op::CJr => write!(f, "{} {}", mnemonic, self.rs1()),
+            Op::CBeqz | Op::CBnez => write!(f, "{} {}, {}", mnemonic, self.rs1(), immediate),
+        }
+    }
+}
+
+impl fmt::Display for Fence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.predecessor(), self.successor())
+    }
+}
+
+/// The set of fence predecessor or successor operations, written as in
+/// `fence rw,w`
+impl fmt::Display for FenceSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !(self.i || self.o || self.r || self.w) {
+            return write!(f, "0");
+        }
+        for (set, c) in &[(self.i, 'i'), (self.o, 'o'), (self.r, 'r'), (self.w, 'w')] {
+            if *set {
+                write!(f, "{}", c)?;
+            }
         }
+        Ok(())
     }
 }
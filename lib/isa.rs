@@ -0,0 +1,386 @@
+use error::ParseIsaError;
+use std::fmt;
+use std::str::FromStr;
+
+/// The base integer register width the decoder targets
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Xlen {
+    Rv32,
+    Rv64,
+}
+
+/// An ISA extension which can be enabled in an `Isa`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Extension {
+    /// Base integer instruction set
+    I,
+    /// Reduced base integer instruction set, with 16 integer registers
+    E,
+    /// Integer multiplication and division
+    M,
+    /// Atomic instructions
+    A,
+    /// Single-precision floating point
+    F,
+    /// Double-precision floating point
+    D,
+    /// Quad-precision floating point
+    Q,
+    /// Compressed instructions
+    C,
+    /// Vector operations
+    V,
+    /// Hypervisor
+    H,
+    /// Cache-block management
+    Zicbom,
+    /// Cache-block prefetch hints
+    Zicbop,
+    /// Cache-block zeroing
+    Zicboz,
+    /// Control and status register instructions
+    Zicsr,
+    /// Instruction-fetch fence
+    Zifencei,
+    /// Non-temporal locality hints
+    Zihintntl,
+    /// Pause hint
+    Zihintpause,
+    /// Additional floating point instructions
+    Zfa,
+    /// Half-precision floating point
+    Zfh,
+    /// Minimal half-precision floating point: loads, stores, moves and
+    /// conversions
+    Zfhmin,
+    /// Address generation bit manipulation
+    Zba,
+    /// Basic bit manipulation
+    Zbb,
+    /// Carry-less multiplication
+    Zbc,
+    /// Single-bit instructions
+    Zbs,
+    /// Bit manipulation for cryptography
+    Zbkb,
+    /// Carry-less multiplication for cryptography
+    Zbkc,
+    /// Crossbar permutations
+    Zbkx,
+    /// NIST suite: AES decryption
+    Zknd,
+    /// NIST suite: AES encryption
+    Zkne,
+    /// NIST suite: SHA-2 hash functions
+    Zknh,
+    /// ShangMi suite: SM4 block cipher
+    Zksed,
+    /// ShangMi suite: SM3 hash function
+    Zksh,
+    /// Fine-grained address-translation cache invalidation
+    Svinval,
+}
+
+const EXTENSIONS: &[Extension] = &[
+    Extension::I,
+    Extension::E,
+    Extension::M,
+    Extension::A,
+    Extension::F,
+    Extension::D,
+    Extension::Q,
+    Extension::C,
+    Extension::V,
+    Extension::H,
+    Extension::Zicbom,
+    Extension::Zicbop,
+    Extension::Zicboz,
+    Extension::Zicsr,
+    Extension::Zifencei,
+    Extension::Zihintntl,
+    Extension::Zihintpause,
+    Extension::Zfa,
+    Extension::Zfh,
+    Extension::Zfhmin,
+    Extension::Zba,
+    Extension::Zbb,
+    Extension::Zbc,
+    Extension::Zbs,
+    Extension::Zbkb,
+    Extension::Zbkc,
+    Extension::Zbkx,
+    Extension::Zknd,
+    Extension::Zkne,
+    Extension::Zknh,
+    Extension::Zksed,
+    Extension::Zksh,
+    Extension::Svinval,
+];
+
+impl Extension {
+    /// The name of this extension as it appears in an ISA string
+    pub fn name(&self) -> &'static str {
+        match self {
+            Extension::I => "i",
+            Extension::E => "e",
+            Extension::M => "m",
+            Extension::A => "a",
+            Extension::F => "f",
+            Extension::D => "d",
+            Extension::Q => "q",
+            Extension::C => "c",
+            Extension::V => "v",
+            Extension::H => "h",
+            Extension::Zicbom => "zicbom",
+            Extension::Zicbop => "zicbop",
+            Extension::Zicboz => "zicboz",
+            Extension::Zicsr => "zicsr",
+            Extension::Zifencei => "zifencei",
+            Extension::Zihintntl => "zihintntl",
+            Extension::Zihintpause => "zihintpause",
+            Extension::Zfa => "zfa",
+            Extension::Zfh => "zfh",
+            Extension::Zfhmin => "zfhmin",
+            Extension::Zba => "zba",
+            Extension::Zbb => "zbb",
+            Extension::Zbc => "zbc",
+            Extension::Zbs => "zbs",
+            Extension::Zbkb => "zbkb",
+            Extension::Zbkc => "zbkc",
+            Extension::Zbkx => "zbkx",
+            Extension::Zknd => "zknd",
+            Extension::Zkne => "zkne",
+            Extension::Zknh => "zknh",
+            Extension::Zksed => "zksed",
+            Extension::Zksh => "zksh",
+            Extension::Svinval => "svinval",
+        }
+    }
+
+    /// Look up an extension by the name it has in an ISA string
+    pub fn from_name(name: &str) -> Option<Extension> {
+        EXTENSIONS
+            .iter()
+            .find(|extension| extension.name() == name)
+            .cloned()
+    }
+
+    /// The extensions this extension depends on, which are enabled along
+    /// with it
+    fn implies(self) -> &'static [Extension] {
+        match self {
+            Extension::F => &[Extension::Zicsr],
+            Extension::D | Extension::Zfhmin | Extension::Zfa => &[Extension::F],
+            Extension::Q | Extension::V => &[Extension::D],
+            Extension::Zfh => &[Extension::Zfhmin],
+            _ => &[],
+        }
+    }
+
+    fn bit(self) -> u64 {
+        1 << (self as u64)
+    }
+}
+
+/// The instruction set a decoder accepts: a base integer width and a set of
+/// enabled extensions.
+///
+/// An `Isa` can be built up from `Isa::new`, or parsed from a canonical ISA
+/// string such as `rv64imafdc_zicsr_zifencei_zba`. Enabling an extension
+/// enables the extensions it depends on too, so `rv64imafd` has Zicsr.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Isa {
+    xlen: Xlen,
+    extensions: u64,
+}
+
+impl Isa {
+    /// The base integer instruction set for the given XLEN, with no other
+    /// extensions
+    pub fn new(xlen: Xlen) -> Isa {
+        Isa {
+            xlen,
+            extensions: Extension::I.bit(),
+        }
+    }
+
+    /// RV32I with the M extension, Zicsr and Zifencei, which is what
+    /// `decode` accepts
+    pub fn rv32im() -> Isa {
+        Isa::new(Xlen::Rv32)
+            .with(Extension::M)
+            .with(Extension::Zicsr)
+            .with(Extension::Zifencei)
+    }
+
+    /// Every extension other than E, for the given XLEN
+    pub fn all(xlen: Xlen) -> Isa {
+        EXTENSIONS
+            .iter()
+            .filter(|extension| **extension != Extension::E)
+            .fold(Isa::new(xlen), |isa, extension| isa.with(*extension))
+    }
+
+    /// Returns this `Isa` with `extension` enabled, along with the
+    /// extensions it depends on, such as F for D
+    pub fn with(mut self, extension: Extension) -> Isa {
+        self.extensions |= extension.bit();
+        extension
+            .implies()
+            .iter()
+            .fold(self, |isa, implied| isa.with(*implied))
+    }
+
+    /// Returns this `Isa` with `extension` disabled, along with the
+    /// extensions which depend on it, such as D for F
+    pub fn without(mut self, extension: Extension) -> Isa {
+        self.extensions &= !extension.bit();
+        EXTENSIONS
+            .iter()
+            .filter(|dependent| dependent.implies().contains(&extension))
+            .fold(self, |isa, dependent| isa.without(*dependent))
+    }
+
+    pub fn xlen(&self) -> Xlen {
+        self.xlen
+    }
+
+    /// Returns true if `extension` is enabled
+    pub fn has(&self, extension: Extension) -> bool {
+        self.extensions & extension.bit() != 0
+    }
+
+    /// The enabled extensions, in canonical order
+    pub fn extensions(&self) -> Vec<Extension> {
+        EXTENSIONS
+            .iter()
+            .filter(|extension| self.has(**extension))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Strips a trailing version number, such as `2` or `2p0`, from the name of
+/// a multi-letter extension, or returns `None` if the version has a `p` with
+/// no minor version after it
+fn strip_version(name: &str) -> Option<&str> {
+    let trimmed = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    if trimmed.len() == name.len() {
+        // A name ending in `p` is only a version if a major version comes
+        // before it, as it does not in `zicbop`
+        return match name.strip_suffix('p') {
+            Some(major) if major.ends_with(|c: char| c.is_ascii_digit()) => None,
+            _ => Some(name),
+        };
+    }
+    match trimmed.strip_suffix('p') {
+        Some(major) if major.ends_with(|c: char| c.is_ascii_digit()) => {
+            Some(major.trim_end_matches(|c: char| c.is_ascii_digit()))
+        }
+        _ => Some(trimmed),
+    }
+}
+
+/// Skips the version number, such as `2` or `2p0`, at the start of what
+/// follows a single-letter extension, or returns `None` if it has a `p` with
+/// no minor version after it
+fn skip_version(s: &str) -> Option<&str> {
+    let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let major = digits(s);
+    if major == 0 {
+        return Some(s);
+    }
+    match s[major..].strip_prefix('p') {
+        Some(rest) if digits(rest) == 0 => None,
+        Some(rest) => Some(&rest[digits(rest)..]),
+        None => Some(&s[major..]),
+    }
+}
+
+impl FromStr for Isa {
+    type Err = ParseIsaError;
+
+    /// Parses an ISA string, such as `rv32imc`, `rv64gc` or
+    /// `rv64imafdc_zicsr_zba`. `g` stands for `imafd_zicsr_zifencei`, and
+    /// version numbers such as `2p0` are accepted after the name of an
+    /// extension and ignored.
+    fn from_str(s: &str) -> Result<Isa, ParseIsaError> {
+        let s = s.to_lowercase();
+        let invalid_version = || ParseIsaError::InvalidVersion(s.clone());
+
+        let mut isa = if s.starts_with("rv32") {
+            Isa::new(Xlen::Rv32)
+        } else if s.starts_with("rv64") {
+            Isa::new(Xlen::Rv64)
+        } else {
+            return Err(ParseIsaError::InvalidBase(s));
+        };
+        let rest = &s[4..];
+
+        match rest.chars().next() {
+            Some('i') => {}
+            Some('e') => isa = isa.without(Extension::I).with(Extension::E),
+            Some('g') => {
+                for extension in &[
+                    Extension::M,
+                    Extension::A,
+                    Extension::F,
+                    Extension::D,
+                    Extension::Zicsr,
+                    Extension::Zifencei,
+                ] {
+                    isa = isa.with(*extension);
+                }
+            }
+            _ => return Err(ParseIsaError::InvalidBase(rest.to_string())),
+        }
+        let mut rest = skip_version(&rest[1..]).ok_or_else(invalid_version)?;
+
+        while let Some(c) = rest.chars().next() {
+            if c == '_' {
+                rest = &rest[1..];
+                continue;
+            }
+            if c.is_ascii_digit() {
+                return Err(invalid_version());
+            }
+            let name = if c == 'z' || c == 's' || c == 'x' {
+                // Multi-letter extensions run to the next underscore
+                let end = rest.find('_').unwrap_or(rest.len());
+                let name = strip_version(&rest[..end]).ok_or_else(invalid_version)?;
+                rest = &rest[end..];
+                name
+            } else {
+                let name = &rest[..c.len_utf8()];
+                rest = skip_version(&rest[c.len_utf8()..]).ok_or_else(invalid_version)?;
+                name
+            };
+            match Extension::from_name(name) {
+                Some(extension) => isa = isa.with(extension),
+                None => return Err(ParseIsaError::UnknownExtension(name.to_string())),
+            }
+        }
+
+        Ok(isa)
+    }
+}
+
+impl fmt::Display for Isa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.xlen {
+            Xlen::Rv32 => write!(f, "rv32")?,
+            Xlen::Rv64 => write!(f, "rv64")?,
+        }
+        for extension in self.extensions() {
+            if extension.name().len() == 1 {
+                write!(f, "{}", extension.name())?;
+            }
+        }
+        for extension in self.extensions() {
+            if extension.name().len() > 1 {
+                write!(f, "_{}", extension.name())?;
+            }
+        }
+        Ok(())
+    }
+}
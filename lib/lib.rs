@@ -1,10 +1,37 @@
+mod assembler;
+/// The table decoder `decode_isa` replaced, kept to benchmark and test the
+/// decoder against
+#[cfg(any(test, feature = "baseline"))]
+#[doc(hidden)]
+pub mod baseline;
+mod compressed;
+mod csr;
 mod decoder;
+mod encoder;
+mod error;
+mod flow;
 mod instruction;
+mod isa;
+mod memory;
+mod operand;
+mod pseudo;
 mod register;
+mod vector;
 
-pub use decoder::decode;
-pub use instruction::{Fence, Instruction, Op};
+pub use assembler::{assemble, assemble_bytes, assemble_bytes_isa, assemble_isa};
+pub use compressed::decode_compressed;
+pub use csr::{Csr, Privilege};
+pub use decoder::{decode, decode_bytes, decode_isa, decode_with_length, instruction_length};
+pub use encoder::encode;
+pub use error::{AssembleError, DecodeError, EncodeError, ParseIsaError};
+pub use flow::Flow;
+pub use instruction::{Aqrl, Fence, FenceSet, FloatFormat, Instruction, Op, RoundingMode};
+pub use isa::{Extension, Isa, Xlen};
+pub use memory::{AccessKind, Extend, MemoryAccess};
+pub use operand::{Location, Operand};
+pub use pseudo::Pseudo;
 pub use register::Register;
+pub use vector::{Lmul, VectorForm, VectorOp, Vtype};
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,83 @@
+use Register;
+
+/// The kind of memory access an instruction makes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccessKind {
+    /// A load, such as `lw` or `fld`
+    Load,
+    /// A store, such as `sw` or `fsd`
+    Store,
+    /// A load which acquires a reservation, `lr`
+    LoadReserved,
+    /// A store which only happens if a reservation is held, `sc`
+    StoreConditional,
+    /// An atomic read-modify-write, such as `amoadd`
+    ReadModifyWrite,
+}
+
+/// How a value loaded from memory is extended to fill its register
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Extend {
+    /// Sign-extended to XLEN, such as by `lb` or `lw`
+    Sign,
+    /// Zero-extended to XLEN, such as by `lbu` or `lwu`
+    Zero,
+    /// Not extended, because the access is a store, fills the register, or
+    /// loads a floating point register
+    None,
+}
+
+/// A description of the memory an instruction accesses, as returned by
+/// `Instruction::memory_access`. The address accessed is the value of the
+/// base register plus the displacement.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemoryAccess {
+    pub(crate) kind: AccessKind,
+    pub(crate) width: usize,
+    pub(crate) extend: Extend,
+    pub(crate) base: Register,
+    pub(crate) displacement: i32,
+}
+
+impl MemoryAccess {
+    pub fn kind(&self) -> AccessKind {
+        self.kind
+    }
+
+    /// The width of the access in bytes, or 0 when it is not known from the
+    /// instruction alone: `cbo.zero` writes a whole cache block of a size
+    /// the implementation chooses, and the elements of an indexed vector
+    /// access are as wide as the SEW in `vtype`
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn extend(&self) -> Extend {
+        self.extend
+    }
+
+    pub fn base(&self) -> &Register {
+        &self.base
+    }
+
+    pub fn displacement(&self) -> i32 {
+        self.displacement
+    }
+
+    /// Returns true if the access reads memory
+    pub fn reads(&self) -> bool {
+        match self.kind {
+            AccessKind::Load | AccessKind::LoadReserved | AccessKind::ReadModifyWrite => true,
+            AccessKind::Store | AccessKind::StoreConditional => false,
+        }
+    }
+
+    /// Returns true if the access may write memory. A store-conditional
+    /// only writes if it succeeds.
+    pub fn writes(&self) -> bool {
+        match self.kind {
+            AccessKind::Store | AccessKind::StoreConditional | AccessKind::ReadModifyWrite => true,
+            AccessKind::Load | AccessKind::LoadReserved => false,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use {Csr, FenceSet, Register, RoundingMode, Vtype};
+
+/// An operand of an instruction, as returned by `Instruction::operands`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Operand {
+    /// An integer, floating point or vector register
+    Register(Register),
+    /// A sign-extended immediate, such as the operand of `addi`
+    SignedImmediate(i32),
+    /// A zero-extended immediate, such as a shift amount, the 5-bit
+    /// immediate of `csrrwi`, or the upper 20 bits loaded by `lui`
+    UnsignedImmediate(u32),
+    /// A branch or jump target, as an offset from the address of the
+    /// instruction
+    PcRelative(i32),
+    /// A memory location addressed as a base register plus an offset
+    Memory { base: Register, offset: i32 },
+    /// A control and status register
+    Csr(Csr),
+    /// The constant `fli` loads, as its index in the table of constants,
+    /// which is what the rs1 field holds. The printed instruction writes
+    /// the constant itself.
+    FloatImmediate(u32),
+    /// A static or dynamic floating point rounding mode
+    RoundingMode(RoundingMode),
+    /// The predecessor or successor set of a fence
+    FenceSet(FenceSet),
+    /// The vector type set by `vsetvli` and `vsetivli`
+    Vtype(Vtype),
+    /// The `v0.t` of a vector instruction masked by v0
+    Mask,
+}
+
+/// A register or CSR which an instruction reads or writes, as returned by
+/// `Instruction::registers_read` and `Instruction::registers_written`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Location {
+    /// An integer, floating point or vector register
+    Register(Register),
+    /// A control and status register
+    Csr(Csr),
+}
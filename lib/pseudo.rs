@@ -0,0 +1,178 @@
+use Op;
+
+/// A pseudo-instruction, the alias under which assemblers and disassemblers
+/// write a particular form of an instruction, as returned by
+/// `Instruction::pseudo`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Pseudo {
+    /// `addi zero, zero, 0`
+    Nop,
+    /// `addi rd, zero, imm`
+    Li,
+    /// `addi rd, rs, 0`
+    Mv,
+    /// `xori rd, rs, -1`
+    Not,
+    /// `sub rd, zero, rs`
+    Neg,
+    /// `subw rd, zero, rs`
+    Negw,
+    /// `addiw rd, rs, 0`
+    SextW,
+    /// `add.uw rd, rs, zero`
+    ZextW,
+    /// `sltiu rd, rs, 1`
+    Seqz,
+    /// `sltu rd, zero, rs`
+    Snez,
+    /// `slt rd, rs, zero`
+    Sltz,
+    /// `slt rd, zero, rs`
+    Sgtz,
+    /// `fsgnj.s rd, rs, rs`
+    FmvS,
+    /// `fsgnjx.s rd, rs, rs`
+    FabsS,
+    /// `fsgnjn.s rd, rs, rs`
+    FnegS,
+    /// `fsgnj.d rd, rs, rs`
+    FmvD,
+    /// `fsgnjx.d rd, rs, rs`
+    FabsD,
+    /// `fsgnjn.d rd, rs, rs`
+    FnegD,
+    /// `fsgnj.h rd, rs, rs`
+    FmvH,
+    /// `fsgnjx.h rd, rs, rs`
+    FabsH,
+    /// `fsgnjn.h rd, rs, rs`
+    FnegH,
+    /// `fsgnj.q rd, rs, rs`
+    FmvQ,
+    /// `fsgnjx.q rd, rs, rs`
+    FabsQ,
+    /// `fsgnjn.q rd, rs, rs`
+    FnegQ,
+    /// `beq rs, zero, offset`
+    Beqz,
+    /// `bne rs, zero, offset`
+    Bnez,
+    /// `bge zero, rs, offset`
+    Blez,
+    /// `bge rs, zero, offset`
+    Bgez,
+    /// `blt rs, zero, offset`
+    Bltz,
+    /// `blt zero, rs, offset`
+    Bgtz,
+    /// `jal zero, offset`
+    J,
+    /// `jal ra, offset`
+    Jal,
+    /// `jalr zero, offset(rs)`
+    Jr,
+    /// `jalr ra, offset(rs)`
+    Jalr,
+    /// `jalr zero, 0(ra)`
+    Ret,
+    /// `auipc ra, hi` followed by `jalr ra, lo(ra)`
+    Call,
+    /// `auipc t1, hi` followed by `jalr zero, lo(t1)`
+    Tail,
+    /// `csrrs rd, csr, zero`
+    Csrr,
+    /// `csrrw zero, csr, rs`
+    Csrw,
+    /// `csrrs zero, csr, rs`
+    Csrs,
+    /// `csrrc zero, csr, rs`
+    Csrc,
+    /// `csrrwi zero, csr, imm`
+    Csrwi,
+    /// `csrrsi zero, csr, imm`
+    Csrsi,
+    /// `csrrci zero, csr, imm`
+    Csrci,
+    /// `csrrs rd, fcsr, zero`
+    Frcsr,
+    /// `csrrw rd, fcsr, rs`
+    Fscsr,
+    /// `csrrs rd, frm, zero`
+    Frrm,
+    /// `csrrw rd, frm, rs`
+    Fsrm,
+    /// `csrrwi rd, frm, imm`
+    Fsrmi,
+    /// `csrrs rd, fflags, zero`
+    Frflags,
+    /// `csrrw rd, fflags, rs`
+    Fsflags,
+    /// `csrrwi rd, fflags, imm`
+    Fsflagsi,
+    /// A read of one of the unprivileged counters, `csrrs rd, cycle, zero`
+    /// and so on, held as `Op::RdCycle`, `Op::RdTime`, `Op::RdInstRet` or
+    /// one of their RV32 high-half forms
+    Counter(Op),
+}
+
+impl Pseudo {
+    /// The assembler mnemonic of this pseudo-instruction, such as `nop` or
+    /// `fmv.s`
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Pseudo::Nop => "nop",
+            Pseudo::Li => "li",
+            Pseudo::Mv => "mv",
+            Pseudo::Not => "not",
+            Pseudo::Neg => "neg",
+            Pseudo::Negw => "negw",
+            Pseudo::SextW => "sext.w",
+            Pseudo::ZextW => "zext.w",
+            Pseudo::Seqz => "seqz",
+            Pseudo::Snez => "snez",
+            Pseudo::Sltz => "sltz",
+            Pseudo::Sgtz => "sgtz",
+            Pseudo::FmvS => "fmv.s",
+            Pseudo::FabsS => "fabs.s",
+            Pseudo::FnegS => "fneg.s",
+            Pseudo::FmvD => "fmv.d",
+            Pseudo::FabsD => "fabs.d",
+            Pseudo::FnegD => "fneg.d",
+            Pseudo::FmvH => "fmv.h",
+            Pseudo::FabsH => "fabs.h",
+            Pseudo::FnegH => "fneg.h",
+            Pseudo::FmvQ => "fmv.q",
+            Pseudo::FabsQ => "fabs.q",
+            Pseudo::FnegQ => "fneg.q",
+            Pseudo::Beqz => "beqz",
+            Pseudo::Bnez => "bnez",
+            Pseudo::Blez => "blez",
+            Pseudo::Bgez => "bgez",
+            Pseudo::Bltz => "bltz",
+            Pseudo::Bgtz => "bgtz",
+            Pseudo::J => "j",
+            Pseudo::Jal => "jal",
+            Pseudo::Jr => "jr",
+            Pseudo::Jalr => "jalr",
+            Pseudo::Ret => "ret",
+            Pseudo::Call => "call",
+            Pseudo::Tail => "tail",
+            Pseudo::Csrr => "csrr",
+            Pseudo::Csrw => "csrw",
+            Pseudo::Csrs => "csrs",
+            Pseudo::Csrc => "csrc",
+            Pseudo::Csrwi => "csrwi",
+            Pseudo::Csrsi => "csrsi",
+            Pseudo::Csrci => "csrci",
+            Pseudo::Frcsr => "frcsr",
+            Pseudo::Fscsr => "fscsr",
+            Pseudo::Frrm => "frrm",
+            Pseudo::Fsrm => "fsrm",
+            Pseudo::Fsrmi => "fsrmi",
+            Pseudo::Frflags => "frflags",
+            Pseudo::Fsflags => "fsflags",
+            Pseudo::Fsflagsi => "fsflagsi",
+            Pseudo::Counter(op) => op.mnemonic(),
+        }
+    }
+}
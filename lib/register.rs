@@ -34,6 +34,70 @@ pub enum Register {
     T4,   // x29
     T5,   // x30
     T6,   // x31
+    Ft0,  // f0
+    Ft1,  // f1
+    Ft2,  // f2
+    Ft3,  // f3
+    Ft4,  // f4
+    Ft5,  // f5
+    Ft6,  // f6
+    Ft7,  // f7
+    Fs0,  // f8
+    Fs1,  // f9
+    Fa0,  // f10
+    Fa1,  // f11
+    Fa2,  // f12
+    Fa3,  // f13
+    Fa4,  // f14
+    Fa5,  // f15
+    Fa6,  // f16
+    Fa7,  // f17
+    Fs2,  // f18
+    Fs3,  // f19
+    Fs4,  // f20
+    Fs5,  // f21
+    Fs6,  // f22
+    Fs7,  // f23
+    Fs8,  // f24
+    Fs9,  // f25
+    Fs10, // f26
+    Fs11, // f27
+    Ft8,  // f28
+    Ft9,  // f29
+    Ft10, // f30
+    Ft11, // f31
+    V0,   // v0
+    V1,   // v1
+    V2,   // v2
+    V3,   // v3
+    V4,   // v4
+    V5,   // v5
+    V6,   // v6
+    V7,   // v7
+    V8,   // v8
+    V9,   // v9
+    V10,  // v10
+    V11,  // v11
+    V12,  // v12
+    V13,  // v13
+    V14,  // v14
+    V15,  // v15
+    V16,  // v16
+    V17,  // v17
+    V18,  // v18
+    V19,  // v19
+    V20,  // v20
+    V21,  // v21
+    V22,  // v22
+    V23,  // v23
+    V24,  // v24
+    V25,  // v25
+    V26,  // v26
+    V27,  // v27
+    V28,  // v28
+    V29,  // v29
+    V30,  // v30
+    V31,  // v31
     Invalid,
 }
 
@@ -75,44 +139,398 @@ impl Register {
             _ => Register::Invalid,
         }
     }
+
+    pub(crate) fn float_from_u32(u: u32) -> Register {
+        match u {
+            0 => Register::Ft0,
+            1 => Register::Ft1,
+            2 => Register::Ft2,
+            3 => Register::Ft3,
+            4 => Register::Ft4,
+            5 => Register::Ft5,
+            6 => Register::Ft6,
+            7 => Register::Ft7,
+            8 => Register::Fs0,
+            9 => Register::Fs1,
+            10 => Register::Fa0,
+            11 => Register::Fa1,
+            12 => Register::Fa2,
+            13 => Register::Fa3,
+            14 => Register::Fa4,
+            15 => Register::Fa5,
+            16 => Register::Fa6,
+            17 => Register::Fa7,
+            18 => Register::Fs2,
+            19 => Register::Fs3,
+            20 => Register::Fs4,
+            21 => Register::Fs5,
+            22 => Register::Fs6,
+            23 => Register::Fs7,
+            24 => Register::Fs8,
+            25 => Register::Fs9,
+            26 => Register::Fs10,
+            27 => Register::Fs11,
+            28 => Register::Ft8,
+            29 => Register::Ft9,
+            30 => Register::Ft10,
+            31 => Register::Ft11,
+            _ => Register::Invalid,
+        }
+    }
+
+    pub(crate) fn vector_from_u32(u: u32) -> Register {
+        match u {
+            0 => Register::V0,
+            1 => Register::V1,
+            2 => Register::V2,
+            3 => Register::V3,
+            4 => Register::V4,
+            5 => Register::V5,
+            6 => Register::V6,
+            7 => Register::V7,
+            8 => Register::V8,
+            9 => Register::V9,
+            10 => Register::V10,
+            11 => Register::V11,
+            12 => Register::V12,
+            13 => Register::V13,
+            14 => Register::V14,
+            15 => Register::V15,
+            16 => Register::V16,
+            17 => Register::V17,
+            18 => Register::V18,
+            19 => Register::V19,
+            20 => Register::V20,
+            21 => Register::V21,
+            22 => Register::V22,
+            23 => Register::V23,
+            24 => Register::V24,
+            25 => Register::V25,
+            26 => Register::V26,
+            27 => Register::V27,
+            28 => Register::V28,
+            29 => Register::V29,
+            30 => Register::V30,
+            31 => Register::V31,
+            _ => Register::Invalid,
+        }
+    }
+
+    /// The ABI name of this register, such as `a0` or `ft1`
+    fn name(&self) -> &'static str {
+        match self {
+            Register::Zero => "zero",
+            Register::Ra => "ra",
+            Register::Sp => "sp",
+            Register::Gp => "gp",
+            Register::Tp => "tp",
+            Register::Fp => "s0",
+            Register::S1 => "s1",
+            Register::S2 => "s2",
+            Register::S3 => "s3",
+            Register::S4 => "s4",
+            Register::S5 => "s5",
+            Register::S6 => "s6",
+            Register::S7 => "s7",
+            Register::S8 => "s8",
+            Register::S9 => "s9",
+            Register::S10 => "s10",
+            Register::S11 => "s11",
+            Register::A0 => "a0",
+            Register::A1 => "a1",
+            Register::A2 => "a2",
+            Register::A3 => "a3",
+            Register::A4 => "a4",
+            Register::A5 => "a5",
+            Register::A6 => "a6",
+            Register::A7 => "a7",
+            Register::T0 => "t0",
+            Register::T1 => "t1",
+            Register::T2 => "t2",
+            Register::T3 => "t3",
+            Register::T4 => "t4",
+            Register::T5 => "t5",
+            Register::T6 => "t6",
+            Register::Ft0 => "ft0",
+            Register::Ft1 => "ft1",
+            Register::Ft2 => "ft2",
+            Register::Ft3 => "ft3",
+            Register::Ft4 => "ft4",
+            Register::Ft5 => "ft5",
+            Register::Ft6 => "ft6",
+            Register::Ft7 => "ft7",
+            Register::Fs0 => "fs0",
+            Register::Fs1 => "fs1",
+            Register::Fa0 => "fa0",
+            Register::Fa1 => "fa1",
+            Register::Fa2 => "fa2",
+            Register::Fa3 => "fa3",
+            Register::Fa4 => "fa4",
+            Register::Fa5 => "fa5",
+            Register::Fa6 => "fa6",
+            Register::Fa7 => "fa7",
+            Register::Fs2 => "fs2",
+            Register::Fs3 => "fs3",
+            Register::Fs4 => "fs4",
+            Register::Fs5 => "fs5",
+            Register::Fs6 => "fs6",
+            Register::Fs7 => "fs7",
+            Register::Fs8 => "fs8",
+            Register::Fs9 => "fs9",
+            Register::Fs10 => "fs10",
+            Register::Fs11 => "fs11",
+            Register::Ft8 => "ft8",
+            Register::Ft9 => "ft9",
+            Register::Ft10 => "ft10",
+            Register::Ft11 => "ft11",
+            Register::V0 => "v0",
+            Register::V1 => "v1",
+            Register::V2 => "v2",
+            Register::V3 => "v3",
+            Register::V4 => "v4",
+            Register::V5 => "v5",
+            Register::V6 => "v6",
+            Register::V7 => "v7",
+            Register::V8 => "v8",
+            Register::V9 => "v9",
+            Register::V10 => "v10",
+            Register::V11 => "v11",
+            Register::V12 => "v12",
+            Register::V13 => "v13",
+            Register::V14 => "v14",
+            Register::V15 => "v15",
+            Register::V16 => "v16",
+            Register::V17 => "v17",
+            Register::V18 => "v18",
+            Register::V19 => "v19",
+            Register::V20 => "v20",
+            Register::V21 => "v21",
+            Register::V22 => "v22",
+            Register::V23 => "v23",
+            Register::V24 => "v24",
+            Register::V25 => "v25",
+            Register::V26 => "v26",
+            Register::V27 => "v27",
+            Register::V28 => "v28",
+            Register::V29 => "v29",
+            Register::V30 => "v30",
+            Register::V31 => "v31",
+            Register::Invalid => "invalid",
+        }
+    }
+
+    /// Looks up a register by its ABI name, such as `a0`, `fp` or `ft1`, or
+    /// its architectural name, such as `x10`, `f1` or `v2`
+    pub(crate) fn from_name(name: &str) -> Option<Register> {
+        if name == "fp" {
+            return Some(Register::Fp);
+        }
+        let numbered = |prefix: &str| {
+            name.strip_prefix(prefix)
+                .filter(|n| n.bytes().all(|b| b.is_ascii_digit()))
+                .filter(|n| n.len() == 1 || !n.starts_with('0'))
+                .and_then(|n| n.parse::<u32>().ok())
+                .filter(|n| *n < 32)
+        };
+        if let Some(n) = numbered("x") {
+            return Some(Register::from_u32(n));
+        }
+        if let Some(n) = numbered("f") {
+            return Some(Register::float_from_u32(n));
+        }
+        if let Some(n) = numbered("v") {
+            return Some(Register::vector_from_u32(n));
+        }
+        (0..32)
+            .flat_map(|n| [Register::from_u32(n), Register::float_from_u32(n)])
+            .find(|register| register.name() == name)
+    }
+
+    /// The number of this register within its register file, such as 10 for
+    /// a0 (x10), fa0 (f10) and v10
+    pub(crate) fn index(&self) -> Option<u32> {
+        match self {
+            Register::Zero => Some(0),
+            Register::Ra => Some(1),
+            Register::Sp => Some(2),
+            Register::Gp => Some(3),
+            Register::Tp => Some(4),
+            Register::T0 => Some(5),
+            Register::T1 => Some(6),
+            Register::T2 => Some(7),
+            Register::Fp => Some(8),
+            Register::S1 => Some(9),
+            Register::A0 => Some(10),
+            Register::A1 => Some(11),
+            Register::A2 => Some(12),
+            Register::A3 => Some(13),
+            Register::A4 => Some(14),
+            Register::A5 => Some(15),
+            Register::A6 => Some(16),
+            Register::A7 => Some(17),
+            Register::S2 => Some(18),
+            Register::S3 => Some(19),
+            Register::S4 => Some(20),
+            Register::S5 => Some(21),
+            Register::S6 => Some(22),
+            Register::S7 => Some(23),
+            Register::S8 => Some(24),
+            Register::S9 => Some(25),
+            Register::S10 => Some(26),
+            Register::S11 => Some(27),
+            Register::T3 => Some(28),
+            Register::T4 => Some(29),
+            Register::T5 => Some(30),
+            Register::T6 => Some(31),
+            Register::Ft0 => Some(0),
+            Register::Ft1 => Some(1),
+            Register::Ft2 => Some(2),
+            Register::Ft3 => Some(3),
+            Register::Ft4 => Some(4),
+            Register::Ft5 => Some(5),
+            Register::Ft6 => Some(6),
+            Register::Ft7 => Some(7),
+            Register::Fs0 => Some(8),
+            Register::Fs1 => Some(9),
+            Register::Fa0 => Some(10),
+            Register::Fa1 => Some(11),
+            Register::Fa2 => Some(12),
+            Register::Fa3 => Some(13),
+            Register::Fa4 => Some(14),
+            Register::Fa5 => Some(15),
+            Register::Fa6 => Some(16),
+            Register::Fa7 => Some(17),
+            Register::Fs2 => Some(18),
+            Register::Fs3 => Some(19),
+            Register::Fs4 => Some(20),
+            Register::Fs5 => Some(21),
+            Register::Fs6 => Some(22),
+            Register::Fs7 => Some(23),
+            Register::Fs8 => Some(24),
+            Register::Fs9 => Some(25),
+            Register::Fs10 => Some(26),
+            Register::Fs11 => Some(27),
+            Register::Ft8 => Some(28),
+            Register::Ft9 => Some(29),
+            Register::Ft10 => Some(30),
+            Register::Ft11 => Some(31),
+            Register::V0 => Some(0),
+            Register::V1 => Some(1),
+            Register::V2 => Some(2),
+            Register::V3 => Some(3),
+            Register::V4 => Some(4),
+            Register::V5 => Some(5),
+            Register::V6 => Some(6),
+            Register::V7 => Some(7),
+            Register::V8 => Some(8),
+            Register::V9 => Some(9),
+            Register::V10 => Some(10),
+            Register::V11 => Some(11),
+            Register::V12 => Some(12),
+            Register::V13 => Some(13),
+            Register::V14 => Some(14),
+            Register::V15 => Some(15),
+            Register::V16 => Some(16),
+            Register::V17 => Some(17),
+            Register::V18 => Some(18),
+            Register::V19 => Some(19),
+            Register::V20 => Some(20),
+            Register::V21 => Some(21),
+            Register::V22 => Some(22),
+            Register::V23 => Some(23),
+            Register::V24 => Some(24),
+            Register::V25 => Some(25),
+            Register::V26 => Some(26),
+            Register::V27 => Some(27),
+            Register::V28 => Some(28),
+            Register::V29 => Some(29),
+            Register::V30 => Some(30),
+            Register::V31 => Some(31),
+            Register::Invalid => None,
+        }
+    }
+
+    /// Returns true if this is one of the floating point registers f0-f31
+    pub fn is_float(&self) -> bool {
+        matches!(
+            self,
+            Register::Ft0
+                | Register::Ft1
+                | Register::Ft2
+                | Register::Ft3
+                | Register::Ft4
+                | Register::Ft5
+                | Register::Ft6
+                | Register::Ft7
+                | Register::Ft8
+                | Register::Ft9
+                | Register::Ft10
+                | Register::Ft11
+                | Register::Fs0
+                | Register::Fs1
+                | Register::Fs2
+                | Register::Fs3
+                | Register::Fs4
+                | Register::Fs5
+                | Register::Fs6
+                | Register::Fs7
+                | Register::Fs8
+                | Register::Fs9
+                | Register::Fs10
+                | Register::Fs11
+                | Register::Fa0
+                | Register::Fa1
+                | Register::Fa2
+                | Register::Fa3
+                | Register::Fa4
+                | Register::Fa5
+                | Register::Fa6
+                | Register::Fa7
+        )
+    }
+
+    /// Returns true if this is one of the vector registers v0-v31
+    pub fn is_vector(&self) -> bool {
+        matches!(
+            self,
+            Register::V0
+                | Register::V1
+                | Register::V2
+                | Register::V3
+                | Register::V4
+                | Register::V5
+                | Register::V6
+                | Register::V7
+                | Register::V8
+                | Register::V9
+                | Register::V10
+                | Register::V11
+                | Register::V12
+                | Register::V13
+                | Register::V14
+                | Register::V15
+                | Register::V16
+                | Register::V17
+                | Register::V18
+                | Register::V19
+                | Register::V20
+                | Register::V21
+                | Register::V22
+                | Register::V23
+                | Register::V24
+                | Register::V25
+                | Register::V26
+                | Register::V27
+                | Register::V28
+                | Register::V29
+                | Register::V30
+                | Register::V31
+        )
+    }
 }
 
 impl fmt::Display for Register {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Register::Zero => write!(f, "0"),
-            Register::Ra => write!(f, "ra"),
-            Register::Sp => write!(f, "sp"),
-            Register::Gp => write!(f, "gp"),
-            Register::Tp => write!(f, "tp"),
-            Register::Fp => write!(f, "fp"),
-            Register::S1 => write!(f, "s1"),
-            Register::S2 => write!(f, "s2"),
-            Register::S3 => write!(f, "s3"),
-            Register::S4 => write!(f, "s4"),
-            Register::S5 => write!(f, "s5"),
-            Register::S6 => write!(f, "s6"),
-            Register::S7 => write!(f, "s7"),
-            Register::S8 => write!(f, "s8"),
-            Register::S9 => write!(f, "s9"),
-            Register::S10 => write!(f, "s10"),
-            Register::S11 => write!(f, "s11"),
-            Register::A0 => write!(f, "a0"),
-            Register::A1 => write!(f, "a1"),
-            Register::A2 => write!(f, "a2"),
-            Register::A3 => write!(f, "a3"),
-            Register::A4 => write!(f, "a4"),
-            Register::A5 => write!(f, "a5"),
-            Register::A6 => write!(f, "a6"),
-            Register::A7 => write!(f, "a7"),
-            Register::T0 => write!(f, "t0"),
-            Register::T1 => write!(f, "t1"),
-            Register::T2 => write!(f, "t2"),
-            Register::T3 => write!(f, "t3"),
-            Register::T4 => write!(f, "t4"),
-            Register::T5 => write!(f, "t5"),
-            Register::T6 => write!(f, "t6"),
-            Register::Invalid => write!(f, "invalid"),
-        }
+        write!(f, "{}", self.name())
     }
 }
@@ -1,4 +1,69 @@
-use {decode, Op, Register};
+use {
+    assemble, assemble_bytes, assemble_bytes_isa, assemble_isa, baseline, decode, decode_bytes,
+    decode_compressed, decode_isa, decode_with_length, encode, instruction_length, AccessKind,
+    AssembleError, Csr, DecodeError, EncodeError, Extend, Extension, FloatFormat, Flow,
+    Instruction, Isa, Lmul, Location, Op, Operand, ParseIsaError, Privilege, Pseudo, Register,
+    RoundingMode, VectorForm, VectorOp, Xlen,
+};
+
+fn rv32gc() -> Isa {
+    "rv32gc".parse().unwrap()
+}
+
+fn rv64gc() -> Isa {
+    "rv64gc".parse().unwrap()
+}
+
+/// Advances a xorshift32 generator of pseudo-random words
+fn xorshift32(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+/// Every combination of the opcode, funct3, funct7 and rs2 fields, which
+/// between them select every operation, with pseudo-random rd and rs1
+/// fields
+fn sample_words() -> impl Iterator<Item = u32> {
+    let mut state: u32 = 0x12345678;
+    (0..1 << 22).map(move |fields| {
+        let random = xorshift32(&mut state);
+        (fields & 0x7f)
+            | (fields >> 7 & 0x7) << 12
+            | (fields >> 10 & 0xfff) << 20
+            | (random & 0x1f) << 7
+            | (random >> 5 & 0x1f) << 15
+    })
+}
+
+/// Returns true if the printed form of `instruction`, decoded from `word`,
+/// leaves out fields set in `word`. Fences are written without their unused
+/// register fields, and a plain fence without its fence mode.
+fn unprinted_fields(instruction: &Instruction, word: u32) -> bool {
+    let unused = match *instruction.op() {
+        Op::Fence => word & 0xf00f_8f80,
+        Op::FenceTso => word & 0x000f_8f80,
+        Op::FenceI => word & 0xffff_8f80,
+        _ => 0,
+    };
+    unused != 0
+}
+
+/// Checks that each word decodes on `isa` to its operation and text, and
+/// that the text assembles and the instruction encodes back to the word
+fn check_cases(isa: &Isa, cases: &[(u32, Op, &str)]) {
+    for &(word, ref op, text) in cases {
+        let instruction = decode_isa(word, isa).unwrap();
+        assert_eq!(instruction.op(), op);
+        assert_eq!(instruction.to_string(), text);
+        assert_eq!(encode(&instruction).unwrap(), word);
+        assert_eq!(
+            assemble_bytes_isa(text, isa).unwrap(),
+            word.to_le_bytes().to_vec()
+        );
+    }
+}
 
 /*
    0:   00c58533            add a0,a1,a2
@@ -52,7 +117,7 @@ fn andi() {
     assert_eq!(*instruction.op(), Op::Andi);
     assert_eq!(*instruction.rd(), Register::S1);
     assert_eq!(*instruction.rs1(), Register::Gp);
-    assert_eq!(instruction.immediate(), 0xffffffff);
+    assert_eq!(instruction.immediate(), -1);
 }
 
 #[test]
@@ -137,3 +202,2733 @@ fn bne() {
     assert_eq!(*instruction.rs2(), Register::A1);
     assert_eq!(instruction.immediate(), 4);
 }
+
+/*
+   0:   feb51ee3            bne a0,a1,-4
+   4:   00b500e3            beq a0,a1,2048
+*/
+
+#[test]
+fn bne_backwards() {
+    let word: u32 = 0xfeb51ee3;
+
+    let instruction = decode(word).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Bne);
+    assert_eq!(instruction.immediate(), -4);
+}
+
+#[test]
+fn beq_bit_11() {
+    // Bit 12 of a branch offset is its sign, so bit 11 alone is positive
+    let word: u32 = 0x00b500e3;
+
+    let instruction = decode(word).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Beq);
+    assert_eq!(instruction.immediate(), 0x800);
+}
+
+/*
+   0:   00813503            ld  a0,8(sp)
+   4:   00a13423            sd  a0,8(sp)
+   8:   0005e503            lwu a0,0(a1)
+   c:   fff5051b            addiw   a0,a0,-1
+  10:   02151513            slli    a0,a0,0x21
+  14:   42855513            srai    a0,a0,0x28
+  18:   40c5d53b            sraw    a0,a1,a2
+*/
+
+#[test]
+fn ld() {
+    let word: u32 = 0x00813503;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Ld);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::Sp);
+    assert_eq!(instruction.immediate(), 8);
+
+    assert_eq!(decode(word).unwrap_err(), DecodeError::ExtensionDisabled);
+}
+
+#[test]
+fn sd() {
+    let word: u32 = 0x00a13423;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Sd);
+    assert_eq!(*instruction.rs1(), Register::Sp);
+    assert_eq!(*instruction.rs2(), Register::A0);
+    assert_eq!(instruction.immediate(), 8);
+
+    assert_eq!(decode(word).unwrap_err(), DecodeError::ExtensionDisabled);
+}
+
+#[test]
+fn lwu() {
+    let word: u32 = 0x0005e503;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Lwu);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A1);
+    assert_eq!(instruction.immediate(), 0);
+}
+
+#[test]
+fn addiw() {
+    let word: u32 = 0xfff5051b;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Addiw);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A0);
+    assert_eq!(instruction.immediate(), -1);
+}
+
+#[test]
+fn slli_rv64() {
+    let word: u32 = 0x02151513;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Slli);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A0);
+    assert_eq!(instruction.shamt(), 33);
+
+    // shamt[5] is reserved in RV32
+    assert_eq!(decode(word).unwrap_err(), DecodeError::ExtensionDisabled);
+}
+
+#[test]
+fn srai() {
+    let word: u32 = 0x40355513;
+
+    let instruction = decode(word).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Srai);
+    assert_eq!(instruction.shamt(), 3);
+
+    let word: u32 = 0x42855513;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Srai);
+    assert_eq!(instruction.shamt(), 40);
+}
+
+#[test]
+fn sraw() {
+    let word: u32 = 0x40c5d53b;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Sraw);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A1);
+    assert_eq!(*instruction.rs2(), Register::A2);
+
+    assert_eq!(decode(word).unwrap_err(), DecodeError::ExtensionDisabled);
+}
+
+/*
+   0:   02c5853b            mulw    a0,a1,a2
+   4:   02c5c53b            divw    a0,a1,a2
+   8:   02c5d53b            divuw   a0,a1,a2
+   c:   02c5f53b            remuw   a0,a1,a2
+*/
+
+#[test]
+fn mulw() {
+    let word: u32 = 0x02c5853b;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Mulw);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A1);
+    assert_eq!(*instruction.rs2(), Register::A2);
+
+    assert_eq!(decode(word).unwrap_err(), DecodeError::ExtensionDisabled);
+}
+
+#[test]
+fn divw() {
+    let instruction = decode_isa(0x02c5c53b, &rv64gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::Divw);
+
+    let instruction = decode_isa(0x02c5d53b, &rv64gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::Divuw);
+}
+
+#[test]
+fn remuw() {
+    let word: u32 = 0x02c5f53b;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Remuw);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A1);
+    assert_eq!(*instruction.rs2(), Register::A2);
+}
+
+/*
+   0:   1405a52f            lr.w.aq a0,(a1)
+   4:   1ac5b52f            sc.d.rl a0,a2,(a1)
+   8:   06c5a52f            amoadd.w.aqrl   a0,a2,(a1)
+   c:   08c5a52f            amoswap.w   a0,a2,(a1)
+*/
+
+#[test]
+fn lr_w() {
+    let word: u32 = 0x1405a52f;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::LrW);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A1);
+    assert!(instruction.aqrl().unwrap().aq());
+    assert!(!instruction.aqrl().unwrap().rl());
+    assert_eq!(format!("{}", instruction), "lr.w.aq a0, (a1)");
+
+    // lr with a non-zero rs2 is reserved
+    assert_eq!(
+        decode_isa(0x1015a52f, &rv32gc()).unwrap_err(),
+        DecodeError::Reserved
+    );
+}
+
+#[test]
+fn sc_d() {
+    let word: u32 = 0x1ac5b52f;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::ScD);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A1);
+    assert_eq!(*instruction.rs2(), Register::A2);
+    assert_eq!(format!("{}", instruction), "sc.d.rl a0, a2, (a1)");
+
+    assert_eq!(
+        decode_isa(word, &rv32gc()).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+}
+
+#[test]
+fn amoadd_w() {
+    let word: u32 = 0x06c5a52f;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::AmoaddW);
+    assert!(instruction.aqrl().unwrap().aq());
+    assert!(instruction.aqrl().unwrap().rl());
+    assert_eq!(format!("{}", instruction), "amoadd.w.aqrl a0, a2, (a1)");
+}
+
+#[test]
+fn amoswap_w() {
+    let word: u32 = 0x08c5a52f;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::AmoswapW);
+    assert_eq!(format!("{}", instruction), "amoswap.w a0, a2, (a1)");
+}
+
+/*
+   0:   00412507            flw fa0,4(sp)
+   4:   00813427            fsd fs0,8(sp)
+   8:   00c59553            fadd.s  fa0,fa1,fa2,rtz
+   c:   6ac5f543            fmadd.d fa0,fa1,fa2,fa3
+  10:   c0059553            fcvt.w.s    a0,fa1,rtz
+  14:   e2058553            fmv.x.d a0,fa1
+*/
+
+#[test]
+fn flw() {
+    let word: u32 = 0x00412507;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Flw);
+    assert_eq!(*instruction.rd(), Register::Fa0);
+    assert_eq!(*instruction.rs1(), Register::Sp);
+    assert_eq!(instruction.immediate(), 4);
+}
+
+#[test]
+fn fsd() {
+    let word: u32 = 0x00813427;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::Fsd);
+    assert_eq!(*instruction.rs1(), Register::Sp);
+    assert_eq!(*instruction.rs2(), Register::Fs0);
+    assert_eq!(instruction.immediate(), 8);
+}
+
+#[test]
+fn fadd_s() {
+    let word: u32 = 0x00c59553;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::FaddS);
+    assert_eq!(*instruction.rd(), Register::Fa0);
+    assert_eq!(*instruction.rs1(), Register::Fa1);
+    assert_eq!(*instruction.rs2(), Register::Fa2);
+    assert_eq!(instruction.rm(), Some(RoundingMode::Rtz));
+    assert_eq!(format!("{}", instruction), "fadd.s fa0, fa1, fa2, rtz");
+
+    // rounding mode 0b101 is reserved
+    assert_eq!(
+        decode_isa(0x00c5d553, &rv32gc()).unwrap_err(),
+        DecodeError::Reserved
+    );
+}
+
+#[test]
+fn fmadd_d() {
+    let word: u32 = 0x6ac5f543;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::FmaddD);
+    assert_eq!(*instruction.rd(), Register::Fa0);
+    assert_eq!(*instruction.rs1(), Register::Fa1);
+    assert_eq!(*instruction.rs2(), Register::Fa2);
+    assert_eq!(*instruction.rs3(), Register::Fa3);
+    assert_eq!(instruction.rm(), Some(RoundingMode::Dyn));
+    assert_eq!(format!("{}", instruction), "fmadd.d fa0, fa1, fa2, fa3");
+}
+
+#[test]
+fn fcvt_w_s() {
+    let word: u32 = 0xc0059553;
+
+    let instruction = decode_isa(word, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::FcvtWS);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::Fa1);
+    assert_eq!(*instruction.rs2(), Register::Invalid);
+    assert_eq!(instruction.rm(), Some(RoundingMode::Rtz));
+}
+
+#[test]
+fn fmv_x_d() {
+    let word: u32 = 0xe2058553;
+
+    let instruction = decode_isa(word, &rv64gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::FmvXD);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::Fa1);
+    assert!(instruction.rs1().is_float());
+
+    assert_eq!(decode(word).unwrap_err(), DecodeError::ExtensionDisabled);
+}
+
+/*
+   0:   0808                    c.addi4spn  a0,sp,16
+   2:   41c8                    c.lw    a0,4(a1)
+   4:   2021                    c.jal   c
+   6:   7505                    c.lui   a0,0xfffe1
+   8:   7139                    c.addi16sp  sp,-64
+   a:   c901                    c.beqz  a0,1a
+   c:   8082                    c.jr    ra
+   e:   852e                    c.mv    a0,a1
+  10:   9002                    c.ebreak
+  12:   c42a                    c.swsp  a0,8(sp)
+*/
+
+#[test]
+fn c_addi4spn() {
+    let instruction = decode_compressed(0x0808, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CAddi4spn);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::Sp);
+    assert_eq!(instruction.immediate(), 16);
+    assert_eq!(instruction.length(), 2);
+
+    let expanded = instruction.expand();
+    assert_eq!(*expanded.op(), Op::Addi);
+    assert_eq!(expanded.length(), 4);
+
+    // The all-zero halfword is illegal
+    assert_eq!(
+        decode_compressed(0x0000, &rv32gc()).unwrap_err(),
+        DecodeError::Illegal
+    );
+}
+
+#[test]
+fn c_lw() {
+    let instruction = decode_compressed(0x41c8, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CLw);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs1(), Register::A1);
+    assert_eq!(instruction.immediate(), 4);
+}
+
+#[test]
+fn c_jal() {
+    let instruction = decode_compressed(0x2021, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CJal);
+    assert_eq!(*instruction.rd(), Register::Ra);
+    assert_eq!(instruction.immediate(), 8);
+
+    // c.addiw occupies this encoding in RV64, where rd may not be zero
+    assert!(decode_compressed(0x2021, &rv64gc()).is_err());
+}
+
+#[test]
+fn c_lui() {
+    let instruction = decode_compressed(0x7505, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CLui);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(instruction.immediate(), 0xfffe1);
+}
+
+#[test]
+fn c_addi16sp() {
+    let instruction = decode_compressed(0x7139, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CAddi16sp);
+    assert_eq!(*instruction.rd(), Register::Sp);
+    assert_eq!(instruction.immediate(), -64);
+}
+
+#[test]
+fn c_beqz() {
+    let instruction = decode_compressed(0xc901, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CBeqz);
+    assert_eq!(*instruction.rs1(), Register::A0);
+    assert_eq!(*instruction.rs2(), Register::Zero);
+    assert_eq!(instruction.immediate(), 16);
+    assert_eq!(*instruction.expand().op(), Op::Beq);
+}
+
+#[test]
+fn c_jr() {
+    let instruction = decode_compressed(0x8082, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CJr);
+    assert_eq!(*instruction.rd(), Register::Zero);
+    assert_eq!(*instruction.rs1(), Register::Ra);
+}
+
+#[test]
+fn c_mv() {
+    let instruction = decode_compressed(0x852e, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CMv);
+    assert_eq!(*instruction.rd(), Register::A0);
+    assert_eq!(*instruction.rs2(), Register::A1);
+
+    let instruction = decode_compressed(0x9002, &rv32gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CEbreak);
+}
+
+#[test]
+fn c_swsp() {
+    let instruction = decode_compressed(0xc42a, &rv32gc()).unwrap();
+
+    assert_eq!(*instruction.op(), Op::CSwsp);
+    assert_eq!(*instruction.rs1(), Register::Sp);
+    assert_eq!(*instruction.rs2(), Register::A0);
+    assert_eq!(instruction.immediate(), 8);
+}
+
+/*
+   0:   6588                    c.ld    a0,8(a1)
+   2:   357d                    c.addiw a0,-1
+   4:   e82a                    c.sdsp  a0,16(sp)
+   6:   1506                    c.slli  a0,0x21
+   8:   9d0d                    c.subw  a0,a1
+*/
+
+#[test]
+fn c_rv64() {
+    let instruction = decode_compressed(0x6588, &rv64gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CLd);
+    assert_eq!(instruction.immediate(), 8);
+
+    let instruction = decode_compressed(0x357d, &rv64gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CAddiw);
+    assert_eq!(instruction.immediate(), -1);
+
+    let instruction = decode_compressed(0xe82a, &rv64gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CSdsp);
+    assert_eq!(instruction.immediate(), 16);
+
+    let instruction = decode_compressed(0x1506, &rv64gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CSlli);
+    assert_eq!(instruction.shamt(), 33);
+    assert!(decode_compressed(0x1506, &rv32gc()).is_err());
+
+    let instruction = decode_compressed(0x9d0d, &rv64gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CSubw);
+    assert!(decode_compressed(0x9d0d, &rv32gc()).is_err());
+}
+
+/*
+   0:   00812503            lw  a0,8(sp)
+   4:   00a12423            sw  a0,8(sp)
+   8:   30059573            csrrw   a0,mstatus,a1
+   c:   3002d573            csrrwi  a0,mstatus,5
+  10:   0330000f            fence   rw,rw
+  14:   000500e7            jalr    ra,0(a0)
+  18:   12345537            lui a0,0x12345
+  1c:   00351513            slli    a0,a0,0x3
+*/
+
+#[test]
+fn display_load_store() {
+    let lw = decode(0x00812503).unwrap();
+    assert_eq!(format!("{}", lw), "lw a0, 8(sp)");
+
+    let sw = decode(0x00a12423).unwrap();
+    assert_eq!(format!("{}", sw), "sw a0, 8(sp)");
+}
+
+#[test]
+fn display_csr() {
+    let csrrw = decode(0x30059573).unwrap();
+    assert_eq!(format!("{}", csrrw), "csrrw a0, mstatus, a1");
+
+    let csrrwi = decode(0x3002d573).unwrap();
+    assert_eq!(format!("{}", csrrwi), "csrrwi a0, mstatus, 5");
+
+    // CSRs without a name are printed as their address
+    let csrrs = decode(0x7c002573).unwrap();
+    assert_eq!(format!("{}", csrrs), "csrrs a0, 0x7c0, zero");
+}
+
+#[test]
+fn display_fence() {
+    let fence = decode(0x0330000f).unwrap();
+    assert_eq!(format!("{}", fence), "fence rw,rw");
+
+    let fence = decode(0x0ff0000f).unwrap();
+    assert_eq!(format!("{}", fence), "fence iorw,iorw");
+}
+
+#[test]
+fn fence_tso() {
+    let tso = decode(0x8330000f).unwrap();
+    assert_eq!(*tso.op(), Op::FenceTso);
+    assert_eq!(tso.fence().unwrap().fm(), 0b1000);
+    assert_eq!(tso.to_string(), "fence.tso");
+    assert_eq!(tso.operands(), vec![]);
+    assert_eq!(encode(&tso), Ok(0x8330000f));
+    assert_eq!(assemble("fence.tso"), Ok(vec![tso]));
+
+    // fence.tso only orders rw,rw, and the other fence modes are reserved,
+    // ordering as a normal fence, but the mode is kept for encoding
+    for &word in &[0x8ff0000f, 0x9330000f] {
+        let fence = decode(word).unwrap();
+        assert_eq!(*fence.op(), Op::Fence);
+        assert_eq!(fence.fence().unwrap().fm(), word >> 28);
+        assert_eq!(encode(&fence), Ok(word));
+    }
+}
+
+#[test]
+fn display_jalr() {
+    let jalr = decode(0x000500e7).unwrap();
+    assert_eq!(format!("{}", jalr), "jalr ra, 0(a0)");
+}
+
+#[test]
+fn display_lui() {
+    let lui = decode(0x12345537).unwrap();
+    assert_eq!(format!("{}", lui), "lui a0, 0x12345");
+}
+
+#[test]
+fn display_slli() {
+    let slli = decode(0x00351513).unwrap();
+    assert_eq!(format!("{}", slli), "slli a0, a0, 0x3");
+}
+
+#[test]
+fn display_compressed() {
+    let rv64 = rv64gc();
+    let display = |halfword| format!("{}", decode_compressed(halfword, &rv64).unwrap());
+
+    assert_eq!(display(0x0808), "c.addi4spn a0, sp, 16");
+    assert_eq!(display(0x6588), "c.ld a0, 8(a1)");
+    assert_eq!(display(0x357d), "c.addiw a0, -1");
+    assert_eq!(display(0xe82a), "c.sdsp a0, 16(sp)");
+    assert_eq!(display(0x1506), "c.slli a0, 0x21");
+    assert_eq!(display(0x9d0d), "c.subw a0, a1");
+    assert_eq!(display(0x8082), "c.jr ra");
+    assert_eq!(display(0x852e), "c.mv a0, a1");
+}
+
+/*
+   0:   fff58513            addi    a0,a1,-1
+   4:   00812503            lw  a0,8(sp)
+   8:   feb51ee3            bne a0,a1,-4
+   c:   3002d573            csrrwi  a0,mstatus,5
+  10:   0330000f            fence   rw,rw
+  14:   06c5a52f            amoadd.w.aqrl   a0,a2,(a1)
+  18:   00c59553            fadd.s  fa0,fa1,fa2,rtz
+*/
+
+#[test]
+fn operands_addi() {
+    let instruction = decode(0xfff58513).unwrap();
+
+    assert_eq!(instruction.immediate(), -1);
+    assert_eq!(format!("{}", instruction), "addi a0, a1, -1");
+    assert_eq!(
+        instruction.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Register(Register::A1),
+            Operand::SignedImmediate(-1),
+        ]
+    );
+}
+
+#[test]
+fn operands_lw() {
+    let instruction = decode(0x00812503).unwrap();
+
+    assert_eq!(
+        instruction.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Memory {
+                base: Register::Sp,
+                offset: 8
+            },
+        ]
+    );
+}
+
+#[test]
+fn operands_bne() {
+    let instruction = decode(0xfeb51ee3).unwrap();
+
+    assert_eq!(
+        instruction.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Register(Register::A1),
+            Operand::PcRelative(-4),
+        ]
+    );
+}
+
+#[test]
+fn operands_csrrwi() {
+    let instruction = decode(0x3002d573).unwrap();
+
+    assert_eq!(
+        instruction.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Csr(Csr::from_address(0x300).unwrap()),
+            Operand::UnsignedImmediate(5),
+        ]
+    );
+}
+
+#[test]
+fn operands_fence() {
+    let instruction = decode(0x0330000f).unwrap();
+    let operands = instruction.operands();
+
+    assert_eq!(operands.len(), 2);
+    match operands[0] {
+        Operand::FenceSet(set) => assert!(!set.i() && !set.o() && set.r() && set.w()),
+        ref operand => panic!("unexpected operand {:?}", operand),
+    }
+}
+
+#[test]
+fn operands_amoadd_w() {
+    let instruction = decode_isa(0x06c5a52f, &rv32gc()).unwrap();
+
+    assert_eq!(
+        instruction.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Register(Register::A2),
+            Operand::Memory {
+                base: Register::A1,
+                offset: 0
+            },
+        ]
+    );
+}
+
+#[test]
+fn operands_fadd_s() {
+    let instruction = decode_isa(0x00c59553, &rv32gc()).unwrap();
+
+    assert_eq!(
+        instruction.operands(),
+        vec![
+            Operand::Register(Register::Fa0),
+            Operand::Register(Register::Fa1),
+            Operand::Register(Register::Fa2),
+            Operand::RoundingMode(RoundingMode::Rtz),
+        ]
+    );
+}
+
+/*
+   0:   008000ef            jal ra,8
+   4:   30002573            csrrs   a0,mstatus,zero
+   8:   30059073            csrrw   zero,mstatus,a1
+   c:   00c5f553            fadd.s  fa0,fa1,fa2
+  10:   00a12423            sw  a0,8(sp)
+  14:   00b58033            add zero,a1,a1
+*/
+
+#[test]
+fn registers_jal() {
+    let instruction = decode(0x008000ef).unwrap();
+
+    assert!(instruction.registers_read().is_empty());
+    assert_eq!(
+        instruction.registers_written(),
+        vec![Location::Register(Register::Ra)]
+    );
+}
+
+#[test]
+fn registers_c_jalr() {
+    let instruction = decode_compressed(0x9502, &rv32gc()).unwrap();
+
+    assert_eq!(
+        instruction.registers_read(),
+        vec![Location::Register(Register::A0)]
+    );
+    assert_eq!(
+        instruction.registers_written(),
+        vec![Location::Register(Register::Ra)]
+    );
+}
+
+#[test]
+fn registers_csr() {
+    // csrrs with rs1 x0 reads the CSR without writing it
+    let instruction = decode(0x30002573).unwrap();
+
+    assert_eq!(
+        instruction.registers_read(),
+        vec![Location::Csr(Csr::from_address(0x300).unwrap())]
+    );
+    assert_eq!(
+        instruction.registers_written(),
+        vec![Location::Register(Register::A0)]
+    );
+
+    // csrrw with rd x0 writes the CSR without reading it
+    let instruction = decode(0x30059073).unwrap();
+
+    assert_eq!(
+        instruction.registers_read(),
+        vec![Location::Register(Register::A1)]
+    );
+    assert_eq!(
+        instruction.registers_written(),
+        vec![Location::Csr(Csr::from_address(0x300).unwrap())]
+    );
+}
+
+#[test]
+fn registers_fadd_s() {
+    let instruction = decode_isa(0x00c5f553, &rv32gc()).unwrap();
+
+    assert_eq!(
+        instruction.registers_read(),
+        vec![
+            Location::Register(Register::Fa1),
+            Location::Register(Register::Fa2),
+            Location::Csr(Csr::from_address(0x002).unwrap()),
+        ]
+    );
+    assert_eq!(
+        instruction.registers_written(),
+        vec![
+            Location::Register(Register::Fa0),
+            Location::Csr(Csr::from_address(0x001).unwrap())
+        ]
+    );
+}
+
+#[test]
+fn registers_sw() {
+    let instruction = decode(0x00a12423).unwrap();
+
+    assert_eq!(
+        instruction.registers_read(),
+        vec![
+            Location::Register(Register::Sp),
+            Location::Register(Register::A0),
+        ]
+    );
+    assert!(instruction.registers_written().is_empty());
+}
+
+#[test]
+fn registers_zero() {
+    let instruction = decode(0x00b58033).unwrap();
+
+    assert_eq!(
+        instruction.registers_read(),
+        vec![Location::Register(Register::A1)]
+    );
+    assert!(instruction.registers_written().is_empty());
+}
+
+/*
+1000:   00b50663            beq a0,a1,100c
+1004:   feb51ee3            bne a0,a1,1000
+1008:   008000ef            jal ra,1010
+100c:   ff9ff06f            j   1004
+1010:   00008067            ret
+1014:   000500e7            jalr    a0
+1018:   00050067            jr  a0
+101c:   10000067            jr  256(zero)
+1020:   00000073            ecall
+*/
+
+#[test]
+fn flow_branch() {
+    let beq = decode(0x00b50663).unwrap();
+    assert_eq!(beq.flow(), Flow::ConditionalBranch);
+    assert_eq!(beq.branch_target(0x1000), Some(0x100c));
+
+    let bne = decode(0xfeb51ee3).unwrap();
+    assert_eq!(bne.flow(), Flow::ConditionalBranch);
+    assert_eq!(bne.branch_target(0x1004), Some(0x1000));
+}
+
+#[test]
+fn flow_jal() {
+    let call = decode(0x008000ef).unwrap();
+    assert_eq!(call.flow(), Flow::Call);
+    assert_eq!(call.branch_target(0x1008), Some(0x1010));
+
+    let jump = decode(0xff9ff06f).unwrap();
+    assert_eq!(jump.flow(), Flow::Jump);
+    assert_eq!(jump.branch_target(0x100c), Some(0x1004));
+}
+
+#[test]
+fn flow_jalr() {
+    let ret = decode(0x00008067).unwrap();
+    assert_eq!(ret.flow(), Flow::Return);
+    assert_eq!(ret.branch_target(0x1010), None);
+
+    let call = decode(0x000500e7).unwrap();
+    assert_eq!(call.flow(), Flow::IndirectCall);
+    assert_eq!(call.branch_target(0x1014), None);
+
+    let jump = decode(0x00050067).unwrap();
+    assert_eq!(jump.flow(), Flow::IndirectJump);
+    assert_eq!(jump.branch_target(0x1018), None);
+
+    // jalr ra, 0(t0) and jalr t0, 0(ra) swap coroutines, while linking in
+    // the register jumped through, as jalr ra, 0(ra) does, is a call
+    for &word in &[0x000280e7, 0x000082e7] {
+        let swap = decode(word).unwrap();
+        assert_eq!(swap.flow(), Flow::CoroutineSwap);
+        assert_eq!(swap.branch_target(0x1018), None);
+    }
+    for &word in &[0x000080e7, 0x000282e7] {
+        assert_eq!(decode(word).unwrap().flow(), Flow::IndirectCall);
+    }
+
+    // The target of jalr is known when the base is x0
+    let absolute = decode(0x10000067).unwrap();
+    assert_eq!(absolute.flow(), Flow::IndirectJump);
+    assert_eq!(absolute.branch_target(0x101c), Some(0x100));
+}
+
+#[test]
+fn flow_compressed() {
+    let rv32 = rv32gc();
+
+    // c.jr ra
+    let ret = decode_compressed(0x8082, &rv32).unwrap();
+    assert_eq!(ret.flow(), Flow::Return);
+
+    // c.jal 8
+    let call = decode_compressed(0x2021, &rv32).unwrap();
+    assert_eq!(call.flow(), Flow::Call);
+    assert_eq!(call.branch_target(0x1000), Some(0x1008));
+
+    // c.beqz a0, 16
+    let beqz = decode_compressed(0xc901, &rv32).unwrap();
+    assert_eq!(beqz.flow(), Flow::ConditionalBranch);
+    assert_eq!(beqz.branch_target(0x1000), Some(0x1010));
+}
+
+#[test]
+fn flow_other() {
+    let ecall = decode(0x00000073).unwrap();
+    assert_eq!(ecall.flow(), Flow::Trap);
+    assert_eq!(ecall.branch_target(0x1020), None);
+
+    let add = decode(0x00c58533).unwrap();
+    assert_eq!(add.flow(), Flow::Sequential);
+    assert_eq!(add.branch_target(0x1024), None);
+}
+
+/*
+   0:   fff58503            lb  a0,-1(a1)
+   4:   0035c503            lbu a0,3(a1)
+   8:   00a11323            sh  a0,6(sp)
+   c:   0005e503            lwu a0,0(a1)
+  10:   00813427            fsd fs0,8(sp)
+  14:   1405a52f            lr.w.aq a0,(a1)
+  18:   06c5a52f            amoadd.w.aqrl   a0,a2,(a1)
+*/
+
+#[test]
+fn memory_lb() {
+    let lb = decode(0xfff58503).unwrap().memory_access().unwrap();
+
+    assert_eq!(lb.kind(), AccessKind::Load);
+    assert_eq!(lb.width(), 1);
+    assert_eq!(lb.extend(), Extend::Sign);
+    assert_eq!(*lb.base(), Register::A1);
+    assert_eq!(lb.displacement(), -1);
+    assert!(lb.reads() && !lb.writes());
+
+    let lbu = decode(0x0035c503).unwrap().memory_access().unwrap();
+
+    assert_eq!(lbu.width(), 1);
+    assert_eq!(lbu.extend(), Extend::Zero);
+    assert_eq!(lbu.displacement(), 3);
+}
+
+#[test]
+fn memory_sh() {
+    let sh = decode(0x00a11323).unwrap().memory_access().unwrap();
+
+    assert_eq!(sh.kind(), AccessKind::Store);
+    assert_eq!(sh.width(), 2);
+    assert_eq!(sh.extend(), Extend::None);
+    assert_eq!(*sh.base(), Register::Sp);
+    assert_eq!(sh.displacement(), 6);
+    assert!(!sh.reads() && sh.writes());
+}
+
+#[test]
+fn memory_lwu() {
+    let lwu = decode_isa(0x0005e503, &rv64gc())
+        .unwrap()
+        .memory_access()
+        .unwrap();
+
+    assert_eq!(lwu.width(), 4);
+    assert_eq!(lwu.extend(), Extend::Zero);
+}
+
+#[test]
+fn memory_fsd() {
+    let fsd = decode_isa(0x00813427, &rv32gc())
+        .unwrap()
+        .memory_access()
+        .unwrap();
+
+    assert_eq!(fsd.kind(), AccessKind::Store);
+    assert_eq!(fsd.width(), 8);
+    assert_eq!(*fsd.base(), Register::Sp);
+    assert_eq!(fsd.displacement(), 8);
+}
+
+#[test]
+fn memory_atomic() {
+    let lr = decode_isa(0x1405a52f, &rv32gc())
+        .unwrap()
+        .memory_access()
+        .unwrap();
+
+    assert_eq!(lr.kind(), AccessKind::LoadReserved);
+    assert_eq!(lr.width(), 4);
+    assert_eq!(lr.extend(), Extend::Sign);
+    assert_eq!(*lr.base(), Register::A1);
+    assert_eq!(lr.displacement(), 0);
+
+    let amoadd = decode_isa(0x06c5a52f, &rv32gc())
+        .unwrap()
+        .memory_access()
+        .unwrap();
+
+    assert_eq!(amoadd.kind(), AccessKind::ReadModifyWrite);
+    assert!(amoadd.reads() && amoadd.writes());
+}
+
+#[test]
+fn memory_compressed() {
+    // c.lw a0, 4(a1)
+    let lw = decode_compressed(0x41c8, &rv32gc())
+        .unwrap()
+        .memory_access()
+        .unwrap();
+
+    assert_eq!(lw.kind(), AccessKind::Load);
+    assert_eq!(lw.width(), 4);
+    assert_eq!(*lw.base(), Register::A1);
+    assert_eq!(lw.displacement(), 4);
+}
+
+#[test]
+fn memory_none() {
+    assert!(decode(0x00c58533).unwrap().memory_access().is_none());
+}
+
+#[test]
+fn with_length() {
+    let (instruction, length) = decode_with_length(0x0000852e, &rv32gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CMv);
+    assert_eq!(length, 2);
+
+    let (instruction, length) = decode_with_length(0x00c58533, &rv32gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::Add);
+    assert_eq!(length, 4);
+}
+
+#[test]
+fn bytes() {
+    // c.mv a0, a1 followed by add a0, a1, a2
+    let bytes = [0x2e, 0x85, 0x33, 0x85, 0xc5, 0x00];
+
+    let (instruction, length) = decode_bytes(&bytes, &rv32gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::CMv);
+    assert_eq!(length, 2);
+
+    let (instruction, length) = decode_bytes(&bytes[2..], &rv32gc()).unwrap();
+    assert_eq!(*instruction.op(), Op::Add);
+    assert_eq!(length, 4);
+
+    assert_eq!(
+        decode_bytes(&bytes[2..5], &rv32gc()).unwrap_err(),
+        DecodeError::Truncated(4)
+    );
+    assert_eq!(
+        decode_bytes(&bytes[..1], &rv32gc()).unwrap_err(),
+        DecodeError::Truncated(2)
+    );
+    assert_eq!(
+        decode_bytes(&[0x00, 0x00], &rv32gc()).unwrap_err(),
+        DecodeError::Illegal
+    );
+    assert_eq!(
+        decode_bytes(&[0xff, 0xff], &rv32gc()).unwrap_err(),
+        DecodeError::Illegal
+    );
+    assert_eq!(
+        decode_bytes(&[0x1f, 0x00], &rv32gc()).unwrap_err(),
+        DecodeError::UnsupportedLength(6)
+    );
+}
+
+#[test]
+fn length() {
+    assert_eq!(instruction_length(0x852e), Some(2));
+    assert_eq!(instruction_length(0x8533), Some(4));
+    assert_eq!(instruction_length(0x001f), Some(6));
+    assert_eq!(instruction_length(0x003f), Some(8));
+    assert_eq!(instruction_length(0x007f), Some(10));
+    assert_eq!(instruction_length(0x607f), Some(22));
+    assert_eq!(instruction_length(0x707f), None);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(decode(0x00000000).unwrap_err(), DecodeError::Illegal);
+    assert_eq!(decode(0xffffffff).unwrap_err(), DecodeError::Illegal);
+    // custom-0 major opcode
+    assert_eq!(
+        decode(0x0000000b).unwrap_err(),
+        DecodeError::UnknownOpcode(0b0001011)
+    );
+    // OP with funct7 0b0000010
+    assert_eq!(decode(0x04c58533).unwrap_err(), DecodeError::Reserved);
+    // slli with shamt[5] set
+    assert_eq!(
+        decode(0x02151513).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+    // ecall with rd set
+    assert_eq!(decode(0x000000f3).unwrap_err(), DecodeError::Reserved);
+    // ebreak with rs1 set
+    assert_eq!(decode(0x00108073).unwrap_err(), DecodeError::Reserved);
+}
+
+#[test]
+fn no_panic() {
+    let rv32 = rv32gc();
+    let rv64 = rv64gc();
+    let mut state: u32 = 0x12345678;
+    for _ in 0..100_000 {
+        let word = xorshift32(&mut state);
+        if let Ok(instruction) = decode_isa(word, &rv32) {
+            let _ = instruction.to_string();
+            let _ = instruction.operands();
+            let _ = instruction.registers_read();
+            let _ = instruction.registers_written();
+        }
+        if let Ok(instruction) = decode_isa(word, &rv64) {
+            let _ = instruction.to_string();
+            let _ = instruction.operands();
+            let _ = instruction.registers_read();
+            let _ = instruction.registers_written();
+        }
+        if let Ok(instruction) = decode_compressed(word as u16, &rv64) {
+            let _ = instruction.to_string();
+            let _ = instruction.operands();
+            let _ = instruction.registers_read();
+            let _ = instruction.registers_written();
+        }
+    }
+}
+
+#[test]
+fn decode_matches_table_decoder() {
+    // The table decoder knows nothing of the extensions added since it was
+    // replaced, so where it rejects a word the word may now report
+    // ExtensionDisabled, or decode as a privileged SYSTEM instruction, and
+    // fence.tso was a plain fence to it
+    for name in &["rv32e", "rv32gc", "rv64gc"] {
+        let isa: Isa = name.parse().unwrap();
+        for word in sample_words() {
+            // Neither decodes the compressed quadrants
+            if word & 0x3 != 0x3 {
+                continue;
+            }
+            match (baseline::decode_isa(word, &isa), decode_isa(word, &isa)) {
+                (Ok(table), Ok(ref instruction)) if *instruction.op() == Op::FenceTso => {
+                    assert_eq!(*table.op(), Op::Fence)
+                }
+                (Err(_), Err(DecodeError::ExtensionDisabled)) => {}
+                (Err(_), Ok(_)) if word & 0x7f == 0x73 => {}
+                (table, result) => assert_eq!(result, table, "{} {:08x}", name, word),
+            }
+        }
+    }
+}
+
+#[test]
+fn isa() {
+    let isa: Isa = "rv64imafdc_zicsr_zifencei_zba".parse().unwrap();
+    assert_eq!(isa.xlen(), Xlen::Rv64);
+    assert!(isa.has(Extension::I));
+    assert!(isa.has(Extension::C));
+    assert!(isa.has(Extension::Zba));
+    assert!(!isa.has(Extension::V));
+    assert_eq!(isa.to_string(), "rv64imafdc_zicsr_zifencei_zba");
+
+    let isa: Isa = "rv64gcv".parse().unwrap();
+    assert_eq!(isa, "rv64imafdcv_zicsr_zifencei".parse::<Isa>().unwrap());
+
+    let isa: Isa = "RV32I2p1_M2p0_Zicsr2p0".parse().unwrap();
+    assert_eq!(
+        isa,
+        Isa::new(Xlen::Rv32)
+            .with(Extension::M)
+            .with(Extension::Zicsr)
+    );
+
+    let isa: Isa = "rv32e".parse().unwrap();
+    assert!(isa.has(Extension::E));
+    assert!(!isa.has(Extension::I));
+
+    assert_eq!(
+        "rv128i".parse::<Isa>().unwrap_err(),
+        ParseIsaError::InvalidBase("rv128i".to_string())
+    );
+    assert_eq!(
+        "rv32imj".parse::<Isa>().unwrap_err(),
+        ParseIsaError::UnknownExtension("j".to_string())
+    );
+    assert_eq!(
+        "rv32i_zfoo".parse::<Isa>().unwrap_err(),
+        ParseIsaError::UnknownExtension("zfoo".to_string())
+    );
+
+    // Version numbers are only accepted as `<major>[p<minor>]` directly
+    // after the name of an extension, and a `p` after a number is only the
+    // P extension if an underscore comes between them
+    let isa: Isa = "rv64i2p1m2_zicsr2p0_zicbop1p0".parse().unwrap();
+    assert_eq!(
+        isa,
+        Isa::new(Xlen::Rv64)
+            .with(Extension::M)
+            .with(Extension::Zicsr)
+            .with(Extension::Zicbop)
+    );
+    for s in &["rv64i2p", "rv64i2pm", "rv64im_2", "rv64i_zba1p", "rv64i__9"] {
+        assert_eq!(
+            s.parse::<Isa>().unwrap_err(),
+            ParseIsaError::InvalidVersion(s.to_string()),
+            "{}",
+            s
+        );
+    }
+    assert_eq!(
+        "rv64ip".parse::<Isa>().unwrap_err(),
+        ParseIsaError::UnknownExtension("p".to_string())
+    );
+
+    // Extensions bring in the extensions they depend on, and take those
+    // which depend on them away
+    let isa: Isa = "rv64imafd".parse().unwrap();
+    assert!(isa.has(Extension::Zicsr));
+    assert_eq!(isa.to_string(), "rv64imafd_zicsr");
+    // frrm a0
+    assert_eq!(
+        decode_isa(0x00202573, &isa).unwrap().to_string(),
+        "csrrs a0, frm, zero"
+    );
+    let isa = Isa::new(Xlen::Rv64).with(Extension::Zfh);
+    assert_eq!(isa.to_string(), "rv64if_zicsr_zfh_zfhmin");
+    assert_eq!(isa.without(Extension::F).to_string(), "rv64i_zicsr");
+}
+
+#[test]
+fn isa_extensions() {
+    // amoadd.w a0, a2, (a1)
+    let word: u32 = 0x00c5a52f;
+    assert_eq!(decode(word).unwrap_err(), DecodeError::ExtensionDisabled);
+    assert_eq!(*decode_isa(word, &rv32gc()).unwrap().op(), Op::AmoaddW);
+
+    // mul a0, a1, a2
+    let word: u32 = 0x02c58533;
+    assert_eq!(*decode(word).unwrap().op(), Op::Mul);
+    assert_eq!(
+        decode_isa(word, &Isa::new(Xlen::Rv32)).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+
+    // c.mv a0, a1
+    let isa: Isa = "rv32imc".parse().unwrap();
+    assert_eq!(*decode_compressed(0x852e, &isa).unwrap().op(), Op::CMv);
+    assert_eq!(
+        decode_compressed(0x852e, &Isa::rv32im()).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+    // c.fld fa0, 8(a1) needs D
+    assert_eq!(
+        decode_compressed(0x2588, &isa).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+
+    // RV32E only has x0-x15: add a0, a1, a2 is fine but add s2, a1, a2 isn't
+    let isa: Isa = "rv32e".parse().unwrap();
+    assert_eq!(*decode_isa(0x00c58533, &isa).unwrap().op(), Op::Add);
+    assert_eq!(
+        decode_isa(0x00c58933, &isa).unwrap_err(),
+        DecodeError::Reserved
+    );
+}
+
+/*
+   0:   00c58533            add a0,a1,a2
+   4:   fe0a0ee3            beqz    s4,0 <.text>
+   8:   0ff0000f            fence
+   c:   00000073            ecall
+  10:   00100073            ebreak
+  14:   4501                li  a0,0
+*/
+
+#[test]
+fn encode_instruction() {
+    for &word in &[0x00c58533, 0xfe0a0ee3, 0x0ff0000f, 0x00000073, 0x00100073] {
+        let instruction = decode(word).unwrap();
+        assert_eq!(encode(&instruction), Ok(word));
+    }
+
+    let instruction = decode_compressed(0x4501, &rv32gc()).unwrap();
+    assert_eq!(encode(&instruction), Ok(0x4501));
+}
+
+#[test]
+fn encode_round_trip() {
+    let isas = [
+        rv32gc(),
+        rv64gc(),
+        Isa::all(Xlen::Rv32),
+        Isa::all(Xlen::Rv64),
+    ];
+    for word in sample_words() {
+        for isa in &isas {
+            let instruction = match decode_isa(word, isa) {
+                Ok(instruction) => instruction,
+                Err(_) => continue,
+            };
+            assert_eq!(encode(&instruction), Ok(word), "{}", instruction);
+        }
+    }
+}
+
+#[test]
+fn encode_compressed_round_trip() {
+    for isa in &[
+        rv32gc(),
+        rv64gc(),
+        Isa::all(Xlen::Rv32),
+        Isa::all(Xlen::Rv64),
+    ] {
+        for halfword in 0..=0xffff {
+            if halfword & 0b11 == 0b11 {
+                continue;
+            }
+            if let Ok(instruction) = decode_compressed(halfword, isa) {
+                assert_eq!(
+                    encode(&instruction),
+                    Ok(u32::from(halfword)),
+                    "{}",
+                    instruction
+                );
+            }
+        }
+    }
+}
+
+/*
+   0:   00b50463            beq a0,a1,8 <.text+0x8>
+   4:   0800                addi    s0,sp,16
+*/
+
+#[test]
+fn encode_invalid() {
+    let mut instruction = decode(0x00b50463).unwrap();
+    instruction.immediate = 7;
+    assert_eq!(encode(&instruction), Err(EncodeError::MisalignedImmediate));
+    instruction.immediate = 4096;
+    assert_eq!(encode(&instruction), Err(EncodeError::ImmediateOutOfRange));
+    instruction.immediate = -4096;
+    assert_eq!(encode(&instruction), Ok(0x80b50063));
+    instruction.rs2 = Register::Fa1;
+    assert_eq!(encode(&instruction), Err(EncodeError::InvalidRegister));
+
+    let mut instruction = decode_compressed(0x0800, &rv32gc()).unwrap();
+    assert_eq!(encode(&instruction), Ok(0x0800));
+    instruction.immediate = 0;
+    assert_eq!(encode(&instruction), Err(EncodeError::Reserved));
+    instruction.immediate = 16;
+    instruction.rd = Register::A6;
+    assert_eq!(encode(&instruction), Err(EncodeError::InvalidRegister));
+}
+
+#[test]
+fn assemble_instruction() {
+    let instructions = assemble("addi a0, a1, -3").unwrap();
+
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(encode(&instructions[0]), Ok(0xffd58513));
+    assert_eq!(instructions[0], decode(0xffd58513).unwrap());
+}
+
+/*
+   0:   00c58533            add a0,a1,a2
+   4:   01d02283            lw  t0,29(zero)
+   8:   fe552e23            sw  t0,-4(a0)
+   c:   3420a5f3            csrrs   a1,mcause,ra
+  10:   3400d073            csrwi   mscratch,1
+  14:   02b51553            fadd.d  fa0,fa0,fa1,rtz
+  18:   1005272f            lr.w    a4,(a0)
+  1c:   0ce6a7af            amoswap.w.aq    a5,a4,(a3)
+  20:   0230000f            fence   r,rw
+  24:   6505                lui a0,0x1
+  26:   00100073            ebreak
+*/
+
+#[test]
+fn assemble_syntax() {
+    let source = "
+        add x10, x11, x12       # architectural names
+        lw t0, 0x1d(zero)
+        sw t0, -4(a0)
+        csrrs a1, mcause, ra
+        csrrwi zero, 0x340, 0b1
+        fadd.d fa0, fa0, fa1, rtz
+        lr.w a4, (a0)
+        amoswap.w.aq a5, a4, 0(a3)
+        fence r, rw
+        c.lui a0, 1
+        ebreak
+    ";
+
+    assert_eq!(
+        assemble_bytes(source).unwrap(),
+        vec![
+            0x33, 0x85, 0xc5, 0x00, 0x83, 0x22, 0xd0, 0x01, 0x23, 0x2e, 0x55, 0xfe, 0xf3, 0xa5,
+            0x20, 0x34, 0x73, 0xd0, 0x00, 0x34, 0x53, 0x15, 0xb5, 0x02, 0x2f, 0x27, 0x05, 0x10,
+            0xaf, 0xa7, 0xe6, 0x0c, 0x0f, 0x00, 0x30, 0x02, 0x05, 0x65, 0x73, 0x00, 0x10, 0x00,
+        ]
+    );
+}
+
+/*
+   0:   00050263            beqz    a0,4 <skip>
+   4:   fff50513            addi    a0,a0,-1
+   8:   fe051ee3            bnez    a0,4 <skip>
+   c:   ff9ff0ef            jal 4 <skip>
+  10:   d975                beqz    a0,4 <skip>
+*/
+
+#[test]
+fn assemble_labels() {
+    let source = "
+        beq a0, zero, skip
+    loop:
+    skip: addi a0, a0, -1
+        bne a0, zero, loop
+        jal loop
+    end: c.beqz a0, skip
+    ";
+
+    let instructions = assemble(source).unwrap();
+
+    assert_eq!(instructions.len(), 5);
+    assert_eq!(instructions[0].immediate(), 4);
+    assert_eq!(instructions[2].immediate(), -4);
+    assert_eq!(*instructions[3].rd(), Register::Ra);
+    assert_eq!(instructions[3].immediate(), -8);
+    assert_eq!(instructions[4].immediate(), -12);
+    assert_eq!(
+        assemble_bytes(source).unwrap(),
+        vec![
+            0x63, 0x02, 0x05, 0x00, 0x13, 0x05, 0xf5, 0xff, 0xe3, 0x1e, 0x05, 0xfe, 0xef, 0xf0,
+            0x9f, 0xff, 0x75, 0xd9,
+        ]
+    );
+}
+
+#[test]
+fn assemble_errors() {
+    assert_eq!(
+        assemble("frob a0, a1\nadd a0, a1, a2"),
+        Err(AssembleError::UnknownMnemonic(1, "frob".to_string()))
+    );
+    assert_eq!(
+        assemble("\n\nadd a0, a1, a16"),
+        Err(AssembleError::InvalidOperand(3, "a16".to_string()))
+    );
+    assert_eq!(assemble("add a0, a1"), Err(AssembleError::OperandCount(1)));
+    assert_eq!(
+        assemble("j: beq a0, a1, k"),
+        Err(AssembleError::UndefinedLabel(1, "k".to_string()))
+    );
+    assert_eq!(
+        assemble("a:\na:"),
+        Err(AssembleError::DuplicateLabel(2, "a".to_string()))
+    );
+    assert_eq!(
+        assemble("addi a0, a1, 2048"),
+        Err(AssembleError::Encode(1, EncodeError::ImmediateOutOfRange))
+    );
+    assert_eq!(
+        assemble("add.aq a0, a1, a2"),
+        Err(AssembleError::UnknownMnemonic(1, "add.aq".to_string()))
+    );
+    assert_eq!(
+        assemble("add a0, a1, a2").unwrap()[0].to_string(),
+        "add a0, a1, a2"
+    );
+}
+
+#[test]
+fn assemble_for_isa() {
+    let rv32 = rv32gc();
+    assert_eq!(
+        assemble_bytes_isa("ld a0, 8(sp)", &rv32),
+        Err(AssembleError::NotInIsa(1, DecodeError::ExtensionDisabled))
+    );
+    assert_eq!(
+        assemble_isa("add a0, a1, a2\naddw a0, a1, a2", &rv32),
+        Err(AssembleError::NotInIsa(2, DecodeError::ExtensionDisabled))
+    );
+    assert_eq!(
+        assemble_isa("andn a0, a1, a2", &rv64gc()),
+        Err(AssembleError::NotInIsa(1, DecodeError::ExtensionDisabled))
+    );
+    assert_eq!(
+        assemble_isa("c.mv a0, a1", &Isa::rv32im()),
+        Err(AssembleError::NotInIsa(1, DecodeError::ExtensionDisabled))
+    );
+
+    // c.flw is c.ld when XLEN is 64
+    assert_eq!(
+        assemble_bytes_isa("c.flw fa0, 4(a1)", &rv32),
+        Ok(vec![0xc8, 0x61])
+    );
+    assert_eq!(
+        assemble_isa("c.flw fa0, 4(a1)", &rv64gc()),
+        Err(AssembleError::NotInIsa(1, DecodeError::ExtensionDisabled))
+    );
+    assert_eq!(
+        assemble_bytes_isa("ld a0, 8(sp)", &rv64gc()),
+        assemble_bytes("ld a0, 8(sp)")
+    );
+}
+
+#[test]
+fn assemble_display_round_trip() {
+    // RV64GC has every 32-bit instruction RV32GC has, but the other
+    // extensions have ops which only exist for one XLEN
+    let isas = [rv64gc(), Isa::all(Xlen::Rv32), Isa::all(Xlen::Rv64)];
+    for word in sample_words() {
+        for isa in &isas {
+            let instruction = match decode_isa(word, isa) {
+                Ok(instruction) => instruction,
+                Err(_) => continue,
+            };
+            if unprinted_fields(&instruction, word) {
+                continue;
+            }
+            let text = instruction.to_string();
+            assert_eq!(assemble_isa(&text, isa), Ok(vec![instruction]), "{}", text);
+        }
+    }
+    for isa in &[
+        rv32gc(),
+        rv64gc(),
+        Isa::all(Xlen::Rv32),
+        Isa::all(Xlen::Rv64),
+    ] {
+        for halfword in 0..=0xffff {
+            if let Ok(instruction) = decode_compressed(halfword, isa) {
+                let text = instruction.to_string();
+                assert_eq!(assemble_isa(&text, isa), Ok(vec![instruction]), "{}", text);
+            }
+        }
+    }
+}
+
+#[test]
+fn assemble_pseudo_round_trip() {
+    // Every single-instruction alias the alternate form writes assembles
+    // back to the same encoding
+    let rv64 = rv64gc();
+    for word in sample_words() {
+        let instruction = match decode_isa(word, &rv64) {
+            Ok(instruction) => instruction,
+            Err(_) => continue,
+        };
+        if unprinted_fields(&instruction, word) {
+            continue;
+        }
+        let text = format!("{:#}", instruction);
+        assert_eq!(
+            assemble_bytes_isa(&text, &rv64),
+            Ok(word.to_le_bytes().to_vec()),
+            "{}",
+            text
+        );
+    }
+
+    for &(text, word) in &[
+        ("nop", 0x00000013),
+        ("ret", 0x00008067),
+        ("mv a0, a1", 0x00058513),
+        ("li a0, 1", 0x00100513),
+        ("j 8", 0x0080006f),
+        ("beqz a0, 8", 0x00050463),
+        ("csrr a0, mstatus", 0x30002573),
+        ("jr 8(t1)", 0x00830067),
+        ("jalr a0", 0x000500e7),
+    ] {
+        assert_eq!(
+            assemble_bytes(text).unwrap(),
+            u32::to_le_bytes(word).to_vec(),
+            "{}",
+            text
+        );
+    }
+}
+
+/*
+   0:   00000013            nop
+   4:   ffb00513            li  a0,-5
+   8:   00058513            mv  a0,a1
+   c:   fff5c513            not a0,a1
+  10:   40b00533            neg a0,a1
+  14:   40b0053b            negw    a0,a1
+  18:   0005851b            sext.w  a0,a1
+  1c:   0015b513            seqz    a0,a1
+  20:   00b03533            snez    a0,a1
+  24:   0005a533            sltz    a0,a1
+  28:   00b02533            sgtz    a0,a1
+  2c:   20b58553            fmv.s   fa0,fa1
+  30:   22b5a553            fabs.d  fa0,fa1
+  34:   20b59553            fneg.s  fa0,fa1
+  38:   00050463            beqz    a0,40
+  3c:   fea05ce3            blez    a0,34
+  40:   00a04863            bgtz    a0,50
+  44:   0100006f            j   54
+  48:   ffdff0ef            jal 44
+  4c:   00050067            jr  a0
+  50:   008500e7            jalr    8(a0)
+  54:   00008067            ret
+  58:   30002573            csrr    a0,mstatus
+  5c:   30551073            csrw    mtvec,a0
+  60:   30446073            csrsi   mie,8
+  64:   00302573            frcsr   a0
+  68:   00259073            fsrm    a1
+  6c:   0020d573            fsrmi   a0,1
+  70:   c0002573            rdcycle a0
+  74:   c81025f3            rdtimeh a1
+  78:   00c58533            add a0,a1,a2
+*/
+
+#[test]
+fn pseudo() {
+    let cases = [
+        (0x00000013, Some(Pseudo::Nop), "nop"),
+        (0xffb00513, Some(Pseudo::Li), "li a0, -5"),
+        (0x00100013, None, "addi zero, zero, 1"),
+        (0x00058513, Some(Pseudo::Mv), "mv a0, a1"),
+        (0xfff5c513, Some(Pseudo::Not), "not a0, a1"),
+        (0x40b00533, Some(Pseudo::Neg), "neg a0, a1"),
+        (0x40b0053b, Some(Pseudo::Negw), "negw a0, a1"),
+        (0x0005851b, Some(Pseudo::SextW), "sext.w a0, a1"),
+        (0x0015b513, Some(Pseudo::Seqz), "seqz a0, a1"),
+        (0x00b03533, Some(Pseudo::Snez), "snez a0, a1"),
+        (0x0005a533, Some(Pseudo::Sltz), "sltz a0, a1"),
+        (0x00b02533, Some(Pseudo::Sgtz), "sgtz a0, a1"),
+        (0x20b58553, Some(Pseudo::FmvS), "fmv.s fa0, fa1"),
+        (0x22b5a553, Some(Pseudo::FabsD), "fabs.d fa0, fa1"),
+        (0x20b59553, Some(Pseudo::FnegS), "fneg.s fa0, fa1"),
+        (0x00050463, Some(Pseudo::Beqz), "beqz a0, 8"),
+        (0xfea05ce3, Some(Pseudo::Blez), "blez a0, -8"),
+        (0x00a04863, Some(Pseudo::Bgtz), "bgtz a0, 16"),
+        (0x0100006f, Some(Pseudo::J), "j 16"),
+        (0xffdff0ef, Some(Pseudo::Jal), "jal -4"),
+        (0x00050067, Some(Pseudo::Jr), "jr a0"),
+        (0x008500e7, Some(Pseudo::Jalr), "jalr 8(a0)"),
+        (0x00008067, Some(Pseudo::Ret), "ret"),
+        (0x30002573, Some(Pseudo::Csrr), "csrr a0, mstatus"),
+        (0x30551073, Some(Pseudo::Csrw), "csrw mtvec, a0"),
+        (0x30446073, Some(Pseudo::Csrsi), "csrsi mie, 8"),
+        (0x00302573, Some(Pseudo::Frcsr), "frcsr a0"),
+        (0x00259073, Some(Pseudo::Fsrm), "fsrm a1"),
+        (0x0020d573, Some(Pseudo::Fsrmi), "fsrmi a0, 1"),
+        (0xc0002573, Some(Pseudo::Counter(Op::RdCycle)), "rdcycle a0"),
+        (0xc81025f3, Some(Pseudo::Counter(Op::RdTimeH)), "rdtimeh a1"),
+        (0x00c58533, None, "add a0, a1, a2"),
+    ];
+
+    for &(word, ref pseudo, text) in &cases {
+        let instruction = decode_isa(word, &rv64gc()).unwrap();
+        assert_eq!(instruction.pseudo(), *pseudo);
+        assert_eq!(format!("{:#}", instruction), text);
+    }
+
+    // Without the alternate flag instructions keep their own mnemonics
+    let instruction = decode_isa(0xc0002573, &rv64gc()).unwrap();
+    assert_eq!(instruction.to_string(), "csrrs a0, cycle, zero");
+}
+
+/*
+   0:   8082                    ret
+   2:   852e                    mv  a0,a1
+   4:   0505                    addi    a0,a0,1
+*/
+
+#[test]
+fn pseudo_compressed() {
+    let rv64 = rv64gc();
+
+    let instruction = decode_compressed(0x8082, &rv64).unwrap();
+    assert_eq!(instruction.pseudo(), Some(Pseudo::Ret));
+    assert_eq!(format!("{:#}", instruction), "ret");
+
+    let instruction = decode_compressed(0x852e, &rv64).unwrap();
+    assert_eq!(instruction.pseudo(), Some(Pseudo::Mv));
+    assert_eq!(format!("{:#}", instruction), "mv a0, a1");
+
+    let instruction = decode_compressed(0x0505, &rv64).unwrap();
+    assert_eq!(instruction.pseudo(), None);
+    assert_eq!(format!("{:#}", instruction), "addi a0, a0, 1");
+
+    // c.nop with a nonzero immediate is a hint
+    let instruction = decode_compressed(0x0005, &rv64).unwrap();
+    assert_eq!(instruction.pseudo(), None);
+    assert_eq!(format!("{:#}", instruction), "addi zero, zero, 1");
+}
+
+/*
+   0:   00001097            auipc   ra,0x1
+   4:   ff0080e7            jalr    -16(ra) # ff0 <.text+0xff0>
+   8:   00000317            auipc   t1,0x0
+   c:   00830067            jr  8(t1) # 10 <.text+0x10>
+*/
+
+#[test]
+fn pseudo_pair() {
+    let auipc = decode(0x00001097).unwrap();
+    let jalr = decode(0xff0080e7).unwrap();
+    assert_eq!(auipc.pseudo_pair(&jalr), Some((Pseudo::Call, 4080)));
+
+    let auipc = decode(0x00000317).unwrap();
+    let jalr = decode(0x00830067).unwrap();
+    assert_eq!(auipc.pseudo_pair(&jalr), Some((Pseudo::Tail, 8)));
+
+    assert_eq!(jalr.pseudo_pair(&auipc), None);
+    assert_eq!(decode(0x00001097).unwrap().pseudo_pair(&jalr), None);
+
+    // The offset is taken from the auipc, and the jalr sign-extends the
+    // lower part
+    assert_eq!(
+        assemble_bytes("call 4080\ntail 8").unwrap(),
+        vec![
+            0x97, 0x10, 0x00, 0x00, 0xe7, 0x80, 0x00, 0xff, 0x17, 0x03, 0x00, 0x00, 0x67, 0x00,
+            0x83, 0x00,
+        ]
+    );
+    let instructions = assemble("nop\nf: call f\ntail f").unwrap();
+    assert_eq!(instructions.len(), 5);
+    assert_eq!(
+        instructions[1].pseudo_pair(&instructions[2]),
+        Some((Pseudo::Call, 0))
+    );
+    assert_eq!(
+        instructions[3].pseudo_pair(&instructions[4]),
+        Some((Pseudo::Tail, -8))
+    );
+}
+
+/*
+   0:   3b302573            csrr    a0,pmpaddr3
+   4:   b8359073            csrw    mhpmcounter3h,a1
+   8:   7b102573            csrr    a0,dpc
+   c:   c1f02573            csrr    a0,hpmcounter31
+  10:   3af59073            csrw    pmpcfg15,a1
+*/
+
+#[test]
+fn csr() {
+    let cases = [
+        (
+            0x3b302573,
+            "csrrs a0, pmpaddr3, zero",
+            0x3b3,
+            Privilege::Machine,
+            false,
+        ),
+        (
+            0xb8359073,
+            "csrrw zero, mhpmcounter3h, a1",
+            0xb83,
+            Privilege::Machine,
+            false,
+        ),
+        (
+            0x7b102573,
+            "csrrs a0, dpc, zero",
+            0x7b1,
+            Privilege::Debug,
+            false,
+        ),
+        (
+            0xc1f02573,
+            "csrrs a0, hpmcounter31, zero",
+            0xc1f,
+            Privilege::User,
+            true,
+        ),
+        (
+            0x3af59073,
+            "csrrw zero, pmpcfg15, a1",
+            0x3af,
+            Privilege::Machine,
+            false,
+        ),
+    ];
+    for &(word, text, address, privilege, read_only) in &cases {
+        let instruction = decode_isa(word, &rv32gc()).unwrap();
+        assert_eq!(instruction.to_string(), text);
+        let csr = instruction.csr().unwrap();
+        assert_eq!(csr.address(), address);
+        assert_eq!(csr.privilege(), privilege);
+        assert_eq!(csr.is_read_only(), read_only);
+        assert_eq!(Csr::from_name(&csr.name().unwrap()), Some(csr));
+    }
+    // Only the Zicsr instructions have a CSR
+    assert_eq!(decode(0x00c58533).unwrap().csr(), None);
+
+    let sstatus = Csr::from_name("sstatus").unwrap();
+    assert_eq!(sstatus.address(), 0x100);
+    assert_eq!(sstatus.privilege(), Privilege::Supervisor);
+    assert_eq!(
+        Csr::from_address(0x600).unwrap().privilege(),
+        Privilege::Hypervisor
+    );
+    assert!(Csr::from_name("mhartid").unwrap().is_read_only());
+
+    // Numbered CSRs outside their family, and unassigned addresses
+    assert_eq!(Csr::from_name("pmpcfg16"), None);
+    assert_eq!(Csr::from_name("pmpaddr03"), None);
+    assert_eq!(Csr::from_name("hpmcounter2"), None);
+    assert_eq!(Csr::from_address(0x1000), None);
+    let custom = Csr::from_address(0x7c0).unwrap();
+    assert_eq!(custom.name(), None);
+    assert_eq!(custom.to_string(), "0x7c0");
+}
+
+/*
+   0:   00200073            uret
+   4:   10200073            sret
+   8:   30200073            mret
+   c:   7b200073            dret
+  10:   10500073            wfi
+  14:   12b50073            sfence.vma  a0,a1
+  18:   16050073            sinval.vma  a0,zero
+  1c:   18000073            sfence.w.inval
+  20:   18100073            sfence.inval.ir
+*/
+
+#[test]
+fn privileged() {
+    let isa = rv64gc().with(Extension::Svinval);
+    let cases = [
+        (0x00200073, Op::Uret, "uret"),
+        (0x10200073, Op::Sret, "sret"),
+        (0x30200073, Op::Mret, "mret"),
+        (0x7b200073, Op::Dret, "dret"),
+        (0x10500073, Op::Wfi, "wfi"),
+        (0x12b50073, Op::SfenceVma, "sfence.vma a0, a1"),
+        (0x16050073, Op::SinvalVma, "sinval.vma a0, zero"),
+        (0x18000073, Op::SfenceWInval, "sfence.w.inval"),
+        (0x18100073, Op::SfenceInvalIr, "sfence.inval.ir"),
+    ];
+    check_cases(&isa, &cases);
+
+    let sfence = decode(0x12b50073).unwrap();
+    assert_eq!(
+        sfence.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Register(Register::A1)
+        ]
+    );
+    assert_eq!(sfence.flow(), Flow::Sequential);
+
+    let mret = decode(0x30200073).unwrap();
+    assert_eq!(mret.flow(), Flow::TrapReturn);
+    assert_eq!(mret.branch_target(0), None);
+    let mepc = Csr::from_name("mepc").unwrap();
+    let mstatus = Csr::from_name("mstatus").unwrap();
+    assert_eq!(
+        mret.registers_read(),
+        vec![Location::Csr(mepc), Location::Csr(mstatus)]
+    );
+    assert_eq!(mret.registers_written(), vec![Location::Csr(mstatus)]);
+
+    // Svinval must be enabled
+    for &word in &[0x16050073, 0x18000073, 0x18100073] {
+        assert_eq!(
+            decode_isa(word, &rv64gc()).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+
+    // Every field outside the ones listed above is reserved: mret with rd
+    // a0, wfi with rs1 a0, sfence.vma with rd a0, and sret with rs2 x1
+    for &word in &[0x30200573, 0x10550073, 0x12b50573, 0x10300073] {
+        assert_eq!(decode(word).unwrap_err(), DecodeError::Reserved);
+    }
+}
+
+#[test]
+fn assemble_privileged() {
+    let expected = [
+        0x73, 0x00, 0x20, 0x30, // mret
+        0x73, 0x00, 0x50, 0x10, // wfi
+        0x73, 0x00, 0x00, 0x12, // sfence.vma zero, zero
+        0x73, 0x00, 0x05, 0x12, // sfence.vma a0, zero
+        0x73, 0x00, 0xb5, 0x12, // sfence.vma a0, a1
+    ];
+    let source = "
+        mret
+        wfi
+        sfence.vma
+        sfence.vma a0
+        sfence.vma a0, a1
+    ";
+    assert_eq!(assemble_bytes(source).unwrap(), expected.to_vec());
+
+    assert_eq!(
+        assemble("sfence.vma a0, a1, a2").unwrap_err(),
+        AssembleError::OperandCount(1)
+    );
+}
+
+/*
+   0:   22b50073            hfence.vvma a0,a1
+   4:   62b00073            hfence.gvma zero,a1
+   8:   6005c573            hlv.b   a0,(a1)
+   c:   6015c573            hlv.bu  a0,(a1)
+  10:   6405c573            hlv.h   a0,(a1)
+  14:   6415c573            hlv.hu  a0,(a1)
+  18:   6435c573            hlvx.hu a0,(a1)
+  1c:   6805c573            hlv.w   a0,(a1)
+  20:   6815c573            hlv.wu  a0,(a1)
+  24:   6835c573            hlvx.wu a0,(a1)
+  28:   6c05c573            hlv.d   a0,(a1)
+  2c:   62a5c073            hsv.b   a0,(a1)
+  30:   66a5c073            hsv.h   a0,(a1)
+  34:   6aa5c073            hsv.w   a0,(a1)
+  38:   6ea5c073            hsv.d   a0,(a1)
+  3c:   60002573            csrr    a0,hstatus
+  40:   28002573            csrr    a0,vsatp
+*/
+
+#[test]
+fn hypervisor() {
+    let isa = rv64gc().with(Extension::H);
+    let cases = [
+        (0x22b50073, Op::HfenceVvma, "hfence.vvma a0, a1"),
+        (0x62b00073, Op::HfenceGvma, "hfence.gvma zero, a1"),
+        (0x6005c573, Op::HlvB, "hlv.b a0, (a1)"),
+        (0x6015c573, Op::HlvBu, "hlv.bu a0, (a1)"),
+        (0x6405c573, Op::HlvH, "hlv.h a0, (a1)"),
+        (0x6415c573, Op::HlvHu, "hlv.hu a0, (a1)"),
+        (0x6435c573, Op::HlvxHu, "hlvx.hu a0, (a1)"),
+        (0x6805c573, Op::HlvW, "hlv.w a0, (a1)"),
+        (0x6815c573, Op::HlvWu, "hlv.wu a0, (a1)"),
+        (0x6835c573, Op::HlvxWu, "hlvx.wu a0, (a1)"),
+        (0x6c05c573, Op::HlvD, "hlv.d a0, (a1)"),
+        (0x62a5c073, Op::HsvB, "hsv.b a0, (a1)"),
+        (0x66a5c073, Op::HsvH, "hsv.h a0, (a1)"),
+        (0x6aa5c073, Op::HsvW, "hsv.w a0, (a1)"),
+        (0x6ea5c073, Op::HsvD, "hsv.d a0, (a1)"),
+        (0x60002573, Op::Csrrs, "csrrs a0, hstatus, zero"),
+        (0x28002573, Op::Csrrs, "csrrs a0, vsatp, zero"),
+    ];
+    check_cases(&isa, &cases);
+
+    let hlvx = decode_isa(0x6835c573, &isa).unwrap();
+    let access = hlvx.memory_access().unwrap();
+    assert_eq!(access.kind(), AccessKind::Load);
+    assert_eq!(access.width(), 4);
+    assert_eq!(access.extend(), Extend::Zero);
+    assert_eq!(*access.base(), Register::A1);
+    let hsv = decode_isa(0x62a5c073, &isa).unwrap();
+    assert_eq!(hsv.memory_access().unwrap().kind(), AccessKind::Store);
+    assert_eq!(
+        hsv.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Memory {
+                base: Register::A1,
+                offset: 0
+            }
+        ]
+    );
+
+    // H must be enabled, and the doubleword and hlv.wu forms need RV64
+    assert_eq!(
+        decode_isa(0x6005c573, &rv64gc()).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+    let rv32 = rv32gc().with(Extension::H);
+    for &word in &[0x6815c573, 0x6c05c573, 0x6ea5c073] {
+        assert_eq!(
+            decode_isa(word, &rv32).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+
+    // hlv with rs2 2, hsv with rd a0, and hfence.vvma with rd a0
+    for &word in &[0x6025c573, 0x62a5c573, 0x22b50573] {
+        assert_eq!(decode_isa(word, &isa).unwrap_err(), DecodeError::Reserved);
+    }
+}
+
+/*
+   0:   02460457            vadd.vv v8,v4,v12
+   4:   024cb457            vadd.vi v8,v4,-7
+   8:   9644b457            vsll.vi v8,v4,9
+   c:   0045d457            vfadd.vf        v8,v4,fa1,v0.t
+  10:   404cb457            vadc.vim        v8,v4,-7,v0
+  14:   46460457            vmadc.vv        v8,v4,v12
+  18:   b645e457            vmacc.vx        v8,a1,v4
+  1c:   b2460457            vnsrl.wv        v8,v4,v12
+  20:   02462457            vredsum.vs      v8,v4,v12
+  24:   66462457            vmand.mm        v8,v4,v12
+  28:   d245e457            vwaddu.wx       v8,v4,a1
+  2c:   42402557            vmv.x.s a0,v4
+  30:   42401557            vfmv.f.s        fa0,v4
+  34:   5e0db457            vmv.v.i v8,-5
+  38:   9e40b457            vmv2r.v v8,v4
+  3c:   5008a457            vid.v   v8,v0.t
+  40:   4a4b9457            vfncvt.rtz.x.f.w        v8,v4
+  44:   0505f557            vsetvli a0,a1,e32,m1,ta,mu
+  48:   c5287557            vsetivli        a0,16,e32,m4,ta,mu
+  4c:   80c5f557            vsetvl  a0,a1,a2
+*/
+
+#[test]
+fn vector() {
+    let isa = rv64gc().with(Extension::V);
+    let cases = [
+        (0x02460457, VectorOp::Vadd, "vadd.vv v8, v4, v12"),
+        (0x024cb457, VectorOp::Vadd, "vadd.vi v8, v4, -7"),
+        (0x9644b457, VectorOp::Vsll, "vsll.vi v8, v4, 9"),
+        (0x0045d457, VectorOp::Vfadd, "vfadd.vf v8, v4, fa1, v0.t"),
+        (0x404cb457, VectorOp::Vadc, "vadc.vim v8, v4, -7, v0"),
+        (0x46460457, VectorOp::Vmadc, "vmadc.vv v8, v4, v12"),
+        (0xb645e457, VectorOp::Vmacc, "vmacc.vx v8, a1, v4"),
+        (0xb2460457, VectorOp::Vnsrl, "vnsrl.wv v8, v4, v12"),
+        (0x02462457, VectorOp::Vredsum, "vredsum.vs v8, v4, v12"),
+        (0x66462457, VectorOp::Vmand, "vmand.mm v8, v4, v12"),
+        (0xd245e457, VectorOp::Vwaddu, "vwaddu.wx v8, v4, a1"),
+        (0x42402557, VectorOp::VmvXS, "vmv.x.s a0, v4"),
+        (0x42401557, VectorOp::VfmvFS, "vfmv.f.s fa0, v4"),
+        (0x5e0db457, VectorOp::VmvVI, "vmv.v.i v8, -5"),
+        (0x9e40b457, VectorOp::Vmv2r, "vmv2r.v v8, v4"),
+        (0x5008a457, VectorOp::Vid, "vid.v v8, v0.t"),
+        (
+            0x4a4b9457,
+            VectorOp::VfncvtRtzXFW,
+            "vfncvt.rtz.x.f.w v8, v4",
+        ),
+        (
+            0x0505f557,
+            VectorOp::Vsetvli,
+            "vsetvli a0, a1, e32, m1, ta, mu",
+        ),
+        (
+            0xc5287557,
+            VectorOp::Vsetivli,
+            "vsetivli a0, 16, e32, m4, ta, mu",
+        ),
+        (0x80c5f557, VectorOp::Vsetvl, "vsetvl a0, a1, a2"),
+    ];
+    for &(word, op, text) in &cases {
+        let instruction = decode_isa(word, &isa).unwrap();
+        assert_eq!(*instruction.op(), Op::Vector(op));
+        assert_eq!(instruction.to_string(), text);
+        assert_eq!(encode(&instruction).unwrap(), word);
+        assert_eq!(assemble_bytes(text).unwrap(), word.to_le_bytes().to_vec());
+    }
+
+    let vfadd = decode_isa(0x0045d457, &isa).unwrap();
+    assert!(vfadd.is_masked());
+    assert_eq!(vfadd.vector_form(), Some(VectorForm::Vf));
+    assert_eq!(
+        vfadd.operands(),
+        vec![
+            Operand::Register(Register::V8),
+            Operand::Register(Register::V4),
+            Operand::Register(Register::Fa1),
+            Operand::Mask,
+        ]
+    );
+    assert_eq!(
+        vfadd.registers_read(),
+        vec![
+            Location::Register(Register::Fa1),
+            Location::Register(Register::V4),
+            Location::Register(Register::V0),
+        ]
+    );
+
+    // The multiply-adds accumulate into vd
+    let vmacc = decode_isa(0xb645e457, &isa).unwrap();
+    assert_eq!(
+        vmacc.registers_read(),
+        vec![
+            Location::Register(Register::A1),
+            Location::Register(Register::V4),
+            Location::Register(Register::V8),
+        ]
+    );
+
+    let vsetivli = decode_isa(0xc5287557, &isa).unwrap();
+    let vtype = vsetivli.vtype().unwrap();
+    assert_eq!(vtype.sew(), 32);
+    assert_eq!(vtype.lmul(), Lmul::M4);
+    assert!(vtype.tail_agnostic());
+    assert!(!vtype.mask_agnostic());
+    assert_eq!(
+        vsetivli.registers_written(),
+        vec![
+            Location::Register(Register::A0),
+            Location::Csr(Csr::from_name("vl").unwrap()),
+            Location::Csr(Csr::from_name("vtype").unwrap()),
+        ]
+    );
+
+    assert_eq!(
+        decode_isa(0x02460457, &rv64gc()).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+
+    // vadc.vim without v0, a masked vmand.mm, a masked vmv2r.v, a reserved
+    // SEW, and vsetvli with the bits above vtype set
+    for &word in &[0x424cb457, 0x64462457, 0x9c40b457, 0x0205f557, 0x1505f557] {
+        assert_eq!(decode_isa(word, &isa).unwrap_err(), DecodeError::Reserved);
+    }
+}
+
+/*
+   0:   02056407            vle32.v v8,(a0)
+   4:   20055407            vlseg2e16.v     v8,(a0),v0.t
+   8:   03057407            vle64ff.v       v8,(a0)
+   c:   0ab50407            vlse8.v v8,(a0),a1
+  10:   04456407            vluxei32.v      v8,(a0),v4,v0.t
+  14:   22856407            vl2re32.v       v8,(a0)
+  18:   02b50407            vlm.v   v8,(a0)
+  1c:   02050427            vse8.v  v8,(a0)
+  20:   e8b57427            vssseg8e64.v    v8,(a0),a1,v0.t
+  24:   0e455427            vsoxei16.v      v8,(a0),v4
+  28:   62850427            vs4r.v  v8,(a0)
+  2c:   02b50427            vsm.v   v8,(a0)
+*/
+
+#[test]
+fn vector_memory() {
+    let isa = rv64gc().with(Extension::V);
+    let cases = [
+        (0x02056407, VectorOp::Vle, 32, 1, "vle32.v v8, (a0)"),
+        (
+            0x20055407,
+            VectorOp::Vle,
+            16,
+            2,
+            "vlseg2e16.v v8, (a0), v0.t",
+        ),
+        (0x03057407, VectorOp::Vleff, 64, 1, "vle64ff.v v8, (a0)"),
+        (0x0ab50407, VectorOp::Vlse, 8, 1, "vlse8.v v8, (a0), a1"),
+        (
+            0x04456407,
+            VectorOp::Vluxei,
+            32,
+            1,
+            "vluxei32.v v8, (a0), v4, v0.t",
+        ),
+        (0x22856407, VectorOp::Vlr, 32, 2, "vl2re32.v v8, (a0)"),
+        (0x02b50407, VectorOp::Vlm, 8, 1, "vlm.v v8, (a0)"),
+        (0x02050427, VectorOp::Vse, 8, 1, "vse8.v v8, (a0)"),
+        (
+            0xe8b57427,
+            VectorOp::Vsse,
+            64,
+            8,
+            "vssseg8e64.v v8, (a0), a1, v0.t",
+        ),
+        (
+            0x0e455427,
+            VectorOp::Vsoxei,
+            16,
+            1,
+            "vsoxei16.v v8, (a0), v4",
+        ),
+        (0x62850427, VectorOp::Vsr, 8, 4, "vs4r.v v8, (a0)"),
+        (0x02b50427, VectorOp::Vsm, 8, 1, "vsm.v v8, (a0)"),
+    ];
+    for &(word, op, eew, nf, text) in &cases {
+        let instruction = decode_isa(word, &isa).unwrap();
+        assert_eq!(*instruction.op(), Op::Vector(op));
+        assert_eq!(instruction.eew(), eew);
+        assert_eq!(instruction.nf(), nf);
+        assert_eq!(instruction.to_string(), text);
+        assert_eq!(encode(&instruction).unwrap(), word);
+        assert_eq!(assemble_bytes(text).unwrap(), word.to_le_bytes().to_vec());
+    }
+
+    let vle = decode_isa(0x02056407, &isa).unwrap();
+    let access = vle.memory_access().unwrap();
+    assert_eq!(access.kind(), AccessKind::Load);
+    assert_eq!(access.width(), 4);
+    assert_eq!(*access.base(), Register::A0);
+    // The eew of an indexed access is the width of its indices, not its
+    // elements
+    let vluxei = decode_isa(0x06b56087, &isa).unwrap();
+    assert_eq!(vluxei.eew(), 32);
+    let access = vluxei.memory_access().unwrap();
+    assert_eq!(access.kind(), AccessKind::Load);
+    assert_eq!(access.width(), 0);
+    let vsse = decode_isa(0xe8b57427, &isa).unwrap();
+    assert_eq!(vsse.memory_access().unwrap().kind(), AccessKind::Store);
+    assert_eq!(vsse.registers_written(), Vec::new());
+    assert_eq!(
+        vsse.registers_read(),
+        vec![
+            Location::Register(Register::A0),
+            Location::Register(Register::A1),
+            Location::Register(Register::V8),
+            Location::Register(Register::V0),
+        ]
+    );
+
+    // The scalar floating point widths are unaffected
+    assert_eq!(*decode_isa(0x00052407, &isa).unwrap().op(), Op::Flw);
+    assert_eq!(
+        decode_isa(0x02056407, &rv64gc()).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+
+    // vlm.v with two fields, and mew set
+    for &word in &[0x22b50407, 0x12056407] {
+        assert_eq!(decode_isa(word, &isa).unwrap_err(), DecodeError::Reserved);
+    }
+}
+
+#[test]
+fn assemble_vector() {
+    // vsetvli defaults to m1, tu and mu
+    assert_eq!(
+        assemble("vsetvli t0, a0, e8").unwrap(),
+        assemble("vsetvli t0, a0, e8, m1, tu, mu").unwrap()
+    );
+    assert_eq!(
+        assemble("vsetvli t0, a0, e8, ta").unwrap()[0]
+            .vtype()
+            .unwrap()
+            .to_string(),
+        "e8, m1, ta, mu"
+    );
+    assert_eq!(
+        assemble("vsetvli t0, a0, e128").unwrap_err(),
+        AssembleError::InvalidOperand(1, "e128".to_string())
+    );
+    // Whole register moves cannot be masked
+    assert_eq!(
+        assemble("vmv2r.v v8, v4, v0.t").unwrap_err(),
+        AssembleError::OperandCount(1)
+    );
+    assert_eq!(
+        assemble("vadc.vvm v8, v4, v12, v1").unwrap_err(),
+        AssembleError::InvalidOperand(1, "v1".to_string())
+    );
+    assert_eq!(
+        assemble("vadd.vv a0, v4, v12").unwrap_err(),
+        AssembleError::Encode(1, EncodeError::InvalidRegister)
+    );
+    assert_eq!(
+        assemble("vadd.vf v8, v4, fa0").unwrap_err(),
+        AssembleError::UnknownMnemonic(1, "vadd.vf".to_string())
+    );
+    assert_eq!(
+        assemble("vlseg9e8.v v8, (a0)").unwrap_err(),
+        AssembleError::UnknownMnemonic(1, "vlseg9e8.v".to_string())
+    );
+}
+
+/*
+   0:   2125a533            sh1add  a0,a1,s2
+   4:   2125c53b            sh2add.uw       a0,a1,s2
+   8:   0a05951b            slli.uw a0,a1,0x20
+   c:   4125f533            andn    a0,a1,s2
+  10:   4125c533            xnor    a0,a1,s2
+  14:   600d9e13            clz     t3,s11
+  18:   602d9e1b            cpopw   t3,s11
+  1c:   0b25f533            maxu    a0,a1,s2
+  20:   604d9e13            sext.b  t3,s11
+  24:   080dce3b            zext.h  t3,s11
+  28:   61259533            rol     a0,a1,s2
+  2c:   63f5d513            rori    a0,a1,0x3f
+  30:   61f5d51b            roriw   a0,a1,0x1f
+  34:   287dde13            orc.b   t3,s11
+  38:   6b8dde13            rev8    t3,s11
+  3c:   0b25b533            clmulh  a0,a1,s2
+  40:   4925d533            bext    a0,a1,s2
+  44:   2bf59513            bseti   a0,a1,0x3f
+  48:   48559513            bclri   a0,a1,0x5
+*/
+
+#[test]
+fn bit_manipulation() {
+    let isa = rv64gc()
+        .with(Extension::Zba)
+        .with(Extension::Zbb)
+        .with(Extension::Zbc)
+        .with(Extension::Zbs);
+    let cases = [
+        (0x2125a533, Op::Sh1add, "sh1add a0, a1, s2"),
+        (0x2125c53b, Op::Sh2addUw, "sh2add.uw a0, a1, s2"),
+        (0x0a05951b, Op::SlliUw, "slli.uw a0, a1, 0x20"),
+        (0x4125f533, Op::Andn, "andn a0, a1, s2"),
+        (0x4125c533, Op::Xnor, "xnor a0, a1, s2"),
+        (0x600d9e13, Op::Clz, "clz t3, s11"),
+        (0x602d9e1b, Op::Cpopw, "cpopw t3, s11"),
+        (0x0b25f533, Op::Maxu, "maxu a0, a1, s2"),
+        (0x604d9e13, Op::SextB, "sext.b t3, s11"),
+        (0x080dce3b, Op::ZextH, "zext.h t3, s11"),
+        (0x61259533, Op::Rol, "rol a0, a1, s2"),
+        (0x63f5d513, Op::Rori, "rori a0, a1, 0x3f"),
+        (0x61f5d51b, Op::Roriw, "roriw a0, a1, 0x1f"),
+        (0x287dde13, Op::OrcB, "orc.b t3, s11"),
+        (0x6b8dde13, Op::Rev8, "rev8 t3, s11"),
+        (0x0b25b533, Op::Clmulh, "clmulh a0, a1, s2"),
+        (0x4925d533, Op::Bext, "bext a0, a1, s2"),
+        (0x2bf59513, Op::Bseti, "bseti a0, a1, 0x3f"),
+        (0x48559513, Op::Bclri, "bclri a0, a1, 0x5"),
+    ];
+    check_cases(&isa, &cases);
+
+    let clz = decode_isa(0x600d9e13, &isa).unwrap();
+    assert_eq!(
+        clz.operands(),
+        vec![
+            Operand::Register(Register::T3),
+            Operand::Register(Register::S11)
+        ]
+    );
+    assert_eq!(
+        clz.registers_read(),
+        vec![Location::Register(Register::S11)]
+    );
+
+    let zext_w = decode_isa(0x0805853b, &isa).unwrap();
+    assert_eq!(zext_w.pseudo(), Some(Pseudo::ZextW));
+    assert_eq!(format!("{:#}", zext_w), "zext.w a0, a1");
+
+    // rev8 and zext.h are encoded differently when XLEN is 32, and
+    // round-trip through the XLEN they were decoded for
+    let rv32 = rv32gc().with(Extension::Zbb).with(Extension::Zbs);
+    for &(word, ref op) in &[(0x698dde13, Op::Rev8), (0x080dce33, Op::ZextH)] {
+        let instruction = decode_isa(word, &rv32).unwrap();
+        assert_eq!(instruction.op(), op);
+        assert_eq!(instruction.xlen(), Some(Xlen::Rv32));
+        assert_eq!(encode(&instruction).unwrap(), word);
+        let text = instruction.to_string();
+        assert_eq!(assemble_isa(&text, &rv32), Ok(vec![instruction]));
+        assert_eq!(
+            assemble_bytes_isa(&text, &rv32).unwrap(),
+            word.to_le_bytes().to_vec()
+        );
+    }
+    assert_eq!(
+        decode_isa(0x6b8dde13, &rv32).unwrap_err(),
+        DecodeError::Reserved
+    );
+    assert_eq!(
+        decode_isa(0x698dde13, &isa).unwrap_err(),
+        DecodeError::Reserved
+    );
+
+    // Shift amounts over 31 and the word forms need RV64
+    for &word in &[0x2bf59513, 0x61f5d51b, 0x2125c53b] {
+        assert_eq!(
+            decode_isa(word, &rv32).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+
+    // Each extension must be enabled
+    for &word in &[0x2125a533, 0x600d9e13, 0x0b25b533, 0x4925d533] {
+        assert_eq!(
+            decode_isa(word, &rv64gc()).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+
+    // clz with a nonzero selector above it
+    assert_eq!(
+        decode_isa(0x603d9e13, &isa).unwrap_err(),
+        DecodeError::Reserved
+    );
+    // pack, which is Zbkb rather than Zbb
+    assert_eq!(
+        decode_isa(0x0925c533, &isa).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+}
+
+/*
+   0:   0925c533            pack    a0,a1,s2
+   4:   0925c53b            packw   a0,a1,s2
+   8:   687dde13            brev8   t3,s11
+   c:   2925c533            xperm8  a0,a1,s2
+  10:   33258533            aes64es a0,a1,s2
+  14:   31a59513            aes64ks1i       a0,a1,0xa
+  18:   300d9e13            aes64im t3,s11
+  1c:   102d9e13            sha256sig0      t3,s11
+  20:   105d9e13            sha512sum1      t3,s11
+  24:   b1258533            sm4ed   a0,a1,s2,2
+  28:   108d9e13            sm3p0   t3,s11
+*/
+
+#[test]
+fn cryptography() {
+    let isa = rv64gc()
+        .with(Extension::Zbkb)
+        .with(Extension::Zbkc)
+        .with(Extension::Zbkx)
+        .with(Extension::Zknd)
+        .with(Extension::Zkne)
+        .with(Extension::Zknh)
+        .with(Extension::Zksed)
+        .with(Extension::Zksh);
+    let cases = [
+        (0x0925c533, Op::Pack, "pack a0, a1, s2"),
+        (0x0925c53b, Op::Packw, "packw a0, a1, s2"),
+        (0x687dde13, Op::Brev8, "brev8 t3, s11"),
+        (0x2925c533, Op::Xperm8, "xperm8 a0, a1, s2"),
+        (0x33258533, Op::Aes64es, "aes64es a0, a1, s2"),
+        (0x31a59513, Op::Aes64ks1i, "aes64ks1i a0, a1, 10"),
+        (0x300d9e13, Op::Aes64im, "aes64im t3, s11"),
+        (0x102d9e13, Op::Sha256sig0, "sha256sig0 t3, s11"),
+        (0x105d9e13, Op::Sha512sum1, "sha512sum1 t3, s11"),
+        (0xb1258533, Op::Sm4ed, "sm4ed a0, a1, s2, 2"),
+        (0x108d9e13, Op::Sm3p0, "sm3p0 t3, s11"),
+    ];
+    check_cases(&isa, &cases);
+
+    // The RV32 forms
+    let rv32 = rv32gc()
+        .with(Extension::Zbkb)
+        .with(Extension::Zkne)
+        .with(Extension::Zknh);
+    let cases = [
+        (0xe3258533, Op::Aes32esi, "aes32esi a0, a1, s2, 3"),
+        (0x53258533, Op::Sha512sum1r, "sha512sum1r a0, a1, s2"),
+        (0x08fd9e13, Op::Zip, "zip t3, s11"),
+        (0x08fdde13, Op::Unzip, "unzip t3, s11"),
+        (0x0805c533, Op::Pack, "pack a0, a1, zero"),
+    ];
+    check_cases(&rv32, &cases);
+
+    // The byte select and round number are immediate operands
+    let aes32esi = decode_isa(0xe3258533, &rv32).unwrap();
+    assert_eq!(aes32esi.immediate(), 3);
+    assert_eq!(
+        aes32esi.operands(),
+        vec![
+            Operand::Register(Register::A0),
+            Operand::Register(Register::A1),
+            Operand::Register(Register::S2),
+            Operand::UnsignedImmediate(3)
+        ]
+    );
+    let aes64ks1i = decode_isa(0x31a59513, &isa).unwrap();
+    assert_eq!(aes64ks1i.immediate(), 10);
+    let mut instruction = aes64ks1i.clone();
+    instruction.immediate = 11;
+    assert_eq!(
+        encode(&instruction).unwrap_err(),
+        EncodeError::ImmediateOutOfRange
+    );
+
+    // Round numbers above 0xa are reserved
+    assert_eq!(
+        decode_isa(0x31b59513, &isa).unwrap_err(),
+        DecodeError::Reserved
+    );
+
+    // The RV32 forms are disabled on RV64, as the RV64 forms are on RV32
+    for &word in &[0xe3258533, 0x53258533, 0x08fd9e13, 0x08fdde13] {
+        assert_eq!(
+            decode_isa(word, &isa).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+    let rv32 = rv32gc().with(Extension::Zkne).with(Extension::Zknh);
+    for &word in &[0x33258533, 0x105d9e13] {
+        assert_eq!(
+            decode_isa(word, &rv32).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+
+    // Zbkb and Zbkc include some of the Zbb and Zbc ops, and the AES key
+    // schedule ops are part of both Zknd and Zkne
+    let rol = 0x61259533;
+    assert_eq!(
+        *decode_isa(rol, &rv64gc().with(Extension::Zbkb))
+            .unwrap()
+            .op(),
+        Op::Rol
+    );
+    assert_eq!(
+        *decode_isa(0x0b259533, &rv64gc().with(Extension::Zbkc))
+            .unwrap()
+            .op(),
+        Op::Clmul
+    );
+    assert_eq!(
+        *decode_isa(0x31a59513, &rv64gc().with(Extension::Zkne))
+            .unwrap()
+            .op(),
+        Op::Aes64ks1i
+    );
+    for &(word, extension) in &[
+        (rol, Extension::Zbkx),
+        (0x0b25a533, Extension::Zbkc),
+        (0x33258533, Extension::Zknd),
+        (0x102d9e13, Extension::Zksh),
+        (0xb1258533, Extension::Zknh),
+    ] {
+        assert_eq!(
+            decode_isa(word, &rv64gc().with(extension)).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+
+    // The RV64 forms need RV64
+    for &word in &[0x0925c53b, 0x33258533, 0x300d9e13, 0x105d9e13] {
+        assert_eq!(
+            decode_isa(word, &rv32).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+}
+
+/*
+   0:   04c5f553            fadd.h  fa0,fa1,fa2
+   4:   40258553            fcvt.s.h        fa0,fa1
+   8:   00059507            flh     fa0,0(a1)
+   c:   01014507            flq     fa0,16(sp)
+  10:   f0108553            fli.s   fa0,min
+  14:   f2180553            fli.d   fa0,1.0
+  18:   28c5a553            fminm.s fa0,fa1,fa2
+  1c:   c2859553            fcvtmod.w.d     a0,fa1,rtz
+  20:   a0c5c553            fleq.s  a0,fa1,fa2
+  24:   4645f553            fround.q        fa0,fa1
+*/
+
+#[test]
+fn floating_point_extensions() {
+    let isa = rv64gc()
+        .with(Extension::Q)
+        .with(Extension::Zfh)
+        .with(Extension::Zfa);
+    let cases = [
+        (
+            0x04c5f553,
+            Op::FaddH,
+            "fadd.h fa0, fa1, fa2",
+            FloatFormat::H,
+        ),
+        (0x40258553, Op::FcvtSH, "fcvt.s.h fa0, fa1", FloatFormat::S),
+        (0x4435f553, Op::FcvtHQ, "fcvt.h.q fa0, fa1", FloatFormat::H),
+        (0xd6250553, Op::FcvtQL, "fcvt.q.l fa0, a0", FloatFormat::Q),
+        (0xe4058553, Op::FmvXH, "fmv.x.h a0, fa1", FloatFormat::H),
+        (0x00059507, Op::Flh, "flh fa0, 0(a1)", FloatFormat::H),
+        (0x00a59527, Op::Fsh, "fsh fa0, 10(a1)", FloatFormat::H),
+        (0x01014507, Op::Flq, "flq fa0, 16(sp)", FloatFormat::Q),
+        (0xf0108553, Op::FliS, "fli.s fa0, min", FloatFormat::S),
+        (0xf2180553, Op::FliD, "fli.d fa0, 1.0", FloatFormat::D),
+        (0xf41600d3, Op::FliH, "fli.h ft1, 0.5", FloatFormat::H),
+        (0xf61b80d3, Op::FliQ, "fli.q ft1, 4.0", FloatFormat::Q),
+        (
+            0x28c5a553,
+            Op::FminmS,
+            "fminm.s fa0, fa1, fa2",
+            FloatFormat::S,
+        ),
+        (
+            0x2e55a553,
+            Op::FminmQ,
+            "fminm.q fa0, fa1, ft5",
+            FloatFormat::Q,
+        ),
+        (
+            0xc2859553,
+            Op::FcvtmodWD,
+            "fcvtmod.w.d a0, fa1, rtz",
+            FloatFormat::D,
+        ),
+        (0xa0c5c553, Op::FleqS, "fleq.s a0, fa1, fa2", FloatFormat::S),
+        (0x4645f553, Op::FroundQ, "fround.q fa0, fa1", FloatFormat::Q),
+        (
+            0x4445b553,
+            Op::FroundH,
+            "fround.h fa0, fa1, rup",
+            FloatFormat::H,
+        ),
+    ];
+    for &(word, ref op, text, format) in &cases {
+        check_cases(&isa, &[(word, op.clone(), text)]);
+        let instruction = decode_isa(word, &isa).unwrap();
+        assert_eq!(instruction.float_format(), Some(format));
+    }
+    assert_eq!(decode_isa(0x00c58533, &isa).unwrap().float_format(), None);
+
+    // The fli immediate is an index into the constant table
+    let fli = decode_isa(0xf2180553, &isa).unwrap();
+    assert_eq!(
+        fli.operands(),
+        vec![
+            Operand::Register(Register::Fa0),
+            Operand::FloatImmediate(16)
+        ]
+    );
+    assert_eq!(
+        assemble_bytes("fli.d fa0, 1").unwrap(),
+        vec![0x53, 0x05, 0x18, 0xf2]
+    );
+    assert_eq!(
+        assemble_bytes("fli.s fa0, 0.1").unwrap_err(),
+        AssembleError::InvalidOperand(1, "0.1".to_string())
+    );
+
+    // fcvtmod.w.d always truncates
+    assert_eq!(
+        assemble_bytes("fcvtmod.w.d a0, fa1, rne").unwrap_err(),
+        AssembleError::InvalidOperand(1, "rne".to_string())
+    );
+
+    // The paired moves only exist on RV32
+    let rv32 = rv32gc().with(Extension::Zfa);
+    let cases = [
+        (0xe2158553, Op::FmvhXD, "fmvh.x.d a0, fa1"),
+        (0xb2c58553, Op::FmvpDX, "fmvp.d.x fa0, a1, a2"),
+    ];
+    check_cases(&rv32, &cases);
+    for &(word, _, _) in &cases {
+        assert_eq!(
+            decode_isa(word, &rv64gc().with(Extension::Zfa)).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+
+    // Zfhmin only provides the loads, stores, moves and conversions
+    let zfhmin = rv64gc().with(Extension::Zfhmin);
+    for &word in &[0x00059507, 0x00a59527, 0x40258553, 0xe4058553] {
+        assert!(decode_isa(word, &zfhmin).is_ok());
+    }
+    for &word in &[0x04c5f553, 0xf41600d3] {
+        assert_eq!(
+            decode_isa(word, &zfhmin).unwrap_err(),
+            DecodeError::ExtensionDisabled
+        );
+    }
+    assert_eq!(
+        decode_isa(0x01014507, &rv64gc()).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+}
+
+/*
+   0:   0015200f            cbo.clean       (a0)
+   4:   0025200f            cbo.flush       (a0)
+   8:   0005200f            cbo.inval       (a0)
+   c:   0041200f            cbo.zero        (sp)
+  10:   02156013            prefetch.r      32(a0)
+  14:   fc316013            prefetch.w      -64(sp)
+  18:   0100000f            pause
+  1c:   00200033            ntl.p1
+  20:   00500033            ntl.all
+*/
+
+#[test]
+fn cache_block_and_hints() {
+    let isa: Isa = "rv64gc_zicbom_zicbop_zicboz_zihintntl_zihintpause"
+        .parse()
+        .unwrap();
+    let cases = [
+        (0x0015200f, Op::CboClean, "cbo.clean (a0)"),
+        (0x0025200f, Op::CboFlush, "cbo.flush (a0)"),
+        (0x0005200f, Op::CboInval, "cbo.inval (a0)"),
+        (0x0041200f, Op::CboZero, "cbo.zero (sp)"),
+        (0x02056013, Op::PrefetchI, "prefetch.i 32(a0)"),
+        (0x02156013, Op::PrefetchR, "prefetch.r 32(a0)"),
+        (0xfc316013, Op::PrefetchW, "prefetch.w -64(sp)"),
+        (0x0100000f, Op::Pause, "pause"),
+        (0x00200033, Op::NtlP1, "ntl.p1"),
+        (0x00300033, Op::NtlPall, "ntl.pall"),
+        (0x00400033, Op::NtlS1, "ntl.s1"),
+        (0x00500033, Op::NtlAll, "ntl.all"),
+    ];
+    check_cases(&isa, &cases);
+
+    // Hints read no registers
+    let pause = decode_isa(0x0100000f, &isa).unwrap();
+    assert!(pause.operands().is_empty());
+    assert!(decode_isa(0x00200033, &isa)
+        .unwrap()
+        .registers_read()
+        .is_empty());
+    let prefetch = decode_isa(0x02156013, &isa).unwrap();
+    assert_eq!(
+        prefetch.registers_read(),
+        vec![Location::Register(Register::A0)]
+    );
+    assert!(prefetch.registers_written().is_empty());
+    assert_eq!(prefetch.memory_access(), None);
+
+    // cbo.zero stores to the cache block holding the address
+    let zero = decode_isa(0x0041200f, &isa)
+        .unwrap()
+        .memory_access()
+        .unwrap();
+    assert_eq!(zero.kind(), AccessKind::Store);
+    assert_eq!(*zero.base(), Register::Sp);
+    assert_eq!(zero.displacement(), 0);
+    assert_eq!(zero.width(), 0);
+    assert!(zero.writes());
+    assert_eq!(decode_isa(0x0015200f, &isa).unwrap().memory_access(), None);
+
+    // The compressed non-temporal hints
+    let c_ntl = decode_compressed(0x900a, &isa).unwrap();
+    assert_eq!(*c_ntl.op(), Op::CNtlP1);
+    assert_eq!(c_ntl.to_string(), "c.ntl.p1");
+    assert_eq!(format!("{:#}", c_ntl), "ntl.p1");
+    assert_eq!(assemble_bytes("c.ntl.all").unwrap(), vec![0x16, 0x90]);
+
+    // Without their extensions, the hints are their base instructions
+    let rv64 = rv64gc();
+    for &(word, ref op) in &[
+        (0x02156013, Op::Ori),
+        (0x0100000f, Op::Fence),
+        (0x00200033, Op::Add),
+    ] {
+        assert_eq!(decode_isa(word, &rv64).unwrap().op(), op);
+    }
+    assert_eq!(*decode_compressed(0x900a, &rv64).unwrap().op(), Op::CAdd);
+
+    // Other ori hints and fences are not prefetches or pause
+    assert_eq!(*decode_isa(0x02256013, &isa).unwrap().op(), Op::Ori);
+    assert_eq!(*decode_isa(0x0110000f, &isa).unwrap().op(), Op::Fence);
+    assert_eq!(*decode_isa(0x00600033, &isa).unwrap().op(), Op::Add);
+
+    // The cache-block operations are not hints
+    assert_eq!(
+        decode_isa(0x0015200f, &rv64).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+    assert_eq!(
+        decode_isa(0x0045200f, &rv64.with(Extension::Zicbom)).unwrap_err(),
+        DecodeError::ExtensionDisabled
+    );
+    assert_eq!(
+        decode_isa(0x0085200f, &isa).unwrap_err(),
+        DecodeError::Reserved
+    );
+    assert_eq!(
+        decode_isa(0x0015258f, &isa).unwrap_err(),
+        DecodeError::Reserved
+    );
+
+    // Prefetch offsets are multiples of 32
+    assert_eq!(
+        assemble_bytes("prefetch.i 33(a0)").unwrap_err(),
+        AssembleError::Encode(1, EncodeError::MisalignedImmediate)
+    );
+}
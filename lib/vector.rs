@@ -0,0 +1,1545 @@
+use std::fmt;
+
+use decoder::{funct3, funct7, rd, require, rs1, rs2};
+use error::DecodeError;
+use isa::{Extension, Isa};
+use {AccessKind, Instruction, Op, Operand, Register};
+
+// The V extension adds the OP-V major opcode, and takes the widths of
+// LOAD-FP and STORE-FP which the scalar floating point loads and stores
+// leave unused. OP-V is split by funct3 into the configuration instructions
+// and six arithmetic groups, by where the second source comes from and
+// whether the operation is integer (OPI), integer multiply and mask (OPM) or
+// floating point (OPF). funct6 selects the operation within a group, and
+// bit 25, vm, is clear when the instruction is masked by v0.
+//
+// Each instruction's operands are described once, as a list of `Slot`s,
+// which the decoder, encoder, assembler and `Display` all follow.
+
+const OP_V: u32 = 0b1010111;
+const LOAD_FP: u32 = 0b0000111;
+const STORE_FP: u32 = 0b0100111;
+
+const OPIVV: u32 = 0b000;
+const OPFVV: u32 = 0b001;
+const OPMVV: u32 = 0b010;
+const OPIVI: u32 = 0b011;
+const OPIVX: u32 = 0b100;
+const OPFVF: u32 = 0b101;
+const OPMVX: u32 = 0b110;
+const OPCFG: u32 = 0b111;
+
+/// An instruction from the V extension, held in `Op::Vector`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VectorOp {
+    // Integer arithmetic, by funct6 in the OPIVV, OPIVX and OPIVI groups
+    Vadd,
+    Vsub,
+    Vrsub,
+    Vminu,
+    Vmin,
+    Vmaxu,
+    Vmax,
+    Vand,
+    Vor,
+    Vxor,
+    Vrgather,
+    Vrgatherei16,
+    Vslideup,
+    Vslidedown,
+    Vadc,
+    Vmadc,
+    Vsbc,
+    Vmsbc,
+    Vmerge,
+    Vmseq,
+    Vmsne,
+    Vmsltu,
+    Vmslt,
+    Vmsleu,
+    Vmsle,
+    Vmsgtu,
+    Vmsgt,
+    Vsaddu,
+    Vsadd,
+    Vssubu,
+    Vssub,
+    Vsll,
+    Vsmul,
+    Vsrl,
+    Vsra,
+    Vssrl,
+    Vssra,
+    Vnsrl,
+    Vnsra,
+    Vnclipu,
+    Vnclip,
+    Vwredsumu,
+    Vwredsum,
+    // Integer arithmetic in the OPMVV and OPMVX groups
+    Vredsum,
+    Vredand,
+    Vredor,
+    Vredxor,
+    Vredminu,
+    Vredmin,
+    Vredmaxu,
+    Vredmax,
+    Vaaddu,
+    Vaadd,
+    Vasubu,
+    Vasub,
+    Vslide1up,
+    Vslide1down,
+    Vmandn,
+    Vmand,
+    Vmor,
+    Vmxor,
+    Vmorn,
+    Vmnand,
+    Vmnor,
+    Vmxnor,
+    Vdivu,
+    Vdiv,
+    Vremu,
+    Vrem,
+    Vmulhu,
+    Vmul,
+    Vmulhsu,
+    Vmulh,
+    Vmadd,
+    Vnmsub,
+    Vmacc,
+    Vnmsac,
+    Vwaddu,
+    Vwadd,
+    Vwsubu,
+    Vwsub,
+    Vwmulu,
+    Vwmulsu,
+    Vwmul,
+    Vwmaccu,
+    Vwmacc,
+    Vwmaccus,
+    Vwmaccsu,
+    // Floating point arithmetic in the OPFVV and OPFVF groups
+    Vfadd,
+    Vfsub,
+    Vfrsub,
+    Vfmin,
+    Vfmax,
+    Vfsgnj,
+    Vfsgnjn,
+    Vfsgnjx,
+    Vfslide1up,
+    Vfslide1down,
+    Vfmerge,
+    Vmfeq,
+    Vmfle,
+    Vmflt,
+    Vmfne,
+    Vmfgt,
+    Vmfge,
+    Vfdiv,
+    Vfrdiv,
+    Vfmul,
+    Vfmadd,
+    Vfnmadd,
+    Vfmsub,
+    Vfnmsub,
+    Vfmacc,
+    Vfnmacc,
+    Vfmsac,
+    Vfnmsac,
+    Vfwadd,
+    Vfwsub,
+    Vfwmul,
+    Vfwmacc,
+    Vfwnmacc,
+    Vfwmsac,
+    Vfwnmsac,
+    Vfredusum,
+    Vfredosum,
+    Vfredmin,
+    Vfredmax,
+    Vfwredusum,
+    Vfwredosum,
+    // Moves, unary operations and conversions, which use the vs1 or vs2
+    // field to select the operation
+    VmvVV,
+    VmvVX,
+    VmvVI,
+    Vmv1r,
+    Vmv2r,
+    Vmv4r,
+    Vmv8r,
+    VmvXS,
+    VmvSX,
+    Vcpop,
+    Vfirst,
+    VzextVf2,
+    VzextVf4,
+    VzextVf8,
+    VsextVf2,
+    VsextVf4,
+    VsextVf8,
+    Vmsbf,
+    Vmsif,
+    Vmsof,
+    Viota,
+    Vid,
+    Vcompress,
+    VfmvFS,
+    VfmvSF,
+    VfmvVF,
+    VfcvtXuFV,
+    VfcvtXFV,
+    VfcvtFXuV,
+    VfcvtFXV,
+    VfcvtRtzXuFV,
+    VfcvtRtzXFV,
+    VfwcvtXuFV,
+    VfwcvtXFV,
+    VfwcvtFXuV,
+    VfwcvtFXV,
+    VfwcvtFFV,
+    VfwcvtRtzXuFV,
+    VfwcvtRtzXFV,
+    VfncvtXuFW,
+    VfncvtXFW,
+    VfncvtFXuW,
+    VfncvtFXW,
+    VfncvtFFW,
+    VfncvtRodFFW,
+    VfncvtRtzXuFW,
+    VfncvtRtzXFW,
+    Vfsqrt,
+    Vfrsqrt7,
+    Vfrec7,
+    Vfclass,
+    // Configuration
+    Vsetvli,
+    Vsetivli,
+    Vsetvl,
+    // Loads and stores, which take their element width and number of
+    // fields from the instruction
+    Vle,
+    Vleff,
+    Vlr,
+    Vlm,
+    Vlse,
+    Vluxei,
+    Vloxei,
+    Vse,
+    Vsr,
+    Vsm,
+    Vsse,
+    Vsuxei,
+    Vsoxei,
+}
+
+impl VectorOp {
+    /// The mnemonic of this op. Arithmetic which takes several operand
+    /// forms gives the mnemonic without its form suffix, such as `vadd` for
+    /// `vadd.vv`, and loads and stores give theirs without the element
+    /// width or number of fields, such as `vle.v` for `vle32.v`.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            VectorOp::Vadd => "vadd",
+            VectorOp::Vsub => "vsub",
+            VectorOp::Vrsub => "vrsub",
+            VectorOp::Vminu => "vminu",
+            VectorOp::Vmin => "vmin",
+            VectorOp::Vmaxu => "vmaxu",
+            VectorOp::Vmax => "vmax",
+            VectorOp::Vand => "vand",
+            VectorOp::Vor => "vor",
+            VectorOp::Vxor => "vxor",
+            VectorOp::Vrgather => "vrgather",
+            VectorOp::Vrgatherei16 => "vrgatherei16",
+            VectorOp::Vslideup => "vslideup",
+            VectorOp::Vslidedown => "vslidedown",
+            VectorOp::Vadc => "vadc",
+            VectorOp::Vmadc => "vmadc",
+            VectorOp::Vsbc => "vsbc",
+            VectorOp::Vmsbc => "vmsbc",
+            VectorOp::Vmerge => "vmerge",
+            VectorOp::Vmseq => "vmseq",
+            VectorOp::Vmsne => "vmsne",
+            VectorOp::Vmsltu => "vmsltu",
+            VectorOp::Vmslt => "vmslt",
+            VectorOp::Vmsleu => "vmsleu",
+            VectorOp::Vmsle => "vmsle",
+            VectorOp::Vmsgtu => "vmsgtu",
+            VectorOp::Vmsgt => "vmsgt",
+            VectorOp::Vsaddu => "vsaddu",
+            VectorOp::Vsadd => "vsadd",
+            VectorOp::Vssubu => "vssubu",
+            VectorOp::Vssub => "vssub",
+            VectorOp::Vsll => "vsll",
+            VectorOp::Vsmul => "vsmul",
+            VectorOp::Vsrl => "vsrl",
+            VectorOp::Vsra => "vsra",
+            VectorOp::Vssrl => "vssrl",
+            VectorOp::Vssra => "vssra",
+            VectorOp::Vnsrl => "vnsrl",
+            VectorOp::Vnsra => "vnsra",
+            VectorOp::Vnclipu => "vnclipu",
+            VectorOp::Vnclip => "vnclip",
+            VectorOp::Vwredsumu => "vwredsumu",
+            VectorOp::Vwredsum => "vwredsum",
+            VectorOp::Vredsum => "vredsum",
+            VectorOp::Vredand => "vredand",
+            VectorOp::Vredor => "vredor",
+            VectorOp::Vredxor => "vredxor",
+            VectorOp::Vredminu => "vredminu",
+            VectorOp::Vredmin => "vredmin",
+            VectorOp::Vredmaxu => "vredmaxu",
+            VectorOp::Vredmax => "vredmax",
+            VectorOp::Vaaddu => "vaaddu",
+            VectorOp::Vaadd => "vaadd",
+            VectorOp::Vasubu => "vasubu",
+            VectorOp::Vasub => "vasub",
+            VectorOp::Vslide1up => "vslide1up",
+            VectorOp::Vslide1down => "vslide1down",
+            VectorOp::Vmandn => "vmandn",
+            VectorOp::Vmand => "vmand",
+            VectorOp::Vmor => "vmor",
+            VectorOp::Vmxor => "vmxor",
+            VectorOp::Vmorn => "vmorn",
+            VectorOp::Vmnand => "vmnand",
+            VectorOp::Vmnor => "vmnor",
+            VectorOp::Vmxnor => "vmxnor",
+            VectorOp::Vdivu => "vdivu",
+            VectorOp::Vdiv => "vdiv",
+            VectorOp::Vremu => "vremu",
+            VectorOp::Vrem => "vrem",
+            VectorOp::Vmulhu => "vmulhu",
+            VectorOp::Vmul => "vmul",
+            VectorOp::Vmulhsu => "vmulhsu",
+            VectorOp::Vmulh => "vmulh",
+            VectorOp::Vmadd => "vmadd",
+            VectorOp::Vnmsub => "vnmsub",
+            VectorOp::Vmacc => "vmacc",
+            VectorOp::Vnmsac => "vnmsac",
+            VectorOp::Vwaddu => "vwaddu",
+            VectorOp::Vwadd => "vwadd",
+            VectorOp::Vwsubu => "vwsubu",
+            VectorOp::Vwsub => "vwsub",
+            VectorOp::Vwmulu => "vwmulu",
+            VectorOp::Vwmulsu => "vwmulsu",
+            VectorOp::Vwmul => "vwmul",
+            VectorOp::Vwmaccu => "vwmaccu",
+            VectorOp::Vwmacc => "vwmacc",
+            VectorOp::Vwmaccus => "vwmaccus",
+            VectorOp::Vwmaccsu => "vwmaccsu",
+            VectorOp::Vfadd => "vfadd",
+            VectorOp::Vfsub => "vfsub",
+            VectorOp::Vfrsub => "vfrsub",
+            VectorOp::Vfmin => "vfmin",
+            VectorOp::Vfmax => "vfmax",
+            VectorOp::Vfsgnj => "vfsgnj",
+            VectorOp::Vfsgnjn => "vfsgnjn",
+            VectorOp::Vfsgnjx => "vfsgnjx",
+            VectorOp::Vfslide1up => "vfslide1up",
+            VectorOp::Vfslide1down => "vfslide1down",
+            VectorOp::Vfmerge => "vfmerge",
+            VectorOp::Vmfeq => "vmfeq",
+            VectorOp::Vmfle => "vmfle",
+            VectorOp::Vmflt => "vmflt",
+            VectorOp::Vmfne => "vmfne",
+            VectorOp::Vmfgt => "vmfgt",
+            VectorOp::Vmfge => "vmfge",
+            VectorOp::Vfdiv => "vfdiv",
+            VectorOp::Vfrdiv => "vfrdiv",
+            VectorOp::Vfmul => "vfmul",
+            VectorOp::Vfmadd => "vfmadd",
+            VectorOp::Vfnmadd => "vfnmadd",
+            VectorOp::Vfmsub => "vfmsub",
+            VectorOp::Vfnmsub => "vfnmsub",
+            VectorOp::Vfmacc => "vfmacc",
+            VectorOp::Vfnmacc => "vfnmacc",
+            VectorOp::Vfmsac => "vfmsac",
+            VectorOp::Vfnmsac => "vfnmsac",
+            VectorOp::Vfwadd => "vfwadd",
+            VectorOp::Vfwsub => "vfwsub",
+            VectorOp::Vfwmul => "vfwmul",
+            VectorOp::Vfwmacc => "vfwmacc",
+            VectorOp::Vfwnmacc => "vfwnmacc",
+            VectorOp::Vfwmsac => "vfwmsac",
+            VectorOp::Vfwnmsac => "vfwnmsac",
+            VectorOp::Vfredusum => "vfredusum",
+            VectorOp::Vfredosum => "vfredosum",
+            VectorOp::Vfredmin => "vfredmin",
+            VectorOp::Vfredmax => "vfredmax",
+            VectorOp::Vfwredusum => "vfwredusum",
+            VectorOp::Vfwredosum => "vfwredosum",
+            VectorOp::VmvVV => "vmv.v.v",
+            VectorOp::VmvVX => "vmv.v.x",
+            VectorOp::VmvVI => "vmv.v.i",
+            VectorOp::Vmv1r => "vmv1r.v",
+            VectorOp::Vmv2r => "vmv2r.v",
+            VectorOp::Vmv4r => "vmv4r.v",
+            VectorOp::Vmv8r => "vmv8r.v",
+            VectorOp::VmvXS => "vmv.x.s",
+            VectorOp::VmvSX => "vmv.s.x",
+            VectorOp::Vcpop => "vcpop.m",
+            VectorOp::Vfirst => "vfirst.m",
+            VectorOp::VzextVf2 => "vzext.vf2",
+            VectorOp::VzextVf4 => "vzext.vf4",
+            VectorOp::VzextVf8 => "vzext.vf8",
+            VectorOp::VsextVf2 => "vsext.vf2",
+            VectorOp::VsextVf4 => "vsext.vf4",
+            VectorOp::VsextVf8 => "vsext.vf8",
+            VectorOp::Vmsbf => "vmsbf.m",
+            VectorOp::Vmsif => "vmsif.m",
+            VectorOp::Vmsof => "vmsof.m",
+            VectorOp::Viota => "viota.m",
+            VectorOp::Vid => "vid.v",
+            VectorOp::Vcompress => "vcompress.vm",
+            VectorOp::VfmvFS => "vfmv.f.s",
+            VectorOp::VfmvSF => "vfmv.s.f",
+            VectorOp::VfmvVF => "vfmv.v.f",
+            VectorOp::VfcvtXuFV => "vfcvt.xu.f.v",
+            VectorOp::VfcvtXFV => "vfcvt.x.f.v",
+            VectorOp::VfcvtFXuV => "vfcvt.f.xu.v",
+            VectorOp::VfcvtFXV => "vfcvt.f.x.v",
+            VectorOp::VfcvtRtzXuFV => "vfcvt.rtz.xu.f.v",
+            VectorOp::VfcvtRtzXFV => "vfcvt.rtz.x.f.v",
+            VectorOp::VfwcvtXuFV => "vfwcvt.xu.f.v",
+            VectorOp::VfwcvtXFV => "vfwcvt.x.f.v",
+            VectorOp::VfwcvtFXuV => "vfwcvt.f.xu.v",
+            VectorOp::VfwcvtFXV => "vfwcvt.f.x.v",
+            VectorOp::VfwcvtFFV => "vfwcvt.f.f.v",
+            VectorOp::VfwcvtRtzXuFV => "vfwcvt.rtz.xu.f.v",
+            VectorOp::VfwcvtRtzXFV => "vfwcvt.rtz.x.f.v",
+            VectorOp::VfncvtXuFW => "vfncvt.xu.f.w",
+            VectorOp::VfncvtXFW => "vfncvt.x.f.w",
+            VectorOp::VfncvtFXuW => "vfncvt.f.xu.w",
+            VectorOp::VfncvtFXW => "vfncvt.f.x.w",
+            VectorOp::VfncvtFFW => "vfncvt.f.f.w",
+            VectorOp::VfncvtRodFFW => "vfncvt.rod.f.f.w",
+            VectorOp::VfncvtRtzXuFW => "vfncvt.rtz.xu.f.w",
+            VectorOp::VfncvtRtzXFW => "vfncvt.rtz.x.f.w",
+            VectorOp::Vfsqrt => "vfsqrt.v",
+            VectorOp::Vfrsqrt7 => "vfrsqrt7.v",
+            VectorOp::Vfrec7 => "vfrec7.v",
+            VectorOp::Vfclass => "vfclass.v",
+            VectorOp::Vsetvli => "vsetvli",
+            VectorOp::Vsetivli => "vsetivli",
+            VectorOp::Vsetvl => "vsetvl",
+            VectorOp::Vle => "vle.v",
+            VectorOp::Vleff => "vleff.v",
+            VectorOp::Vlr => "vlr.v",
+            VectorOp::Vlm => "vlm.v",
+            VectorOp::Vlse => "vlse.v",
+            VectorOp::Vluxei => "vluxei.v",
+            VectorOp::Vloxei => "vloxei.v",
+            VectorOp::Vse => "vse.v",
+            VectorOp::Vsr => "vsr.v",
+            VectorOp::Vsm => "vsm.v",
+            VectorOp::Vsse => "vsse.v",
+            VectorOp::Vsuxei => "vsuxei.v",
+            VectorOp::Vsoxei => "vsoxei.v",
+        }
+    }
+}
+
+/// The operand form of a vector arithmetic instruction, written as the
+/// suffix of its mnemonic, as returned by `Instruction::vector_form`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VectorForm {
+    /// Two vector sources, `.vv`
+    Vv,
+    /// A vector and an integer register, `.vx`
+    Vx,
+    /// A vector and a 5-bit immediate, `.vi`
+    Vi,
+    /// A vector and a floating point register, `.vf`
+    Vf,
+    /// A double-width vector vs2 and a vector, `.wv`
+    Wv,
+    /// A double-width vector vs2 and an integer register, `.wx`
+    Wx,
+    /// A double-width vector vs2 and an immediate, `.wi`
+    Wi,
+    /// A double-width vector vs2 and a floating point register, `.wf`
+    Wf,
+    /// Two vector sources and v0 as carry in or merge mask, `.vvm`
+    Vvm,
+    /// A vector, an integer register and v0, `.vxm`
+    Vxm,
+    /// A vector, an immediate and v0, `.vim`
+    Vim,
+    /// A vector, a floating point register and v0, `.vfm`
+    Vfm,
+    /// A reduction of vs2 into element 0 of vs1, `.vs`
+    Vs,
+    /// Two mask registers, `.mm`
+    Mm,
+}
+
+const FORMS: &[VectorForm] = &[
+    VectorForm::Vv,
+    VectorForm::Vx,
+    VectorForm::Vi,
+    VectorForm::Vf,
+    VectorForm::Wv,
+    VectorForm::Wx,
+    VectorForm::Wi,
+    VectorForm::Wf,
+    VectorForm::Vvm,
+    VectorForm::Vxm,
+    VectorForm::Vim,
+    VectorForm::Vfm,
+    VectorForm::Vs,
+    VectorForm::Mm,
+];
+
+impl VectorForm {
+    /// The suffix this form adds to a mnemonic, such as `vv` or `vxm`
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            VectorForm::Vv => "vv",
+            VectorForm::Vx => "vx",
+            VectorForm::Vi => "vi",
+            VectorForm::Vf => "vf",
+            VectorForm::Wv => "wv",
+            VectorForm::Wx => "wx",
+            VectorForm::Wi => "wi",
+            VectorForm::Wf => "wf",
+            VectorForm::Vvm => "vvm",
+            VectorForm::Vxm => "vxm",
+            VectorForm::Vim => "vim",
+            VectorForm::Vfm => "vfm",
+            VectorForm::Vs => "vs",
+            VectorForm::Mm => "mm",
+        }
+    }
+
+    fn source(self) -> Source {
+        match self {
+            VectorForm::Vx
+            | VectorForm::Vf
+            | VectorForm::Wx
+            | VectorForm::Wf
+            | VectorForm::Vxm
+            | VectorForm::Vfm => Source::Scalar,
+            VectorForm::Vi | VectorForm::Wi | VectorForm::Vim => Source::Immediate,
+            _ => Source::Vector,
+        }
+    }
+
+    /// Returns true for the forms which take v0 as a carry in or merge
+    /// mask. These are encoded with vm clear, and cannot be masked.
+    fn merges(self) -> bool {
+        matches!(
+            self,
+            VectorForm::Vvm | VectorForm::Vxm | VectorForm::Vim | VectorForm::Vfm
+        )
+    }
+}
+
+impl fmt::Display for VectorForm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.suffix())
+    }
+}
+
+/// The vector register group multiplier, LMUL. The fractional multipliers
+/// use part of a single register.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Lmul {
+    Mf8,
+    Mf4,
+    Mf2,
+    M1,
+    M2,
+    M4,
+    M8,
+}
+
+const LMULS: &[Lmul] = &[
+    Lmul::Mf8,
+    Lmul::Mf4,
+    Lmul::Mf2,
+    Lmul::M1,
+    Lmul::M2,
+    Lmul::M4,
+    Lmul::M8,
+];
+
+impl Lmul {
+    /// The vlmul field of vtype
+    fn bits(self) -> u32 {
+        match self {
+            Lmul::M1 => 0b000,
+            Lmul::M2 => 0b001,
+            Lmul::M4 => 0b010,
+            Lmul::M8 => 0b011,
+            Lmul::Mf8 => 0b101,
+            Lmul::Mf4 => 0b110,
+            Lmul::Mf2 => 0b111,
+        }
+    }
+}
+
+impl fmt::Display for Lmul {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Lmul::Mf8 => write!(f, "mf8"),
+            Lmul::Mf4 => write!(f, "mf4"),
+            Lmul::Mf2 => write!(f, "mf2"),
+            Lmul::M1 => write!(f, "m1"),
+            Lmul::M2 => write!(f, "m2"),
+            Lmul::M4 => write!(f, "m4"),
+            Lmul::M8 => write!(f, "m8"),
+        }
+    }
+}
+
+/// The vector type set by `vsetvli` and `vsetivli`, as returned by
+/// `Instruction::vtype`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Vtype {
+    pub(crate) sew: usize,
+    pub(crate) lmul: Lmul,
+    pub(crate) tail_agnostic: bool,
+    pub(crate) mask_agnostic: bool,
+}
+
+impl Vtype {
+    /// The selected element width in bits, SEW
+    pub fn sew(&self) -> usize {
+        self.sew
+    }
+
+    pub fn lmul(&self) -> Lmul {
+        self.lmul
+    }
+
+    /// Returns true if tail elements may be overwritten, written `ta`,
+    /// rather than left undisturbed, `tu`
+    pub fn tail_agnostic(&self) -> bool {
+        self.tail_agnostic
+    }
+
+    /// Returns true if masked-off elements may be overwritten, written
+    /// `ma`, rather than left undisturbed, `mu`
+    pub fn mask_agnostic(&self) -> bool {
+        self.mask_agnostic
+    }
+
+    /// The vtype held in the low eight bits of `u`, or `None` if the
+    /// element width or multiplier is reserved
+    fn from_u32(u: u32) -> Option<Vtype> {
+        let lmul = match u & 0b111 {
+            0b000 => Lmul::M1,
+            0b001 => Lmul::M2,
+            0b010 => Lmul::M4,
+            0b011 => Lmul::M8,
+            0b101 => Lmul::Mf8,
+            0b110 => Lmul::Mf4,
+            0b111 => Lmul::Mf2,
+            _ => return None,
+        };
+        let sew = match (u >> 3) & 0b111 {
+            0b000 => 8,
+            0b001 => 16,
+            0b010 => 32,
+            0b011 => 64,
+            _ => return None,
+        };
+        Some(Vtype {
+            sew,
+            lmul,
+            tail_agnostic: (u >> 6) & 1 == 1,
+            mask_agnostic: (u >> 7) & 1 == 1,
+        })
+    }
+
+    pub(crate) fn bits(self) -> u32 {
+        let vsew = match self.sew {
+            8 => 0b000,
+            16 => 0b001,
+            32 => 0b010,
+            _ => 0b011,
+        };
+        (self.mask_agnostic as u32) << 7
+            | (self.tail_agnostic as u32) << 6
+            | vsew << 3
+            | self.lmul.bits()
+    }
+}
+
+/// Displays a vtype as vsetvli takes it, such as `e32, m1, ta, mu`
+impl fmt::Display for Vtype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "e{}, {}, {}, {}",
+            self.sew,
+            self.lmul,
+            if self.tail_agnostic { "ta" } else { "tu" },
+            if self.mask_agnostic { "ma" } else { "mu" }
+        )
+    }
+}
+
+/// Parses the vtype operands of vsetvli and vsetivli, such as `e32, m2, ta,
+/// mu`. The element width is required, and the others default to `m1`,
+/// `tu` and `mu` when left out.
+pub(crate) fn parse_vtype(operands: &[&str]) -> Option<Vtype> {
+    let mut operands = operands.iter();
+    let sew = operands.next()?.strip_prefix('e')?;
+    let mut vtype = Vtype {
+        sew: [8, 16, 32, 64]
+            .iter()
+            .cloned()
+            .find(|width| width.to_string() == sew)?,
+        lmul: Lmul::M1,
+        tail_agnostic: false,
+        mask_agnostic: false,
+    };
+
+    let mut next = operands.next();
+    if let Some(&lmul) = next.and_then(|name| LMULS.iter().find(|lmul| lmul.to_string() == *name)) {
+        vtype.lmul = lmul;
+        next = operands.next();
+    }
+    if let Some(&policy) = next.filter(|&&name| name == "ta" || name == "tu") {
+        vtype.tail_agnostic = policy == "ta";
+        next = operands.next();
+    }
+    if let Some(&policy) = next.filter(|&&name| name == "ma" || name == "mu") {
+        vtype.mask_agnostic = policy == "ma";
+        next = operands.next();
+    }
+    match next {
+        Some(_) => None,
+        None => Some(vtype),
+    }
+}
+
+/// The register file an operand of a vector instruction belongs to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Class {
+    X,
+    F,
+    V,
+}
+
+/// Where an operand of a vector instruction is held. `slots` lists them in
+/// the order they are written, leaving out the `v0.t` of a masked
+/// instruction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Slot {
+    Rd(Class),
+    Rs1(Class),
+    Rs2(Class),
+    /// The data of a store, held in the `rs3` field of the instruction and
+    /// in bits 11:7 of the encoding
+    Rs3,
+    /// A 5-bit immediate in bits 19:15, which is sign-extended if `true`
+    Immediate(bool),
+    /// The base register of a load or store, written `(rs1)`
+    Base,
+    /// The vtype of vsetvli and vsetivli, held in bits 30:20
+    Vtype,
+    /// The v0 of the carry and merge forms, which is implied by the encoding
+    V0,
+}
+
+/// Where the second source of an arithmetic instruction comes from
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Source {
+    Vector,
+    Scalar,
+    Immediate,
+}
+
+/// The arithmetic groups, each of which has its own funct6 space
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Group {
+    Opi,
+    Opm,
+    Opf,
+}
+
+/// The group and source kind selected by funct3, other than OPCFG
+fn group(funct3: u32) -> (Group, Source) {
+    match funct3 {
+        OPIVV => (Group::Opi, Source::Vector),
+        OPFVV => (Group::Opf, Source::Vector),
+        OPMVV => (Group::Opm, Source::Vector),
+        OPIVI => (Group::Opi, Source::Immediate),
+        OPIVX => (Group::Opi, Source::Scalar),
+        OPFVF => (Group::Opf, Source::Scalar),
+        _ => (Group::Opm, Source::Scalar),
+    }
+}
+
+fn group_funct3(group: Group, source: Source) -> u32 {
+    match (group, source) {
+        (Group::Opi, Source::Vector) => OPIVV,
+        (Group::Opf, Source::Vector) => OPFVV,
+        (Group::Opm, Source::Vector) => OPMVV,
+        (Group::Opi, Source::Scalar) => OPIVX,
+        (Group::Opf, Source::Scalar) => OPFVF,
+        (Group::Opm, Source::Scalar) => OPMVX,
+        // Only the OPI group has immediate forms
+        (_, Source::Immediate) => OPIVI,
+    }
+}
+
+/// The moves, unary operations and conversions, which take at most one
+/// vector source, as (op, funct3, funct6, vs1). Those with a value for vs1
+/// use the vs1 field to select the operation.
+const UNARY: &[(VectorOp, u32, u32, Option<u32>)] = &[
+    (VectorOp::VmvXS, OPMVV, 0b010000, Some(0b00000)),
+    (VectorOp::Vcpop, OPMVV, 0b010000, Some(0b10000)),
+    (VectorOp::Vfirst, OPMVV, 0b010000, Some(0b10001)),
+    (VectorOp::VmvSX, OPMVX, 0b010000, None),
+    (VectorOp::VzextVf8, OPMVV, 0b010010, Some(0b00010)),
+    (VectorOp::VsextVf8, OPMVV, 0b010010, Some(0b00011)),
+    (VectorOp::VzextVf4, OPMVV, 0b010010, Some(0b00100)),
+    (VectorOp::VsextVf4, OPMVV, 0b010010, Some(0b00101)),
+    (VectorOp::VzextVf2, OPMVV, 0b010010, Some(0b00110)),
+    (VectorOp::VsextVf2, OPMVV, 0b010010, Some(0b00111)),
+    (VectorOp::Vmsbf, OPMVV, 0b010100, Some(0b00001)),
+    (VectorOp::Vmsof, OPMVV, 0b010100, Some(0b00010)),
+    (VectorOp::Vmsif, OPMVV, 0b010100, Some(0b00011)),
+    (VectorOp::Viota, OPMVV, 0b010100, Some(0b10000)),
+    (VectorOp::Vid, OPMVV, 0b010100, Some(0b10001)),
+    (VectorOp::Vcompress, OPMVV, 0b010111, None),
+    (VectorOp::VmvVV, OPIVV, 0b010111, None),
+    (VectorOp::VmvVX, OPIVX, 0b010111, None),
+    (VectorOp::VmvVI, OPIVI, 0b010111, None),
+    (VectorOp::Vmv1r, OPIVI, 0b100111, Some(0)),
+    (VectorOp::Vmv2r, OPIVI, 0b100111, Some(1)),
+    (VectorOp::Vmv4r, OPIVI, 0b100111, Some(3)),
+    (VectorOp::Vmv8r, OPIVI, 0b100111, Some(7)),
+    (VectorOp::VfmvFS, OPFVV, 0b010000, Some(0b00000)),
+    (VectorOp::VfmvSF, OPFVF, 0b010000, None),
+    (VectorOp::VfmvVF, OPFVF, 0b010111, None),
+    (VectorOp::VfcvtXuFV, OPFVV, 0b010010, Some(0b00000)),
+    (VectorOp::VfcvtXFV, OPFVV, 0b010010, Some(0b00001)),
+    (VectorOp::VfcvtFXuV, OPFVV, 0b010010, Some(0b00010)),
+    (VectorOp::VfcvtFXV, OPFVV, 0b010010, Some(0b00011)),
+    (VectorOp::VfcvtRtzXuFV, OPFVV, 0b010010, Some(0b00110)),
+    (VectorOp::VfcvtRtzXFV, OPFVV, 0b010010, Some(0b00111)),
+    (VectorOp::VfwcvtXuFV, OPFVV, 0b010010, Some(0b01000)),
+    (VectorOp::VfwcvtXFV, OPFVV, 0b010010, Some(0b01001)),
+    (VectorOp::VfwcvtFXuV, OPFVV, 0b010010, Some(0b01010)),
+    (VectorOp::VfwcvtFXV, OPFVV, 0b010010, Some(0b01011)),
+    (VectorOp::VfwcvtFFV, OPFVV, 0b010010, Some(0b01100)),
+    (VectorOp::VfwcvtRtzXuFV, OPFVV, 0b010010, Some(0b01110)),
+    (VectorOp::VfwcvtRtzXFV, OPFVV, 0b010010, Some(0b01111)),
+    (VectorOp::VfncvtXuFW, OPFVV, 0b010010, Some(0b10000)),
+    (VectorOp::VfncvtXFW, OPFVV, 0b010010, Some(0b10001)),
+    (VectorOp::VfncvtFXuW, OPFVV, 0b010010, Some(0b10010)),
+    (VectorOp::VfncvtFXW, OPFVV, 0b010010, Some(0b10011)),
+    (VectorOp::VfncvtFFW, OPFVV, 0b010010, Some(0b10100)),
+    (VectorOp::VfncvtRodFFW, OPFVV, 0b010010, Some(0b10101)),
+    (VectorOp::VfncvtRtzXuFW, OPFVV, 0b010010, Some(0b10110)),
+    (VectorOp::VfncvtRtzXFW, OPFVV, 0b010010, Some(0b10111)),
+    (VectorOp::Vfsqrt, OPFVV, 0b010011, Some(0b00000)),
+    (VectorOp::Vfrsqrt7, OPFVV, 0b010011, Some(0b00100)),
+    (VectorOp::Vfrec7, OPFVV, 0b010011, Some(0b00101)),
+    (VectorOp::Vfclass, OPFVV, 0b010011, Some(0b10000)),
+];
+
+// The sets of forms the arithmetic instructions take
+const VV_VX_VI: &[VectorForm] = &[VectorForm::Vv, VectorForm::Vx, VectorForm::Vi];
+const VV_VX: &[VectorForm] = &[VectorForm::Vv, VectorForm::Vx];
+const VX_VI: &[VectorForm] = &[VectorForm::Vx, VectorForm::Vi];
+const VV: &[VectorForm] = &[VectorForm::Vv];
+const VX: &[VectorForm] = &[VectorForm::Vx];
+const VS: &[VectorForm] = &[VectorForm::Vs];
+const MM: &[VectorForm] = &[VectorForm::Mm];
+const WV_WX_WI: &[VectorForm] = &[VectorForm::Wv, VectorForm::Wx, VectorForm::Wi];
+const WV_WX: &[VectorForm] = &[VectorForm::Wv, VectorForm::Wx];
+const VVM_VXM_VIM: &[VectorForm] = &[VectorForm::Vvm, VectorForm::Vxm, VectorForm::Vim];
+const VVM_VXM_VIM_VV_VX_VI: &[VectorForm] = &[
+    VectorForm::Vvm,
+    VectorForm::Vxm,
+    VectorForm::Vim,
+    VectorForm::Vv,
+    VectorForm::Vx,
+    VectorForm::Vi,
+];
+const VVM_VXM: &[VectorForm] = &[VectorForm::Vvm, VectorForm::Vxm];
+const VVM_VXM_VV_VX: &[VectorForm] = &[
+    VectorForm::Vvm,
+    VectorForm::Vxm,
+    VectorForm::Vv,
+    VectorForm::Vx,
+];
+const VV_VF: &[VectorForm] = &[VectorForm::Vv, VectorForm::Vf];
+const VF: &[VectorForm] = &[VectorForm::Vf];
+const WV_WF: &[VectorForm] = &[VectorForm::Wv, VectorForm::Wf];
+const VFM: &[VectorForm] = &[VectorForm::Vfm];
+
+/// The arithmetic instructions which take two sources, as (op, group, funct6,
+/// forms)
+const BINARY: &[(VectorOp, Group, u32, &[VectorForm])] = &[
+    (VectorOp::Vadd, Group::Opi, 0b000000, VV_VX_VI),
+    (VectorOp::Vsub, Group::Opi, 0b000010, VV_VX),
+    (VectorOp::Vrsub, Group::Opi, 0b000011, VX_VI),
+    (VectorOp::Vminu, Group::Opi, 0b000100, VV_VX),
+    (VectorOp::Vmin, Group::Opi, 0b000101, VV_VX),
+    (VectorOp::Vmaxu, Group::Opi, 0b000110, VV_VX),
+    (VectorOp::Vmax, Group::Opi, 0b000111, VV_VX),
+    (VectorOp::Vand, Group::Opi, 0b001001, VV_VX_VI),
+    (VectorOp::Vor, Group::Opi, 0b001010, VV_VX_VI),
+    (VectorOp::Vxor, Group::Opi, 0b001011, VV_VX_VI),
+    (VectorOp::Vrgather, Group::Opi, 0b001100, VV_VX_VI),
+    (VectorOp::Vrgatherei16, Group::Opi, 0b001110, VV),
+    (VectorOp::Vslideup, Group::Opi, 0b001110, VX_VI),
+    (VectorOp::Vslidedown, Group::Opi, 0b001111, VX_VI),
+    (VectorOp::Vadc, Group::Opi, 0b010000, VVM_VXM_VIM),
+    (VectorOp::Vmadc, Group::Opi, 0b010001, VVM_VXM_VIM_VV_VX_VI),
+    (VectorOp::Vsbc, Group::Opi, 0b010010, VVM_VXM),
+    (VectorOp::Vmsbc, Group::Opi, 0b010011, VVM_VXM_VV_VX),
+    (VectorOp::Vmerge, Group::Opi, 0b010111, VVM_VXM_VIM),
+    (VectorOp::Vmseq, Group::Opi, 0b011000, VV_VX_VI),
+    (VectorOp::Vmsne, Group::Opi, 0b011001, VV_VX_VI),
+    (VectorOp::Vmsltu, Group::Opi, 0b011010, VV_VX),
+    (VectorOp::Vmslt, Group::Opi, 0b011011, VV_VX),
+    (VectorOp::Vmsleu, Group::Opi, 0b011100, VV_VX_VI),
+    (VectorOp::Vmsle, Group::Opi, 0b011101, VV_VX_VI),
+    (VectorOp::Vmsgtu, Group::Opi, 0b011110, VX_VI),
+    (VectorOp::Vmsgt, Group::Opi, 0b011111, VX_VI),
+    (VectorOp::Vsaddu, Group::Opi, 0b100000, VV_VX_VI),
+    (VectorOp::Vsadd, Group::Opi, 0b100001, VV_VX_VI),
+    (VectorOp::Vssubu, Group::Opi, 0b100010, VV_VX),
+    (VectorOp::Vssub, Group::Opi, 0b100011, VV_VX),
+    (VectorOp::Vsll, Group::Opi, 0b100101, VV_VX_VI),
+    (VectorOp::Vsmul, Group::Opi, 0b100111, VV_VX),
+    (VectorOp::Vsrl, Group::Opi, 0b101000, VV_VX_VI),
+    (VectorOp::Vsra, Group::Opi, 0b101001, VV_VX_VI),
+    (VectorOp::Vssrl, Group::Opi, 0b101010, VV_VX_VI),
+    (VectorOp::Vssra, Group::Opi, 0b101011, VV_VX_VI),
+    (VectorOp::Vnsrl, Group::Opi, 0b101100, WV_WX_WI),
+    (VectorOp::Vnsra, Group::Opi, 0b101101, WV_WX_WI),
+    (VectorOp::Vnclipu, Group::Opi, 0b101110, WV_WX_WI),
+    (VectorOp::Vnclip, Group::Opi, 0b101111, WV_WX_WI),
+    (VectorOp::Vwredsumu, Group::Opi, 0b110000, VS),
+    (VectorOp::Vwredsum, Group::Opi, 0b110001, VS),
+    (VectorOp::Vredsum, Group::Opm, 0b000000, VS),
+    (VectorOp::Vredand, Group::Opm, 0b000001, VS),
+    (VectorOp::Vredor, Group::Opm, 0b000010, VS),
+    (VectorOp::Vredxor, Group::Opm, 0b000011, VS),
+    (VectorOp::Vredminu, Group::Opm, 0b000100, VS),
+    (VectorOp::Vredmin, Group::Opm, 0b000101, VS),
+    (VectorOp::Vredmaxu, Group::Opm, 0b000110, VS),
+    (VectorOp::Vredmax, Group::Opm, 0b000111, VS),
+    (VectorOp::Vaaddu, Group::Opm, 0b001000, VV_VX),
+    (VectorOp::Vaadd, Group::Opm, 0b001001, VV_VX),
+    (VectorOp::Vasubu, Group::Opm, 0b001010, VV_VX),
+    (VectorOp::Vasub, Group::Opm, 0b001011, VV_VX),
+    (VectorOp::Vslide1up, Group::Opm, 0b001110, VX),
+    (VectorOp::Vslide1down, Group::Opm, 0b001111, VX),
+    (VectorOp::Vmandn, Group::Opm, 0b011000, MM),
+    (VectorOp::Vmand, Group::Opm, 0b011001, MM),
+    (VectorOp::Vmor, Group::Opm, 0b011010, MM),
+    (VectorOp::Vmxor, Group::Opm, 0b011011, MM),
+    (VectorOp::Vmorn, Group::Opm, 0b011100, MM),
+    (VectorOp::Vmnand, Group::Opm, 0b011101, MM),
+    (VectorOp::Vmnor, Group::Opm, 0b011110, MM),
+    (VectorOp::Vmxnor, Group::Opm, 0b011111, MM),
+    (VectorOp::Vdivu, Group::Opm, 0b100000, VV_VX),
+    (VectorOp::Vdiv, Group::Opm, 0b100001, VV_VX),
+    (VectorOp::Vremu, Group::Opm, 0b100010, VV_VX),
+    (VectorOp::Vrem, Group::Opm, 0b100011, VV_VX),
+    (VectorOp::Vmulhu, Group::Opm, 0b100100, VV_VX),
+    (VectorOp::Vmul, Group::Opm, 0b100101, VV_VX),
+    (VectorOp::Vmulhsu, Group::Opm, 0b100110, VV_VX),
+    (VectorOp::Vmulh, Group::Opm, 0b100111, VV_VX),
+    (VectorOp::Vmadd, Group::Opm, 0b101001, VV_VX),
+    (VectorOp::Vnmsub, Group::Opm, 0b101011, VV_VX),
+    (VectorOp::Vmacc, Group::Opm, 0b101101, VV_VX),
+    (VectorOp::Vnmsac, Group::Opm, 0b101111, VV_VX),
+    (VectorOp::Vwaddu, Group::Opm, 0b110000, VV_VX),
+    (VectorOp::Vwadd, Group::Opm, 0b110001, VV_VX),
+    (VectorOp::Vwsubu, Group::Opm, 0b110010, VV_VX),
+    (VectorOp::Vwsub, Group::Opm, 0b110011, VV_VX),
+    (VectorOp::Vwaddu, Group::Opm, 0b110100, WV_WX),
+    (VectorOp::Vwadd, Group::Opm, 0b110101, WV_WX),
+    (VectorOp::Vwsubu, Group::Opm, 0b110110, WV_WX),
+    (VectorOp::Vwsub, Group::Opm, 0b110111, WV_WX),
+    (VectorOp::Vwmulu, Group::Opm, 0b111000, VV_VX),
+    (VectorOp::Vwmulsu, Group::Opm, 0b111010, VV_VX),
+    (VectorOp::Vwmul, Group::Opm, 0b111011, VV_VX),
+    (VectorOp::Vwmaccu, Group::Opm, 0b111100, VV_VX),
+    (VectorOp::Vwmacc, Group::Opm, 0b111101, VV_VX),
+    (VectorOp::Vwmaccus, Group::Opm, 0b111110, VX),
+    (VectorOp::Vwmaccsu, Group::Opm, 0b111111, VV_VX),
+    (VectorOp::Vfadd, Group::Opf, 0b000000, VV_VF),
+    (VectorOp::Vfredusum, Group::Opf, 0b000001, VS),
+    (VectorOp::Vfsub, Group::Opf, 0b000010, VV_VF),
+    (VectorOp::Vfredosum, Group::Opf, 0b000011, VS),
+    (VectorOp::Vfmin, Group::Opf, 0b000100, VV_VF),
+    (VectorOp::Vfredmin, Group::Opf, 0b000101, VS),
+    (VectorOp::Vfmax, Group::Opf, 0b000110, VV_VF),
+    (VectorOp::Vfredmax, Group::Opf, 0b000111, VS),
+    (VectorOp::Vfsgnj, Group::Opf, 0b001000, VV_VF),
+    (VectorOp::Vfsgnjn, Group::Opf, 0b001001, VV_VF),
+    (VectorOp::Vfsgnjx, Group::Opf, 0b001010, VV_VF),
+    (VectorOp::Vfslide1up, Group::Opf, 0b001110, VF),
+    (VectorOp::Vfslide1down, Group::Opf, 0b001111, VF),
+    (VectorOp::Vfmerge, Group::Opf, 0b010111, VFM),
+    (VectorOp::Vmfeq, Group::Opf, 0b011000, VV_VF),
+    (VectorOp::Vmfle, Group::Opf, 0b011001, VV_VF),
+    (VectorOp::Vmflt, Group::Opf, 0b011011, VV_VF),
+    (VectorOp::Vmfne, Group::Opf, 0b011100, VV_VF),
+    (VectorOp::Vmfgt, Group::Opf, 0b011101, VF),
+    (VectorOp::Vmfge, Group::Opf, 0b011111, VF),
+    (VectorOp::Vfdiv, Group::Opf, 0b100000, VV_VF),
+    (VectorOp::Vfrdiv, Group::Opf, 0b100001, VF),
+    (VectorOp::Vfmul, Group::Opf, 0b100100, VV_VF),
+    (VectorOp::Vfrsub, Group::Opf, 0b100111, VF),
+    (VectorOp::Vfmadd, Group::Opf, 0b101000, VV_VF),
+    (VectorOp::Vfnmadd, Group::Opf, 0b101001, VV_VF),
+    (VectorOp::Vfmsub, Group::Opf, 0b101010, VV_VF),
+    (VectorOp::Vfnmsub, Group::Opf, 0b101011, VV_VF),
+    (VectorOp::Vfmacc, Group::Opf, 0b101100, VV_VF),
+    (VectorOp::Vfnmacc, Group::Opf, 0b101101, VV_VF),
+    (VectorOp::Vfmsac, Group::Opf, 0b101110, VV_VF),
+    (VectorOp::Vfnmsac, Group::Opf, 0b101111, VV_VF),
+    (VectorOp::Vfwadd, Group::Opf, 0b110000, VV_VF),
+    (VectorOp::Vfwredusum, Group::Opf, 0b110001, VS),
+    (VectorOp::Vfwsub, Group::Opf, 0b110010, VV_VF),
+    (VectorOp::Vfwredosum, Group::Opf, 0b110011, VS),
+    (VectorOp::Vfwadd, Group::Opf, 0b110100, WV_WF),
+    (VectorOp::Vfwsub, Group::Opf, 0b110110, WV_WF),
+    (VectorOp::Vfwmul, Group::Opf, 0b111000, VV_VF),
+    (VectorOp::Vfwmacc, Group::Opf, 0b111100, VV_VF),
+    (VectorOp::Vfwnmacc, Group::Opf, 0b111101, VV_VF),
+    (VectorOp::Vfwmsac, Group::Opf, 0b111110, VV_VF),
+    (VectorOp::Vfwnmsac, Group::Opf, 0b111111, VV_VF),
+];
+
+/// The loads and stores, as (op, store, mop, lumop or sumop). The unit-stride
+/// accesses select the operation with bits 24:20, which the strided and
+/// indexed accesses use for rs2.
+const MEMORY: &[(VectorOp, bool, u32, Option<u32>)] = &[
+    (VectorOp::Vle, false, 0b00, Some(0b00000)),
+    (VectorOp::Vlr, false, 0b00, Some(0b01000)),
+    (VectorOp::Vlm, false, 0b00, Some(0b01011)),
+    (VectorOp::Vleff, false, 0b00, Some(0b10000)),
+    (VectorOp::Vluxei, false, 0b01, None),
+    (VectorOp::Vlse, false, 0b10, None),
+    (VectorOp::Vloxei, false, 0b11, None),
+    (VectorOp::Vse, true, 0b00, Some(0b00000)),
+    (VectorOp::Vsr, true, 0b00, Some(0b01000)),
+    (VectorOp::Vsm, true, 0b00, Some(0b01011)),
+    (VectorOp::Vsuxei, true, 0b01, None),
+    (VectorOp::Vsse, true, 0b10, None),
+    (VectorOp::Vsoxei, true, 0b11, None),
+];
+
+fn unary(op: VectorOp) -> Option<&'static (VectorOp, u32, u32, Option<u32>)> {
+    UNARY.iter().find(|row| row.0 == op)
+}
+
+/// The group and funct6 of an arithmetic instruction in the given form
+fn binary(op: VectorOp, form: VectorForm) -> Option<(Group, u32)> {
+    BINARY
+        .iter()
+        .find(|&&(o, _, _, forms)| o == op && forms.contains(&form))
+        .map(|&(_, group, funct6, _)| (group, funct6))
+}
+
+fn memory(op: VectorOp) -> Option<&'static (VectorOp, bool, u32, Option<u32>)> {
+    MEMORY.iter().find(|row| row.0 == op)
+}
+
+/// Returns true for the multiply-adds, which accumulate into vd and are
+/// written with vd, the scalar or vs1, and then vs2
+fn is_multiply_add(op: VectorOp) -> bool {
+    matches!(
+        op,
+        VectorOp::Vmadd
+            | VectorOp::Vnmsub
+            | VectorOp::Vmacc
+            | VectorOp::Vnmsac
+            | VectorOp::Vwmaccu
+            | VectorOp::Vwmacc
+            | VectorOp::Vwmaccus
+            | VectorOp::Vwmaccsu
+            | VectorOp::Vfmadd
+            | VectorOp::Vfnmadd
+            | VectorOp::Vfmsub
+            | VectorOp::Vfnmsub
+            | VectorOp::Vfmacc
+            | VectorOp::Vfnmacc
+            | VectorOp::Vfmsac
+            | VectorOp::Vfnmsac
+            | VectorOp::Vfwmacc
+            | VectorOp::Vfwnmacc
+            | VectorOp::Vfwmsac
+            | VectorOp::Vfwnmsac
+    )
+}
+
+/// Returns true for the ops whose immediate is a zero-extended shift
+/// amount or index
+fn is_unsigned_immediate(op: VectorOp) -> bool {
+    matches!(
+        op,
+        VectorOp::Vsll
+            | VectorOp::Vsrl
+            | VectorOp::Vsra
+            | VectorOp::Vssrl
+            | VectorOp::Vssra
+            | VectorOp::Vnsrl
+            | VectorOp::Vnsra
+            | VectorOp::Vnclipu
+            | VectorOp::Vnclip
+            | VectorOp::Vslideup
+            | VectorOp::Vslidedown
+            | VectorOp::Vrgather
+    )
+}
+
+/// Returns true if the instruction may be masked with `v0.t`
+pub(crate) fn is_maskable(instruction: &Instruction, op: VectorOp) -> bool {
+    match op {
+        // The whole register and mask loads and stores ignore vl
+        VectorOp::Vlr | VectorOp::Vlm | VectorOp::Vsr | VectorOp::Vsm => false,
+        // vmadc and vmsbc use vm to select whether there is a carry in
+        VectorOp::Vmadc | VectorOp::Vmsbc => false,
+        _ if memory(op).is_some() => true,
+        // The moves between registers
+        VectorOp::VmvXS
+        | VectorOp::VmvSX
+        | VectorOp::VfmvFS
+        | VectorOp::VfmvSF
+        | VectorOp::VmvVV
+        | VectorOp::VmvVX
+        | VectorOp::VmvVI
+        | VectorOp::VfmvVF
+        | VectorOp::Vmv1r
+        | VectorOp::Vmv2r
+        | VectorOp::Vmv4r
+        | VectorOp::Vmv8r
+        | VectorOp::Vcompress => false,
+        _ if unary(op).is_some() => true,
+        _ => instruction
+            .form
+            .is_some_and(|form| !form.merges() && form != VectorForm::Mm),
+    }
+}
+
+/// The operands of a vector instruction, in the order they are written
+pub(crate) fn slots(instruction: &Instruction, op: VectorOp) -> Vec<Slot> {
+    let access = match memory(op) {
+        Some(&(_, true, _, _)) => Slot::Rs3,
+        _ => Slot::Rd(Class::V),
+    };
+    match op {
+        VectorOp::Vsetvli => vec![Slot::Rd(Class::X), Slot::Rs1(Class::X), Slot::Vtype],
+        VectorOp::Vsetivli => vec![Slot::Rd(Class::X), Slot::Immediate(false), Slot::Vtype],
+        VectorOp::Vsetvl => vec![Slot::Rd(Class::X), Slot::Rs1(Class::X), Slot::Rs2(Class::X)],
+        VectorOp::Vlse | VectorOp::Vsse => vec![access, Slot::Base, Slot::Rs2(Class::X)],
+        VectorOp::Vluxei | VectorOp::Vloxei | VectorOp::Vsuxei | VectorOp::Vsoxei => {
+            vec![access, Slot::Base, Slot::Rs2(Class::V)]
+        }
+        _ if memory(op).is_some() => vec![access, Slot::Base],
+        VectorOp::VmvXS | VectorOp::Vcpop | VectorOp::Vfirst => {
+            vec![Slot::Rd(Class::X), Slot::Rs2(Class::V)]
+        }
+        VectorOp::VfmvFS => vec![Slot::Rd(Class::F), Slot::Rs2(Class::V)],
+        VectorOp::VmvSX | VectorOp::VmvVX => vec![Slot::Rd(Class::V), Slot::Rs1(Class::X)],
+        VectorOp::VfmvSF | VectorOp::VfmvVF => vec![Slot::Rd(Class::V), Slot::Rs1(Class::F)],
+        VectorOp::VmvVV => vec![Slot::Rd(Class::V), Slot::Rs1(Class::V)],
+        VectorOp::VmvVI => vec![Slot::Rd(Class::V), Slot::Immediate(true)],
+        VectorOp::Vid => vec![Slot::Rd(Class::V)],
+        VectorOp::Vcompress => vec![Slot::Rd(Class::V), Slot::Rs2(Class::V), Slot::Rs1(Class::V)],
+        _ if unary(op).is_some() => vec![Slot::Rd(Class::V), Slot::Rs2(Class::V)],
+        _ => {
+            let (form, group) = match instruction
+                .form
+                .and_then(|form| binary(op, form).map(|(group, _)| (form, group)))
+            {
+                Some(form) => form,
+                None => return Vec::new(),
+            };
+            let source = match form.source() {
+                Source::Vector => Slot::Rs1(Class::V),
+                Source::Scalar if group == Group::Opf => Slot::Rs1(Class::F),
+                Source::Scalar => Slot::Rs1(Class::X),
+                Source::Immediate => Slot::Immediate(!is_unsigned_immediate(op)),
+            };
+            if is_multiply_add(op) {
+                vec![Slot::Rd(Class::V), source, Slot::Rs2(Class::V)]
+            } else if form.merges() {
+                vec![Slot::Rd(Class::V), Slot::Rs2(Class::V), source, Slot::V0]
+            } else {
+                vec![Slot::Rd(Class::V), Slot::Rs2(Class::V), source]
+            }
+        }
+    }
+}
+
+/// The encoding of a vector instruction with its operand fields left
+/// clear, or `None` if the instruction has no encoding, such as a masked
+/// whole register move or a segment load of more than eight fields
+pub(crate) fn opcode_bits(instruction: &Instruction, op: VectorOp) -> Option<u32> {
+    if instruction.masked && !is_maskable(instruction, op) {
+        return None;
+    }
+    let vm = if instruction.masked { 0 } else { 1 << 25 };
+    match op {
+        VectorOp::Vsetvli => return Some(OPCFG << 12 | OP_V),
+        VectorOp::Vsetivli => return Some(0b11 << 30 | OPCFG << 12 | OP_V),
+        VectorOp::Vsetvl => return Some(0b1000000 << 25 | OPCFG << 12 | OP_V),
+        _ => {}
+    }
+    if let Some(&(_, store, mop, umop)) = memory(op) {
+        let opcode = if store { STORE_FP } else { LOAD_FP };
+        return memory_bits(instruction, op).map(|width| {
+            (instruction.nf as u32 - 1) << 29
+                | mop << 26
+                | vm
+                | umop.unwrap_or(0) << 20
+                | width << 12
+                | opcode
+        });
+    }
+    if let Some(&(_, funct3, funct6, vs1)) = unary(op) {
+        return Some(funct6 << 26 | vm | vs1.unwrap_or(0) << 15 | funct3 << 12 | OP_V);
+    }
+    let form = instruction.form?;
+    let (group, funct6) = binary(op, form)?;
+    // The carry and merge forms are encoded with vm clear
+    let vm = if form.merges() { 0 } else { vm };
+    Some(funct6 << 26 | vm | group_funct3(group, form.source()) << 12 | OP_V)
+}
+
+/// The width field of a load or store, after checking its element width
+/// and number of fields
+fn memory_bits(instruction: &Instruction, op: VectorOp) -> Option<u32> {
+    let (eew, nf) = (instruction.eew, instruction.nf);
+    let width = match eew {
+        8 => 0b000,
+        16 => 0b101,
+        32 => 0b110,
+        64 => 0b111,
+        _ => return None,
+    };
+    let valid = match op {
+        // Whole register accesses move 1, 2, 4 or 8 registers, and stores
+        // are only encoded with an element width of 8
+        VectorOp::Vlr => nf.is_power_of_two() && nf <= 8,
+        VectorOp::Vsr => nf.is_power_of_two() && nf <= 8 && eew == 8,
+        VectorOp::Vlm | VectorOp::Vsm => nf == 1 && eew == 8,
+        _ => (1..=8).contains(&nf),
+    };
+    if valid {
+        Some(width)
+    } else {
+        None
+    }
+}
+
+/// The mnemonic of a vector instruction, including its operand form,
+/// element width and number of fields
+pub(crate) fn mnemonic(instruction: &Instruction, op: VectorOp) -> String {
+    let (eew, nf) = (instruction.eew, instruction.nf);
+    let segment = match nf {
+        1 => String::new(),
+        _ => format!("seg{}", nf),
+    };
+    match op {
+        VectorOp::Vle => format!("vl{}e{}.v", segment, eew),
+        VectorOp::Vleff => format!("vl{}e{}ff.v", segment, eew),
+        VectorOp::Vlr => format!("vl{}re{}.v", nf, eew),
+        VectorOp::Vlse => format!("vls{}e{}.v", segment, eew),
+        VectorOp::Vluxei => format!("vlux{}ei{}.v", segment, eew),
+        VectorOp::Vloxei => format!("vlox{}ei{}.v", segment, eew),
+        VectorOp::Vse => format!("vs{}e{}.v", segment, eew),
+        VectorOp::Vsr => format!("vs{}r.v", nf),
+        VectorOp::Vsse => format!("vss{}e{}.v", segment, eew),
+        VectorOp::Vsuxei => format!("vsux{}ei{}.v", segment, eew),
+        VectorOp::Vsoxei => format!("vsox{}ei{}.v", segment, eew),
+        _ => match instruction.form {
+            Some(form) => format!("{}.{}", op.mnemonic(), form),
+            None => op.mnemonic().to_string(),
+        },
+    }
+}
+
+/// Looks up the mnemonic of a vector instruction, returning an instruction
+/// with no operands to be filled in
+pub(crate) fn lookup(mnemonic: &str) -> Option<Instruction> {
+    let fixed = [VectorOp::Vsetvli, VectorOp::Vsetivli, VectorOp::Vsetvl];
+    if let Some(&op) = fixed
+        .iter()
+        .chain(UNARY.iter().map(|row| &row.0))
+        .find(|op| op.mnemonic() == mnemonic)
+    {
+        return Some(Instruction::new(Op::Vector(op)));
+    }
+
+    if let Some((base, suffix)) = mnemonic.rsplit_once('.') {
+        let form = FORMS.iter().find(|form| form.suffix() == suffix);
+        if let Some(&form) = form {
+            if let Some(&(op, ..)) = BINARY
+                .iter()
+                .find(|&&(op, _, _, forms)| op.mnemonic() == base && forms.contains(&form))
+            {
+                return Some(Instruction {
+                    form: Some(form),
+                    ..Instruction::new(Op::Vector(op))
+                });
+            }
+        }
+    }
+
+    for &(op, ..) in MEMORY {
+        for &eew in &[8, 16, 32, 64] {
+            for nf in 1..=8 {
+                let instruction = Instruction {
+                    eew,
+                    nf,
+                    ..Instruction::new(Op::Vector(op))
+                };
+                if memory_bits(&instruction, op).is_some()
+                    && self::mnemonic(&instruction, op) == mnemonic
+                {
+                    return Some(instruction);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The bits of an encoding which hold operands
+fn operand_bits(slots: &[Slot]) -> u32 {
+    slots
+        .iter()
+        .map(|slot| match slot {
+            Slot::Rd(_) | Slot::Rs3 => 0x1f << 7,
+            Slot::Rs1(_) | Slot::Immediate(_) | Slot::Base => 0x1f << 15,
+            Slot::Rs2(_) => 0x1f << 20,
+            Slot::Vtype => 0xff << 20,
+            Slot::V0 => 0,
+        })
+        .fold(0, |bits, field| bits | field)
+}
+
+/// Reads the operand fields of `word` into `instruction`, as its slots
+/// describe. Every other bit must match the encoding of the instruction,
+/// or the encoding is reserved.
+fn fill(mut instruction: Instruction, op: VectorOp, word: u32) -> Result<Instruction, DecodeError> {
+    let slots = slots(&instruction, op);
+    match opcode_bits(&instruction, op) {
+        Some(bits) if word & !operand_bits(&slots) == bits => {}
+        _ => return Err(DecodeError::Reserved),
+    }
+
+    let register = |class, u| match class {
+        Class::X => Register::from_u32(u),
+        Class::F => Register::float_from_u32(u),
+        Class::V => Register::vector_from_u32(u),
+    };
+    for slot in slots {
+        match slot {
+            Slot::Rd(class) => instruction.rd = register(class, rd(word)),
+            Slot::Rs1(class) => instruction.rs1 = register(class, rs1(word)),
+            Slot::Rs2(class) => instruction.rs2 = register(class, rs2(word)),
+            Slot::Rs3 => instruction.rs3 = Register::vector_from_u32(rd(word)),
+            Slot::Immediate(true) => instruction.immediate = ((rs1(word) << 27) as i32) >> 27,
+            Slot::Immediate(false) => instruction.immediate = rs1(word) as i32,
+            Slot::Base => instruction.rs1 = Register::from_u32(rs1(word)),
+            Slot::Vtype | Slot::V0 => {}
+        }
+    }
+    Ok(instruction)
+}
+
+fn op_of(instruction: &Instruction) -> VectorOp {
+    match instruction.op {
+        Op::Vector(op) => op,
+        _ => unreachable!(),
+    }
+}
+
+/// vsetvli, vsetivli and vsetvl, in the OPCFG group
+fn decode_config(word: u32) -> Result<Instruction, DecodeError> {
+    let op = if word >> 31 == 0 {
+        VectorOp::Vsetvli
+    } else if word >> 30 == 0b11 {
+        VectorOp::Vsetivli
+    } else if funct7(word) == 0b1000000 {
+        return Ok(Instruction::new(Op::Vector(VectorOp::Vsetvl)));
+    } else {
+        return Err(DecodeError::Reserved);
+    };
+    // The bits of zimm above the eight vtype uses are reserved, which
+    // `fill` checks
+    Ok(Instruction {
+        vtype: Some(Vtype::from_u32(word >> 20).ok_or(DecodeError::Reserved)?),
+        ..Instruction::new(Op::Vector(op))
+    })
+}
+
+/// Decodes the OP-V major opcode
+pub(crate) fn decode_op_v(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    require(isa, Extension::V)?;
+    let funct3 = funct3(word);
+    let funct6 = word >> 26;
+    let masked = (word >> 25) & 1 == 0;
+
+    let instruction = if funct3 == OPCFG {
+        decode_config(word)?
+    } else if let Some(instruction) = UNARY
+        .iter()
+        .filter(|&&(_, f3, f6, vs1)| {
+            f3 == funct3 && f6 == funct6 && vs1.is_none_or(|vs1| vs1 == rs1(word))
+        })
+        .map(|&(op, ..)| Instruction {
+            masked,
+            ..Instruction::new(Op::Vector(op))
+        })
+        // The moves which cannot be masked share funct6 with vmerge
+        .find(|instruction| !masked || is_maskable(instruction, op_of(instruction)))
+    {
+        instruction
+    } else {
+        let (group, source) = group(funct3);
+        let candidates = BINARY
+            .iter()
+            .filter(|&&(_, g, f6, _)| g == group && f6 == funct6)
+            .flat_map(|&(op, _, _, forms)| forms.iter().map(move |&form| (op, form)))
+            .filter(|&(_, form)| form.source() == source);
+        // With vm clear, a carry or merge form takes precedence over masking
+        let merging = candidates
+            .clone()
+            .find(|&(_, form)| form.merges() && masked);
+        let (op, form) = merging
+            .or_else(|| candidates.clone().find(|&(_, form)| !form.merges()))
+            .ok_or(DecodeError::Reserved)?;
+        Instruction {
+            masked: masked && !form.merges(),
+            form: Some(form),
+            ..Instruction::new(Op::Vector(op))
+        }
+    };
+
+    let op = op_of(&instruction);
+    fill(instruction, op, word)
+}
+
+/// Decodes a vector load or store, from the widths of LOAD-FP and STORE-FP
+/// which the scalar floating point loads and stores do not use
+pub(crate) fn decode_memory(word: u32, isa: &Isa) -> Result<Instruction, DecodeError> {
+    require(isa, Extension::V)?;
+    // mew, bit 28, is reserved for element widths above 64 bits
+    if (word >> 28) & 1 == 1 {
+        return Err(DecodeError::Reserved);
+    }
+    let store = word & 0x7f == STORE_FP;
+    let mop = (word >> 26) & 0b11;
+    let &(op, ..) = MEMORY
+        .iter()
+        .find(|&&(_, s, m, umop)| s == store && m == mop && umop.is_none_or(|u| u == rs2(word)))
+        .ok_or(DecodeError::Reserved)?;
+
+    let instruction = Instruction {
+        masked: (word >> 25) & 1 == 0,
+        eew: match funct3(word) {
+            0b000 => 8,
+            0b101 => 16,
+            0b110 => 32,
+            _ => 64,
+        },
+        nf: (word >> 29) as usize + 1,
+        ..Instruction::new(Op::Vector(op))
+    };
+    fill(instruction, op, word)
+}
+
+/// Returns true for the widths of LOAD-FP and STORE-FP which belong to the
+/// vector loads and stores
+pub(crate) fn is_vector_width(word: u32) -> bool {
+    matches!(funct3(word), 0b000 | 0b101 | 0b110 | 0b111)
+}
+
+/// Whether a vector op loads or stores, for `Instruction::memory_access`
+pub(crate) fn access_kind(op: VectorOp) -> Option<AccessKind> {
+    memory(op).map(|&(_, store, _, _)| {
+        if store {
+            AccessKind::Store
+        } else {
+            AccessKind::Load
+        }
+    })
+}
+
+/// The registers a vector instruction reads which are not held in its rs1,
+/// rs2 or rs3 fields: v0 when it is the mask, carry or merge operand, and vd
+/// for the multiply-adds
+pub(crate) fn implicit_reads(instruction: &Instruction, op: VectorOp) -> Vec<Register> {
+    let mut read = Vec::new();
+    if is_multiply_add(op) {
+        read.push(instruction.rd.clone());
+    }
+    if instruction.masked || slots(instruction, op).contains(&Slot::V0) {
+        read.push(Register::V0);
+    }
+    read
+}
+
+pub(crate) fn operands(instruction: &Instruction, op: VectorOp) -> Vec<Operand> {
+    let mut operands: Vec<Operand> = slots(instruction, op)
+        .into_iter()
+        .filter_map(|slot| match slot {
+            Slot::Rd(_) => Some(Operand::Register(instruction.rd.clone())),
+            Slot::Rs1(_) => Some(Operand::Register(instruction.rs1.clone())),
+            Slot::Rs2(_) => Some(Operand::Register(instruction.rs2.clone())),
+            Slot::Rs3 => Some(Operand::Register(instruction.rs3.clone())),
+            Slot::Immediate(true) => Some(Operand::SignedImmediate(instruction.immediate)),
+            Slot::Immediate(false) => {
+                Some(Operand::UnsignedImmediate(instruction.immediate as u32))
+            }
+            Slot::Base => Some(Operand::Memory {
+                base: instruction.rs1.clone(),
+                offset: 0,
+            }),
+            Slot::Vtype => instruction.vtype.map(Operand::Vtype),
+            Slot::V0 => Some(Operand::Register(Register::V0)),
+        })
+        .collect();
+    if instruction.masked {
+        operands.push(Operand::Mask);
+    }
+    operands
+}
+
+/// Writes a vector instruction in the GNU assembler syntax
+pub(crate) fn fmt(instruction: &Instruction, op: VectorOp, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut operands: Vec<String> = slots(instruction, op)
+        .into_iter()
+        .map(|slot| match slot {
+            Slot::Rd(_) => instruction.rd.to_string(),
+            Slot::Rs1(_) => instruction.rs1.to_string(),
+            Slot::Rs2(_) => instruction.rs2.to_string(),
+            Slot::Rs3 => instruction.rs3.to_string(),
+            Slot::Immediate(_) => instruction.immediate.to_string(),
+            Slot::Base => format!("({})", instruction.rs1),
+            Slot::Vtype => instruction
+                .vtype
+                .map(|vtype| vtype.to_string())
+                .unwrap_or_default(),
+            Slot::V0 => Register::V0.to_string(),
+        })
+        .collect();
+    if instruction.masked {
+        operands.push("v0.t".to_string());
+    }
+    write!(f, "{} {}", mnemonic(instruction, op), operands.join(", "))
+}